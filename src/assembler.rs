@@ -1,9 +1,19 @@
+mod error;
 mod main;
+mod options;
 
-pub use main::{assemble_code, assemble_file};
+pub use decoder::{decode, decode_instruction, DecodeError, DecodedInstruction};
+pub use error::{AsmError, Severity};
+pub use listing::{Listing, ListingChunk, ListingLine, ListingRelocation};
+pub use main::{
+    assemble_code, assemble_code_with_options, assemble_file, assemble_file_with_options, Assembled,
+};
+pub use options::Options;
 
+mod decoder;
 mod generator;
+mod listing;
 mod parser;
 mod resource;
 mod tests;
-pub use resource::{ELFBuilder, Syntax};
+pub use resource::{Instruction, Opcode, Operand, Syntax};
@@ -0,0 +1,606 @@
+//! Decoding machine code into instructions.
+
+use crate::assembler::resource::*;
+use fmt::Formatter;
+use std::fmt;
+
+/// an instruction decoded from machine code.
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub struct DecodedInstruction {
+    /// the offset from the beginning of the code
+    pub offset: usize,
+    /// the number of bytes of the instruction
+    pub length: usize,
+    pub instruction: Instruction,
+}
+
+/// machine code that can't be decoded.
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub struct DecodeError {
+    /// the offset of the instruction
+    pub offset: usize,
+    pub message: String,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#x}: {}", self.offset, self.message)
+    }
+}
+
+/// decode machine code into instructions.
+///
+/// # Examples
+///
+/// ```
+/// use asmpeach::*;
+///
+/// let insts = decode(&[0x55, 0x48, 0x89, 0xe5, 0xc3]).unwrap();
+/// assert_eq!(3, insts.len());
+/// assert_eq!(1, insts[1].offset);
+/// assert_eq!(3, insts[1].length);
+/// assert_eq!("movq %rsp, %rbp", insts[1].instruction.to_at_string());
+/// assert_eq!("mov rbp, rsp", insts[1].instruction.to_intel_string());
+/// ```
+pub fn decode(codes: &[u8]) -> Result<Vec<DecodedInstruction>, DecodeError> {
+    let mut insts = Vec::new();
+    let mut offset = 0;
+
+    while offset < codes.len() {
+        let inst = decode_instruction(codes, offset)?;
+        offset += inst.length;
+        insts.push(inst);
+    }
+
+    Ok(insts)
+}
+
+/// decode an instruction at `offset` in `codes`.
+/// ジャンプ先は `codes` の先頭からのオフセットで表す
+pub fn decode_instruction(codes: &[u8], offset: usize) -> Result<DecodedInstruction, DecodeError> {
+    let mut decoder = Decoder { codes, pos: offset };
+    let instruction = decoder
+        .instruction()
+        .map_err(|message| DecodeError { offset, message })?;
+
+    Ok(DecodedInstruction {
+        offset,
+        length: decoder.pos - offset,
+        instruction,
+    })
+}
+
+struct Decoder<'a> {
+    codes: &'a [u8],
+    /// the offset of the next byte
+    pos: usize,
+}
+
+/// the operand encoded in ModRM:r/m.
+enum RegOrMem {
+    /// レジスタ番号( REX.B を含む )
+    Register(u8),
+    Memory(Operand),
+}
+
+impl<'a> Decoder<'a> {
+    fn instruction(&mut self) -> Result<Instruction, String> {
+        // 64bit モードでは `c4`, `c5` は常に VEX prefix
+        let vex = match self.peek() {
+            Some(VEXPrefix::TWO_BYTE) => Some(VEXPrefix::from_bytes(&self.bytes::<2>()?)),
+            Some(VEXPrefix::THREE_BYTE) => Some(VEXPrefix::from_bytes(&self.bytes::<3>()?)),
+            _ => None,
+        };
+
+        // REX prefix より前に置く prefix
+        let prefix = match (vex, self.peek()) {
+            (Some(vex), _) => vex.prefix(),
+            (None, Some(p @ (0x66 | 0xf2 | 0xf3))) => {
+                self.pos += 1;
+                Some(p)
+            }
+            _ => None,
+        };
+        let rex = match self.peek() {
+            Some(byte) if vex.is_none() && byte & 0xf0 == REXPrefix::BASE => {
+                self.pos += 1;
+                Some(REXPrefix::from_byte(byte))
+            }
+            _ => None,
+        };
+        let (w, r, x, b) = match (rex, vex) {
+            (Some(rex), _) => (rex.w_bit, rex.r_bit, rex.x_bit, rex.b_bit),
+            (_, Some(vex)) => (vex.w_bit, vex.r_bit, vex.x_bit, vex.b_bit),
+            _ => (false, false, false, false),
+        };
+        let escape = match vex {
+            Some(vex) => vex
+                .escape()
+                .ok_or_else(|| format!("unknown VEX map {:#04x}", vex.map))?,
+            None => &[],
+        };
+        let length = vex.map(|vex| vex.length);
+
+        let first = self.peek().ok_or_else(end_of_code)?;
+        let form = INSTRUCTION_TABLE
+            .iter()
+            .find(|form| {
+                form.vex == length
+                    && form.prefix == prefix
+                    && form.rex_w == w
+                    && self.matches_opcode(form, escape)
+            })
+            .ok_or_else(|| match (vex, first, self.codes.get(self.pos + 1)) {
+                (Some(vex), _, _) => format!("unknown opcode {} {:#04x}", vex, first),
+                (None, 0x0f, Some(second)) => {
+                    format!("unknown opcode {:#04x} {:#04x}", first, second)
+                }
+                _ => format!("unknown opcode {:#04x}", first),
+            })?;
+
+        self.pos += form.opcode.len() - escape.len();
+        let last = self.codes[self.pos - 1];
+        let cond = match form.cc {
+            true => ConditionCode::from_number(last & 0xf),
+            false => None,
+        };
+
+        // ModRM:reg( ModRM が無ければ opcode + rd のレジスタ )と ModRM:r/m
+        let (reg, rm) = match form.modrm {
+            Some(_) => {
+                let modrm = self.byte()?;
+                (
+                    (modrm >> 3) & 0b111 | extension(r),
+                    Some(self.rm(modrm, x, b)?),
+                )
+            }
+            None => (last & 0b111 | extension(b), None),
+        };
+
+        let mut operands = Vec::new();
+        for (i, kind) in form.operands.iter().enumerate() {
+            let operand = match (kind, &rm) {
+                // VEX.vvvv は反転済み
+                _ if form.encoding == Encoding::RVM && i == 1 => {
+                    vector_register(kind, vex.map_or(0, |vex| vex.vvvv))
+                }
+                (OperandKind::Reg(size), _) => register(reg, *size, rex.is_some()),
+                (OperandKind::RegMem(size), Some(RegOrMem::Register(code))) => {
+                    register(*code, *size, rex.is_some())
+                }
+                (
+                    OperandKind::XmmMem(_) | OperandKind::YmmMem(_),
+                    Some(RegOrMem::Register(code)),
+                ) => vector_register(kind, *code),
+                (
+                    OperandKind::RegMem(_)
+                    | OperandKind::Mem
+                    | OperandKind::XmmMem(_)
+                    | OperandKind::YmmMem(_),
+                    Some(RegOrMem::Memory(mem)),
+                ) => mem.clone(),
+                (
+                    OperandKind::RegMem(_)
+                    | OperandKind::Mem
+                    | OperandKind::XmmMem(_)
+                    | OperandKind::YmmMem(_),
+                    _,
+                ) => return Err(format!("'{}' requires a memory operand", form.mnemonic)),
+                (OperandKind::Xmm | OperandKind::Ymm, _) => vector_register(kind, reg),
+                (OperandKind::Imm(OperandSize::BYTE) | OperandKind::SImm8, _) => {
+                    Operand::Immediate(Immediate::I8(self.byte()? as i8))
+                }
+                (OperandKind::Imm(OperandSize::WORD), _) => {
+                    Operand::Immediate(Immediate::I16(i16::from_le_bytes(self.bytes()?)))
+                }
+                (OperandKind::Imm(_), _) => {
+                    Operand::Immediate(Immediate::I32(i32::from_le_bytes(self.bytes()?)))
+                }
+                (OperandKind::Acc(size), _) => register(0, *size, rex.is_some()),
+                (OperandKind::Cl, _) => Operand::GENERALREGISTER(GeneralPurposeRegister::CL),
+                (OperandKind::One, _) => Operand::Immediate(Immediate::I8(1)),
+                (OperandKind::Rel(size), _) => {
+                    let rel = match size {
+                        OperandSize::BYTE => self.byte()? as i8 as i64,
+                        _ => i32::from_le_bytes(self.bytes()?) as i64,
+                    };
+                    // 次の命令の先頭からの相対位置
+                    Operand::LABEL {
+                        label: hex(self.pos as i64 + rel),
+                        addend: 0,
+                    }
+                }
+            };
+            operands.push(operand);
+        }
+
+        Ok(Instruction {
+            opcode: Opcode::new(form, cond, operands),
+        })
+    }
+
+    /// 条件コードや opcode + rd のレジスタ番号は,オペコードの最後のバイトに含まれる
+    /// `escape` は VEX prefix に含まれていたエスケープ( `0f` 等 )
+    fn matches_opcode(&self, form: &InstructionForm, escape: &[u8]) -> bool {
+        let opcode = match form.vex {
+            Some(_) if form.split_escape().0 == escape => form.split_escape().1,
+            Some(_) => return false,
+            None => form.opcode,
+        };
+
+        let codes = &self.codes[self.pos..];
+        let (last, init) = opcode.split_last().unwrap();
+        if codes.len() < opcode.len() || !codes.starts_with(init) {
+            return false;
+        }
+
+        let byte = codes[init.len()];
+        let matches_last = if form.cc {
+            byte & 0xf0 == *last
+        } else if matches!(form.encoding, Encoding::O | Encoding::OI) {
+            byte & 0xf8 == *last
+        } else {
+            byte == *last
+        };
+
+        // `/digit` の形式は ModRM:reg も一致する必要がある
+        let matches_digit = match form.modrm {
+            Some(ModRMReg::Digit(digit)) => codes
+                .get(opcode.len())
+                .is_some_and(|modrm| (modrm >> 3) & 0b111 == digit),
+            _ => true,
+        };
+
+        matches_last && matches_digit
+    }
+
+    /// ModRM:mod, ModRM:r/m( と SIB-Byte, displacement )からオペランドを読む
+    fn rm(&mut self, modrm: u8, x: bool, b: bool) -> Result<RegOrMem, String> {
+        let mode = modrm >> 6;
+        let rm = modrm & 0b111;
+        if mode == 0b11 {
+            return Ok(RegOrMem::Register(rm | extension(b)));
+        }
+
+        // ModRM:r/m が 0b101 かつ mod が 0b00 のときRIP相対となる
+        if mode == 0b00 && rm == 0b101 {
+            let disp = i32::from_le_bytes(self.bytes()?);
+            return Ok(RegOrMem::Memory(Operand::RIPRELATIVE {
+                label: hex(disp as i64),
+                addend: 0,
+            }));
+        }
+
+        let (base, index, scale) = if rm == 0b100 {
+            let sib = self.byte()?;
+            let base = sib & 0b111;
+            // index-reg が 0b100( %rsp )ならインデックスを使わない
+            let index = match (sib >> 3) & 0b111 | extension(x) {
+                0b100 => None,
+                index => Some(GeneralPurposeRegister::new_64bit_from_code(index as usize)),
+            };
+
+            // SIB:base が 0b101 かつ mod が 0b00 のときベースレジスタ無し
+            if mode == 0b00 && base == 0b101 {
+                return Ok(RegOrMem::Memory(Operand::ABSOLUTE {
+                    index,
+                    disp: i32::from_le_bytes(self.bytes()?),
+                    scale: index.map(|_| 1 << (sib >> 6)),
+                }));
+            }
+
+            (base | extension(b), index, index.map(|_| 1 << (sib >> 6)))
+        } else {
+            (rm | extension(b), None, None)
+        };
+
+        let disp = match mode {
+            0b01 => Some(Displacement::DISP8(self.byte()? as i8)),
+            0b10 => Some(Displacement::DISP32(i32::from_le_bytes(self.bytes()?))),
+            _ => None,
+        };
+
+        Ok(RegOrMem::Memory(Operand::ADDRESSING {
+            base: GeneralPurposeRegister::new_64bit_from_code(base as usize),
+            index,
+            disp,
+            scale,
+        }))
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.codes.get(self.pos).copied()
+    }
+
+    fn byte(&mut self) -> Result<u8, String> {
+        let byte = self.peek().ok_or_else(end_of_code)?;
+        self.pos += 1;
+        Ok(byte)
+    }
+
+    fn bytes<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        let mut bytes = [0; N];
+        for byte in bytes.iter_mut() {
+            *byte = self.byte()?;
+        }
+        Ok(bytes)
+    }
+}
+
+/// REX prefix のビットはレジスタ番号の 4bit 目になる
+fn extension(bit: bool) -> u8 {
+    if bit {
+        0b1000
+    } else {
+        0b0
+    }
+}
+
+/// XMM レジスタか YMM レジスタ
+fn vector_register(kind: &OperandKind, code: u8) -> Operand {
+    match kind {
+        OperandKind::Ymm | OperandKind::YmmMem(_) => {
+            Operand::YMMREGISTER(YMMRegister::new_from_code(code as usize))
+        }
+        _ => Operand::XMMREGISTER(XMMRegister::new_from_code(code as usize)),
+    }
+}
+
+/// `%sil` 等は REX prefix がある場合のみ使える( 無ければ `%dh` 等 )
+fn register(code: u8, size: OperandSize, rex: bool) -> Operand {
+    let code = code as usize;
+    let reg = match size {
+        OperandSize::BYTE if rex && (4..8).contains(&code) => [
+            GeneralPurposeRegister::SPL,
+            GeneralPurposeRegister::BPL,
+            GeneralPurposeRegister::SIL,
+            GeneralPurposeRegister::DIL,
+        ][code - 4],
+        OperandSize::BYTE => GeneralPurposeRegister::new_8bit_from_code(code),
+        OperandSize::WORD => GeneralPurposeRegister::new_16bit_from_code(code),
+        OperandSize::DWORD => GeneralPurposeRegister::new_32bit_from_code(code),
+        OperandSize::QWORD => GeneralPurposeRegister::new_64bit_from_code(code),
+        OperandSize::XMMWORD | OperandSize::YMMWORD => {
+            unreachable!("not a size of general-purpose registers")
+        }
+    };
+
+    Operand::GENERALREGISTER(reg)
+}
+
+/// `-0x10` のように符号を付けて表す
+fn hex(value: i64) -> String {
+    if value < 0 {
+        format!("-{:#x}", -value)
+    } else {
+        format!("{:#x}", value)
+    }
+}
+
+fn end_of_code() -> String {
+    "unexpected end of code".to_string()
+}
+
+#[cfg(test)]
+mod decode_tests {
+    use super::*;
+
+    #[test]
+    fn decode_test() {
+        // pushq %rbp; movq %rsp, %rbp; movq $42, %rax; popq %rbp; ret
+        let codes = [
+            0x55, 0x48, 0x89, 0xe5, 0x48, 0xc7, 0xc0, 0x2a, 0x00, 0x00, 0x00, 0x5d, 0xc3,
+        ];
+        let insts = decode(&codes).unwrap();
+
+        let lines: Vec<(usize, usize, String)> = insts
+            .iter()
+            .map(|inst| (inst.offset, inst.length, inst.instruction.to_at_string()))
+            .collect();
+        assert_eq!(
+            vec![
+                (0, 1, "pushq %rbp".to_string()),
+                (1, 3, "movq %rsp, %rbp".to_string()),
+                (4, 7, "movq $42, %rax".to_string()),
+                (11, 1, "popq %rbp".to_string()),
+                (12, 1, "ret".to_string()),
+            ],
+            lines
+        );
+        assert_eq!("mov rax, 42", insts[2].instruction.to_intel_string());
+    }
+
+    #[test]
+    fn decode_register_test() {
+        // REX prefix があれば %sil, 無ければ %dh
+        assert_eq!("movb %sil, %dil", at_string(&[0x40, 0x88, 0xf7]));
+        assert_eq!("movb %dh, %bh", at_string(&[0x88, 0xf7]));
+        assert_eq!("pushq %r15", at_string(&[0x41, 0x57]));
+        assert_eq!("orw $300, %ax", at_string(&[0x66, 0x0d, 0x2c, 0x01]));
+        assert_eq!("shlq %cl, %rax", at_string(&[0x48, 0xd3, 0xe0]));
+        assert_eq!("shll %eax", at_string(&[0xd1, 0xe0]));
+        assert_eq!(
+            "shldq $3, %rbx, %rax",
+            at_string(&[0x48, 0x0f, 0xa4, 0xd8, 0x03])
+        );
+        assert_eq!("movslq %eax, %rdx", at_string(&[0x48, 0x63, 0xd0]));
+        assert_eq!("movzbl %al, %eax", at_string(&[0x0f, 0xb6, 0xc0]));
+        assert_eq!("cmovlq %rbx, %rax", at_string(&[0x48, 0x0f, 0x4c, 0xc3]));
+        assert_eq!("setne %al", at_string(&[0x0f, 0x95, 0xc0]));
+        assert_eq!("cltq", at_string(&[0x48, 0x98]));
+        assert_eq!("endbr64", at_string(&[0xf3, 0x0f, 0x1e, 0xfa]));
+    }
+
+    #[test]
+    fn decode_memory_test() {
+        assert_eq!("movq -8(%rbp), %rax", at_string(&[0x48, 0x8b, 0x45, 0xf8]));
+        assert_eq!(
+            "mov rax, QWORD PTR -8[rbp]",
+            intel_string(&[0x48, 0x8b, 0x45, 0xf8])
+        );
+        assert_eq!(
+            "movq 8(%rsp), %rax",
+            at_string(&[0x48, 0x8b, 0x44, 0x24, 0x08])
+        );
+        assert_eq!(
+            "movq (%r12, %r13, 4), %rax",
+            at_string(&[0x4b, 0x8b, 0x04, 0xac])
+        );
+        assert_eq!(
+            "cmpl $1, 0x10(%rip)",
+            at_string(&[0x83, 0x3d, 0x10, 0x00, 0x00, 0x00, 0x01])
+        );
+        assert_eq!(
+            "test WORD PTR -2[rbp], 1",
+            intel_string(&[0x66, 0xf7, 0x45, 0xfe, 0x01, 0x00])
+        );
+        assert_eq!("lea rax, 8[rbp]", intel_string(&[0x48, 0x8d, 0x45, 0x08]));
+
+        // ベースレジスタ無し
+        assert_eq!(
+            "leaq 0(, %rax, 4), %rdx",
+            at_string(&[0x48, 0x8d, 0x14, 0x85, 0x00, 0x00, 0x00, 0x00])
+        );
+        assert_eq!(
+            "mov eax, DWORD PTR [4096]",
+            intel_string(&[0x8b, 0x04, 0x25, 0x00, 0x10, 0x00, 0x00])
+        );
+    }
+
+    #[test]
+    fn decode_sse_test() {
+        assert_eq!(
+            "movq %rax, %xmm0",
+            at_string(&[0x66, 0x48, 0x0f, 0x6e, 0xc0])
+        );
+        assert_eq!("movq %xmm0, %xmm1", at_string(&[0xf3, 0x0f, 0x7e, 0xc8]));
+        assert_eq!("movd %xmm3, %ecx", at_string(&[0x66, 0x0f, 0x7e, 0xd9]));
+        assert_eq!(
+            "movsd -8(%rbp), %xmm8",
+            at_string(&[0xf2, 0x44, 0x0f, 0x10, 0x45, 0xf8])
+        );
+        assert_eq!(
+            "movsd xmm8, QWORD PTR -8[rbp]",
+            intel_string(&[0xf2, 0x44, 0x0f, 0x10, 0x45, 0xf8])
+        );
+        assert_eq!(
+            "cvtsi2sdl (%rax), %xmm0",
+            at_string(&[0xf2, 0x0f, 0x2a, 0x00])
+        );
+        assert_eq!(
+            "cvttsd2si %xmm0, %r11",
+            at_string(&[0xf2, 0x4c, 0x0f, 0x2c, 0xd8])
+        );
+        assert_eq!("ucomiss %xmm1, %xmm0", at_string(&[0x0f, 0x2e, 0xc1]));
+    }
+
+    #[test]
+    fn decode_avx_test() {
+        assert_eq!(
+            "vaddps %ymm2, %ymm1, %ymm0",
+            at_string(&[0xc5, 0xf4, 0x58, 0xc2])
+        );
+        assert_eq!(
+            "vaddps ymm0, ymm1, YMMWORD PTR 32[rax + r10 * 4]",
+            intel_string(&[0xc4, 0xa1, 0x74, 0x58, 0x44, 0x90, 0x20])
+        );
+        assert_eq!(
+            "vmovdqu %ymm8, 32(%r8)",
+            at_string(&[0xc4, 0x41, 0x7e, 0x7f, 0x40, 0x20])
+        );
+        assert_eq!(
+            "vpbroadcastd (%rdi), %ymm1",
+            at_string(&[0xc4, 0xe2, 0x7d, 0x58, 0x0f])
+        );
+        assert_eq!(
+            "vpermq $1, (%r11), %ymm0",
+            at_string(&[0xc4, 0xc3, 0xfd, 0x00, 0x03, 0x01])
+        );
+        assert_eq!(
+            "vfmadd231ss %xmm12, %xmm1, %xmm0",
+            at_string(&[0xc4, 0xc2, 0x71, 0xb9, 0xc4])
+        );
+        assert_eq!("vzeroupper", at_string(&[0xc5, 0xf8, 0x77]));
+    }
+
+    #[test]
+    fn decode_jump_test() {
+        // ジャンプ先は先頭からのオフセット
+        let codes = [0xeb, 0x00, 0x74, 0xfc, 0xe8, 0xf7, 0xff, 0xff, 0xff];
+        let targets: Vec<String> = decode(&codes)
+            .unwrap()
+            .iter()
+            .map(|inst| inst.instruction.to_at_string())
+            .collect();
+        assert_eq!(vec!["jmp 0x2", "je 0x0", "call 0x0"], targets);
+    }
+
+    #[test]
+    fn reencode_test() {
+        let all = [
+            vec![0x4f, 0x1b, 0x24, 0xda],
+            vec![0x40, 0x80, 0xe6, 0xff],
+            vec![0x41, 0x81, 0xe0, 0x00, 0x10, 0x00, 0x00],
+            vec![0x48, 0x3d, 0x2c, 0x01, 0x00, 0x00],
+            vec![0x66, 0x41, 0xf7, 0xd7],
+            vec![0x48, 0xc1, 0x65, 0xf8, 0x04],
+            vec![0x66, 0x0f, 0xad, 0x75, 0xfe],
+            vec![0x48, 0x0f, 0xb7, 0x04, 0x77],
+            vec![0x41, 0x0f, 0x9f, 0xc2],
+            vec![0x4d, 0x0f, 0x4c, 0x27],
+            vec![0x66, 0xc7, 0x45, 0xfe, 0x2c, 0x01],
+            vec![0x4c, 0x0f, 0xaf, 0xe0],
+            vec![0x68, 0x2c, 0x01, 0x00, 0x00],
+            vec![0x8f, 0x43, 0x08],
+            vec![0x66, 0x4d, 0x0f, 0x7e, 0xca],
+            vec![0x66, 0x0f, 0xd6, 0x08],
+            vec![0x66, 0x41, 0x0f, 0x28, 0xd2],
+            vec![0xf2, 0x0f, 0x11, 0x45, 0xf8],
+            vec![0x45, 0x0f, 0x57, 0xc0],
+            vec![0xc4, 0x41, 0x31, 0xef, 0xc9],
+            vec![0xc5, 0x7c, 0x29, 0xf8],
+            vec![0xc4, 0xe2, 0xf5, 0xb8, 0xc2],
+            vec![0xc4, 0xe3, 0xfd, 0x00, 0xc1, 0xd8],
+            vec![0xc5, 0xfc, 0x77],
+        ];
+
+        for codes in all.iter() {
+            let inst = decode_instruction(codes, 0).unwrap();
+            assert_eq!(codes.len(), inst.length);
+            assert_eq!(*codes, inst.instruction.to_bytes());
+        }
+    }
+
+    #[test]
+    fn decode_error_test() {
+        let err = decode(&[0xc3, 0x0f, 0x0b]).unwrap_err();
+        assert_eq!("0x1: unknown opcode 0x0f 0x0b", err.to_string());
+
+        let err = decode(&[0x48]).unwrap_err();
+        assert_eq!("unexpected end of code", err.message);
+
+        let err = decode(&[0x48, 0x83, 0xc4]).unwrap_err();
+        assert_eq!("unexpected end of code", err.message);
+
+        let err = decode(&[0x48, 0x8d, 0xc0]).unwrap_err();
+        assert_eq!("'lea' requires a memory operand", err.message);
+
+        let err = decode(&[0xc5, 0xf8, 0x0b]).unwrap_err();
+        assert_eq!("unknown opcode VEX(c5 f8) 0x0b", err.message);
+        let err = decode(&[0xc4, 0xe4, 0x7d, 0x58, 0xc8]).unwrap_err();
+        assert_eq!("unknown VEX map 0x04", err.message);
+    }
+
+    fn at_string(codes: &[u8]) -> String {
+        let inst = decode_instruction(codes, 0).unwrap();
+        assert_eq!(codes.len(), inst.length);
+        inst.instruction.to_at_string()
+    }
+
+    fn intel_string(codes: &[u8]) -> String {
+        decode_instruction(codes, 0)
+            .unwrap()
+            .instruction
+            .to_intel_string()
+    }
+}
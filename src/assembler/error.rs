@@ -0,0 +1,76 @@
+//! Type definitions for errors reported while assembling.
+
+use fmt::Formatter;
+use std::fmt;
+
+/// An error found in assembly source.
+/// it points the location(file, line and column) that causes the error.
+/// warnings(e.g. `.warning`) are also reported as this type.
+#[derive(Eq, Ord, PartialOrd, PartialEq, Debug, Clone)]
+pub struct AsmError {
+    /// file name (`<input>` if the source isn't read from a file)
+    pub file: String,
+    /// 1-origin line number (0 if the error isn't related to any line)
+    pub line: usize,
+    /// 1-origin column number (0 if the error isn't related to any column)
+    pub column: usize,
+    pub message: String,
+    pub severity: Severity,
+}
+
+/// whether the assembling fails or not.
+#[derive(Eq, Ord, PartialOrd, PartialEq, Debug, Clone, Copy)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl AsmError {
+    pub fn new(file: &str, line: usize, column: usize, message: String) -> Self {
+        Self {
+            file: file.to_string(),
+            line,
+            column,
+            message,
+            severity: Severity::Error,
+        }
+    }
+
+    /// a warning that doesn't stop assembling(e.g. `.warning`).
+    pub fn warning(file: &str, line: usize, column: usize, message: String) -> Self {
+        Self {
+            severity: Severity::Warning,
+            ..Self::new(file, line, column, message)
+        }
+    }
+
+    pub fn is_warning(&self) -> bool {
+        self.severity == Severity::Warning
+    }
+
+    /// an error that isn't related to any line(e.g. failed to read a file).
+    pub fn without_location(file: &str, message: String) -> Self {
+        Self::new(file, 0, 0, message)
+    }
+}
+
+impl fmt::Display for AsmError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+
+        if self.line == 0 {
+            return write!(f, "{}: {}: {}", self.file, severity, self.message);
+        }
+
+        write!(
+            f,
+            "{}:{}:{}: {}: {}",
+            self.file, self.line, self.column, severity, self.message
+        )
+    }
+}
+
+impl std::error::Error for AsmError {}
@@ -1,26 +1,39 @@
-use crate::assembler::resource::{Opcode, RelaSymbol, Symbol};
+use crate::assembler::resource::{
+    Fixup, Instruction, Opcode, Operand, OperandSize, RelaSymbol, Symbol, SymbolSize, R_X86_64_16,
+    R_X86_64_32S, R_X86_64_64, R_X86_64_8, R_X86_64_PC16, R_X86_64_PC64, R_X86_64_PC8,
+};
 use indexmap::map::IndexMap;
 
-#[derive(Ord, PartialOrd, Eq, PartialEq, Hash, Copy, Clone)]
-struct RelativeJumpSpec {
-    operand_offset: isize,
-    address: isize,
-    is_label: bool,
+/// 命令列をレイアウトする単位
+enum Fragment<'a> {
+    /// 長さが確定している機械語と,その中の再配置情報,レイアウト後に埋める即値( offset は断片の先頭から )
+    Code(Vec<u8>, Vec<RelaSymbol>, Vec<Fixup>),
+    /// ラベルへの相対ジャンプ
+    /// 変位が rel8 に収まるかどうかで長さが変わる
+    Jump(Jump<'a>),
+    /// シンボル先頭のアラインメント調整
+    Align(u64),
+    /// シンボルの末尾
+    End,
 }
 
-impl RelativeJumpSpec {
-    fn new_label(addr: isize) -> Self {
-        Self {
-            operand_offset: 0,
-            address: addr,
-            is_label: true,
-        }
-    }
-    fn new_jump(offset: isize) -> Self {
-        Self {
-            operand_offset: offset,
-            address: offset,
-            is_label: false,
+struct Jump<'a> {
+    label: &'a str,
+    /// `jmp bar+4` の `4`
+    addend: i64,
+    /// `eb`, `74` 等
+    /// rel8 の形式を持たない命令( `call` )は None
+    short_opcode: Option<Vec<u8>>,
+    /// `e9`, `0f 84` 等
+    near_opcode: Vec<u8>,
+    is_near: bool,
+}
+
+impl Jump<'_> {
+    fn len(&self) -> isize {
+        match &self.short_opcode {
+            Some(short_opcode) if !self.is_near => short_opcode.len() as isize + 1,
+            _ => self.near_opcode.len() as isize + 4,
         }
     }
 }
@@ -28,157 +41,837 @@ impl RelativeJumpSpec {
 pub fn generate_main(symbols: &mut IndexMap<String, Symbol>) -> IndexMap<String, Vec<RelaSymbol>> {
     let mut reloc_syms = IndexMap::new();
 
-    for (sym_name, sym) in symbols.iter_mut() {
-        let (mut sym_codes, relocs_in_sym) = gen_symbol_code(sym);
-        reloc_syms.insert(sym_name.to_string(), relocs_in_sym);
+    // データセクションのシンボルは,パース時にバイト列が決まっている
+    let (text_codes, text_labels) = gen_text_section(symbols);
+    for (sym_name, (sym_codes, relocs_in_sym, mut fixups, inst_offsets)) in text_codes {
+        let sym = symbols.get_mut(&sym_name).unwrap();
+        reloc_syms.insert(sym_name, relocs_in_sym);
+        sym.fixups.append(&mut fixups);
 
-        // アラインメント調整
-        let mut extra_bytes: Vec<u8> = Vec::new();
+        // 命令の番号の範囲を,バイト列の範囲にする
+        for line in sym.lines.iter_mut() {
+            line.start = inst_offsets[line.start];
+            line.end = inst_offsets[line.end];
+        }
 
-        let rest_bytes = sym_codes.len() % 4;
-        for _ in 0..(4 - rest_bytes) {
-            extra_bytes.push(0x00);
+        // `.size main, .-main`
+        if sym.size == Some(SymbolSize::CodeLength) {
+            sym.size = Some(SymbolSize::Bytes(sym_codes.len() as u64));
         }
-        sym_codes.append(&mut extra_bytes);
 
         sym.codes = sym_codes;
     }
 
+    layout_symbols(symbols);
+    resolve_fixups(symbols, &text_labels, &mut reloc_syms);
+    add_undefined_symbols(symbols, &reloc_syms);
+
     reloc_syms
 }
 
-fn gen_symbol_code(sym: &Symbol) -> (Vec<u8>, Vec<RelaSymbol>) {
-    let mut relative_jump_offset: IndexMap<String, Vec<RelativeJumpSpec>> = IndexMap::new();
-    let mut code_offset = 0;
+/// セクションごとに,各シンボルのオフセットを決める
+fn layout_symbols(symbols: &mut IndexMap<String, Symbol>) {
+    let mut section_sizes: IndexMap<String, u64> = IndexMap::new();
 
-    let mut symbol_codes = Vec::new();
-    let mut relocations = Vec::new();
+    // 絶対シンボルの値は `.set` 等で,COMMON シンボルの値はアラインメントで決まっている
+    for (_name, sym) in symbols
+        .iter_mut()
+        .filter(|(_, sym)| !sym.is_absolute() && !sym.is_common())
+    {
+        let size = section_sizes.entry(sym.section.to_string()).or_insert(0);
 
-    // ラベルごとに機械語に変換
-    for group in sym.groups.iter() {
-        // jump系命令がラベルの前に存在した場合
-        if let Some(specs) = relative_jump_offset.get(&group.label) {
-            for spec in specs {
-                // 相対オフセットの計算
-                let relative_offset = code_offset - spec.address;
+        // アラインメントに合わせてパディングする
+        *size = size.div_ceil(sym.align) * sym.align;
+        sym.value = *size;
+        *size += sym.codes.len() as u64;
+    }
+}
 
-                for (idx, addr) in (relative_offset as u32).to_le_bytes().iter().enumerate() {
-                    symbol_codes[idx + (spec.operand_offset - 4) as usize] = *addr;
-                }
-            }
-        } else {
-            // ラベルがjump系命令の前に存在した場合
-            if !group.label.ends_with("_entry") {
-                // ラベルの位置を保存しておく
-                relative_jump_offset.insert(
-                    group.label.to_string(),
-                    vec![RelativeJumpSpec::new_label(code_offset)],
-                );
-            }
+/// 定義されていないシンボルへの参照は,リンク時に解決される未定義シンボルとして追加する
+fn add_undefined_symbols(
+    symbols: &mut IndexMap<String, Symbol>,
+    reloc_syms: &IndexMap<String, Vec<RelaSymbol>>,
+) {
+    for rela in reloc_syms.values().flatten() {
+        // .text のラベルはセクションシンボルからの相対で参照する
+        if symbols.contains_key(&rela.name)
+            || Symbol::is_local_label(&rela.name)
+            || rela.name == ".text"
+        {
+            continue;
         }
 
-        for inst in group.insts.iter() {
-            // いくつかの命令は再配置シンボルの生成など，
-            // 機械語への変換以外にも操作が必要．
+        symbols.insert(rela.name.to_string(), Symbol::undefined());
+    }
 
-            match &inst.opcode {
-                Opcode::CALLFUNC(func) => {
-                    // 適当なアドレスを生成しておく
-                    let mut inst_bytes = vec![0xe8, 0x00, 0x00, 0x00, 0x00];
+    // `.type` 等で宣言されただけのシンボルも,GNU as と同様にグローバルにする
+    // `.local` で宣言したシンボルが定義されない場合は,パース時にエラーにしている
+    for sym in symbols.values_mut() {
+        if sym.is_undefined() && sym.is_local() && !sym.declared_local {
+            sym.as_global();
+        }
+    }
+}
+
+/// .data, .rodata の `.quad .LC0`, `.long .L3-.L2` や, `$.L3-.L2` 等の即値を,値か再配置で埋める
+/// `text_labels` は .text のラベルのセクション内オフセット
+fn resolve_fixups(
+    symbols: &mut IndexMap<String, Symbol>,
+    text_labels: &IndexMap<String, u64>,
+    reloc_syms: &mut IndexMap<String, Vec<RelaSymbol>>,
+) {
+    // (セクション, セクション内オフセット)
+    let address = |symbols: &IndexMap<String, Symbol>, name: &str| match symbols.get(name) {
+        Some(sym) if !sym.is_undefined() => Some((sym.section.to_string(), sym.value as i64)),
+        _ => text_labels
+            .get(name)
+            .map(|offset| (".text".to_string(), *offset as i64)),
+    };
+
+    for sym_idx in 0..symbols.len() {
+        let fixups = std::mem::take(&mut symbols[sym_idx].fixups);
+
+        for fixup in fixups.iter() {
+            let place = symbols[sym_idx].value as i64 + fixup.offset as i64;
+            let target = address(symbols, &fixup.symbol);
 
-                    let rela64 = new_rela64(func.copy_label(), code_offset);
-                    relocations.push(rela64);
+            let (rela_type, addend) = match &fixup.subtrahend {
+                // パース時に解決できることを確かめている
+                Some(subtrahend) => {
+                    let (sub_section, sub_offset) = address(symbols, subtrahend).unwrap();
 
-                    code_offset += inst_bytes.len() as isize;
-                    symbol_codes.append(&mut inst_bytes);
+                    match &target {
+                        // 同じセクション内の差は定数
+                        Some((section, offset)) if *section == sub_section => {
+                            let value = offset - sub_offset + fixup.addend;
+                            let codes = &mut symbols[sym_idx].codes;
+                            codes[fixup.offset..fixup.offset + fixup.size]
+                                .copy_from_slice(&value.to_le_bytes()[..fixup.size]);
+                            continue;
+                        }
+                        // `foo-.` は PC相対
+                        _ => (
+                            pc_relative_relocation_type(fixup.size),
+                            fixup.addend + place - sub_offset,
+                        ),
+                    }
                 }
+                None if fixup.signed => (R_X86_64_32S, fixup.addend),
+                None => (absolute_relocation_type(fixup.size), fixup.addend),
+            };
 
-                // jump
-                Opcode::JELABEL { label } => {
-                    let mut inst_bytes = inst.to_bytes();
-                    inst_bytes.append(&mut vec![0x00, 0x00, 0x00, 0x00]);
-                    code_offset += inst_bytes.len() as isize;
-                    symbol_codes.append(&mut inst_bytes);
-
-                    resolve_jump(
-                        label,
-                        code_offset,
-                        &mut relative_jump_offset,
-                        &mut symbol_codes,
-                    );
+            // .text のラベルはシンボルテーブルに無いので,セクションシンボルからの相対にする
+            let (name, addend) = match text_labels.get(&fixup.symbol) {
+                Some(offset) if !symbols.contains_key(&fixup.symbol) => {
+                    (".text".to_string(), addend + *offset as i64)
                 }
-                Opcode::JLELABEL { label } => {
-                    let mut inst_bytes = inst.to_bytes();
-                    inst_bytes.append(&mut vec![0x00, 0x00, 0x00, 0x00]);
-                    code_offset += inst_bytes.len() as isize;
-                    symbol_codes.append(&mut inst_bytes);
-
-                    resolve_jump(
-                        label,
-                        code_offset,
-                        &mut relative_jump_offset,
-                        &mut symbol_codes,
-                    );
+                _ => (fixup.symbol.to_string(), addend),
+            };
+
+            let (sym_name, _) = symbols.get_index(sym_idx).unwrap();
+            reloc_syms
+                .entry(sym_name.to_string())
+                .or_default()
+                .push(new_rela64(name, fixup.offset as isize, addend, rela_type));
+        }
+
+        symbols[sym_idx].fixups = fixups;
+    }
+}
+
+fn absolute_relocation_type(size: usize) -> u64 {
+    match size {
+        1 => R_X86_64_8,
+        2 => R_X86_64_16,
+        4 => elf_utilities::relocation::R_X86_64_32,
+        _ => R_X86_64_64,
+    }
+}
+
+fn pc_relative_relocation_type(size: usize) -> u64 {
+    match size {
+        1 => R_X86_64_PC8,
+        2 => R_X86_64_PC16,
+        4 => elf_utilities::relocation::R_X86_64_PC32,
+        _ => R_X86_64_PC64,
+    }
+}
+
+/// シンボルごとの機械語と再配置情報,レイアウト後に埋める即値,
+/// 各命令のシンボル先頭からのオフセット(末尾にコード長を含む)
+type TextCodes = IndexMap<String, (Vec<u8>, Vec<RelaSymbol>, Vec<Fixup>, Vec<usize>)>;
+
+/// .text セクション全体をレイアウトして,シンボルごとの機械語と再配置情報を生成する
+/// 関数をまたいだジャンプもレイアウト時に解決する
+/// .text 内のラベルのオフセットも返す
+fn gen_text_section(symbols: &IndexMap<String, Symbol>) -> (TextCodes, IndexMap<String, u64>) {
+    // ラベル -> そのラベルが指す断片のインデックス
+    // グローバルなシンボルは実行時に差し替えられうるので,リンカに解決させる
+    let mut labels: IndexMap<&str, usize> = IndexMap::new();
+    let mut fragments = Vec::new();
+    // 断片の列に現れる順のシンボル名
+    let mut text_symbols = Vec::new();
+
+    for (sym_name, sym) in symbols.iter().filter(|(_, sym)| sym.in_text()) {
+        fragments.push(Fragment::Align(sym.align));
+        let start = fragments.len();
+
+        if sym.is_local() {
+            labels.insert(sym_name, start);
+        }
+
+        for group in sym.groups.iter() {
+            labels.insert(&group.label, fragments.len());
+
+            for inst in group.insts.iter() {
+                fragments.push(gen_fragment(&inst.opcode));
+            }
+        }
+
+        text_symbols.push(sym_name);
+        fragments.push(Fragment::End);
+    }
+
+    relax_jumps(&mut fragments, &labels);
+
+    // 機械語の生成
+    let offsets = fragment_offsets(&fragments);
+    let mut text_symbols = text_symbols.into_iter();
+    let mut text_codes = IndexMap::new();
+
+    let mut sym_start = 0;
+    let mut symbol_codes = Vec::new();
+    let mut relocations = Vec::new();
+    let mut fixups = Vec::new();
+    let mut inst_offsets = Vec::new();
+
+    for (idx, fragment) in fragments.into_iter().enumerate() {
+        match fragment {
+            Fragment::Code(mut codes, relocs, fixups_in_fragment) => {
+                inst_offsets.push(symbol_codes.len());
+                for mut rela in relocs {
+                    let offset_in_fragment = rela.rela64.get_offset() as usize;
+
+                    // .text 内のローカルなラベルへのRIP相対は,ここで解決できる
+                    if let Some(label_idx) = labels.get(rela.name.as_str()) {
+                        let place = offsets[idx] + offset_in_fragment as isize;
+                        let value = offsets[*label_idx] + rela.rela64.get_addend() as isize - place;
+                        codes[offset_in_fragment..offset_in_fragment + 4]
+                            .copy_from_slice(&(value as i32).to_le_bytes());
+                        continue;
+                    }
+
+                    // シンボル先頭からのオフセットにする
+                    rela.rela64
+                        .set_offset(offset_in_fragment as u64 + (offsets[idx] - sym_start) as u64);
+                    relocations.push(rela);
                 }
-                Opcode::JMPLABEL { label } => {
-                    let mut inst_bytes = inst.to_bytes();
-                    inst_bytes.append(&mut vec![0x00, 0x00, 0x00, 0x00]);
-                    code_offset += inst_bytes.len() as isize;
-                    symbol_codes.append(&mut inst_bytes);
-
-                    resolve_jump(
-                        label,
-                        code_offset,
-                        &mut relative_jump_offset,
-                        &mut symbol_codes,
-                    );
+                for mut fixup in fixups_in_fragment {
+                    fixup.offset += (offsets[idx] - sym_start) as usize;
+                    fixups.push(fixup);
                 }
-                _ => {
-                    let mut inst_bytes = inst.to_bytes();
-                    code_offset += inst_bytes.len() as isize;
-                    symbol_codes.append(&mut inst_bytes);
+                symbol_codes.append(&mut codes);
+            }
+            Fragment::Jump(jump) => {
+                inst_offsets.push(symbol_codes.len());
+                let end = offsets[idx + 1];
+
+                match (labels.get(jump.label), jump.short_opcode) {
+                    (Some(label_idx), Some(short_opcode)) if !jump.is_near => {
+                        let relative_offset = offsets[*label_idx] + jump.addend as isize - end;
+                        symbol_codes.extend(short_opcode);
+                        symbol_codes.push(relative_offset as i8 as u8);
+                    }
+                    (Some(label_idx), _) => {
+                        let relative_offset = offsets[*label_idx] + jump.addend as isize - end;
+                        symbol_codes.extend(jump.near_opcode);
+                        symbol_codes.extend(&(relative_offset as i32).to_le_bytes());
+                    }
+                    // レイアウト時に解決できないジャンプは再配置で埋める
+                    (None, _) => {
+                        symbol_codes.extend(jump.near_opcode);
+                        let rela64 = new_rela64(
+                            jump.label.to_string(),
+                            symbol_codes.len() as isize,
+                            -4 + jump.addend,
+                            jump_relocation_type(symbols, jump.label),
+                        );
+                        relocations.push(rela64);
+                        symbol_codes.extend(&[0x00; 4]);
+                    }
                 }
             }
+            Fragment::Align(_) => sym_start = offsets[idx + 1],
+            Fragment::End => {
+                let sym_name = text_symbols.next().unwrap();
+                inst_offsets.push(symbol_codes.len());
+                text_codes.insert(
+                    sym_name.to_string(),
+                    (
+                        std::mem::take(&mut symbol_codes),
+                        std::mem::take(&mut relocations),
+                        std::mem::take(&mut fixups),
+                        std::mem::take(&mut inst_offsets),
+                    ),
+                );
+            }
         }
     }
 
-    (symbol_codes, relocations)
+    let text_labels = labels
+        .iter()
+        .map(|(label, idx)| (label.to_string(), offsets[*idx] as u64))
+        .collect();
+
+    (text_codes, text_labels)
 }
 
-fn resolve_jump(
-    label: &str,
-    length: isize,
-    relative_jump: &mut IndexMap<String, Vec<RelativeJumpSpec>>,
-    sym_codes: &mut Vec<u8>,
-) {
-    if let Some(specs) = relative_jump.get_mut(label) {
-        for spec in specs.iter() {
-            // jump -> jump みたいなものは無視
-            if !spec.is_label {
-                continue;
+/// 未定義のシンボルや関数へのジャンプは PLT 経由,
+/// データセクションのシンボルへは PC 相対で再配置する
+fn jump_relocation_type(symbols: &IndexMap<String, Symbol>, label: &str) -> u64 {
+    match symbols.get(label) {
+        Some(sym) if !sym.in_text() && !sym.is_undefined() => {
+            elf_utilities::relocation::R_X86_64_PC32
+        }
+        _ => elf_utilities::relocation::R_X86_64_PLT32,
+    }
+}
+
+/// 命令を断片に変換する
+/// いくつかの命令は再配置シンボルの生成など，機械語への変換以外にも操作が必要．
+fn gen_fragment(opcode: &Opcode) -> Fragment<'_> {
+    match opcode.operands.as_slice() {
+        // ラベルへの相対ジャンプ( `jmp`, `jcc`, `call` )
+        // call は rel8 の形式を持たない
+        [Operand::LABEL { label, addend }] => {
+            let short_opcode = opcode.relative_opcode(OperandSize::BYTE);
+            Fragment::Jump(Jump {
+                label,
+                addend: *addend,
+                is_near: short_opcode.is_none(),
+                short_opcode,
+                near_opcode: opcode.relative_opcode(OperandSize::DWORD).unwrap(),
+            })
+        }
+        _ => {
+            let inst = Instruction {
+                opcode: opcode.clone(),
+            };
+            let inst_bytes = inst.to_bytes();
+            let mut relocations = Vec::new();
+
+            // RIP相対のdisplacementは再配置で埋める
+            if let Some(Operand::RIPRELATIVE { label, addend }) = opcode.rm_operand() {
+                // displacementは即値の直前に配置される
+                // RIPは命令の末尾を指すので,即値の分もaddendで調整する
+                let imm_length = match opcode.get_immediate() {
+                    Some(imm) => imm.to_bytes().len() as isize,
+                    None => 0,
+                };
+                let disp_offset = inst_bytes.len() as isize - imm_length - 4;
+
+                let rela64 = new_rela64(
+                    label.to_string(),
+                    disp_offset,
+                    -4 - imm_length as i64 + addend,
+                    elf_utilities::relocation::R_X86_64_PC32,
+                );
+                relocations.push(rela64);
+            }
+
+            // シンボルを含む即値は,命令の末尾の imm32
+            // 64bit の形式と `push` では符号拡張される
+            let fixups = opcode
+                .operands
+                .iter()
+                .filter_map(|operand| match operand {
+                    Operand::SYMBOLIC {
+                        symbol,
+                        subtrahend,
+                        addend,
+                    } => Some(Fixup {
+                        offset: inst_bytes.len() - 4,
+                        size: 4,
+                        symbol: symbol.to_string(),
+                        subtrahend: subtrahend.clone(),
+                        addend: *addend,
+                        signed: opcode.form.size() != Some(OperandSize::DWORD),
+                    }),
+                    _ => None,
+                })
+                .collect();
+
+            Fragment::Code(inst_bytes, relocations, fixups)
+        }
+    }
+}
+
+/// 各断片の先頭オフセット(末尾に全体の長さを含む)
+fn fragment_offsets(fragments: &[Fragment]) -> Vec<isize> {
+    let mut offsets: Vec<isize> = vec![0];
+    for fragment in fragments.iter() {
+        let offset = offsets[offsets.len() - 1];
+        let length = match fragment {
+            Fragment::Code(codes, _, _) => codes.len() as isize,
+            Fragment::Jump(jump) => jump.len(),
+            Fragment::Align(align) => {
+                (offset as u64).div_ceil(*align) as isize * *align as isize - offset
             }
+            Fragment::End => 0,
+        };
+        offsets.push(offset + length);
+    }
+
+    offsets
+}
+
+/// すべてのジャンプを rel8 と仮定してレイアウトし,
+/// 変位が収まらないジャンプを rel32 に変更することを,長さが変わらなくなるまで繰り返す.
+/// ジャンプは伸びる方向にしか変化しないので,必ず停止する.
+fn relax_jumps(fragments: &mut [Fragment], labels: &IndexMap<&str, usize>) {
+    loop {
+        let offsets = fragment_offsets(fragments);
+        let mut changed = false;
+
+        for (idx, fragment) in fragments.iter_mut().enumerate() {
+            let jump = match fragment {
+                Fragment::Jump(jump) if !jump.is_near => jump,
+                _ => continue,
+            };
 
-            // 相対オフセットの計算
-            let relative_offset = spec.address - length;
-            for (idx, addr) in (relative_offset as i32).to_le_bytes().iter().enumerate() {
-                sym_codes[idx + (length - 4) as usize] = *addr;
+            let fits_in_rel8 = match labels.get(jump.label) {
+                Some(label_idx) => {
+                    let relative_offset =
+                        offsets[*label_idx] + jump.addend as isize - offsets[idx + 1];
+                    i8::MIN as isize <= relative_offset && relative_offset <= i8::MAX as isize
+                }
+                None => false,
+            };
+
+            if !fits_in_rel8 {
+                jump.is_near = true;
+                changed = true;
             }
         }
 
-        specs.push(RelativeJumpSpec::new_jump(length));
-    } else {
-        // jump系命令がラベルの前に存在した場合
-        relative_jump.insert(label.to_string(), vec![RelativeJumpSpec::new_jump(length)]);
+        if !changed {
+            break;
+        }
     }
 }
 
-fn new_rela64(name: String, offset: isize) -> RelaSymbol {
+/// シンボルのインデックスは setup_relocation() で設定する
+fn new_rela64(name: String, offset: isize, addend: i64, rela_type: u64) -> RelaSymbol {
     let mut rela64: RelaSymbol = Default::default();
-    rela64.rela64.set_addend(-4);
+    rela64.rela64.set_addend(addend);
+    rela64.rela64.set_info(rela_type);
     rela64.name = name;
 
-    // opcode 分スキップ
-    rela64.rela64.set_offset(offset as u64 + 1);
+    rela64.rela64.set_offset(offset as u64);
 
     rela64
 }
+
+#[cfg(test)]
+mod generate_tests {
+    use super::*;
+    use crate::assembler::resource::{
+        Displacement, GeneralPurposeRegister, Group, Immediate, OperandKind,
+    };
+    use OperandKind::*;
+    use OperandSize::*;
+
+    #[test]
+    fn short_jump_test() {
+        let sym = Symbol {
+            groups: vec![
+                new_group(".Lmain_entry", vec![jmp(".L2")]),
+                new_group(".L3", vec![ret()]),
+                new_group(".L2", vec![je(".L3")]),
+            ],
+            ..Default::default()
+        };
+
+        let (codes, _) = gen_symbol_code(sym);
+        assert_eq!(vec![0xeb, 0x01, 0xc3, 0x74, 0xfd], codes);
+    }
+
+    #[test]
+    fn near_jump_test() {
+        // 8バイトの命令 * 16 = 128 バイトは rel8 に収まらない
+        let mov = Opcode::with_form(
+            "mov",
+            &[RegMem(QWORD), Imm(DWORD)],
+            vec![
+                Operand::ADDRESSING {
+                    base: GeneralPurposeRegister::RBP,
+                    index: None,
+                    disp: Some(Displacement::DISP8(-8)),
+                    scale: None,
+                },
+                Operand::Immediate(Immediate::I32(0)),
+            ],
+        )
+        .unwrap();
+        let sym = Symbol {
+            groups: vec![
+                new_group(".Lmain_entry", vec![jle(".L2")]),
+                new_group(".L1", vec![mov; 16]),
+                new_group(".L2", vec![jmp(".L1"), jmp(".L2")]),
+            ],
+            ..Default::default()
+        };
+
+        let (codes, _) = gen_symbol_code(sym);
+        assert_eq!(
+            vec![0x0f, 0x8e, 0x80, 0x00, 0x00, 0x00],
+            codes[..6].to_vec()
+        );
+        // 後方へのジャンプ
+        assert_eq!(vec![0xe9, 0x7b, 0xff, 0xff, 0xff], codes[134..139].to_vec());
+        assert_eq!(vec![0xeb, 0xf9], codes[139..].to_vec());
+    }
+
+    #[test]
+    fn cross_symbol_jump_test() {
+        // ローカルな関数への末尾呼び出しと,他の関数内のラベルへのジャンプ
+        let mut symbols = IndexMap::new();
+        symbols.insert(
+            "foo".to_string(),
+            Symbol {
+                groups: vec![
+                    new_group(".Lfoo_entry", vec![jmp("bar")]),
+                    new_group(".L1", vec![ret()]),
+                ],
+                ..Default::default()
+            },
+        );
+        symbols.insert(
+            "bar".to_string(),
+            Symbol {
+                groups: vec![new_group(
+                    ".Lbar_entry",
+                    vec![je(".L1"), call("foo"), ret()],
+                )],
+                ..Default::default()
+            },
+        );
+
+        let (text_codes, _) = gen_text_section(&symbols);
+
+        // シンボルの間にパディングは入らない
+        let (foo_codes, foo_relocs, _, _) = &text_codes["foo"];
+        assert_eq!(&vec![0xeb, 0x01, 0xc3], foo_codes);
+        assert!(foo_relocs.is_empty());
+
+        let (bar_codes, bar_relocs, _, _) = &text_codes["bar"];
+        assert_eq!(
+            &vec![0x74, 0xfd, 0xe8, 0xf6, 0xff, 0xff, 0xff, 0xc3],
+            bar_codes
+        );
+        assert!(bar_relocs.is_empty());
+    }
+
+    #[test]
+    fn external_jump_test() {
+        // グローバルなシンボルや未定義のシンボルはリンカが解決する
+        let mut symbols = IndexMap::new();
+        let mut main = Symbol {
+            groups: vec![new_group(
+                ".Lmain_entry",
+                vec![call("printf"), jne("main"), jmp("exit")],
+            )],
+            ..Default::default()
+        };
+        main.as_global();
+        symbols.insert("main".to_string(), main);
+
+        let (text_codes, _) = gen_text_section(&symbols);
+        let (codes, relocs, _, _) = &text_codes["main"];
+
+        assert_eq!(
+            &vec![
+                0xe8, 0x00, 0x00, 0x00, 0x00, 0x0f, 0x85, 0x00, 0x00, 0x00, 0x00, 0xe9, 0x00, 0x00,
+                0x00, 0x00
+            ],
+            codes
+        );
+
+        let relocs: Vec<(&str, u64, i64, u64)> = relocs
+            .iter()
+            .map(|rela| {
+                (
+                    rela.name.as_str(),
+                    rela.rela64.get_offset(),
+                    rela.rela64.get_addend(),
+                    rela.rela64.get_type(),
+                )
+            })
+            .collect();
+        let plt32 = elf_utilities::relocation::R_X86_64_PLT32;
+        assert_eq!(
+            vec![
+                ("printf", 1, -4, plt32),
+                ("main", 7, -4, plt32),
+                ("exit", 12, -4, plt32)
+            ],
+            relocs
+        );
+    }
+
+    #[test]
+    fn jump_with_addend_test() {
+        // `jmp .L2+1`, `jmp bar+4`
+        let jmp_with_addend = |label: &str, addend: i64| {
+            Opcode::with_form(
+                "jmp",
+                &[Rel(DWORD)],
+                vec![Operand::LABEL {
+                    label: label.to_string(),
+                    addend,
+                }],
+            )
+            .unwrap()
+        };
+        let sym = Symbol {
+            groups: vec![
+                new_group(
+                    ".Lmain_entry",
+                    vec![jmp_with_addend(".L2", 1), jmp_with_addend("bar", 4)],
+                ),
+                new_group(".L2", vec![ret(), ret()]),
+            ],
+            ..Default::default()
+        };
+
+        let (codes, relocs) = gen_symbol_code(sym);
+        assert_eq!(
+            vec![0xeb, 0x06, 0xe9, 0x00, 0x00, 0x00, 0x00, 0xc3, 0xc3],
+            codes
+        );
+
+        assert_eq!(1, relocs.len());
+        assert_eq!("bar", relocs[0].name);
+        assert_eq!(3, relocs[0].rela64.get_offset());
+        assert_eq!(0, relocs[0].rela64.get_addend());
+    }
+
+    #[test]
+    fn add_undefined_symbols_test() {
+        let mut symbols = IndexMap::new();
+        symbols.insert(
+            "main".to_string(),
+            Symbol {
+                groups: vec![new_group(".Lmain_entry", vec![call("printf")])],
+                ..Default::default()
+            },
+        );
+
+        generate_main(&mut symbols);
+
+        let printf = &symbols["printf"];
+        assert!(printf.is_undefined());
+        assert_eq!(elf_utilities::symbol::Bind::Global, printf.bind);
+    }
+
+    #[test]
+    fn resolve_fixups_test() {
+        let mut symbols = IndexMap::new();
+        symbols.insert(
+            "main".to_string(),
+            Symbol {
+                groups: vec![
+                    new_group(".Lmain_entry", vec![ret()]),
+                    new_group(".L2", vec![ret(), ret()]),
+                    new_group(".L3", vec![ret()]),
+                ],
+                ..Default::default()
+            },
+        );
+        symbols.insert(
+            "table".to_string(),
+            Symbol {
+                section: ".rodata".to_string(),
+                codes: vec![0x00; 20],
+                fixups: vec![
+                    new_fixup(0, 4, ".L3", Some(".L2"), 1),
+                    new_fixup(4, 8, ".L2", None, 0),
+                    new_fixup(12, 4, "printf", Some("table"), 0),
+                    new_fixup(16, 4, "table", None, 8),
+                ],
+                ..Default::default()
+            },
+        );
+
+        let reloc_syms = generate_main(&mut symbols);
+
+        // .L3 - .L2 + 1
+        assert_eq!(vec![0x03, 0x00, 0x00, 0x00], symbols["table"].codes[..4]);
+
+        let relocs: Vec<(&str, u64, i64, u64)> = reloc_syms["table"]
+            .iter()
+            .map(|rela| {
+                (
+                    rela.name.as_str(),
+                    rela.rela64.get_offset(),
+                    rela.rela64.get_addend(),
+                    rela.rela64.get_type(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                (".text", 4, 1, R_X86_64_64),
+                ("printf", 12, 12, elf_utilities::relocation::R_X86_64_PC32),
+                ("table", 16, 8, elf_utilities::relocation::R_X86_64_32),
+            ],
+            relocs
+        );
+        assert!(symbols["printf"].is_undefined());
+    }
+
+    #[test]
+    fn symbolic_immediate_test() {
+        // movq $sym+4, %rax
+        // .L1: ret
+        // .L2: movl $.L2-.L1, %eax
+        //      movl $sym, %eax
+        let mov = |size: OperandSize, reg, symbol: &str, subtrahend: Option<&str>, addend| {
+            Opcode::with_form(
+                "mov",
+                &[RegMem(size), Imm(DWORD)],
+                vec![
+                    Operand::GENERALREGISTER(reg),
+                    Operand::SYMBOLIC {
+                        symbol: symbol.to_string(),
+                        subtrahend: subtrahend.map(str::to_string),
+                        addend,
+                    },
+                ],
+            )
+            .unwrap()
+        };
+        let mut symbols = IndexMap::new();
+        symbols.insert(
+            "main".to_string(),
+            Symbol {
+                groups: vec![
+                    new_group(
+                        ".Lmain_entry",
+                        vec![mov(QWORD, GeneralPurposeRegister::RAX, "sym", None, 4)],
+                    ),
+                    new_group(".L1", vec![ret()]),
+                    new_group(
+                        ".L2",
+                        vec![
+                            mov(DWORD, GeneralPurposeRegister::EAX, ".L2", Some(".L1"), 0),
+                            mov(DWORD, GeneralPurposeRegister::EAX, "sym", None, 0),
+                        ],
+                    ),
+                ],
+                ..Default::default()
+            },
+        );
+
+        let reloc_syms = generate_main(&mut symbols);
+
+        assert_eq!(
+            vec![
+                0x48, 0xc7, 0xc0, 0x00, 0x00, 0x00, 0x00, 0xc3, 0xc7, 0xc0, 0x01, 0x00, 0x00, 0x00,
+                0xc7, 0xc0, 0x00, 0x00, 0x00, 0x00
+            ],
+            symbols["main"].codes[..20]
+        );
+
+        let relocs: Vec<(&str, u64, i64, u64)> = reloc_syms["main"]
+            .iter()
+            .map(|rela| {
+                (
+                    rela.name.as_str(),
+                    rela.rela64.get_offset(),
+                    rela.rela64.get_addend(),
+                    rela.rela64.get_type(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                ("sym", 3, 4, R_X86_64_32S),
+                ("sym", 16, 0, elf_utilities::relocation::R_X86_64_32),
+            ],
+            relocs
+        );
+        assert!(symbols["sym"].is_undefined());
+    }
+
+    fn new_fixup(
+        offset: usize,
+        size: usize,
+        symbol: &str,
+        subtrahend: Option<&str>,
+        addend: i64,
+    ) -> Fixup {
+        Fixup {
+            offset,
+            size,
+            symbol: symbol.to_string(),
+            subtrahend: subtrahend.map(str::to_string),
+            addend,
+            signed: false,
+        }
+    }
+
+    /// main だけからなる .text を生成する
+    fn gen_symbol_code(sym: Symbol) -> (Vec<u8>, Vec<RelaSymbol>) {
+        let mut symbols = IndexMap::new();
+        symbols.insert("main".to_string(), sym);
+
+        let (codes, relocs, _, _) = gen_text_section(&symbols).0.swap_remove("main").unwrap();
+        (codes, relocs)
+    }
+
+    fn new_group(label: &str, opcodes: Vec<Opcode>) -> Group {
+        let mut group = Group::new(label);
+        group.insts = opcodes
+            .into_iter()
+            .map(|opcode| Instruction { opcode })
+            .collect();
+        group
+    }
+
+    fn ret() -> Opcode {
+        Opcode::with_form("ret", &[], vec![]).unwrap()
+    }
+
+    fn jmp(label: &str) -> Opcode {
+        relative("jmp", label)
+    }
+
+    fn call(label: &str) -> Opcode {
+        relative("call", label)
+    }
+
+    fn jne(label: &str) -> Opcode {
+        relative("jne", label)
+    }
+
+    fn je(label: &str) -> Opcode {
+        relative("je", label)
+    }
+
+    fn jle(label: &str) -> Opcode {
+        relative("jle", label)
+    }
+
+    fn relative(mnemonic: &str, label: &str) -> Opcode {
+        Opcode::with_form(
+            mnemonic,
+            &[Rel(DWORD)],
+            vec![Operand::LABEL {
+                label: label.to_string(),
+                addend: 0,
+            }],
+        )
+        .unwrap()
+    }
+}
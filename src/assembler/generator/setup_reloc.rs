@@ -6,35 +6,33 @@ use indexmap::IndexMap;
 pub fn setup_relocation(
     symbols: &IndexMap<String, Symbol>,
     reloc_syms: &mut IndexMap<String, Vec<RelaSymbol>>,
+    symbol_indices: &IndexMap<String, usize>,
 ) {
-    let mut current_offset = 0;
-
     for (sym_name, sym) in symbols.iter() {
         if let Some(relocations) = reloc_syms.get_mut(sym_name) {
             for rela in relocations.iter_mut() {
                 // シンボル内でのオフセットからテーブル全体でのオフセットに
                 let offset_in_symbol = rela.rela64.get_offset();
-                rela.rela64.set_offset(offset_in_symbol + current_offset);
-
-                // 呼び出された名前(再配置対象)と一致しなければ関係ない
-                // eprintln!("{} == {}", rela.name, sym_name);
-                // if &rela.name != sym_name {
-                //     continue;
-                // }
+                rela.rela64.set_offset(offset_in_symbol + sym.value);
 
-                // NULL シンボル + セクションシンボル数のことを考えて+2する
+                // .L から始まるシンボルはシンボルテーブルに無いので,
+                // セクションシンボルからの相対で参照する
                 // 存在しない場合はリンカがあとから関連付けるので，0としておく．
-                let relation_idx = match symbols.get_index_of(&rela.name) {
-                    Some(callee_idx) => callee_idx as u64 + 2,
-                    None => 0,
+                let (relation_idx, addend) = match symbols.get(&rela.name) {
+                    Some(callee) if Symbol::is_local_label(&rela.name) => {
+                        (symbol_indices.get(&callee.section), callee.value as i64)
+                    }
+                    _ => (symbol_indices.get(&rela.name), 0),
                 };
+                let relation_idx = relation_idx.copied().unwrap_or(0) as u64;
+                rela.rela64.set_addend(rela.rela64.get_addend() + addend);
+
                 // シンボルテーブルのインデックスはr_infoのうち上位32bitを使う
+                // 再配置タイプはコード生成時に設定済み
                 let relation_idx = relation_idx << 32;
-                rela.rela64
-                    .set_info(relation_idx + elf_utilities::relocation::R_X86_64_PLT32);
+                let rela_type = rela.rela64.get_type();
+                rela.rela64.set_info(relation_idx + rela_type);
             }
         }
-
-        current_offset += sym.codes.len() as u64;
     }
 }
@@ -0,0 +1,357 @@
+use crate::assembler::parser::SourceFile;
+use crate::assembler::resource::{RelaSymbol, Symbol};
+use indexmap::map::IndexMap;
+use std::fmt;
+
+/// 1行に表示するバイト数
+const BYTES_PER_ROW: usize = 8;
+/// 1つのバイト列を表示する行数の上限( `.zero 4096` 等 )
+/// GNU as の `--listing-cont-lines` の既定値と同じく,継続行は4行まで
+const MAX_ROWS: usize = 5;
+/// バイト列の桁幅( 再配置の `[]` を含む )
+const BYTES_WIDTH: usize = 20;
+
+/// an assembly listing, like GNU as's `-al`.
+///
+/// each line of the source file is shown with the offset in its section and the bytes it emitted.
+/// the fields filled by the linker are enclosed in brackets and followed by their relocations.
+/// the bytes emitted by macros are shown at the line which invoked them.
+/// the lines of a file read by `.include` follow the `.include` line with their own line numbers.
+///
+/// # Examples
+///
+/// ```
+/// use asmpeach::*;
+///
+/// let s = "main:
+///     call foo
+///     ret"
+///     .to_string();
+/// let mut options = Options::new(Syntax::ATANDT);
+/// options.listing = true;
+///
+/// let listing = assemble_code_with_options(s, &options).unwrap().listing.unwrap();
+/// assert_eq!(5, listing.lines[1].chunks[0].bytes.len());
+/// assert_eq!(
+///     "   2 0000 E8[00000000]             call foo",
+///     listing.lines[1].to_string().lines().next().unwrap()
+/// );
+/// ```
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub struct Listing {
+    pub lines: Vec<ListingLine>,
+}
+
+/// a line of the source file(or an `.include`d file) and the bytes it emitted.
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub struct ListingLine {
+    /// the name of the file which contains the line.
+    pub file: String,
+    /// 1始まりの行番号
+    pub line: usize,
+    pub text: String,
+    /// 出力した順のバイト列( セクションを切り替えた行では複数になりうる )
+    pub chunks: Vec<ListingChunk>,
+}
+
+/// bytes emitted into a section by a line.
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub struct ListingChunk {
+    pub section: String,
+    /// offset in the section
+    pub offset: u64,
+    /// 再配置で埋める部分は0
+    pub bytes: Vec<u8>,
+    pub relocations: Vec<ListingRelocation>,
+}
+
+/// a field in the bytes of a chunk filled by the linker.
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub struct ListingRelocation {
+    /// offset in the bytes of the chunk
+    pub offset: usize,
+    pub size: usize,
+    /// the name of the relocation type(e.g. `R_X86_64_PLT32`)
+    pub rela_type: &'static str,
+    pub symbol: String,
+    pub addend: i64,
+}
+
+impl Listing {
+    /// 再配置のオフセットがシンボル先頭からの値のうちに( setup_relocation() の前に )作る
+    pub(crate) fn new(
+        sources: &[SourceFile],
+        symbols: &IndexMap<String, Symbol>,
+        reloc_syms: &IndexMap<String, Vec<RelaSymbol>>,
+    ) -> Self {
+        let mut lines = Vec::new();
+        // (ファイル, 行番号) -> `lines` の添字
+        let mut line_indices = IndexMap::new();
+        push_source_lines(sources, 0, &mut lines, &mut line_indices);
+
+        for (sym_name, sym) in symbols.iter() {
+            let relocs = reloc_syms
+                .get(sym_name)
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+
+            for source_line in sym.lines.iter() {
+                let mut relocations: Vec<ListingRelocation> = relocs
+                    .iter()
+                    .filter(|rela| {
+                        let offset = rela.rela64.get_offset() as usize;
+                        source_line.start <= offset && offset < source_line.end
+                    })
+                    .map(|rela| ListingRelocation {
+                        offset: rela.rela64.get_offset() as usize - source_line.start,
+                        size: rela.field_size(),
+                        rela_type: rela.type_name(),
+                        symbol: rela.name.to_string(),
+                        addend: rela.rela64.get_addend(),
+                    })
+                    .collect();
+                relocations.sort_by_key(|rela| rela.offset);
+
+                let line = match line_indices.get(&(source_line.source, source_line.line)) {
+                    Some(idx) => &mut lines[*idx],
+                    None => continue,
+                };
+                line.chunks.push(ListingChunk {
+                    section: sym.section.to_string(),
+                    offset: sym.value + source_line.start as u64,
+                    bytes: sym.codes[source_line.start..source_line.end].to_vec(),
+                    relocations,
+                });
+            }
+        }
+
+        Self { lines }
+    }
+}
+
+impl ListingChunk {
+    /// 表示する行ごとの( バイト列の範囲, 16進表記 )
+    /// 再配置で埋める部分は `[]` で囲み,行をまたがないようにする
+    fn rows(&self) -> Vec<(usize, usize, String)> {
+        let mut rows = Vec::new();
+        let mut row_start = 0;
+        let mut row = String::new();
+        let mut pos = 0;
+
+        while pos < self.bytes.len() {
+            let (length, token) = match self.relocations.iter().find(|rela| rela.offset == pos) {
+                Some(rela) => {
+                    let end = (pos + rela.size).min(self.bytes.len());
+                    (end - pos, format!("[{}]", hex(&self.bytes[pos..end])))
+                }
+                None => (1, hex(&self.bytes[pos..pos + 1])),
+            };
+
+            if pos > row_start && pos + length - row_start > BYTES_PER_ROW {
+                rows.push((row_start, pos, std::mem::take(&mut row)));
+                row_start = pos;
+            }
+            row.push_str(&token);
+            pos += length;
+        }
+        rows.push((row_start, pos, row));
+
+        rows
+    }
+}
+
+impl fmt::Display for Listing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for line in self.lines.iter() {
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ListingLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.chunks.is_empty() {
+            return write!(
+                f,
+                "{:>4} {:4} {:BYTES_WIDTH$} {}",
+                self.line, "", "", self.text
+            );
+        }
+
+        let mut rows = Vec::new();
+        for chunk in self.chunks.iter() {
+            let mut shown = 0;
+            for (start, end, bytes) in chunk.rows().into_iter().take(MAX_ROWS) {
+                let offset = chunk.offset + start as u64;
+                rows.push(format!("{:04X} {:BYTES_WIDTH$}", offset, bytes));
+                shown = end;
+            }
+
+            for rela in chunk.relocations.iter().filter(|rela| rela.offset < shown) {
+                let offset = chunk.offset + rela.offset as u64;
+                rows.push(format!(
+                    "{:04X} {:BYTES_WIDTH$} {} {}{}",
+                    offset,
+                    "",
+                    rela.rela_type,
+                    rela.symbol,
+                    addend_to_string(rela.addend)
+                ));
+            }
+        }
+
+        // 行番号とソースは最初の行にだけ表示する
+        let mut rows = rows.into_iter();
+        let first = rows.next().unwrap();
+        write!(f, "{:>4} {} {}", self.line, first, self.text)?;
+        for row in rows {
+            write!(f, "\n{:4} {}", "", row.trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+/// `source` の各行を並べる
+/// GNU as と同じく, `.include` の行の直後に読み込んだファイルの行を並べる
+fn push_source_lines(
+    sources: &[SourceFile],
+    source: usize,
+    lines: &mut Vec<ListingLine>,
+    line_indices: &mut IndexMap<(usize, usize), usize>,
+) {
+    for (idx, text) in sources[source].text.lines().enumerate() {
+        line_indices.insert((source, idx + 1), lines.len());
+        lines.push(ListingLine {
+            file: sources[source].name.to_string(),
+            line: idx + 1,
+            text: text.to_string(),
+            chunks: Vec::new(),
+        });
+
+        let included = (0..sources.len()).filter(|included| {
+            sources[*included].parent == Some(source) && sources[*included].included_at == idx + 1
+        });
+        for included in included {
+            push_source_lines(sources, included, lines, line_indices);
+        }
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02X}", b)).collect()
+}
+
+/// `objdump -r` と同じく, `foo-0x4` のように表示する
+fn addend_to_string(addend: i64) -> String {
+    match addend {
+        0 => String::new(),
+        _ if addend < 0 => format!("-{:#x}", addend.unsigned_abs()),
+        _ => format!("+{:#x}", addend),
+    }
+}
+
+#[cfg(test)]
+mod listing_tests {
+    use crate::assembler::{assemble_code_with_options, Options, Syntax};
+
+    #[test]
+    fn listing_test() {
+        let s = "    .globl main
+main:
+    call foo
+    leaq .LC0(%rip), %rax
+    ret
+    .data
+.LC0:
+    .quad main+8
+    .zero 10"
+            .to_string();
+        let mut options = Options::new(Syntax::ATANDT);
+        options.listing = true;
+
+        let listing = assemble_code_with_options(s, &options)
+            .unwrap()
+            .listing
+            .unwrap();
+        let expected = "   1                               .globl main
+   2                           main:
+   3 0000 E8[00000000]             call foo
+     0001                      R_X86_64_PLT32 foo-0x4
+   4 0005 488D05[00000000]         leaq .LC0(%rip), %rax
+     0008                      R_X86_64_PC32 .LC0-0x4
+   5 000C C3                       ret
+   6                               .data
+   7                           .LC0:
+   8 0000 [0000000000000000]       .quad main+8
+     0000                      R_X86_64_64 main+0x8
+   9 0008 0000000000000000         .zero 10
+     0010 0000
+";
+        assert_eq!(expected, listing.to_string());
+    }
+
+    #[test]
+    fn macro_listing_test() {
+        // マクロが出力したバイト列は,呼び出した行に表示する
+        let s = "    .macro twice insn
+    \\insn
+    \\insn
+    .endm
+main:
+    twice ret"
+            .to_string();
+        let mut options = Options::new(Syntax::ATANDT);
+        options.listing = true;
+
+        let listing = assemble_code_with_options(s, &options)
+            .unwrap()
+            .listing
+            .unwrap();
+        assert!(listing.lines[..5].iter().all(|line| line.chunks.is_empty()));
+        assert_eq!(vec![0xc3, 0xc3], listing.lines[5].chunks[0].bytes);
+    }
+
+    #[test]
+    fn include_listing_test() {
+        // `.include` で読み込んだ行は,そのファイルの行番号で `.include` の直後に表示する
+        let dir = std::env::temp_dir().join("asmpeach_include_listing_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("inc1.s"), "    pushq %rbp\n    popq %rbp\n").unwrap();
+
+        let s = "main:
+    .include \"inc1.s\"
+    ret
+    .data
+x:
+    .fill 2, 1, 7"
+            .to_string();
+        let mut options = Options::new(Syntax::ATANDT);
+        options.listing = true;
+        options.add_include_path(dir.to_str().unwrap());
+
+        let listing = assemble_code_with_options(s, &options)
+            .unwrap()
+            .listing
+            .unwrap();
+        let expected = "   1                           main:
+   2                               .include \"inc1.s\"
+   1 0000 55                       pushq %rbp
+   2 0001 5D                       popq %rbp
+   3 0002 C3                       ret
+   4                               .data
+   5                           x:
+   6 0000 0707                     .fill 2, 1, 7
+";
+        assert_eq!(expected, listing.to_string());
+        assert!(listing.lines[2].file.ends_with("inc1.s"));
+        assert_eq!("<input>", listing.lines[4].file);
+    }
+
+    #[test]
+    fn without_listing_test() {
+        let options = Options::new(Syntax::ATANDT);
+        let assembled = assemble_code_with_options("main:\n    ret".to_string(), &options);
+        assert!(assembled.unwrap().listing.is_none());
+    }
+}
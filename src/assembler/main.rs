@@ -1,17 +1,44 @@
-use crate::assembler::resource::{RelaSymbol, Symbol};
+use crate::assembler::resource::{CustomSection, RelaSymbol, Symbol, SHF_WRITE};
 use crate::assembler::{
     generator, parser,
     resource::{ELFBuilder, Syntax},
+    AsmError, Listing, Options,
 };
 use indexmap::map::IndexMap;
 use std::fs;
 
-type ELFOrError = Result<elf_utilities::file::ELF64Dumper, Box<dyn std::error::Error>>;
+/// an object file, or all errors found in the assembly code.
+type ELFOrError = Result<elf_utilities::file::ELF64Dumper, Vec<AsmError>>;
+
+/// the file name used in errors when the assembly code isn't read from a file.
+const NO_FILE_NAME: &str = "<input>";
+
+/// an object file and the warnings found while assembling.
+pub struct Assembled {
+    pub elf: elf_utilities::file::ELF64Dumper,
+    pub warnings: Vec<AsmError>,
+    /// the listing if `Options::listing` is set.
+    pub listing: Option<Listing>,
+}
+
+/// an object file with warnings, or all errors(and warnings) found in the assembly code.
+type AssembledOrError = Result<Assembled, Vec<AsmError>>;
 
 /// translate assembly file into object file
 pub fn assemble_file(input_file: &str, syntax: Syntax) -> ELFOrError {
-    let source = fs::read_to_string(input_file)?;
-    assemble(source, syntax)
+    assemble_file_with_options(input_file, &Options::new(syntax)).map(|assembled| assembled.elf)
+}
+
+/// translate assembly file into object file with `options`.
+/// warnings are returned with the object file.
+pub fn assemble_file_with_options(input_file: &str, options: &Options) -> AssembledOrError {
+    let source = fs::read_to_string(input_file).map_err(|e| {
+        vec![AsmError::without_location(
+            input_file,
+            format!("cannot read file: {}", e),
+        )]
+    })?;
+    assemble(source, input_file, options)
 }
 
 /// translate assembly code into object file.
@@ -31,23 +58,63 @@ pub fn assemble_file(input_file: &str, syntax: Syntax) -> ELFOrError {
 ///     ret"
 ///     .to_string();
 /// let elf_builder = assemble_code(s, Syntax::ATANDT).unwrap();
-/// elf_builder.generate_elf_file("obj.o", 0o644);
+/// let obj_file = std::env::temp_dir().join("asmpeach_doctest.o");
+/// elf_builder.generate_elf_file(obj_file.to_str().unwrap(), 0o644).unwrap();
+/// ```
+///
+/// all errors in the code are reported at once.
+///
+/// ```
+/// use asmpeach::*;
+///
+/// let s = "main:
+///     movq $42, %rax
+///     foo %rax
+///     popq %xyz"
+///     .to_string();
+/// let errors = assemble_code(s, Syntax::ATANDT).err().unwrap();
+/// assert_eq!(2, errors.len());
+/// assert_eq!(3, errors[0].line);
+/// assert_eq!(10, errors[1].column);
 /// ```
 pub fn assemble_code(assembly_code: String, syntax: Syntax) -> ELFOrError {
-    assemble(assembly_code, syntax)
+    assemble_code_with_options(assembly_code, &Options::new(syntax)).map(|assembled| assembled.elf)
 }
 
-fn assemble(source: String, syntax: Syntax) -> ELFOrError {
-    let mut symbols = match syntax {
-        Syntax::INTEL => unimplemented!(),
-        Syntax::ATANDT => parser::parse_atandt(source),
-    };
+/// translate assembly code into object file with `options`.
+///
+/// # Examples
+///
+/// ```
+/// use asmpeach::*;
+///
+/// let s = "    .ifdef DEBUG
+///     .warning \"debug build\"
+///     .endif
+/// main:
+///     ret"
+///     .to_string();
+/// let mut options = Options::new(Syntax::ATANDT);
+/// options.define_symbol("DEBUG", 1);
+///
+/// let assembled = assemble_code_with_options(s, &options).unwrap();
+/// assert_eq!(1, assembled.warnings.len());
+/// assert_eq!("<input>:2:5: warning: debug build", assembled.warnings[0].to_string());
+/// ```
+pub fn assemble_code_with_options(assembly_code: String, options: &Options) -> AssembledOrError {
+    assemble(assembly_code, NO_FILE_NAME, options)
+}
+
+fn assemble(source: String, file_name: &str, options: &Options) -> AssembledOrError {
+    let (mut symbols, sections, sources, warnings) = parser::parse(source, file_name, options)?;
 
     // コード生成
     // この時点で再配置シンボルが定義される
     let mut reloc_syms = generator::generate_main(&mut symbols);
-    // 再配置テーブルを探索して，シンボルテーブル内に該当するエントリがあれば再配置シンボルを更新する
-    generator::setup_relocation(&symbols, &mut reloc_syms);
+    // 再配置のオフセットは,まだシンボル先頭からの値
+    let listing = options
+        .listing
+        .then(|| Listing::new(&sources, &symbols, &reloc_syms));
 
     let mut builder = ELFBuilder::new();
 
@@ -55,32 +122,33 @@ fn assemble(source: String, syntax: Syntax) -> ELFOrError {
     builder.add_section(elf_utilities::section::Section64::new_null_section());
     // .text セクション
     builder.add_text_section(&symbols);
+    // .data, .bss, .rodata セクションと, `.section` で登録したセクション
+    builder.add_data_sections(&symbols, &sections);
     // .symtab セクション
     builder.add_symbol_table_section(&symbols);
     // .strtab セクション
     builder.add_symtab_string_section(&symbols);
-    // .rela.text セクション
-    builder.add_relatext_section(&reloc_syms);
-    // .rodata セクション
-    // object_file_builder.add_rodata_section(&symbols);
+    // 再配置テーブルを探索して，シンボルテーブル内に該当するエントリがあれば再配置シンボルを更新する
+    generator::setup_relocation(&symbols, &mut reloc_syms, builder.symbol_indices());
+    // .rela.text, .rela.data 等のセクション
+    builder.add_rela_sections(&symbols, &sections, &reloc_syms);
     // .shstrtab セクション
     builder.add_shstrtab_string_section();
 
     // ヘッダの調整
     builder.condition_elf_header();
 
-    Ok(elf_utilities::file::ELF64Dumper::new(builder.give_file()))
+    Ok(Assembled {
+        elf: elf_utilities::file::ELF64Dumper::new(builder.give_file()),
+        warnings,
+        listing,
+    })
 }
 
 impl ELFBuilder {
     fn add_text_section(&mut self, symbols: &IndexMap<String, Symbol>) {
         // すべてのシンボルのコードを結合する
-        let mut all_symbol_codes: Vec<u8> = Vec::new();
-
-        for (_name, sym) in symbols.iter() {
-            let mut symbol_codes = sym.codes.clone();
-            all_symbol_codes.append(&mut symbol_codes);
-        }
+        let (all_symbol_codes, _align) = Self::section_bytes(symbols, ".text");
 
         // .textセクションの生成
         let text_shdr = self.init_text_section_header(all_symbol_codes.len());
@@ -91,52 +159,136 @@ impl ELFBuilder {
         self.add_section(text_section);
     }
 
+    fn add_data_sections(
+        &mut self,
+        symbols: &IndexMap<String, Symbol>,
+        sections: &IndexMap<String, CustomSection>,
+    ) {
+        let write_alloc = elf_utilities::section::SHF_ALLOC | SHF_WRITE;
+        let progbits = elf_utilities::section::Type::ProgBits.to_bytes();
+        let nobits = elf_utilities::section::Type::NoBits.to_bytes();
+
+        self.add_data_section(symbols, ".data", progbits, write_alloc);
+        self.add_data_section(symbols, ".bss", nobits, write_alloc);
+
+        // .rodata は使われている場合のみ生成する
+        if symbols.values().any(|sym| sym.section == ".rodata") {
+            let flags = elf_utilities::section::SHF_ALLOC;
+            self.add_data_section(symbols, ".rodata", progbits, flags);
+        }
+
+        // `.note.GNU-stack` のように,空でも出力する
+        for (name, section) in sections.iter() {
+            self.add_data_section(symbols, name, section.ty, section.flags);
+
+            let index = self.section_index(name).unwrap();
+            self.file.sections[index].header.sh_entsize = section.entsize;
+        }
+    }
+
+    fn add_data_section(
+        &mut self,
+        symbols: &IndexMap<String, Symbol>,
+        name: &str,
+        ty: elf_utilities::Elf64Word,
+        flags: elf_utilities::Elf64Xword,
+    ) {
+        let (bytes, align) = Self::section_bytes(symbols, name);
+        let mut shdr = self.init_data_section_header(bytes.len(), align, flags);
+        shdr.sh_type = ty;
+        let mut section = elf_utilities::section::Section64::new(name.to_string(), shdr);
+
+        // .bss はファイル上にバイト列を持たない
+        if shdr.get_type() == elf_utilities::section::Type::NoBits {
+            section.bytes = Some(Vec::new());
+        } else {
+            section.bytes = Some(bytes);
+        }
+
+        self.add_section(section);
+    }
+
+    /// セクションに属するシンボルのバイト列を,オフセットに従って配置する
+    /// セクションのアラインメントも返す
+    fn section_bytes(symbols: &IndexMap<String, Symbol>, section: &str) -> (Vec<u8>, u64) {
+        let mut bytes = Vec::new();
+        let mut align = 1;
+        // .text のアラインメント調整は,直前の関数から続けて実行されうるので nop で埋める
+        let fill = match section {
+            ".text" => 0x90,
+            _ => 0x00,
+        };
+
+        for (_name, sym) in symbols.iter().filter(|(_, sym)| sym.section == section) {
+            bytes.resize(sym.value as usize, fill);
+            bytes.extend_from_slice(&sym.codes);
+            align = align.max(sym.align);
+        }
+
+        (bytes, align)
+    }
+
     fn add_symbol_table_section(&mut self, symbols: &IndexMap<String, Symbol>) {
-        // NULLシンボル + .textシンボル (+ .rodataシンボル)
-        let mut elf_symbols = vec![
-            elf_utilities::symbol::Symbol64::new_null_symbol(),
-            self.create_section_symbol(1),
-        ];
+        let mut elf_symbols = vec![elf_utilities::symbol::Symbol64::new_null_symbol()];
+
+        // セクションシンボル( この時点で追加済みのセクションは,内容を持つセクションのみ )
+        let section_names: Vec<String> = self
+            .section_names()
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+        for section_name in section_names.iter() {
+            let shndx = self.section_index(section_name).unwrap();
+            self.register_symbol(section_name, elf_symbols.len());
+            elf_symbols.push(self.create_section_symbol(shndx as u16));
+        }
 
         // シンボルを走査する
         // name_indexの操作も行う.
-        // また,各シンボルのオフセットも計算する.
         let mut symbol_name_index: elf_utilities::Elf64Word = 1; // 最初のnull文字を飛ばす
-        let mut symbol_offset: elf_utilities::Elf64Addr = 0; // st_value用
 
-        for (symbol_name, symbol_info) in symbols.iter() {
-            let symbol_code_length = symbol_info.codes.len();
-            let symbol_name_length = symbol_name.len();
+        for (symbol_name, symbol_info) in Self::symbol_table_entries(symbols) {
+            let mut elf_symbol = self.create_symbol(symbol_name_index, symbol_info);
+            elf_symbol.symbol_name = Some(symbol_name.to_string());
 
-            let mut global_symbol = self.create_global_symbol(
-                symbol_name_index,
-                symbol_code_length as u64,
-                symbol_offset,
-            );
-            global_symbol.symbol_name = Some(symbol_name.to_string());
-            elf_symbols.push(global_symbol);
+            self.register_symbol(symbol_name, elf_symbols.len());
+            elf_symbols.push(elf_symbol);
 
             // シンボル名を指すインデックスの更新( null byte を見越して+1する)
-            symbol_name_index += symbol_name_length as elf_utilities::Elf64Word + 1;
-
-            // オフセットの更新
-            // 後ろのシンボルのオフセット <- 前のシンボルのサイズの総合値
-            symbol_offset += symbol_code_length as elf_utilities::Elf64Addr;
+            symbol_name_index += symbol_name.len() as elf_utilities::Elf64Word + 1;
         }
 
+        // sh_info には最初のグローバルシンボルのインデックスを設定する
+        let first_global = elf_symbols
+            .iter()
+            .position(|sym| sym.get_bind() != elf_utilities::symbol::Bind::Local)
+            .unwrap_or(elf_symbols.len());
+
         let symbol_table_size =
             elf_symbols.len() * elf_utilities::symbol::Symbol64::size() as usize;
         // セクションの追加
-        let symtab_section_header = self.init_symbol_table_section_header(symbol_table_size as u64);
+        let symtab_section_header =
+            self.init_symbol_table_section_header(symbol_table_size as u64, first_global);
         let mut symtab_section =
             elf_utilities::section::Section64::new(".symtab".to_string(), symtab_section_header);
         symtab_section.symbols = Some(elf_symbols);
         self.add_section(symtab_section);
     }
 
+    /// シンボルテーブルに出力するシンボル
+    /// ローカルシンボルをグローバルシンボルより前に並べる
+    fn symbol_table_entries(symbols: &IndexMap<String, Symbol>) -> Vec<(&String, &Symbol)> {
+        let (locals, globals): (Vec<_>, Vec<_>) = symbols
+            .iter()
+            .filter(|(name, _)| !Symbol::is_local_label(name))
+            .partition(|(_, sym)| sym.is_local());
+
+        locals.into_iter().chain(globals).collect()
+    }
+
     fn add_symtab_string_section(&mut self, symbols: &IndexMap<String, Symbol>) {
-        // シンボルマップをイテレートして,名前を集める.
-        let symbol_names: Vec<&str> = symbols
+        // シンボルテーブルと同じ順に,名前を集める.
+        let symbol_names: Vec<&str> = Self::symbol_table_entries(symbols)
             .iter()
             .map(|(name, _)| name.as_str())
             .collect::<Vec<&str>>();
@@ -150,15 +302,43 @@ impl ELFBuilder {
         self.add_section(strtab_section);
     }
 
-    fn add_relatext_section(&mut self, reloc_syms: &IndexMap<String, Vec<RelaSymbol>>) {
-        let mut relas = Vec::new();
+    fn add_rela_sections(
+        &mut self,
+        symbols: &IndexMap<String, Symbol>,
+        sections: &IndexMap<String, CustomSection>,
+        reloc_syms: &IndexMap<String, Vec<RelaSymbol>>,
+    ) {
+        let section_names = [".text", ".data", ".rodata"]
+            .iter()
+            .copied()
+            .chain(sections.keys().map(String::as_str));
+
+        for section_name in section_names {
+            let mut relas = Vec::new();
+
+            for (sym_name, relocs_in_syms) in reloc_syms.iter() {
+                if symbols[sym_name].section != section_name {
+                    continue;
+                }
+                for rela in relocs_in_syms.iter() {
+                    relas.push(rela.rela64);
+                }
+            }
 
-        for (_rela_name, relocs_in_syms) in reloc_syms.iter() {
-            for rela in relocs_in_syms.iter() {
-                relas.push(rela.rela64);
+            // .rela.text 以外は再配置がある場合のみ生成する
+            if relas.is_empty() && section_name != ".text" {
+                continue;
             }
+
+            self.add_rela_section(section_name, relas);
         }
+    }
 
+    fn add_rela_section(
+        &mut self,
+        section_name: &str,
+        relas: Vec<elf_utilities::relocation::Rela64>,
+    ) {
         // Relaオブジェクトをバイナリに変換
         let mut rela_table_binary: Vec<u8> = Vec::new();
         for rela in relas.iter() {
@@ -166,16 +346,17 @@ impl ELFBuilder {
             rela_table_binary.append(&mut rela_entry_binary);
         }
 
-        let relatext_hdr = self.init_relatext_header(rela_table_binary.len() as u64);
-        let mut relatext_section =
-            elf_utilities::section::Section64::new(".rela.text".to_string(), relatext_hdr);
-        relatext_section.rela_symbols = Some(relas);
-        self.add_section(relatext_section);
+        let rela_hdr = self.init_rela_header(rela_table_binary.len() as u64);
+        let mut rela_section =
+            elf_utilities::section::Section64::new(format!(".rela{}", section_name), rela_hdr);
+        rela_section.rela_symbols = Some(relas);
+        self.add_section(rela_section);
     }
 
     pub fn add_shstrtab_string_section(&mut self) {
-        // TODO: 決め打ち
-        let section_names = vec![".text", ".symtab", ".strtab", ".rela.text", ".shstrtab"];
+        // 追加済みのセクション名 + .shstrtab
+        let mut section_names = self.section_names();
+        section_names.push(".shstrtab");
 
         let section_string_table = elf_utilities::section::build_string_table(section_names);
         let shstrtab_header =
@@ -187,7 +368,17 @@ impl ELFBuilder {
     }
 
     fn condition_elf_header(&mut self) {
+        self.link_sections();
         self.file.finalize();
+
+        // finalize() は sh_size からオフセットを計算するが,
+        // .bss はファイル上にバイト列を持たないので計算し直す
+        let mut offset = elf_utilities::header::Ehdr64::size() as elf_utilities::Elf64Off;
+        for section in self.file.sections.iter_mut() {
+            section.header.sh_offset = offset;
+            offset += section.to_le_bytes().len() as elf_utilities::Elf64Off;
+        }
+        self.file.ehdr.e_shoff = offset;
     }
 
     fn init_text_section_header(&self, length: usize) -> elf_utilities::section::Shdr64 {
@@ -201,9 +392,27 @@ impl ELFBuilder {
         shdr
     }
 
+    fn init_data_section_header(
+        &self,
+        length: usize,
+        align: u64,
+        flags: elf_utilities::Elf64Xword,
+    ) -> elf_utilities::section::Shdr64 {
+        let mut shdr: elf_utilities::section::Shdr64 = Default::default();
+
+        shdr.set_type(elf_utilities::section::Type::ProgBits);
+        shdr.sh_size = length as elf_utilities::Elf64Xword;
+        shdr.sh_addralign = align;
+        shdr.sh_flags = flags;
+
+        shdr
+    }
+
+    /// sh_link は link_sections() で設定する
     fn init_symbol_table_section_header(
         &self,
         length: elf_utilities::Elf64Xword,
+        first_global: usize,
     ) -> elf_utilities::section::Shdr64 {
         let mut shdr: elf_utilities::section::Shdr64 = Default::default();
 
@@ -211,12 +420,8 @@ impl ELFBuilder {
         shdr.sh_size = length;
         shdr.sh_addralign = 1;
         shdr.sh_entsize = elf_utilities::symbol::Symbol64::size();
+        shdr.sh_info = first_global as elf_utilities::Elf64Word;
 
-        // TODO: .strtabが3番目にあることを決め打ち
-        shdr.sh_link = 3;
-
-        // TODO: 最初のグローバルシンボルが3番目にあることを決め打ち
-        shdr.sh_info = 2;
         shdr
     }
 
@@ -233,7 +438,8 @@ impl ELFBuilder {
         shdr
     }
 
-    fn init_relatext_header(
+    /// sh_link, sh_info は link_sections() で設定する
+    fn init_rela_header(
         &self,
         length: elf_utilities::Elf64Xword,
     ) -> elf_utilities::section::Shdr64 {
@@ -245,43 +451,47 @@ impl ELFBuilder {
         shdr.sh_addralign = 8;
         shdr.sh_entsize = elf_utilities::relocation::Rela64::size();
 
-        // TODO: シンボルテーブルが2番目にあることを決め打ち
-        shdr.sh_link = 2;
-
-        // TODO: .textセクションが一番目にあることを決め打ち
-        shdr.sh_info = 1;
-
         shdr
     }
 
-    fn create_global_symbol(
+    fn create_symbol(
         &self,
         st_name: elf_utilities::Elf64Word,
-        st_size: elf_utilities::Elf64Xword,
-        st_offset: elf_utilities::Elf64Addr,
+        sym: &Symbol,
     ) -> elf_utilities::symbol::Symbol64 {
-        let mut symbol: elf_utilities::symbol::Symbol64 = Default::default();
-        symbol.st_name = st_name;
-        symbol.st_size = st_size;
-        symbol.st_value = st_offset;
-
-        // TODO: .textが1番目にあることを決め打ち
-        symbol.st_shndx = 1;
-
-        // グローバル + Function属性
-
+        // 未定義シンボルは SHN_UNDEF(0)
+        let st_shndx = if sym.is_undefined() {
+            elf_utilities::section::SHN_UNDEF
+        } else if sym.is_absolute() {
+            elf_utilities::section::SHN_ABS
+        } else if sym.is_common() {
+            elf_utilities::section::SHN_COMMON
+        } else {
+            self.section_index(&sym.section).unwrap() as u16
+        };
+
+        let mut symbol = elf_utilities::symbol::Symbol64 {
+            st_name,
+            st_size: sym.st_size(),
+            st_value: sym.value,
+            st_shndx,
+            ..Default::default()
+        };
+
+        // Bind, Type は .globl, .type 等で指定されたもの
         symbol.set_info(
-            elf_utilities::symbol::Type::Func,
-            elf_utilities::symbol::Bind::Global,
+            elf_utilities::symbol::Type::from(sym.ty.to_byte()),
+            elf_utilities::symbol::Bind::from(sym.bind.to_byte()),
         );
 
         symbol
     }
 
     fn create_section_symbol(&self, shndx: u16) -> elf_utilities::symbol::Symbol64 {
-        let mut symbol: elf_utilities::symbol::Symbol64 = Default::default();
-
-        symbol.st_shndx = shndx;
+        let mut symbol = elf_utilities::symbol::Symbol64 {
+            st_shndx: shndx,
+            ..Default::default()
+        };
 
         // ローカル + SECTION属性
         symbol.set_info(
@@ -0,0 +1,46 @@
+use crate::assembler::resource::Syntax;
+
+/// options for assembling.
+///
+/// # Examples
+///
+/// ```
+/// use asmpeach::*;
+///
+/// let mut options = Options::new(Syntax::ATANDT);
+/// options.define_symbol("HAVE_AVX2", 1);
+/// options.add_include_path("include");
+/// ```
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub struct Options {
+    /// the syntax at the beginning of the source(`.intel_syntax` can switch it).
+    pub syntax: Syntax,
+    /// absolute symbols defined before the source, like GNU as's `--defsym`.
+    pub defined_symbols: Vec<(String, i64)>,
+    /// directories searched for `.include` and `.incbin` files,
+    /// after the directory of the including file.
+    pub include_paths: Vec<String>,
+    /// build an assembly listing, like GNU as's `-al`.
+    pub listing: bool,
+}
+
+impl Options {
+    pub fn new(syntax: Syntax) -> Self {
+        Self {
+            syntax,
+            defined_symbols: Vec::new(),
+            include_paths: Vec::new(),
+            listing: false,
+        }
+    }
+
+    /// `--defsym name=value`
+    pub fn define_symbol(&mut self, name: &str, value: i64) {
+        self.defined_symbols.push((name.to_string(), value));
+    }
+
+    /// `-I path`
+    pub fn add_include_path(&mut self, path: &str) {
+        self.include_paths.push(path.to_string());
+    }
+}
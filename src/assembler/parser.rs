@@ -1,4 +1,16 @@
+mod assignment;
 mod atandt_main;
+mod conditional;
+mod context;
+mod data;
+mod expr;
+mod include;
+mod intel_main;
+mod label;
+mod lexer;
+mod macros;
 
 mod atandt_test;
-pub use atandt_main::*;
+mod intel_test;
+pub use context::parse;
+pub use include::SourceFile;
@@ -0,0 +1,166 @@
+use super::context::*;
+use super::expr::Value;
+use crate::assembler::resource::Symbol;
+use crate::assembler::AsmError;
+
+impl Context {
+    /// `.set SYS_write, 1`, `.equ FRAME, 48` みたいなやつ
+    /// `.equiv` は再定義できない( `redefinable == false` )
+    pub fn parse_set_directive(
+        &mut self,
+        line: &str,
+        words: &Words,
+        redefinable: bool,
+    ) -> Result<(), AsmError> {
+        let (directive_column, directive) = words[0];
+        let args = Self::split_arguments(line, words);
+        let ((name_column, name), (column, expr)) = match args.as_slice() {
+            [(name_column, name), expr] if !name.is_empty() && !expr.1.is_empty() => {
+                ((*name_column, Self::remove_double_quote(name)), *expr)
+            }
+            _ => {
+                return Err(self.error_at(
+                    directive_column,
+                    format!("'{}' expects a symbol and its value", directive),
+                ));
+            }
+        };
+
+        // ラベルは `.set` でも再定義できない
+        let is_label = !self.equates.contains_key(&name) && self.is_defined(&name);
+        if is_label || (!redefinable && self.is_defined(&name)) {
+            return Err(self.error_at(name_column, format!("symbol '{}' is already defined", name)));
+        }
+
+        let value = self
+            .evaluate(expr)
+            .map_err(|message| self.error_at(column, message))?;
+
+        self.assign(&name, value);
+        Ok(())
+    }
+
+    /// シンボルに値を割り当てる
+    /// 定数ならシンボルテーブルにも出力する
+    pub fn assign(&mut self, name: &str, value: Value) {
+        // `.globl` 等で宣言済みなら,その属性を引き継ぐ
+        if let Some(constant) = value.as_constant() {
+            let sym = self
+                .syms
+                .entry(name.to_string())
+                .or_insert_with(Symbol::declared);
+            let absolute = Symbol::absolute(constant as u64);
+            sym.section = absolute.section;
+            sym.value = absolute.value;
+        }
+
+        self.equates.insert(name.to_string(), value);
+    }
+}
+
+#[cfg(test)]
+mod assignment_tests {
+    use super::*;
+    use crate::assembler::resource::*;
+    use OperandKind::*;
+    use OperandSize::*;
+
+    #[test]
+    fn parse_set_directive_test() {
+        let mut ctxt = new_context();
+        ctxt.toplevel("    .globl SYS_write").unwrap();
+        ctxt.toplevel("    .set SYS_write, 1").unwrap();
+        ctxt.toplevel("    .equ FRAME, 6*8").unwrap();
+        ctxt.toplevel("    .set FRAME, FRAME+16").unwrap();
+        ctxt.toplevel("    .set alias, foo+8").unwrap();
+
+        let sys_write = ctxt.syms.get("SYS_write").unwrap();
+        assert!(sys_write.is_absolute());
+        assert_eq!(elf_utilities::symbol::Bind::Global, sys_write.bind);
+        assert_eq!(1, sys_write.value);
+        assert_eq!(64, ctxt.syms.get("FRAME").unwrap().value);
+
+        // シンボルを含む値はシンボルテーブルに出力しない
+        assert!(ctxt.syms.get("alias").is_none());
+
+        ctxt.toplevel("main:").unwrap();
+        ctxt.in_symbol("    movq $SYS_write, %rax", "main").unwrap();
+        ctxt.in_symbol("    movq -FRAME(%rbp), %rax", "main")
+            .unwrap();
+        ctxt.in_symbol("    movq alias(%rip), %rax", "main")
+            .unwrap();
+
+        let insts = &ctxt.syms.get("main").unwrap().groups[0].insts;
+        assert_eq!(
+            opcode(
+                "mov",
+                &[RegMem(QWORD), Imm(DWORD)],
+                vec![
+                    Operand::GENERALREGISTER(GeneralPurposeRegister::RAX),
+                    Operand::Immediate(Immediate::I32(1))
+                ]
+            ),
+            insts[0].opcode
+        );
+        assert_eq!(
+            opcode(
+                "mov",
+                &[Reg(QWORD), RegMem(QWORD)],
+                vec![
+                    Operand::GENERALREGISTER(GeneralPurposeRegister::RAX),
+                    Operand::ADDRESSING {
+                        base: GeneralPurposeRegister::RBP,
+                        index: None,
+                        disp: Some(Displacement::DISP8(-64)),
+                        scale: None
+                    }
+                ]
+            ),
+            insts[1].opcode
+        );
+        assert_eq!(
+            opcode(
+                "mov",
+                &[Reg(QWORD), RegMem(QWORD)],
+                vec![
+                    Operand::GENERALREGISTER(GeneralPurposeRegister::RAX),
+                    Operand::RIPRELATIVE {
+                        label: "foo".to_string(),
+                        addend: 8
+                    }
+                ]
+            ),
+            insts[2].opcode
+        );
+    }
+
+    #[test]
+    fn redefinition_test() {
+        let mut ctxt = new_context();
+        ctxt.toplevel("    .equiv SIZE, 8").unwrap();
+        ctxt.toplevel("main:").unwrap();
+
+        let err = ctxt.toplevel("    .equiv SIZE, 16").unwrap_err();
+        assert_eq!("symbol 'SIZE' is already defined", err.message);
+        assert_eq!(12, err.column);
+
+        // ラベルは再定義できない
+        assert!(ctxt.toplevel("    .set main, 1").is_err());
+        assert!(ctxt.toplevel("SIZE:").is_err());
+        assert!(ctxt.toplevel("    .equiv main, 1").is_err());
+
+        // `.set` は再定義できる
+        ctxt.toplevel("    .set SIZE, 16").unwrap();
+        assert_eq!(16, ctxt.syms.get("SIZE").unwrap().value);
+
+        assert!(ctxt.toplevel("    .set SIZE").is_err());
+    }
+
+    fn new_context() -> Context {
+        Context::new("test.s", Syntax::ATANDT)
+    }
+
+    fn opcode(mnemonic: &str, kinds: &[OperandKind], operands: Vec<Operand>) -> Opcode {
+        Opcode::with_form(mnemonic, kinds, operands).unwrap()
+    }
+}
@@ -1,440 +1,805 @@
+use super::context::*;
+use super::expr::Value;
+use super::lexer::split_operands;
 use crate::assembler::resource::*;
-use indexmap::map::IndexMap;
-use std::str::SplitAsciiWhitespace;
-
-struct Context {
-    state: State,
-    syms: IndexMap<String, Symbol>,
-}
-
-#[derive(Eq, Ord, PartialOrd, PartialEq, Debug, Clone)]
-enum State {
-    TopLevel,
-    InSymbol(String),
-}
-
-/// parse AT&T syntax assembly.
-pub fn parse_atandt(source: String) -> IndexMap<String, Symbol> {
-    let lines_iter = source.lines();
-    let mut context = Context {
-        state: State::TopLevel,
-        syms: Default::default(),
-    };
-
-    // 各行に対して処理を行う
-    for l in lines_iter {
-        match context.state.clone() {
-            State::TopLevel => context.toplevel(l),
-            State::InSymbol(sym_name) => context.in_symbol(l, &sym_name),
-        }
-    }
-
-    context.syms
-}
+use crate::assembler::AsmError;
 
 impl Context {
-    fn toplevel(&mut self, line: &str) {
-        // 空行だったら無視
-        if Self::is_blank_line(line) {
-            return;
-        }
-
-        // シンボル名の場合
-        if line.trim_end().ends_with(':') {
-            let sym_name = Self::remove_double_quote(&Self::remove_pat_and_newline(line, ":"));
-            self.state = State::InSymbol(sym_name.clone());
-            self.syms.entry(sym_name).or_insert_with(Symbol::default);
-            return;
+    /// `movq $42, %rax` みたいなやつ
+    pub fn parse_atandt_instruction(
+        &mut self,
+        sym_name: &str,
+        line: &str,
+        (column, mnemonic): (usize, &str),
+    ) -> Result<(), AsmError> {
+        // オペランドは空白を含みうるので,カンマで区切る
+        let operands_start = column - 1 + mnemonic.len();
+
+        let mut operands = Vec::new();
+        for operand in split_operands(line, operands_start) {
+            operands.push((operand.0, self.parse_operand_at(mnemonic, operand)?));
         }
 
-        let mut iterator = line.split_ascii_whitespace();
-
-        self.parse_directive(&mut iterator);
+        let (name, size) = Self::split_size_suffix(mnemonic, &operands);
+        self.push_instruction(sym_name, (column, mnemonic), name, size, operands)
     }
 
-    fn parse_directive(&mut self, iterator: &mut SplitAsciiWhitespace) {
-        let directive = iterator.next().unwrap();
-
-        match directive {
-            ".global" | ".globl" => self.parse_global_directive(iterator),
-            ".type" => self.parse_symbol_type_directive(iterator),
-            ".section" | ".text" | ".size" | ".ident" | ".align" | ".long" | ".string" => {}
-            _ => {}
+    /// parse an operand with its column for error reporting.
+    /// 間接分岐( `jmp *%rax`, `call *8(%rax)` )のオペランドには `*` を付ける
+    fn parse_operand_at(
+        &self,
+        mnemonic: &str,
+        (column, operand): (usize, &str),
+    ) -> Result<Operand, AsmError> {
+        let indirect = operand.trim_start().strip_prefix('*');
+        let parsed = self
+            .parse_operand(indirect.unwrap_or(operand))
+            .map_err(|message| self.error_at(column, message))?;
+
+        let is_branch = matches!(mnemonic, "jmp" | "jmpq" | "call" | "callq");
+        let is_register_or_memory = matches!(
+            parsed,
+            Operand::GENERALREGISTER(_)
+                | Operand::ADDRESSING { .. }
+                | Operand::ABSOLUTE { .. }
+                | Operand::RIPRELATIVE { .. }
+        );
+        match (indirect.is_some(), is_branch && is_register_or_memory) {
+            (false, true) if matches!(parsed, Operand::ABSOLUTE { index: None, .. }) => Err(self
+                .error_at(
+                    column,
+                    format!("branch to an absolute address is not supported: '{}'", operand.trim()),
+                )),
+            (true, false) => Err(self.error_at(
+                column,
+                format!(
+                    "'*' can be used only with a register or memory operand of 'jmp' and 'call': '{}'",
+                    operand.trim()
+                ),
+            )),
+            (false, true) => Err(self.error_at(
+                column,
+                format!("indirect branch target must be prefixed with '*': '{}'", operand.trim()),
+            )),
+            _ => Ok(parsed),
         }
     }
 
-    /// `.global main` みたいなやつ
-    fn parse_global_directive(&mut self, iterator: &mut SplitAsciiWhitespace) {
-        let sym_name = Self::remove_double_quote(iterator.next().unwrap());
-
-        self.syms
-            .entry(sym_name)
-            .or_insert_with(Symbol::default)
-            .as_global();
-        assert!(iterator.next().is_none());
-    }
+    fn parse_operand(&self, operand: &str) -> Result<Operand, String> {
+        let stripped = operand.trim();
 
-    /// `.type main, @function` みたいなやつ
-    fn parse_symbol_type_directive(&mut self, iterator: &mut SplitAsciiWhitespace) {
-        let sym_name =
-            Self::remove_double_quote(&Self::remove_pat_and_newline(iterator.next().unwrap(), ","));
-        let sym_type = iterator.next().unwrap();
-        assert_eq!(sym_type, "@function");
-
-        self.syms
-            .entry(sym_name)
-            .or_insert_with(Symbol::default)
-            .as_function();
-    }
-
-    // シンボル名をパース後
-    fn in_symbol(&mut self, line: &str, sym_name: &str) {
-        let line = line.trim_start().trim_end();
-        // シンボル名の場合
-        if line.ends_with(':') {
-            // ラベルかどうかチェック
-            if line.starts_with(".L") || line.starts_with("\".L") {
-                self.push_group(sym_name, &Self::remove_pat_and_newline(&line, ":"));
-            } else {
-                // ラベルではない => 別のシンボル定義と解釈
-                let another_sym = Self::remove_pat_and_newline(&line, ":");
-                self.state = State::InSymbol(another_sym.clone());
-                self.syms.entry(another_sym).or_insert_with(Symbol::default);
+        // レジスタの場合
+        if stripped.starts_with('%') {
+            if let Some(xmm) = XMMRegister::from_at_string(stripped) {
+                return Ok(Operand::XMMREGISTER(xmm));
             }
-
-            return;
-        }
-
-        if Self::is_blank_line(&line) {
-            return;
-        }
-
-        let mut iterator = line.split_ascii_whitespace();
-
-        let opcode = iterator.next().unwrap();
-
-        // .global等のディレクティブを見つけたら
-        if self.is_directive_start(opcode) {
-            self.state = State::TopLevel;
-            self.toplevel(&line);
-            return;
+            if let Some(ymm) = YMMRegister::from_at_string(stripped) {
+                return Ok(Operand::YMMREGISTER(ymm));
+            }
+            return Self::parse_register(stripped).map(Operand::GENERALREGISTER);
         }
 
-        // オペランドの数を調べる．
-        let count = iterator.clone().count();
-
-        match count {
-            0 => self.parse_no_operand_instruction(&mut iterator, sym_name, opcode),
-            1 => self.parse_unary_instruction(&mut iterator, sym_name, opcode),
-            2 => self.parse_binary_instruction(&mut iterator, sym_name, opcode),
-            _ => panic!("unsupported instruction -> {}", line),
+        // 即値の場合
+        // `$(1<<12)+8`, `$sym+4` のような式も書ける
+        if let Some(immediate) = stripped.strip_prefix('$') {
+            return self.evaluate(immediate)?.into_immediate_operand();
         }
-    }
-
-    fn parse_no_operand_instruction(
-        &mut self,
-        iter: &mut SplitAsciiWhitespace,
-        sym_name: &str,
-        opcode: &str,
-    ) {
-        let opcode = match opcode {
-            "ret" => Opcode::RET,
-            "endbr64" => Opcode::ENDBR64,
-            "syscall" => Opcode::SYSCALL,
-            _ => panic!("not implemented generating '{}' yet", opcode),
-        };
 
-        self.push_inst_cur_sym(sym_name, Instruction { opcode });
-        assert!(iter.next().is_none());
-    }
-
-    fn parse_unary_instruction(
-        &mut self,
-        iter: &mut SplitAsciiWhitespace,
-        sym_name: &str,
-        opcode: &str,
-    ) {
-        let operand = iter.next();
-        assert!(operand.is_some());
-
-        let operand = Self::parse_operand(operand.unwrap());
-        let opcode = match opcode {
-            "pushq" => Opcode::push(OperandSize::QWORD, operand),
-            "popq" => Opcode::pop(OperandSize::QWORD, operand),
-            "call" => Opcode::call(operand),
-            "jle" => Opcode::JLELABEL {
-                label: operand.copy_label(),
-            },
-            "je" => Opcode::JELABEL {
-                label: operand.copy_label(),
-            },
-            "jmp" => Opcode::JMPLABEL {
-                label: operand.copy_label(),
-            },
-            _ => panic!("not implemented generating '{}' yet", opcode),
-        };
-
-        self.push_inst_cur_sym(sym_name, Instruction { opcode });
-        assert!(iter.next().is_none());
-    }
-
-    fn parse_binary_instruction(
-        &mut self,
-        iter: &mut SplitAsciiWhitespace,
-        sym_name: &str,
-        opcode: &str,
-    ) {
-        let src = iter.next();
-        assert!(src.is_some());
-        let src_op = Self::parse_operand(src.unwrap());
-
-        let dst = iter.next();
-        assert!(dst.is_some());
-        let dst_op = Self::parse_operand(dst.unwrap());
-
-        let opcode = match opcode {
-            "addl" => Opcode::add(OperandSize::DWORD, src_op.to_32bit(), dst_op.to_32bit()),
-            "addq" => Opcode::add(OperandSize::QWORD, src_op.to_64bit(), dst_op.to_64bit()),
-            "cmpq" => Opcode::cmp(OperandSize::QWORD, src_op.to_64bit(), dst_op.to_64bit()),
-            "subq" => Opcode::sub(OperandSize::QWORD, src_op.to_64bit(), dst_op.to_64bit()),
-            "leaq" => Opcode::lea(OperandSize::QWORD, src_op.to_64bit(), dst_op.to_64bit()),
-            "imulq" => Opcode::imul(OperandSize::QWORD, src_op.to_64bit(), dst_op.to_64bit()),
-            "movb" => Opcode::mov(OperandSize::BYTE, src_op.to_8bit(), dst_op.to_8bit()),
-            "movw" => Opcode::mov(OperandSize::WORD, src_op.to_16bit(), dst_op.to_16bit()),
-            "movl" => Opcode::mov(OperandSize::DWORD, src_op.to_32bit(), dst_op.to_32bit()),
-            "movq" => Opcode::mov(OperandSize::QWORD, src_op.to_64bit(), dst_op.to_64bit()),
-            _ => panic!("not implemented generating '{}' yet", opcode),
+        // `disp(%base, %index, scale)`, `disp(, %index, scale)` でなければラベルか絶対アドレス
+        let (disp_str, memory_operand_str) = match Self::split_memory_operand(stripped) {
+            Some(memory) => memory,
+            None if stripped.contains("(%") => {
+                return Err(format!("junk after memory operand '{}'", stripped));
+            }
+            None => return self.parse_label(stripped),
         };
 
-        self.push_inst_cur_sym(sym_name, Instruction { opcode });
-        assert!(iter.next().is_none());
-    }
-
-    fn remove_double_quote(op: &str) -> String {
-        op.trim_start_matches('"').trim_end_matches('"').to_string()
-    }
-
-    fn parse_operand(operand: &str) -> Operand {
-        let stripped = Self::remove_pat_and_newline(operand, ",");
+        let mut memory_operand_str = memory_operand_str.split(',');
+        let base_reg = memory_operand_str.next().unwrap().trim();
 
-        // レジスタの場合
-        if stripped.starts_with('%') {
-            return Operand::GENERALREGISTER(GeneralPurposeRegister::from_at_string(&stripped));
-        }
-
-        // 即値の場合
-        let immediate = stripped.trim_start_matches('$');
-        match immediate.parse::<i8>() {
-            Ok(v) => {
-                return Operand::Immediate(Immediate::I8(v));
+        // RIP相対 ( `.LC0(%rip)`, `sym+8(%rip)` )
+        if base_reg == "%rip" {
+            if memory_operand_str.next().is_some() {
+                return Err(format!(
+                    "'%rip' can't be used with index register: '{}'",
+                    stripped
+                ));
             }
-            Err(_e) => match immediate.parse::<i32>() {
-                Ok(v) => {
-                    return Operand::Immediate(Immediate::I32(v));
-                }
-                // 即値オペランドでなかった場合
-                Err(_e) => {}
-            },
-        }
 
-        // '(' がない => label
-        if !stripped.contains("(") {
-            return Operand::LABEL(Self::remove_double_quote(&stripped));
+            return match self.evaluate(disp_str) {
+                Ok(Value {
+                    symbol: Some(label),
+                    subtrahend: None,
+                    constant,
+                }) => Ok(Operand::RIPRELATIVE {
+                    label,
+                    addend: constant as i64,
+                }),
+                _ => Err(format!(
+                    "unsupported displacement for '%rip': '{}'",
+                    disp_str
+                )),
+            };
         }
 
-        // メモリオペランド
-        let mut splitted = stripped.split('(');
-        let disp_str = splitted.next();
-
-        let displacement = match disp_str.unwrap() {
+        // `-16+8(%rbp)` のような式も書ける
+        let disp = match disp_str {
             // 単純なでリファレンス
             "" => None,
-            disp => match disp.parse::<i8>() {
-                Ok(v) => Some(Displacement::DISP8(v)),
-                Err(_e) => match stripped.parse::<i32>() {
-                    Ok(v) => Some(Displacement::DISP32(v)),
-                    // offset無し
-                    Err(_e) => None,
-                },
-            },
+            disp => Some(self.evaluate(disp)?),
         };
 
-        let base_reg = splitted.next().unwrap();
-        let mut memory_operand_str = base_reg.trim_end_matches(')').split(',');
-        let base_reg = GeneralPurposeRegister::from_at_string(memory_operand_str.next().unwrap());
+        // `0(,%rax,4)` のようにベースレジスタを省略できる
+        let base_reg = match base_reg {
+            "" => None,
+            base_reg => Some(Self::parse_register(base_reg)?),
+        };
 
         let index_reg = match memory_operand_str.next() {
-            Some(ireg_str) => Some(GeneralPurposeRegister::from_at_string(
-                ireg_str.trim_start(),
-            )),
+            Some(ireg_str) => Some(Self::parse_register(ireg_str.trim())?),
             None => None,
         };
         let scale = match memory_operand_str.next() {
-            Some(scale_str) => Some(scale_str.trim_start().parse::<u8>().unwrap()),
+            Some(scale_str) => match scale_str.trim().parse::<u8>() {
+                Ok(v) if v == 1 || v == 2 || v == 4 || v == 8 => Some(v),
+                _ => {
+                    return Err(format!(
+                        "scale must be 1, 2, 4 or 8, but got '{}'",
+                        scale_str.trim()
+                    ))
+                }
+            },
             None => None,
         };
 
-        Operand::ADDRESSING {
-            index: index_reg,
-            base: base_reg,
-            disp: displacement,
-            scale,
+        if memory_operand_str.next().is_some() {
+            return Err(format!("too many registers in '{}'", stripped));
         }
-    }
 
-    fn is_directive_start(&self, directive: &str) -> bool {
-        match directive {
-            ".globl" | ".global" | ".type" | ".section" | ".text" | ".size" | ".align"
-            | ".long" | ".string" | "" => true,
-            _ => false,
+        match base_reg {
+            Some(base_reg) => Ok(Operand::ADDRESSING {
+                index: index_reg,
+                base: base_reg,
+                disp: disp.map(|v| v.to_displacement()).transpose()?,
+                scale,
+            }),
+            None => Ok(Operand::ABSOLUTE {
+                index: index_reg,
+                disp: disp.unwrap_or_default().to_absolute_displacement()?,
+                scale,
+            }),
         }
     }
 
-    fn push_inst_cur_sym(&mut self, sym_name: &str, inst: Instruction) {
-        if let Some(sym) = self.syms.get_mut(sym_name) {
-            if sym.groups.is_empty() {
-                sym.groups
-                    .push(Group::new(&format!(".L{}_entry", sym_name)));
-            }
+    /// `jmp .L2`, `call foo@PLT`, `jmp bar+4` のラベル
+    /// 関数の呼び出しは常に PLT32 で再配置するので, `@PLT` は取り除く
+    /// `movl 0x1000, %eax` のように定数であれば絶対アドレス
+    fn parse_label(&self, operand: &str) -> Result<Operand, String> {
+        let expr = operand.strip_suffix("@PLT").unwrap_or(operand);
+
+        match self.evaluate(expr)? {
+            Value {
+                symbol: Some(label),
+                subtrahend: None,
+                constant,
+            } => Ok(Operand::LABEL {
+                label,
+                addend: constant as i64,
+            }),
+            value if value.as_constant().is_some() => Ok(Operand::ABSOLUTE {
+                index: None,
+                disp: value.to_absolute_displacement()?,
+                scale: None,
+            }),
+            _ => Err(format!("expected a label, but got '{}'", operand)),
+        }
+    }
 
-            let group_idx = sym.groups.len() - 1;
-            sym.groups[group_idx].insts.push(inst);
+    /// `(1+2)*4(%rbp, %rax)` -> ("(1+2)*4", "%rbp, %rax")
+    /// `0(,%rax,4)` -> ("0", ",%rax,4")
+    /// 末尾の括弧の中がレジスタ( かベースの省略 )でなければ,メモリオペランドではない
+    fn split_memory_operand(operand: &str) -> Option<(&str, &str)> {
+        let inner_end = operand.strip_suffix(')')?.len();
+        let open = operand[..inner_end].rfind('(')?;
+        let inner = &operand[open + 1..inner_end];
 
-            return;
+        if !inner.trim_start().starts_with(['%', ',']) {
+            return None;
         }
 
-        unreachable!();
+        Some((operand[..open].trim(), inner))
     }
 
-    fn push_group(&mut self, sym_name: &str, label_name: &str) {
-        self.syms
-            .get_mut(sym_name)
-            .unwrap()
-            .groups
-            .push(Group::new(&Self::remove_double_quote(label_name)));
+    fn parse_register(s: &str) -> Result<GeneralPurposeRegister, String> {
+        GeneralPurposeRegister::from_at_string(s)
+            .ok_or_else(|| format!("bad register name '{}'", s))
     }
 
-    fn remove_pat_and_newline(line: &str, pat: &str) -> String {
-        line.trim_end().trim_end_matches(pat).to_string()
-    }
+    /// `movq` -> ("mov", QWORD) のように,サフィックスを取り除く
+    /// サフィックスのない命令( `call` 等)や, `mov` のような省略形はそのまま返す
+    /// `movq` は XMM レジスタを取るなら SSE2 の命令, そうでなければ `mov` + `q`
+    fn split_size_suffix<'a>(
+        mnemonic: &'a str,
+        operands: &[(usize, Operand)],
+    ) -> (&'a str, Option<OperandSize>) {
+        let operand_count = operands.len();
+        let takes_xmm = operands
+            .iter()
+            .any(|(_, operand)| matches!(operand, Operand::XMMREGISTER(_)));
+        if is_sized_instruction(mnemonic, operand_count)
+            && (takes_xmm || !is_simd_instruction(mnemonic, operand_count))
+        {
+            return (mnemonic, None);
+        }
+
+        let size = match mnemonic.chars().last() {
+            Some('b') => OperandSize::BYTE,
+            Some('w') => OperandSize::WORD,
+            Some('l') => OperandSize::DWORD,
+            Some('q') => OperandSize::QWORD,
+            _ => return (mnemonic, None),
+        };
+
+        let name = &mnemonic[..mnemonic.len() - 1];
+        if !is_sized_instruction(name, operand_count) {
+            return (mnemonic, None);
+        }
 
-    fn is_blank_line(line: &str) -> bool {
-        line.trim_end().is_empty()
+        (name, Some(size))
     }
 }
 
 #[cfg(test)]
 mod parse_tests {
     use super::*;
+    use OperandKind::*;
+    use OperandSize::*;
 
     #[test]
-    fn parse_symname_test() {
+    fn parse_pushq_test() {
         let mut ctxt = new_context();
-        ctxt.toplevel("main:    \n");
+        ctxt.toplevel("main:    \n").unwrap();
+        ctxt.in_symbol("pushq %rax", "main").unwrap();
+        assert_eq!(
+            opcode(
+                "push",
+                &[Reg(QWORD)],
+                vec![Operand::GENERALREGISTER(GeneralPurposeRegister::RAX)]
+            ),
+            ctxt.syms.get("main").unwrap().groups[0].insts[0].opcode
+        );
+    }
 
-        assert!(!ctxt.syms.is_empty());
-        assert_eq!(State::InSymbol("main".to_string()), ctxt.state);
-        assert!(ctxt.syms.get("main").is_some());
+    #[test]
+    fn parse_popq_test() {
+        let mut ctxt = new_context();
+        ctxt.toplevel("main:    \n").unwrap();
+        ctxt.in_symbol("    popq %rax", "main").unwrap();
+        assert_eq!(
+            opcode(
+                "pop",
+                &[Reg(QWORD)],
+                vec![Operand::GENERALREGISTER(GeneralPurposeRegister::RAX)]
+            ),
+            ctxt.syms.get("main").unwrap().groups[0].insts[0].opcode
+        );
 
-        ctxt.toplevel("\"aarch64::main\":    \n");
+        ctxt.in_symbol("    popq     %rbp", "main").unwrap();
+        assert_eq!(
+            opcode(
+                "pop",
+                &[Reg(QWORD)],
+                vec![Operand::GENERALREGISTER(GeneralPurposeRegister::RBP)]
+            ),
+            ctxt.syms.get("main").unwrap().groups[0].insts[1].opcode
+        );
+    }
+
+    #[test]
+    fn parse_moveq_test() {
+        let mut ctxt = new_context();
+        ctxt.toplevel("main:    \n").unwrap();
+        ctxt.in_symbol("movq $42, %rax", "main").unwrap();
+        assert_eq!(
+            opcode(
+                "mov",
+                &[RegMem(QWORD), Imm(DWORD)],
+                vec![
+                    Operand::GENERALREGISTER(GeneralPurposeRegister::RAX),
+                    Operand::Immediate(Immediate::I32(42))
+                ]
+            ),
+            ctxt.syms.get("main").unwrap().groups[0].insts[0].opcode
+        );
 
-        assert!(!ctxt.syms.is_empty());
-        assert_eq!(State::InSymbol("aarch64::main".to_string()), ctxt.state);
-        assert!(ctxt.syms.get("aarch64::main").is_some());
+        ctxt.in_symbol("movq $3, -24(%rbp)", "main").unwrap();
+        assert_eq!(
+            opcode(
+                "mov",
+                &[RegMem(QWORD), Imm(DWORD)],
+                vec![
+                    Operand::ADDRESSING {
+                        base: GeneralPurposeRegister::RBP,
+                        index: None,
+                        disp: Some(Displacement::DISP8(-24)),
+                        scale: None
+                    },
+                    Operand::Immediate(Immediate::I32(3))
+                ]
+            ),
+            ctxt.syms.get("main").unwrap().groups[0].insts[1].opcode
+        );
     }
 
     #[test]
-    fn parse_global_directive_test() {
+    fn parse_arithmetic_test() {
         let mut ctxt = new_context();
-        ctxt.toplevel("    .global main    \n");
+        ctxt.toplevel("main:    \n").unwrap();
+        ctxt.in_symbol("xorl %eax, %eax", "main").unwrap();
+        assert_eq!(
+            opcode(
+                "xor",
+                &[RegMem(DWORD), Reg(DWORD)],
+                vec![
+                    Operand::GENERALREGISTER(GeneralPurposeRegister::EAX),
+                    Operand::GENERALREGISTER(GeneralPurposeRegister::EAX)
+                ]
+            ),
+            ctxt.syms.get("main").unwrap().groups[0].insts[0].opcode
+        );
+
+        ctxt.in_symbol("andb $1, %sil", "main").unwrap();
+        assert_eq!(
+            opcode(
+                "and",
+                &[RegMem(BYTE), Imm(BYTE)],
+                vec![
+                    Operand::GENERALREGISTER(GeneralPurposeRegister::SIL),
+                    Operand::Immediate(Immediate::I8(1))
+                ]
+            ),
+            ctxt.syms.get("main").unwrap().groups[0].insts[1].opcode
+        );
 
-        assert!(!ctxt.syms.is_empty());
-        assert_eq!(State::TopLevel, ctxt.state);
-        assert!(ctxt.syms.get("main").unwrap().is_global());
+        ctxt.in_symbol("testq -8(%rbp), %rax", "main").unwrap();
+        assert_eq!(
+            opcode(
+                "test",
+                &[Reg(QWORD), RegMem(QWORD)],
+                vec![
+                    Operand::GENERALREGISTER(GeneralPurposeRegister::RAX),
+                    Operand::ADDRESSING {
+                        base: GeneralPurposeRegister::RBP,
+                        index: None,
+                        disp: Some(Displacement::DISP8(-8)),
+                        scale: None
+                    }
+                ]
+            ),
+            ctxt.syms.get("main").unwrap().groups[0].insts[2].opcode
+        );
+
+        // 8bit の命令に収まらない即値
+        let err = ctxt.in_symbol("andb $300, %al", "main").unwrap_err();
+        assert_eq!("immediate 300 is out of range for 'andb'", err.message);
     }
 
     #[test]
-    fn parse_type_directive_test() {
+    fn parse_alu_immediate_boundary_test() {
         let mut ctxt = new_context();
-        ctxt.toplevel("    .type    main, @function    \n");
+        ctxt.toplevel("main:    \n").unwrap();
+        let mut bytes = |line: &str| {
+            ctxt.in_symbol(line, "main").map(|_| {
+                let insts = &ctxt.syms["main"].groups[0].insts;
+                insts[insts.len() - 1].to_bytes()
+            })
+        };
+
+        // 32bit の命令は符号無しの値も書ける
+        assert_eq!(Ok(vec![0x83, 0xe0, 0xf0]), bytes("andl $0xfffffff0, %eax"));
+        assert_eq!(
+            Ok(vec![0xa9, 0x00, 0x00, 0x00, 0x80]),
+            bytes("testl $0x80000000, %eax")
+        );
+        assert_eq!(
+            Ok(vec![0x35, 0x00, 0x00, 0x00, 0x80]),
+            bytes("xorl $0x80000000, %eax")
+        );
+        assert_eq!(Ok(vec![0x83, 0xc0, 0xff]), bytes("addl $0xffffffff, %eax"));
+        assert_eq!(
+            Ok(vec![0xc7, 0xc0, 0x00, 0x00, 0x00, 0x80]),
+            bytes("movl $0x80000000, %eax")
+        );
+        assert_eq!(
+            Ok(vec![0x35, 0x00, 0x00, 0x00, 0x80]),
+            bytes("xorl $-0x80000000, %eax")
+        );
+        assert!(bytes("andl $0x100000000, %eax").is_err());
+        assert!(bytes("andl $-0x80000001, %eax").is_err());
 
-        assert!(!ctxt.syms.is_empty());
-        assert_eq!(State::TopLevel, ctxt.state);
-        assert!(ctxt.syms.get("main").unwrap().is_function());
+        // 64bit の命令の即値は,64bit に切り詰めてから符号拡張できる値
+        assert_eq!(
+            Ok(vec![0x48, 0x83, 0xe4, 0xf0]),
+            bytes("andq $0xfffffffffffffff0, %rsp")
+        );
+        assert_eq!(
+            Ok(vec![0x48, 0x25, 0x00, 0x00, 0x00, 0x80]),
+            bytes("andq $0xffffffff80000000, %rax")
+        );
+        assert_eq!(
+            Ok(vec![0x48, 0x25, 0xff, 0xff, 0xff, 0x7f]),
+            bytes("andq $0x7fffffff, %rax")
+        );
+        assert!(bytes("andq $0x80000000, %rax").is_err());
+        assert!(bytes("andq $0xffffffff, %rax").is_err());
+        assert!(bytes("andq $0xffffffff7fffffff, %rax").is_err());
+        assert!(bytes("andq $0x10000000000000000, %rax").is_err());
     }
 
     #[test]
-    fn parse_pushq_test() {
+    fn parse_conditional_test() {
         let mut ctxt = new_context();
-        ctxt.toplevel("main:    \n");
-        ctxt.in_symbol("pushq %rax", "main");
+        ctxt.toplevel("main:    \n").unwrap();
+        // `l` はサイズではなく条件
+        ctxt.in_symbol("setl %dil", "main").unwrap();
         assert_eq!(
-            Opcode::PUSHR64 {
-                r64: GeneralPurposeRegister::RAX
-            },
+            opcode(
+                "setl",
+                &[RegMem(BYTE)],
+                vec![Operand::GENERALREGISTER(GeneralPurposeRegister::DIL)]
+            ),
             ctxt.syms.get("main").unwrap().groups[0].insts[0].opcode
         );
+
+        ctxt.in_symbol("setnae (%rax)", "main").unwrap();
+        assert_eq!(
+            opcode(
+                "setb",
+                &[RegMem(BYTE)],
+                vec![Operand::ADDRESSING {
+                    base: GeneralPurposeRegister::RAX,
+                    index: None,
+                    disp: None,
+                    scale: None
+                }]
+            ),
+            ctxt.syms.get("main").unwrap().groups[0].insts[1].opcode
+        );
+
+        ctxt.in_symbol("cmovll %ebx, %eax", "main").unwrap();
+        assert_eq!(
+            opcode(
+                "cmovl",
+                &[Reg(DWORD), RegMem(DWORD)],
+                vec![
+                    Operand::GENERALREGISTER(GeneralPurposeRegister::EAX),
+                    Operand::GENERALREGISTER(GeneralPurposeRegister::EBX)
+                ]
+            ),
+            ctxt.syms.get("main").unwrap().groups[0].insts[2].opcode
+        );
+
+        let err = ctxt.in_symbol("cmovzb %al, %bl", "main").unwrap_err();
+        assert_eq!("invalid operand combination for 'cmovzb'", err.message);
     }
 
     #[test]
-    fn parse_popq_test() {
+    fn parse_extension_test() {
         let mut ctxt = new_context();
-        ctxt.toplevel("main:    \n");
-        ctxt.in_symbol("    popq %rax", "main");
+        ctxt.toplevel("main:    \n").unwrap();
+        ctxt.in_symbol("movzbl %sil, %eax", "main").unwrap();
         assert_eq!(
-            Opcode::POPR64 {
-                r64: GeneralPurposeRegister::RAX
-            },
+            opcode(
+                "movzx",
+                &[Reg(DWORD), RegMem(BYTE)],
+                vec![
+                    Operand::GENERALREGISTER(GeneralPurposeRegister::EAX),
+                    Operand::GENERALREGISTER(GeneralPurposeRegister::SIL)
+                ]
+            ),
             ctxt.syms.get("main").unwrap().groups[0].insts[0].opcode
         );
 
-        ctxt.in_symbol("    popq     %rbp", "main");
+        ctxt.in_symbol("movswq -2(%rbp), %rax", "main").unwrap();
         assert_eq!(
-            Opcode::POPR64 {
-                r64: GeneralPurposeRegister::RBP
-            },
+            opcode(
+                "movsx",
+                &[Reg(QWORD), RegMem(WORD)],
+                vec![
+                    Operand::GENERALREGISTER(GeneralPurposeRegister::RAX),
+                    Operand::ADDRESSING {
+                        base: GeneralPurposeRegister::RBP,
+                        index: None,
+                        disp: Some(Displacement::DISP8(-2)),
+                        scale: None
+                    }
+                ]
+            ),
             ctxt.syms.get("main").unwrap().groups[0].insts[1].opcode
         );
+
+        ctxt.in_symbol("cltq", "main").unwrap();
+        assert_eq!(
+            opcode("cdqe", &[], vec![]),
+            ctxt.syms.get("main").unwrap().groups[0].insts[2].opcode
+        );
+
+        let err = ctxt.in_symbol("movzbl %ax, %eax", "main").unwrap_err();
+        assert_eq!("incorrect register '%ax' used with 'movzbl'", err.message);
+        let err = ctxt.in_symbol("movzx (%rax), %eax", "main").unwrap_err();
+        assert_eq!("ambiguous operand size for 'movzx'", err.message);
     }
 
     #[test]
-    fn parse_moveq_test() {
+    fn parse_byte_word_test() {
         let mut ctxt = new_context();
-        ctxt.toplevel("main:    \n");
-        ctxt.in_symbol("movq $42, %rax", "main");
+        ctxt.toplevel("main:    \n").unwrap();
+        ctxt.in_symbol("movb $1, (%rax)", "main").unwrap();
         assert_eq!(
-            Opcode::MOVRM64IMM32 {
-                imm: Immediate::I32(42),
-                rm64: Operand::GENERALREGISTER(GeneralPurposeRegister::RAX),
-            },
+            opcode(
+                "mov",
+                &[RegMem(BYTE), Imm(BYTE)],
+                vec![
+                    Operand::ADDRESSING {
+                        base: GeneralPurposeRegister::RAX,
+                        index: None,
+                        disp: None,
+                        scale: None
+                    },
+                    Operand::Immediate(Immediate::I8(1))
+                ]
+            ),
             ctxt.syms.get("main").unwrap().groups[0].insts[0].opcode
         );
 
-        ctxt.in_symbol("movq $3, -24(%rbp)", "main");
+        ctxt.in_symbol("movw $300, -2(%rbp)", "main").unwrap();
         assert_eq!(
-            Opcode::MOVRM64IMM32 {
-                imm: Immediate::I32(3),
-                rm64: Operand::ADDRESSING {
-                    base: GeneralPurposeRegister::RBP,
-                    index: None,
-                    disp: Some(Displacement::DISP8(-24)),
-                    scale: None,
-                },
-            },
+            opcode(
+                "mov",
+                &[RegMem(WORD), Imm(WORD)],
+                vec![
+                    Operand::ADDRESSING {
+                        base: GeneralPurposeRegister::RBP,
+                        index: None,
+                        disp: Some(Displacement::DISP8(-2)),
+                        scale: None
+                    },
+                    Operand::Immediate(Immediate::I16(300))
+                ]
+            ),
             ctxt.syms.get("main").unwrap().groups[0].insts[1].opcode
         );
+
+        ctxt.in_symbol("incw %ax", "main").unwrap();
+        assert_eq!(
+            opcode(
+                "inc",
+                &[RegMem(WORD)],
+                vec![Operand::GENERALREGISTER(GeneralPurposeRegister::AX)]
+            ),
+            ctxt.syms.get("main").unwrap().groups[0].insts[2].opcode
+        );
+
+        ctxt.in_symbol("idivb %bl", "main").unwrap();
+        assert_eq!(
+            opcode(
+                "idiv",
+                &[RegMem(BYTE)],
+                vec![Operand::GENERALREGISTER(GeneralPurposeRegister::BL)]
+            ),
+            ctxt.syms.get("main").unwrap().groups[0].insts[3].opcode
+        );
+
+        let err = ctxt.in_symbol("movb $300, (%rax)", "main").unwrap_err();
+        assert_eq!("immediate 300 is out of range for 'movb'", err.message);
+        let err = ctxt.in_symbol("imulb %bl, %al", "main").unwrap_err();
+        assert_eq!("invalid operand combination for 'imulb'", err.message);
+    }
+
+    #[test]
+    fn high_byte_register_with_rex_test() {
+        let mut ctxt = new_context();
+        ctxt.toplevel("main:    \n").unwrap();
+
+        // REX prefix が無ければエンコードできる
+        assert!(ctxt.in_symbol("movb %ah, %bl", "main").is_ok());
+        assert!(ctxt.in_symbol("movzbl %ah, %eax", "main").is_ok());
+
+        let cases = [
+            // `%sil` のために REX prefix が必要
+            ("movb %ah, %sil", 6, "%ah"),
+            ("movb %dil, %ch", 12, "%ch"),
+            ("movzbl %ah, %r8d", 8, "%ah"),
+            ("addb %bh, %r9b", 6, "%bh"),
+            ("movb %dh, (%r8)", 6, "%dh"),
+            ("movsbq %ah, %rax", 8, "%ah"),
+        ];
+        for (line, column, reg) in cases {
+            let err = ctxt.in_symbol(line, "main").unwrap_err();
+            assert_eq!(column, err.column, "{}", line);
+            assert_eq!(
+                format!(
+                    "can't encode register '{}' in an instruction requiring REX prefix",
+                    reg
+                ),
+                err.message
+            );
+        }
+    }
+
+    #[test]
+    fn parse_shift_test() {
+        let mut ctxt = new_context();
+        ctxt.toplevel("main:    \n").unwrap();
+        ctxt.in_symbol("shl %cl, %rax", "main").unwrap();
+        assert_eq!(
+            opcode(
+                "shl",
+                &[RegMem(QWORD), Cl],
+                vec![
+                    Operand::GENERALREGISTER(GeneralPurposeRegister::RAX),
+                    Operand::GENERALREGISTER(GeneralPurposeRegister::CL)
+                ]
+            ),
+            ctxt.syms.get("main").unwrap().groups[0].insts[0].opcode
+        );
+
+        ctxt.in_symbol("sall $1, %eax", "main").unwrap();
+        assert_eq!(
+            opcode(
+                "shl",
+                &[RegMem(DWORD), One],
+                vec![
+                    Operand::GENERALREGISTER(GeneralPurposeRegister::EAX),
+                    Operand::Immediate(Immediate::I8(1))
+                ]
+            ),
+            ctxt.syms.get("main").unwrap().groups[0].insts[1].opcode
+        );
+
+        ctxt.in_symbol("shrdq %rbx, %rax", "main").unwrap();
+        assert_eq!(
+            opcode(
+                "shrd",
+                &[RegMem(QWORD), Reg(QWORD), Cl],
+                vec![
+                    Operand::GENERALREGISTER(GeneralPurposeRegister::RAX),
+                    Operand::GENERALREGISTER(GeneralPurposeRegister::RBX),
+                    Operand::GENERALREGISTER(GeneralPurposeRegister::CL)
+                ]
+            ),
+            ctxt.syms.get("main").unwrap().groups[0].insts[2].opcode
+        );
+
+        // 回数は `%cl` か 8bit の即値
+        let err = ctxt.in_symbol("shrq %dl, %rax", "main").unwrap_err();
+        assert_eq!("incorrect register '%dl' used with 'shrq'", err.message);
+        let err = ctxt.in_symbol("rolq $256, %rax", "main").unwrap_err();
+        assert_eq!("immediate 256 is out of range for 'rolq'", err.message);
+    }
+
+    #[test]
+    fn parse_sse_test() {
+        let mut ctxt = new_context();
+        ctxt.toplevel("main:    \n").unwrap();
+        ctxt.in_symbol("movq %rax, %xmm0", "main").unwrap();
+        ctxt.in_symbol("movq %rax, %rbx", "main").unwrap();
+        ctxt.in_symbol("addsd -8(%rbp), %xmm9", "main").unwrap();
+        ctxt.in_symbol("cvtsi2sdl (%rax), %xmm0", "main").unwrap();
+
+        let insts = &ctxt.syms.get("main").unwrap().groups[0].insts;
+        assert_eq!(
+            opcode(
+                "movq",
+                &[Xmm, RegMem(QWORD)],
+                vec![
+                    Operand::XMMREGISTER(XMMRegister::XMM0),
+                    Operand::GENERALREGISTER(GeneralPurposeRegister::RAX)
+                ]
+            ),
+            insts[0].opcode
+        );
+        // XMM レジスタを取らない `movq` は `mov` + `q`
+        assert_eq!(
+            opcode(
+                "mov",
+                &[RegMem(QWORD), Reg(QWORD)],
+                vec![
+                    Operand::GENERALREGISTER(GeneralPurposeRegister::RBX),
+                    Operand::GENERALREGISTER(GeneralPurposeRegister::RAX)
+                ]
+            ),
+            insts[1].opcode
+        );
+        assert_eq!(
+            opcode(
+                "addsd",
+                &[Xmm, XmmMem(QWORD)],
+                vec![
+                    Operand::XMMREGISTER(XMMRegister::XMM9),
+                    Operand::ADDRESSING {
+                        base: GeneralPurposeRegister::RBP,
+                        index: None,
+                        disp: Some(Displacement::DISP8(-8)),
+                        scale: None
+                    }
+                ]
+            ),
+            insts[2].opcode
+        );
+        assert_eq!(&[Xmm, RegMem(DWORD)], insts[3].opcode.form.operands);
+
+        let err = ctxt
+            .in_symbol("cvtsi2sd (%rax), %xmm0", "main")
+            .unwrap_err();
+        assert_eq!("ambiguous operand size for 'cvtsi2sd'", err.message);
+        let err = ctxt.in_symbol("addsd %rax, %xmm0", "main").unwrap_err();
+        assert_eq!("invalid operand combination for 'addsd'", err.message);
+    }
+
+    #[test]
+    fn parse_avx_test() {
+        let mut ctxt = new_context();
+        ctxt.toplevel("main:    \n").unwrap();
+        ctxt.in_symbol("vfmadd231ps (%rdi), %ymm1, %ymm0", "main")
+            .unwrap();
+        ctxt.in_symbol("vpermq $0xd8, %ymm1, %ymm0", "main")
+            .unwrap();
+        ctxt.in_symbol("vpbroadcastd %xmm0, %ymm15", "main")
+            .unwrap();
+
+        let insts = &ctxt.syms.get("main").unwrap().groups[0].insts;
+        assert_eq!(
+            opcode(
+                "vfmadd231ps",
+                &[Ymm, Ymm, YmmMem(YMMWORD)],
+                vec![
+                    Operand::YMMREGISTER(YMMRegister::YMM0),
+                    Operand::YMMREGISTER(YMMRegister::YMM1),
+                    Operand::ADDRESSING {
+                        base: GeneralPurposeRegister::RDI,
+                        index: None,
+                        disp: None,
+                        scale: None
+                    }
+                ]
+            ),
+            insts[0].opcode
+        );
+        // `vpermq` の `q` はサフィックスではない
+        assert_eq!(
+            &[Ymm, YmmMem(YMMWORD), Imm(BYTE)],
+            insts[1].opcode.form.operands
+        );
+        assert_eq!(
+            opcode(
+                "vpbroadcastd",
+                &[Ymm, XmmMem(DWORD)],
+                vec![
+                    Operand::YMMREGISTER(YMMRegister::YMM15),
+                    Operand::XMMREGISTER(XMMRegister::XMM0)
+                ]
+            ),
+            insts[2].opcode
+        );
+
+        // XMM と YMM は混ぜられない
+        let err = ctxt
+            .in_symbol("vaddps %xmm2, %ymm1, %ymm0", "main")
+            .unwrap_err();
+        assert_eq!("invalid operand combination for 'vaddps'", err.message);
     }
 
     #[test]
     fn parse_ret_test() {
         let mut ctxt = new_context();
-        ctxt.toplevel("main:    \n");
+        ctxt.toplevel("main:    \n").unwrap();
 
-        ctxt.in_symbol("  ret\n", "main");
+        ctxt.in_symbol("  ret\n", "main").unwrap();
         assert_eq!(State::InSymbol("main".to_string()), ctxt.state);
         assert_eq!(1, ctxt.syms.get("main").unwrap().groups[0].insts.len());
         assert_eq!(
-            Opcode::RET,
+            opcode("ret", &[], vec![]),
             ctxt.syms.get("main").unwrap().groups[0].insts[0].opcode
         );
     }
@@ -443,15 +808,11 @@ mod parse_tests {
     fn parse_operand_test() {
         assert_eq!(
             Operand::GENERALREGISTER(GeneralPurposeRegister::RAX),
-            Context::parse_operand("%rax")
-        );
-        assert_eq!(
-            Operand::GENERALREGISTER(GeneralPurposeRegister::RAX),
-            Context::parse_operand("%rax,")
+            new_context().parse_operand("%rax").unwrap()
         );
         assert_eq!(
             Operand::Immediate(Immediate::I8(30)),
-            Context::parse_operand("$30")
+            new_context().parse_operand("$30").unwrap()
         );
         assert_eq!(
             Operand::ADDRESSING {
@@ -460,7 +821,7 @@ mod parse_tests {
                 disp: None,
                 scale: None,
             },
-            Context::parse_operand("(%rax)"),
+            new_context().parse_operand("(%rax)").unwrap(),
         );
         assert_eq!(
             Operand::ADDRESSING {
@@ -469,7 +830,7 @@ mod parse_tests {
                 disp: Some(Displacement::DISP8(-8)),
                 scale: None,
             },
-            Context::parse_operand("-8(%rax)"),
+            new_context().parse_operand("-8(%rax)").unwrap(),
         );
         assert_eq!(
             Operand::ADDRESSING {
@@ -478,7 +839,7 @@ mod parse_tests {
                 disp: Some(Displacement::DISP8(-8)),
                 scale: None,
             },
-            Context::parse_operand("-8(%rax, %rbx)"),
+            new_context().parse_operand("-8(%rax, %rbx)").unwrap(),
         );
         assert_eq!(
             Operand::ADDRESSING {
@@ -487,29 +848,280 @@ mod parse_tests {
                 disp: Some(Displacement::DISP8(16)),
                 scale: Some(4),
             },
-            Context::parse_operand("16(%rax, %rbx, 4)"),
+            new_context().parse_operand("16(%rax, %rbx, 4)").unwrap(),
+        );
+        assert_eq!(
+            Operand::ADDRESSING {
+                base: GeneralPurposeRegister::RAX,
+                index: Some(GeneralPurposeRegister::RBX),
+                disp: Some(Displacement::DISP8(16)),
+                scale: Some(8),
+            },
+            new_context()
+                .parse_operand("16 ( %rax , %rbx , 8 )")
+                .unwrap(),
+        );
+    }
+
+    #[test]
+    fn parse_expression_operand_test() {
+        assert_eq!(
+            Operand::Immediate(Immediate::I32(4104)),
+            new_context().parse_operand("$(1<<12)+8").unwrap()
+        );
+        assert_eq!(
+            Operand::Immediate(Immediate::I32(0x7fff)),
+            new_context().parse_operand("$0x7fff").unwrap()
+        );
+        assert_eq!(
+            Operand::ADDRESSING {
+                base: GeneralPurposeRegister::RBP,
+                index: None,
+                disp: Some(Displacement::DISP8(-8)),
+                scale: None,
+            },
+            new_context().parse_operand("-16+8(%rbp)").unwrap()
+        );
+        assert_eq!(
+            Operand::ADDRESSING {
+                base: GeneralPurposeRegister::RBP,
+                index: None,
+                disp: Some(Displacement::DISP32(-12 * 16)),
+                scale: None,
+            },
+            new_context().parse_operand("(1-13)*16(%rbp)").unwrap()
         );
     }
 
     #[test]
-    fn is_blank_line_test() {
-        assert!(Context::is_blank_line("\n"));
-        assert!(Context::is_blank_line("        \n"));
-        assert!(Context::is_blank_line("\t\t\t\t\n"));
+    fn parse_rip_relative_operand_test() {
+        assert_eq!(
+            Operand::RIPRELATIVE {
+                label: ".LC0".to_string(),
+                addend: 0
+            },
+            new_context().parse_operand(".LC0(%rip)").unwrap()
+        );
+        assert_eq!(
+            Operand::RIPRELATIVE {
+                label: "x".to_string(),
+                addend: 0
+            },
+            new_context().parse_operand("\"x\"(%rip)").unwrap()
+        );
+        assert_eq!(
+            Operand::RIPRELATIVE {
+                label: "sym".to_string(),
+                addend: 8
+            },
+            new_context().parse_operand("sym+8(%rip)").unwrap()
+        );
     }
 
     #[test]
-    fn remove_double_quote_test() {
+    fn parse_symbolic_immediate_test() {
+        assert_eq!(
+            Operand::SYMBOLIC {
+                symbol: "sym".to_string(),
+                subtrahend: None,
+                addend: 0,
+            },
+            new_context().parse_operand("$sym").unwrap()
+        );
         assert_eq!(
-            "main::entry".to_string(),
-            Context::remove_double_quote("\"main::entry\"")
+            Operand::SYMBOLIC {
+                symbol: "sym".to_string(),
+                subtrahend: None,
+                addend: 4,
+            },
+            new_context().parse_operand("$sym+4").unwrap()
         );
+        assert_eq!(
+            Operand::SYMBOLIC {
+                symbol: ".L2".to_string(),
+                subtrahend: Some(".L1".to_string()),
+                addend: 0,
+            },
+            new_context().parse_operand("$.L2-.L1").unwrap()
+        );
+
+        // imm32 の形式しか持たない
+        let mut ctxt = new_context();
+        ctxt.toplevel("main:").unwrap();
+        assert!(ctxt.in_symbol("    movq $sym, %rax", "main").is_ok());
+        assert!(ctxt.in_symbol("    pushq $sym", "main").is_ok());
+        assert!(ctxt.in_symbol("    movb $sym, %al", "main").is_err());
     }
 
-    fn new_context() -> Context {
-        Context {
-            state: State::TopLevel,
-            syms: IndexMap::new(),
+    #[test]
+    fn parse_branch_target_test() {
+        assert_eq!(
+            Operand::LABEL {
+                label: "foo".to_string(),
+                addend: 0,
+            },
+            new_context().parse_operand("foo@PLT").unwrap()
+        );
+        assert_eq!(
+            Operand::LABEL {
+                label: "bar".to_string(),
+                addend: 4,
+            },
+            new_context().parse_operand("bar+4").unwrap()
+        );
+        assert!(new_context().parse_operand("foo@GOTPCREL").is_err());
+
+        // 間接分岐は `FF /4`, `FF /2`
+        let mut ctxt = new_context();
+        ctxt.toplevel("main:    \n").unwrap();
+        ctxt.in_symbol("jmp *%rax", "main").unwrap();
+        ctxt.in_symbol("call *%rdx", "main").unwrap();
+        ctxt.in_symbol("callq *8(%rax)", "main").unwrap();
+        ctxt.in_symbol("jmp *.LC0(%rip)", "main").unwrap();
+        let bytes: Vec<Vec<u8>> = ctxt.syms["main"].groups[0]
+            .insts
+            .iter()
+            .map(|inst| inst.to_bytes())
+            .collect();
+        assert_eq!(
+            vec![
+                vec![0xff, 0xe0],
+                vec![0xff, 0xd2],
+                vec![0xff, 0x50, 0x08],
+                vec![0xff, 0x25, 0x00, 0x00, 0x00, 0x00],
+            ],
+            bytes
+        );
+
+        let cases = [
+            ("jmp %rax", 5),
+            ("call (%rax)", 6),
+            ("movq *%rax, %rbx", 6),
+            ("jmp *foo", 5),
+            ("jmp *%eax", 1),
+            ("jmp 8", 5),
+        ];
+        for (line, column) in cases {
+            let err = ctxt.in_symbol(line, "main").unwrap_err();
+            assert_eq!(column, err.column, "{}", line);
         }
     }
+
+    #[test]
+    fn parse_absolute_operand_test() {
+        assert_eq!(
+            Operand::ABSOLUTE {
+                index: Some(GeneralPurposeRegister::RAX),
+                disp: 0,
+                scale: Some(4),
+            },
+            new_context().parse_operand("0(,%rax,4)").unwrap()
+        );
+        assert_eq!(
+            Operand::ABSOLUTE {
+                index: Some(GeneralPurposeRegister::R9),
+                disp: 8,
+                scale: None,
+            },
+            new_context().parse_operand("8( , %r9)").unwrap()
+        );
+        assert_eq!(
+            Operand::ABSOLUTE {
+                index: None,
+                disp: 0x1000,
+                scale: None,
+            },
+            new_context().parse_operand("0x1000").unwrap()
+        );
+
+        // ModRM:r/m = 0b100, SIB:base = 0b101, mod = 0b00 で disp32 が続く
+        let mut ctxt = new_context();
+        ctxt.toplevel("main:    \n").unwrap();
+        ctxt.in_symbol("leaq 0(,%rax,4), %rdx", "main").unwrap();
+        ctxt.in_symbol("movl 0x1000, %eax", "main").unwrap();
+        ctxt.in_symbol("movl 8(,%r9,8), %ecx", "main").unwrap();
+        ctxt.in_symbol("jmp *-8(,%rax,8)", "main").unwrap();
+        let bytes: Vec<Vec<u8>> = ctxt.syms["main"].groups[0]
+            .insts
+            .iter()
+            .map(|inst| inst.to_bytes())
+            .collect();
+        assert_eq!(
+            vec![
+                vec![0x48, 0x8d, 0x14, 0x85, 0x00, 0x00, 0x00, 0x00],
+                vec![0x8b, 0x04, 0x25, 0x00, 0x10, 0x00, 0x00],
+                vec![0x42, 0x8b, 0x0c, 0xcd, 0x08, 0x00, 0x00, 0x00],
+                vec![0xff, 0x24, 0xc5, 0xf8, 0xff, 0xff, 0xff],
+            ],
+            bytes
+        );
+
+        // 再配置が必要な絶対アドレスは未対応
+        let err = new_context().parse_operand(".L4(,%rax,8)").unwrap_err();
+        assert_eq!(
+            "symbol in a displacement without a base register is not supported",
+            err
+        );
+        assert!(new_context().parse_operand("0x80000000").is_err());
+        let err = ctxt.in_symbol("movl foo, %eax", "main").unwrap_err();
+        assert_eq!(
+            "absolute address of a symbol is not supported for 'movl' (use RIP-relative addressing)",
+            err.message
+        );
+    }
+
+    #[test]
+    fn parse_character_constant_test() {
+        assert_eq!(
+            Operand::Immediate(Immediate::I8(97)),
+            new_context().parse_operand("$'a'").unwrap()
+        );
+        assert_eq!(
+            Operand::Immediate(Immediate::I8(10)),
+            new_context().parse_operand("$'\\n'").unwrap()
+        );
+
+        // `;`, `#`, `,` を含む文字定数で文やオペランドを区切らない
+        let mut ctxt = new_context();
+        ctxt.toplevel("main:    \n").unwrap();
+        ctxt.in_symbol("movl $';', %eax", "main").unwrap();
+        ctxt.in_symbol("cmpb $'#', %al", "main").unwrap();
+        ctxt.in_symbol("movb $',', %cl", "main").unwrap();
+        let bytes: Vec<Vec<u8>> = ctxt.syms["main"].groups[0]
+            .insts
+            .iter()
+            .map(|inst| inst.to_bytes())
+            .collect();
+        assert_eq!(
+            vec![
+                vec![0xc7, 0xc0, 0x3b, 0x00, 0x00, 0x00],
+                vec![0x3c, 0x23],
+                vec![0xc6, 0xc1, 0x2c],
+            ],
+            bytes
+        );
+    }
+
+    #[test]
+    fn parse_invalid_operand_test() {
+        assert!(new_context().parse_operand("%foo").is_err());
+        assert!(new_context().parse_operand("%xmm16").is_err());
+        assert!(new_context().parse_operand("%ymm16").is_err());
+        assert!(new_context().parse_operand("(%xmm0)").is_err());
+        assert!(new_context().parse_operand("$0x10000000000000000").is_err());
+        assert!(new_context().parse_operand("(%rax, %rbx, 3)").is_err());
+        assert!(new_context().parse_operand("-8(%rax").is_err());
+        assert!(new_context().parse_operand("8(%rip)").is_err());
+        assert!(new_context().parse_operand("$sym+0x100000000").is_err());
+        assert!(new_context().parse_operand("sym(%rax)").is_err());
+        assert!(new_context().parse_operand(".LC0(%rip, %rax)").is_err());
+    }
+
+    fn new_context() -> Context {
+        Context::new("test.s", Syntax::ATANDT)
+    }
+
+    fn opcode(mnemonic: &str, kinds: &[OperandKind], operands: Vec<Operand>) -> Opcode {
+        Opcode::with_form(mnemonic, kinds, operands).unwrap()
+    }
 }
@@ -1,6 +1,9 @@
 #[cfg(test)]
 mod parser_tests {
-    use super::super::atandt_main::*;
+    use super::super::context::parse;
+    use crate::assembler::resource::{Operand, Symbol, Syntax};
+    use crate::assembler::{AsmError, Options};
+    use indexmap::map::IndexMap;
 
     #[test]
     fn case1_test() {
@@ -23,14 +26,20 @@ main:
     ret"
         .to_string();
 
-        let syms = parse_atandt(s);
+        let syms = parse_atandt(s, "test.s").unwrap();
 
         for s in syms.iter() {
             eprintln!("{}", s.0);
         }
         assert_eq!(2, syms.len());
-        assert!(syms.get("foo").unwrap().is_global() && syms.get("foo").unwrap().is_function());
-        assert!(syms.get("main").unwrap().is_global() && syms.get("main").unwrap().is_function());
+        assert!(
+            syms.get("foo").unwrap().bind == elf_utilities::symbol::Bind::Global
+                && syms.get("foo").unwrap().ty == elf_utilities::symbol::Type::Func
+        );
+        assert!(
+            syms.get("main").unwrap().bind == elf_utilities::symbol::Bind::Global
+                && syms.get("main").unwrap().ty == elf_utilities::symbol::Type::Func
+        );
 
         assert_eq!(5, syms.get("foo").unwrap().groups[0].insts.len());
         assert_eq!(6, syms.get("main").unwrap().groups[0].insts.len());
@@ -54,15 +63,149 @@ main:
     ret"
         .to_string();
 
-        let syms = parse_atandt(s);
+        let syms = parse_atandt(s, "test.s").unwrap();
 
         for s in syms.iter() {
             eprintln!("{}", s.0);
         }
 
         assert_eq!(1, syms.len());
-        assert!(syms.get("main").unwrap().is_function());
+        assert_eq!(
+            elf_utilities::symbol::Type::Func,
+            syms.get("main").unwrap().ty
+        );
 
         assert_eq!(11, syms.get("main").unwrap().groups[0].insts.len());
     }
+
+    #[test]
+    fn collect_all_errors_test() {
+        let s = "	.globl	main
+    .type	main, @function
+main:
+    pushq	%rbp
+    movq	%rsp, %rbp
+    movq	$42, %rxx
+    frobq	%rax
+    addq	%eax, %rbx
+    movq	-8(%rbp,%rax,3), %rax
+    popq	%rbp
+    ret"
+        .to_string();
+
+        let errors = parse_atandt(s, "test.s").unwrap_err();
+
+        assert_eq!(4, errors.len());
+        assert_eq!(
+            ("test.s", 6, 15),
+            (errors[0].file.as_str(), errors[0].line, errors[0].column)
+        );
+        assert_eq!((7, 5), (errors[1].line, errors[1].column));
+        assert_eq!((8, 10), (errors[2].line, errors[2].column));
+        assert_eq!((9, 10), (errors[3].line, errors[3].column));
+        assert_eq!(
+            "test.s:8:10: error: incorrect register '%eax' used with 'addq'",
+            format!("{}", errors[2])
+        );
+    }
+
+    #[test]
+    fn numeric_label_test() {
+        let s = "main:
+1:
+    jmp 1f
+1:
+    jne 1b
+    jmp 2f
+2:
+    jmp 1b
+    ret"
+        .to_string();
+
+        let syms = parse_atandt(s, "test.s").unwrap();
+        let groups = &syms.get("main").unwrap().groups;
+
+        // 数値ラベルは定義ごとに別のラベルになる
+        assert_eq!(3, groups.len());
+        let labels: Vec<&str> = groups.iter().map(|g| g.label.as_str()).collect();
+        let targets: Vec<String> = groups
+            .iter()
+            .flat_map(|g| g.insts.iter())
+            .filter_map(|inst| {
+                inst.opcode.operands.iter().find_map(|op| match op {
+                    Operand::LABEL { label, .. } => Some(label.to_string()),
+                    _ => None,
+                })
+            })
+            .collect();
+        assert_eq!(vec![labels[1], labels[1], labels[2], labels[1]], targets);
+    }
+
+    #[test]
+    fn undefined_numeric_label_test() {
+        let s = "main:
+    jmp 1b
+    jmp 2f
+    ret"
+        .to_string();
+
+        let errors = parse_atandt(s, "test.s").unwrap_err();
+
+        assert_eq!(2, errors.len());
+        assert_eq!((2, 9), (errors[0].line, errors[0].column));
+        assert_eq!(
+            "test.s:3:9: error: undefined local label '2f'",
+            format!("{}", errors[1])
+        );
+    }
+
+    #[test]
+    fn comments_and_separators_test() {
+        let s = "/* entry point
+ */	.globl	\"my main\"  # global
+\"my main\": pushq %rbp ; movq %rsp,%rbp
+    movq -8( %rbp , %rax, 8 ),%rax # load
+1:  popq %rbp; ret"
+            .to_string();
+
+        let syms = parse_atandt(s, "test.s").unwrap();
+        let sym = syms.get("my main").unwrap();
+
+        assert_eq!(elf_utilities::symbol::Bind::Global, sym.bind);
+        assert_eq!(2, sym.groups.len());
+        assert_eq!(3, sym.groups[0].insts.len());
+        assert_eq!(2, sym.groups[1].insts.len());
+    }
+
+    #[test]
+    fn error_column_after_separator_test() {
+        let s = "main:
+    movq $1, %rax; movq $2, %rxx"
+            .to_string();
+
+        let errors = parse_atandt(s, "test.s").unwrap_err();
+
+        assert_eq!(1, errors.len());
+        assert_eq!((2, 29), (errors[0].line, errors[0].column));
+    }
+
+    #[test]
+    fn invalid_label_test() {
+        // `:` で終わるだけの文はラベルではない
+        let s = "label with space:
+main:
+    movq %rax:
+    ret"
+        .to_string();
+
+        let errors = parse_atandt(s, "test.s").unwrap_err();
+
+        assert_eq!(2, errors.len());
+        assert_eq!((1, 1), (errors[0].line, errors[0].column));
+        assert_eq!(3, errors[1].line);
+    }
+
+    fn parse_atandt(s: String, file_name: &str) -> Result<IndexMap<String, Symbol>, Vec<AsmError>> {
+        parse(s, file_name, &Options::new(Syntax::ATANDT)).map(|(syms, _, _, _)| syms)
+    }
 }
@@ -0,0 +1,328 @@
+use super::context::*;
+use super::data::parse_string_literal;
+use super::lexer::split_operands;
+use crate::assembler::AsmError;
+
+/// `.if` から `.endif` までの状態
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub struct Conditional {
+    /// `.if` の位置( `.endif` が無い場合のエラー用 )
+    line: usize,
+    column: usize,
+    /// `.if` を含むマクロ展開の深さ( `.exitm` で閉じるため )
+    depth: usize,
+    /// 外側の条件が成り立っているか
+    enclosing_active: bool,
+    /// 現在の分岐を処理するか
+    active: bool,
+    /// いずれかの分岐がすでに成り立ったか
+    taken: bool,
+    in_else: bool,
+}
+
+impl Context {
+    /// 条件が成り立たない分岐の中では,文を処理しない
+    pub fn is_assembling(&self) -> bool {
+        match self.conditionals.last() {
+            Some(conditional) => conditional.active,
+            None => true,
+        }
+    }
+
+    /// `.if`, `.else`, `.endif` 等を処理する
+    /// それ以外のディレクティブなら None
+    /// `depth` は文のマクロ展開の深さ
+    pub fn parse_conditional_directive(
+        &mut self,
+        (column, directive): (usize, &str),
+        rest: &str,
+        depth: usize,
+    ) -> Option<Result<(), AsmError>> {
+        let result = match directive {
+            ".else" => self.parse_else_directive(column, directive, None),
+            ".elseif" => self.parse_else_directive(column, directive, Some(rest)),
+            ".endif" => match self.conditionals.pop() {
+                Some(_conditional) => Ok(()),
+                None => Err(self.error_at(column, "'.endif' without '.if'".to_string())),
+            },
+            _ if is_if_directive(directive) => {
+                let enclosing_active = self.is_assembling();
+
+                // 外側の条件が成り立たない場合,条件は評価しない
+                let active = match enclosing_active {
+                    true => match self.evaluate_condition(column, directive, rest) {
+                        Ok(active) => active,
+                        Err(e) => {
+                            // 対応する `.endif` のために,成り立たない分岐として扱う
+                            self.push_conditional(column, depth, false, false);
+                            return Some(Err(e));
+                        }
+                    },
+                    false => false,
+                };
+
+                self.push_conditional(column, depth, enclosing_active, active);
+                Ok(())
+            }
+            _ => return None,
+        };
+
+        Some(result)
+    }
+
+    /// `.error "message"`, `.warning "message"`
+    pub fn parse_diagnostic_directive(
+        &mut self,
+        (column, directive): (usize, &str),
+        rest: &str,
+    ) -> Option<Result<(), AsmError>> {
+        let default_message = match directive {
+            ".error" => ".error directive invoked in source file",
+            ".warning" => ".warning directive invoked in source file",
+            ".err" => ".err encountered",
+            _ => return None,
+        };
+
+        let message = match rest.trim() {
+            "" => default_message.to_string(),
+            literal => match parse_string_literal(literal) {
+                Ok(bytes) => String::from_utf8_lossy(&bytes).to_string(),
+                Err(message) => return Some(Err(self.error_at(column, message))),
+            },
+        };
+
+        if directive == ".warning" {
+            let warning = AsmError::warning(&self.file, self.line, column, message);
+            self.warnings.push(warning);
+            return Some(Ok(()));
+        }
+
+        Some(Err(self.error_at(column, message)))
+    }
+
+    /// `.endif` が無い `.if` のエラー
+    pub fn unterminated_conditionals(&mut self) -> Vec<AsmError> {
+        std::mem::take(&mut self.conditionals)
+            .into_iter()
+            .map(|conditional| {
+                AsmError::new(
+                    &self.file,
+                    conditional.line,
+                    conditional.column,
+                    "missing '.endif'".to_string(),
+                )
+            })
+            .collect()
+    }
+
+    /// `.exitm` で抜けるマクロの中で始まった `.if` を閉じる
+    pub fn exit_conditionals(&mut self, depth: usize) {
+        while matches!(self.conditionals.last(), Some(c) if c.depth >= depth) {
+            self.conditionals.pop();
+        }
+    }
+
+    fn push_conditional(
+        &mut self,
+        column: usize,
+        depth: usize,
+        enclosing_active: bool,
+        active: bool,
+    ) {
+        self.conditionals.push(Conditional {
+            line: self.line,
+            column,
+            depth,
+            enclosing_active,
+            active,
+            taken: active,
+            in_else: false,
+        });
+    }
+
+    /// `.else`, `.elseif expr`
+    fn parse_else_directive(
+        &mut self,
+        column: usize,
+        directive: &str,
+        condition: Option<&str>,
+    ) -> Result<(), AsmError> {
+        let conditional = match self.conditionals.last() {
+            Some(conditional) if !conditional.in_else => conditional.clone(),
+            Some(_conditional) => {
+                return Err(self.error_at(column, format!("'{}' after '.else'", directive)));
+            }
+            None => return Err(self.error_at(column, format!("'{}' without '.if'", directive))),
+        };
+
+        // すでに成り立った分岐があれば,以降の分岐は処理しない
+        let active = match condition {
+            _ if !conditional.enclosing_active || conditional.taken => false,
+            Some(expr) => self.evaluate_condition(column, ".if", expr)?,
+            None => true,
+        };
+
+        let conditional = self.conditionals.last_mut().unwrap();
+        conditional.active = active;
+        conditional.taken |= active;
+        conditional.in_else = condition.is_none();
+        Ok(())
+    }
+
+    fn evaluate_condition(
+        &self,
+        column: usize,
+        directive: &str,
+        rest: &str,
+    ) -> Result<bool, AsmError> {
+        let rest = rest.trim();
+
+        let condition = match directive {
+            ".ifdef" => self.is_defined(&Self::remove_double_quote(rest)),
+            ".ifndef" | ".ifnotdef" => !self.is_defined(&Self::remove_double_quote(rest)),
+            ".ifb" => rest.is_empty(),
+            ".ifnb" => !rest.is_empty(),
+            ".ifc" | ".ifnc" => {
+                let strings = split_operands(rest, 0);
+                let equal = match strings.as_slice() {
+                    [(_, lhs), (_, rhs)] => {
+                        Self::remove_double_quote(lhs) == Self::remove_double_quote(rhs)
+                    }
+                    _ => {
+                        return Err(
+                            self.error_at(column, format!("'{}' expects two strings", directive))
+                        );
+                    }
+                };
+                equal == (directive == ".ifc")
+            }
+            _ => {
+                let value = self.evaluate_constant(column + directive.len() + 1, rest)?;
+                match directive {
+                    ".ifeq" => value == 0,
+                    ".ifgt" => value > 0,
+                    ".ifge" => value >= 0,
+                    ".iflt" => value < 0,
+                    ".ifle" => value <= 0,
+                    _ => value != 0,
+                }
+            }
+        };
+
+        Ok(condition)
+    }
+}
+
+fn is_if_directive(directive: &str) -> bool {
+    matches!(
+        directive,
+        ".if"
+            | ".ifne"
+            | ".ifeq"
+            | ".ifgt"
+            | ".ifge"
+            | ".iflt"
+            | ".ifle"
+            | ".ifdef"
+            | ".ifndef"
+            | ".ifnotdef"
+            | ".ifb"
+            | ".ifnb"
+            | ".ifc"
+            | ".ifnc"
+    )
+}
+
+#[cfg(test)]
+mod conditional_tests {
+    use crate::assembler::resource::Syntax;
+    use crate::assembler::Options;
+
+    #[test]
+    fn nested_conditional_test() {
+        let source = "    .set FRAME_SIZE, 256
+    .if FRAME_SIZE > 128
+    .byte 1
+    .ifdef HAVE_AVX2
+    .byte 2
+    .elseif FRAME_SIZE == 256
+    .byte 3
+    .else
+    .byte 4
+    .endif
+    .else
+    .ifndef UNDEFINED
+    .byte 5
+    .endif
+    .endif
+    .ifc \"a b\", a b
+    .byte 6
+    .endif
+    .ifnb
+    .byte 7
+    .endif";
+
+        assert_eq!(vec![1, 3, 6], data_bytes(source, &[]).unwrap());
+        assert_eq!(
+            vec![1, 2, 6],
+            data_bytes(source, &[("HAVE_AVX2", 1)]).unwrap()
+        );
+    }
+
+    #[test]
+    fn diagnostic_directive_test() {
+        let source = "    .ifndef CONFIG
+    .warning \"CONFIG isn't defined\"
+    .endif
+    .if CONFIG > 1
+    .error \"unsupported CONFIG\"
+    .endif";
+
+        let errors = data_bytes(source, &[("CONFIG", 2)]).unwrap_err();
+        assert_eq!(1, errors.len());
+        assert_eq!((7, 5), (errors[0].line, errors[0].column));
+        assert_eq!("unsupported CONFIG", errors[0].message);
+
+        // 警告はエラーとともに返る
+        let diagnostics = data_bytes(source, &[]).unwrap_err();
+        assert_eq!(2, diagnostics.len());
+        assert!(diagnostics[0].is_warning());
+        assert_eq!("CONFIG isn't defined", diagnostics[0].message);
+        // 未定義のシンボルは定数ではない
+        assert!(!diagnostics[1].is_warning());
+    }
+
+    #[test]
+    fn invalid_conditional_test() {
+        let errors = data_bytes("    .else\n    .endif", &[]).unwrap_err();
+        assert_eq!(2, errors.len());
+        assert_eq!("'.else' without '.if'", errors[0].message);
+
+        let errors = data_bytes("    .if 1\n    .else\n    .else\n    .endif", &[]).unwrap_err();
+        assert_eq!("'.else' after '.else'", errors[0].message);
+
+        let errors = data_bytes("\n  .if 1\n    .if 0\n    .endif", &[]).unwrap_err();
+        assert_eq!(1, errors.len());
+        assert_eq!((4, 3), (errors[0].line, errors[0].column));
+        assert_eq!("missing '.endif'", errors[0].message);
+    }
+
+    /// `source` を .data のシンボルの中身として解析する
+    fn data_bytes(
+        source: &str,
+        defined_symbols: &[(&str, i64)],
+    ) -> Result<Vec<u8>, Vec<crate::assembler::AsmError>> {
+        let source = format!("    .data\nx:\n{}", source);
+        let (syms, _, _, _) =
+            super::super::context::parse(source, "test.s", &new_options(defined_symbols))?;
+        Ok(syms["x"].codes.clone())
+    }
+
+    fn new_options(defined_symbols: &[(&str, i64)]) -> Options {
+        let mut options = Options::new(Syntax::ATANDT);
+        for (name, value) in defined_symbols.iter() {
+            options.define_symbol(name, *value);
+        }
+        options
+    }
+}
@@ -0,0 +1,1085 @@
+use super::conditional::Conditional;
+use super::expr::Value;
+use super::include::SourceFile;
+use super::lexer::Statement;
+use crate::assembler::resource::*;
+use crate::assembler::{AsmError, Options};
+use indexmap::map::IndexMap;
+
+/// 空白区切りの単語と,その桁位置(1始まり)
+pub type Words<'a> = [(usize, &'a str)];
+
+/// the parsing state shared by AT&T and Intel front-ends.
+pub struct Context {
+    pub state: State,
+    pub syms: IndexMap<String, Symbol>,
+    /// 現在の構文( `.intel_syntax`, `.att_syntax` で切り替わる)
+    pub syntax: Syntax,
+    /// ソースファイル名
+    pub file: String,
+    /// 現在解析中の行番号(1始まり)
+    pub line: usize,
+    /// リスティングで,出力したバイト列を載せる行( `sources` の添字, 行番号 )
+    /// マクロ展開の中では,呼び出した行になる
+    pub listing_line: (usize, usize),
+    /// 現在のセクション
+    pub section: String,
+    /// `.section` で登録した, `.text` 等以外のセクション
+    pub sections: IndexMap<String, CustomSection>,
+    /// セクションを切り替える直前の状態( 戻ってきたときに,同じシンボルの続きにする )
+    pub section_states: IndexMap<String, State>,
+    /// 次に定義されるシンボルに適用するアラインメント
+    pub pending_align: u64,
+    /// 数値ラベル( `1:` )ごとの定義回数
+    pub numeric_labels: IndexMap<String, usize>,
+    /// `1f` のような前方参照( ラベル, 何番目の定義を指すか, 未定義だった場合のエラー )
+    pub forward_references: Vec<(String, usize, AsmError)>,
+    /// `.long a-b` ( 値を書き込むシンボル, a, b, 解決できなかった場合のエラー )
+    pub symbol_differences: Vec<(String, String, String, AsmError)>,
+    /// `.local` で宣言したシンボル( シンボル, 定義されなかった場合のエラー )
+    pub local_declarations: Vec<(String, AsmError)>,
+    /// `.set`, `.equ`, `.equiv` で定義されたシンボルの値
+    pub equates: IndexMap<String, super::expr::Value>,
+    /// `.macro` で定義されたマクロ(名前は小文字にしてある)
+    pub macros: IndexMap<String, super::macros::Macro>,
+    /// マクロを展開した回数( `\@` の値)
+    pub macro_expansions: usize,
+    /// `.if` 等の入れ子( 内側が末尾 )
+    pub conditionals: Vec<Conditional>,
+    /// `.warning` 等の警告( 文ごとに取り出す )
+    pub warnings: Vec<AsmError>,
+    /// ソースファイルと `.include` で読み込んだファイル(先頭がソースファイル)
+    pub sources: Vec<SourceFile>,
+    /// 現在解析中のファイル( `sources` の添字 )
+    pub source: usize,
+    /// `.include`, `.incbin` のファイルを探すディレクトリ
+    pub include_paths: Vec<String>,
+}
+
+#[derive(Eq, Ord, PartialOrd, PartialEq, Debug, Clone)]
+pub enum State {
+    TopLevel,
+    InSymbol(String),
+}
+
+/// an error while building an instruction from its mnemonic and operands.
+/// the index of the operand is converted to the column by the caller.
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub enum InstError {
+    Unknown,
+    InvalidOperands,
+    AmbiguousSize,
+    /// the operand must be a label.
+    NotLabel(usize),
+    /// the operand must not be a label.
+    SymbolOperand(usize),
+    /// the register size doesn't match with the operand size.
+    IncorrectRegister(usize),
+    /// the immediate doesn't fit in the operand size.
+    ImmediateOutOfRange(usize),
+    /// `%ah` 等は REX prefix を必要とする命令では使えない
+    HighByteWithRex(usize),
+}
+
+/// the symbols, the sections registered by `.section` and the source files(with `.include`d ones)
+/// with warnings, or all errors(and warnings) found in the source.
+pub type Parsed = Result<
+    (
+        IndexMap<String, Symbol>,
+        IndexMap<String, CustomSection>,
+        Vec<SourceFile>,
+        Vec<AsmError>,
+    ),
+    Vec<AsmError>,
+>;
+
+/// parse assembly with `options`.
+pub fn parse(source: String, file_name: &str, options: &Options) -> Parsed {
+    let (statements, unterminated_comment) = super::lexer::split_statements(&source);
+    let mut context = Context::new(file_name, options.syntax);
+    context.sources[0].text = source;
+    context.include_paths = options.include_paths.clone();
+    // エラーと警告を,見つかった順に並べる
+    let mut diagnostics = Vec::new();
+
+    // `--defsym` で定義されたシンボル
+    for (name, value) in options.defined_symbols.iter() {
+        context.assign(name, Value::constant(*value as i128));
+    }
+
+    // 各文に対して処理を行う
+    // マクロは展開して,未処理の文の先頭に積む
+    // エラーが起きても次の文から解析を続ける
+    let mut pending: Vec<Statement> = statements.into_iter().rev().collect();
+    while let Some(statement) = pending.pop() {
+        context.line = statement.line;
+        context.source = statement.source;
+        context.file = context.sources[statement.source].name.clone();
+        if statement.depth == 0 {
+            context.listing_line = (statement.source, statement.line);
+        }
+
+        let result = match context.expand_statement(statement, &mut pending) {
+            Ok(Some(statement)) => match context.state.clone() {
+                State::TopLevel => context.toplevel(&statement.text),
+                State::InSymbol(sym_name) => context.in_symbol(&statement.text, &sym_name),
+            },
+            Ok(None) => Ok(()),
+            Err(e) => Err(e),
+        };
+
+        diagnostics.append(&mut context.warnings);
+        if let Err(e) = result {
+            diagnostics.push(e);
+        }
+    }
+
+    if let Some((line, column)) = unterminated_comment {
+        diagnostics.push(AsmError::new(
+            file_name,
+            line,
+            column,
+            "unterminated comment".to_string(),
+        ));
+    }
+    diagnostics.append(&mut context.unterminated_conditionals());
+    diagnostics.append(&mut context.undefined_forward_references());
+    diagnostics.append(&mut context.unresolved_differences());
+    diagnostics.append(&mut context.undefined_local_symbols());
+    if diagnostics.iter().any(|d| !d.is_warning()) {
+        return Err(diagnostics);
+    }
+
+    Ok((context.syms, context.sections, context.sources, diagnostics))
+}
+
+impl Context {
+    pub fn new(file_name: &str, syntax: Syntax) -> Self {
+        Self {
+            state: State::TopLevel,
+            syms: Default::default(),
+            syntax,
+            file: file_name.to_string(),
+            line: 0,
+            listing_line: (0, 0),
+            section: ".text".to_string(),
+            sections: IndexMap::new(),
+            section_states: IndexMap::new(),
+            pending_align: 1,
+            numeric_labels: IndexMap::new(),
+            forward_references: Vec::new(),
+            symbol_differences: Vec::new(),
+            local_declarations: Vec::new(),
+            equates: IndexMap::new(),
+            macros: IndexMap::new(),
+            macro_expansions: 0,
+            conditionals: Vec::new(),
+            warnings: Vec::new(),
+            sources: vec![SourceFile::new(file_name, None)],
+            source: 0,
+            include_paths: Vec::new(),
+        }
+    }
+
+    pub fn toplevel(&mut self, line: &str) -> Result<(), AsmError> {
+        // 空行だったら無視
+        if Self::is_blank_line(line) {
+            return Ok(());
+        }
+
+        // シンボル名の場合
+        if Self::is_label(line) {
+            let sym_name = self.label_name(line);
+            return self.define_symbol(line, &sym_name);
+        }
+
+        let words = Self::split_words(line);
+
+        self.parse_directive(line, &words)
+    }
+
+    /// `main:` のように,ラベル定義だけの文かどうか
+    /// `label with space:` や `movq %rax:` はラベルではない
+    fn is_label(line: &str) -> bool {
+        match super::lexer::label_end(line) {
+            Some(colon) => line[colon + 1..].trim().is_empty(),
+            None => false,
+        }
+    }
+
+    fn parse_directive(&mut self, line: &str, words: &Words) -> Result<(), AsmError> {
+        let (column, directive) = words[0];
+
+        match directive {
+            ".global" | ".globl" => self.parse_bind_directive(line, words, Symbol::as_global),
+            ".local" => self.parse_bind_directive(line, words, Symbol::as_local),
+            ".weak" => self.parse_bind_directive(line, words, Symbol::as_weak),
+            ".type" => self.parse_symbol_type_directive(line, words),
+            ".size" => self.parse_size_directive(line, words),
+            ".intel_syntax" => self.parse_intel_syntax_directive(words),
+            ".att_syntax" => self.parse_att_syntax_directive(words),
+            ".text" | ".data" | ".bss" => {
+                self.switch_section(directive);
+                Ok(())
+            }
+            ".section" => self.parse_section_directive(line, words),
+            ".set" | ".equ" => self.parse_set_directive(line, words, true),
+            ".equiv" => self.parse_set_directive(line, words, false),
+            ".byte" => self.parse_integer_directive(line, words, 1),
+            ".short" | ".value" | ".word" | ".2byte" => {
+                self.parse_integer_directive(line, words, 2)
+            }
+            ".long" | ".int" | ".4byte" => self.parse_integer_directive(line, words, 4),
+            ".quad" | ".8byte" => self.parse_integer_directive(line, words, 8),
+            ".zero" | ".skip" | ".space" => self.parse_zero_directive(line, words),
+            ".fill" => self.parse_fill_directive(line, words),
+            ".comm" => self.parse_common_directive(line, words, false),
+            ".lcomm" => self.parse_common_directive(line, words, true),
+            ".string" | ".asciz" => self.parse_string_directive(line, words, true),
+            ".ascii" => self.parse_string_directive(line, words, false),
+            ".align" | ".balign" => self.parse_align_directive(line, words, false),
+            ".p2align" => self.parse_align_directive(line, words, true),
+            ".incbin" => self.parse_incbin_directive(line, words),
+            // ソースファイル名やコンパイラのバージョンは出力しない
+            ".file" | ".ident" => Ok(()),
+            _ if !directive.starts_with('.') => {
+                Err(self.error_at(column, format!("'{}' must be in a symbol", directive)))
+            }
+            _ => Err(self.error_at(column, format!("unknown pseudo-op '{}'", directive))),
+        }
+    }
+
+    /// `main:` からラベル名を取り出す
+    /// 数値ラベルは定義ごとに一意な名前に変換する
+    fn label_name(&mut self, line: &str) -> String {
+        let label = Self::remove_double_quote(&Self::remove_pat_and_newline(line.trim(), ":"));
+
+        if Self::is_numeric_label(&label) {
+            return self.define_numeric_label(&label);
+        }
+
+        label
+    }
+
+    /// ラベルの定義
+    /// データセクション内のラベルは `STT_OBJECT` なシンボルとして扱う
+    fn define_symbol(&mut self, line: &str, sym_name: &str) -> Result<(), AsmError> {
+        if self.is_defined(sym_name) {
+            let column = line.len() - line.trim_start().len() + 1;
+            return Err(self.error_at(column, format!("symbol '{}' is already defined", sym_name)));
+        }
+
+        self.state = State::InSymbol(sym_name.to_string());
+
+        let section = self.section.clone();
+        let align = std::mem::replace(&mut self.pending_align, 1);
+
+        let sym = self.syms.entry(sym_name.to_string()).or_default();
+        sym.section = section.to_string();
+        sym.align = sym.align.max(align);
+        if section != ".text" && sym.ty == elf_utilities::symbol::Type::NoType {
+            sym.as_object();
+        }
+
+        Ok(())
+    }
+
+    /// ラベルか `.set` 等で定義済みかどうか
+    pub fn is_defined(&self, sym_name: &str) -> bool {
+        if self.equates.contains_key(sym_name) {
+            return true;
+        }
+
+        match self.syms.get(sym_name) {
+            Some(sym) => !sym.is_undefined(),
+            None => false,
+        }
+    }
+
+    /// `.global main` みたいなやつ
+    /// `.local main`, `.weak main` も同様
+    /// `.globl foo, bar` のように複数のシンボルを指定できる
+    fn parse_bind_directive(
+        &mut self,
+        line: &str,
+        words: &Words,
+        set_bind: fn(&mut Symbol),
+    ) -> Result<(), AsmError> {
+        let args = Self::split_arguments(line, words);
+        if args.is_empty() {
+            return Err(self.error_at(words[0].0, format!("'{}' expects symbol names", words[0].1)));
+        }
+
+        for (column, arg) in args {
+            if arg.is_empty() {
+                return Err(self.error_at(column, "missing symbol name".to_string()));
+            }
+
+            let sym_name = Self::remove_double_quote(arg);
+            let sym = self
+                .syms
+                .entry(sym_name.to_string())
+                .or_insert_with(Symbol::declared);
+            set_bind(sym);
+
+            let declared_local = sym.declared_local;
+            if declared_local && !self.local_declarations.iter().any(|(n, _)| *n == sym_name) {
+                let error = self.error_at(
+                    column,
+                    format!("local symbol '{}' is never defined", sym_name),
+                );
+                self.local_declarations.push((sym_name, error));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// `.local` で宣言したが,ラベル, `.comm` 等で定義されなかったシンボルのエラー
+    /// 未定義のローカルシンボルはリンクできないので,グローバルにせずエラーにする
+    pub fn undefined_local_symbols(&mut self) -> Vec<AsmError> {
+        let declarations = std::mem::take(&mut self.local_declarations);
+
+        declarations
+            .into_iter()
+            .filter(|(sym_name, _)| !self.is_defined(sym_name) && self.syms[sym_name].is_local())
+            .map(|(_, error)| error)
+            .collect()
+    }
+
+    /// `.size main, .-main`, `.size x, 4` みたいなやつ
+    fn parse_size_directive(&mut self, line: &str, words: &Words) -> Result<(), AsmError> {
+        let args = Self::split_arguments(line, words);
+        let (sym_name, (column, expr)) = match args.as_slice() {
+            [(_, sym_name), expr] => (Self::remove_double_quote(sym_name), *expr),
+            _ => {
+                return Err(self.error_at(
+                    words[0].0,
+                    "'.size' expects a symbol and its size".to_string(),
+                ));
+            }
+        };
+
+        let constant = self.evaluate(expr).ok().and_then(|v| v.as_constant());
+        let sym = self
+            .syms
+            .entry(sym_name.to_string())
+            .or_insert_with(Symbol::declared);
+
+        // `.-main`
+        let location_diff = expr
+            .strip_prefix('.')
+            .and_then(|rest| rest.trim_start().strip_prefix('-'))
+            .map(|name| Self::remove_double_quote(name.trim()));
+
+        let size = match (constant, location_diff) {
+            (Some(size), _) if size >= 0 => SymbolSize::Bytes(size as u64),
+            (None, Some(name)) if name == sym_name && sym.in_text() => SymbolSize::CodeLength,
+            (None, Some(name)) if name == sym_name => SymbolSize::Bytes(sym.codes.len() as u64),
+            _ => {
+                return Err(self.error_at(column, format!("unsupported size '{}'", expr)));
+            }
+        };
+
+        self.syms.get_mut(&sym_name).unwrap().size = Some(size);
+        Ok(())
+    }
+
+    /// `.type main, @function` みたいなやつ
+    fn parse_symbol_type_directive(&mut self, line: &str, words: &Words) -> Result<(), AsmError> {
+        let args = Self::split_arguments(line, words);
+        let (sym_name, (column, sym_type)) = match args.as_slice() {
+            [(_, sym_name), sym_type] if !sym_name.is_empty() => {
+                (Self::remove_double_quote(sym_name), *sym_type)
+            }
+            _ => {
+                return Err(self.error_at(
+                    words[0].0,
+                    "'.type' expects a symbol and its type".to_string(),
+                ));
+            }
+        };
+
+        let sym = self.syms.entry(sym_name).or_insert_with(Symbol::declared);
+        match sym_type {
+            "@function" => sym.as_function(),
+            "@object" => sym.as_object(),
+            _ => {
+                return Err(self.error_at(column, format!("unknown symbol type '{}'", sym_type)));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// `.intel_syntax noprefix`
+    /// GNU as と同様に,レジスタの '%' を要求する `prefix` は受け付けない
+    fn parse_intel_syntax_directive(&mut self, words: &Words) -> Result<(), AsmError> {
+        match &words[1..] {
+            [] | [(_, "noprefix")] => {
+                self.syntax = Syntax::INTEL;
+                Ok(())
+            }
+            [(column, arg), ..] => Err(self.error_at(
+                *column,
+                format!("unsupported argument '{}' for '.intel_syntax'", arg),
+            )),
+        }
+    }
+
+    /// `.att_syntax prefix`
+    fn parse_att_syntax_directive(&mut self, words: &Words) -> Result<(), AsmError> {
+        match &words[1..] {
+            [] | [(_, "prefix")] => {
+                self.syntax = Syntax::ATANDT;
+                Ok(())
+            }
+            [(column, arg), ..] => Err(self.error_at(
+                *column,
+                format!("unsupported argument '{}' for '.att_syntax'", arg),
+            )),
+        }
+    }
+
+    // シンボル名をパース後
+    pub fn in_symbol(&mut self, line: &str, sym_name: &str) -> Result<(), AsmError> {
+        // シンボル名の場合
+        if Self::is_label(line) {
+            let label_name = self.label_name(line);
+
+            // ラベルかどうかチェック
+            // データセクション内では,ラベルもシンボルとして扱う
+            if Symbol::is_local_label(&label_name) && self.section == ".text" {
+                self.push_group(sym_name, &label_name);
+            } else {
+                // ラベルではない => 別のシンボル定義と解釈
+                self.define_symbol(line, &label_name)?;
+            }
+
+            return Ok(());
+        }
+
+        if Self::is_blank_line(line) {
+            return Ok(());
+        }
+
+        // 桁位置を保つため,トリム前の行を分割する
+        let words = Self::split_words(line);
+
+        // .global等のディレクティブを見つけたら
+        // セクションを切り替えるディレクティブ以外は,シンボルの途中でも現れうる
+        if self.is_directive_start(words[0].1) {
+            return self.toplevel(line);
+        }
+
+        if self.section != ".text" {
+            return Err(self.error_at(
+                words[0].0,
+                format!("instruction '{}' must be in '.text' section", words[0].1),
+            ));
+        }
+
+        match self.syntax {
+            Syntax::ATANDT => self.parse_atandt_instruction(sym_name, line, words[0]),
+            Syntax::INTEL => self.parse_intel_instruction(sym_name, line, words[0]),
+        }
+    }
+
+    /// 構文に依らない形で命令を組み立て,現在のシンボルに追加する.
+    /// `operands` は AT&T 構文の順( src, dst )で渡す.
+    pub fn push_instruction(
+        &mut self,
+        sym_name: &str,
+        (column, mnemonic): (usize, &str),
+        name: &str,
+        size: Option<OperandSize>,
+        operands: Vec<(usize, Operand)>,
+    ) -> Result<(), AsmError> {
+        // `1b`, `1f` はここで解決しておく
+        let operands = operands
+            .into_iter()
+            .map(|(column, operand)| Ok((column, self.resolve_numeric_operand(column, operand)?)))
+            .collect::<Result<Vec<_>, AsmError>>()?;
+        let (columns, operands): (Vec<usize>, Vec<Operand>) = operands.into_iter().unzip();
+
+        let opcode = build_opcode(name, size, &operands)
+            .and_then(|opcode| reject_high_byte_with_rex(opcode, &operands))
+            .map_err(|e| {
+                let message = match e {
+                    InstError::Unknown => format!(
+                        "unknown instruction '{}' with {} operand(s)",
+                        mnemonic,
+                        columns.len()
+                    ),
+                    InstError::InvalidOperands => {
+                        format!("invalid operand combination for '{}'", mnemonic)
+                    }
+                    InstError::AmbiguousSize => {
+                        format!("ambiguous operand size for '{}'", mnemonic)
+                    }
+                    InstError::NotLabel(_) => {
+                        format!("'{}' supports only a label operand", mnemonic)
+                    }
+                    InstError::SymbolOperand(_) => {
+                        format!(
+                            "absolute address of a symbol is not supported for '{}' (use RIP-relative addressing)",
+                            mnemonic
+                        )
+                    }
+                    InstError::ImmediateOutOfRange(i) => format!(
+                        "immediate {} is out of range for '{}'",
+                        operands[i].to_intel_string(),
+                        mnemonic
+                    ),
+                    InstError::IncorrectRegister(i) => format!(
+                        "incorrect register '{}' used with '{}'",
+                        match self.syntax {
+                            Syntax::ATANDT => operands[i].to_at_string(),
+                            Syntax::INTEL => operands[i].to_intel_string(),
+                        },
+                        mnemonic
+                    ),
+                    InstError::HighByteWithRex(i) => format!(
+                        "can't encode register '{}' in an instruction requiring REX prefix",
+                        match self.syntax {
+                            Syntax::ATANDT => operands[i].to_at_string(),
+                            Syntax::INTEL => operands[i].to_intel_string(),
+                        },
+                    ),
+                };
+
+                let column = match e {
+                    InstError::NotLabel(i)
+                    | InstError::SymbolOperand(i)
+                    | InstError::IncorrectRegister(i)
+                    | InstError::ImmediateOutOfRange(i)
+                    | InstError::HighByteWithRex(i) => columns[i],
+                    _ => column,
+                };
+
+                self.error_at(column, message)
+            })?;
+
+        // `$.L2-.L1` が解決できるかは,すべてのラベルが定義されてから確かめる
+        for (column, operand) in columns.iter().zip(operands.iter()) {
+            if let Operand::SYMBOLIC {
+                symbol,
+                subtrahend: Some(subtrahend),
+                ..
+            } = operand
+            {
+                let error = self.error_at(
+                    *column,
+                    format!("can't resolve '{}' - '{}'", symbol, subtrahend),
+                );
+                self.symbol_differences.push((
+                    sym_name.to_string(),
+                    symbol.to_string(),
+                    subtrahend.to_string(),
+                    error,
+                ));
+            }
+        }
+
+        self.push_inst_cur_sym(sym_name, Instruction { opcode });
+        Ok(())
+    }
+
+    pub fn remove_double_quote(op: &str) -> String {
+        op.trim_start_matches('"').trim_end_matches('"').to_string()
+    }
+
+    fn is_directive_start(&self, directive: &str) -> bool {
+        directive.starts_with('.')
+    }
+
+    fn push_inst_cur_sym(&mut self, sym_name: &str, inst: Instruction) {
+        if let Some(sym) = self.syms.get_mut(sym_name) {
+            if sym.groups.is_empty() {
+                sym.groups
+                    .push(Group::new(&format!(".L{}_entry", sym_name)));
+            }
+
+            let group_idx = sym.groups.len() - 1;
+            sym.groups[group_idx].insts.push(inst);
+
+            // バイト列の範囲はコード生成時に決まる
+            let inst_idx = sym
+                .groups
+                .iter()
+                .map(|group| group.insts.len())
+                .sum::<usize>()
+                - 1;
+            sym.push_line(self.listing_line, inst_idx, inst_idx + 1);
+
+            return;
+        }
+
+        unreachable!();
+    }
+
+    fn push_group(&mut self, sym_name: &str, label_name: &str) {
+        self.syms
+            .get_mut(sym_name)
+            .unwrap()
+            .groups
+            .push(Group::new(&Self::remove_double_quote(label_name)));
+    }
+
+    pub fn remove_pat_and_newline(line: &str, pat: &str) -> String {
+        line.trim_end().trim_end_matches(pat).to_string()
+    }
+
+    pub fn is_blank_line(line: &str) -> bool {
+        line.trim_end().is_empty()
+    }
+
+    /// 空白で区切った単語を,桁位置とともに返す
+    pub fn split_words(line: &str) -> Vec<(usize, &str)> {
+        let mut words = Vec::new();
+        let mut start = None;
+
+        for (idx, c) in line.char_indices() {
+            match (c.is_ascii_whitespace(), start) {
+                (true, Some(s)) => {
+                    words.push((s + 1, &line[s..idx]));
+                    start = None;
+                }
+                (false, None) => start = Some(idx),
+                _ => {}
+            }
+        }
+
+        if let Some(s) = start {
+            words.push((s + 1, &line[s..]));
+        }
+
+        words
+    }
+
+    pub fn error_at(&self, column: usize, message: String) -> AsmError {
+        AsmError::new(&self.file, self.line, column, message)
+    }
+}
+
+/// build an instruction from the mnemonic(without size suffix) and its operands.
+/// if `size` is `None`, it's inferred from register operands.
+fn build_opcode(
+    name: &str,
+    size: Option<OperandSize>,
+    operands: &[Operand],
+) -> Result<Opcode, InstError> {
+    // 転送元と転送先のサイズが異なる
+    if let (Some(extension), 2) = (Extension::from_mnemonic(name), operands.len()) {
+        return build_extension(extension, size, operands);
+    }
+
+    let candidates = InstructionForm::candidates(name, operands.len());
+    if candidates.is_empty() {
+        return Err(InstError::Unknown);
+    }
+
+    // 命令表は Intel SDM の順なので,エラーの位置は AT&T 構文の順に戻す
+    let operands: Vec<Operand> = operands.iter().rev().cloned().collect();
+    let at = |i: usize| operands.len() - 1 - i;
+
+    // シフト回数( `$3`, `%cl` )のように命令のサイズに従わないオペランドは,サイズの推論・検査から除く
+    let sized: Vec<bool> = (0..operands.len())
+        .map(|i| candidates.iter().all(|(form, _)| form.follows_size(i)))
+        .collect();
+
+    // `sete (%rax)` のように,サイズが一つしかない命令は省略できる
+    let size = match size.or_else(|| infer_size(&operands, &sized)) {
+        Some(size) => Some(size),
+        // `jmp .L2` と `jmp *(%rax)` のように,ラベルを取るかどうかで形式が分かれる命令もある
+        None => {
+            let takes_label = operands
+                .iter()
+                .any(|operand| matches!(operand, Operand::LABEL { .. }));
+            let mut sizes = candidates
+                .iter()
+                .filter(|(form, _)| form.takes_label() == takes_label)
+                .map(|(form, _)| form.size());
+            let size = sizes.next().flatten();
+            if sizes.any(|s| s != size) {
+                return Err(InstError::AmbiguousSize);
+            }
+            size
+        }
+    };
+
+    for (i, operand) in operands.iter().enumerate() {
+        let mut kinds = candidates
+            .iter()
+            .filter_map(|(form, _)| form.operands.get(i));
+        let takes_label = kinds
+            .clone()
+            .any(|kind| matches!(kind, OperandKind::Rel(_)));
+
+        match (operand, size) {
+            // `call`, `jmp` 等以外では,ラベルオペランドは未対応
+            (Operand::LABEL { .. }, _) if !takes_label => {
+                return Err(InstError::SymbolOperand(at(i)))
+            }
+            (Operand::LABEL { .. }, _) => {}
+            _ if kinds
+                .clone()
+                .all(|kind| matches!(kind, OperandKind::Rel(_))) =>
+            {
+                return Err(InstError::NotLabel(at(i)))
+            }
+            // レジスタのサイズは命令のサイズと一致している必要がある
+            (Operand::GENERALREGISTER(_), Some(size)) if sized[i] && operand.size() != size => {
+                return Err(InstError::IncorrectRegister(at(i)))
+            }
+            // 8bit, 16bit の即値は符号無しの値も書ける( `andb $0xff, %al` )
+            (Operand::Immediate(imm), Some(size)) if sized[i] && !imm.fits_in(size) => {
+                return Err(InstError::ImmediateOutOfRange(at(i)))
+            }
+            // シフト回数は `%cl` か 8bit の即値
+            (Operand::GENERALREGISTER(_), _)
+                if !sized[i]
+                    && kinds.clone().all(|kind| kind.is_count())
+                    && !kinds.any(|kind| kind.matches(operand)) =>
+            {
+                return Err(InstError::IncorrectRegister(at(i)))
+            }
+            (Operand::Immediate(_), _)
+                if !sized[i]
+                    && kinds.clone().all(|kind| kind.is_count())
+                    && !kinds.any(|kind| kind.matches(operand)) =>
+            {
+                return Err(InstError::ImmediateOutOfRange(at(i)))
+            }
+            _ => {}
+        }
+    }
+
+    let operands: Vec<Operand> = operands
+        .iter()
+        .zip(sized.iter())
+        .map(|(operand, sized)| match size {
+            Some(OperandSize::BYTE) if *sized => operand.to_8bit(),
+            Some(OperandSize::WORD) if *sized => operand.to_16bit(),
+            Some(OperandSize::DWORD) if *sized => operand.to_32bit(),
+            Some(OperandSize::QWORD) if *sized => operand.to_64bit(),
+            _ => operand.clone(),
+        })
+        .collect();
+
+    // 先に書かれた形式を優先する
+    // SSE, AVX の命令の `QWORD PTR` 等は,メモリオペランドのサイズ( `movsd xmm0, QWORD PTR [rax]` )
+    candidates
+        .iter()
+        .filter(|(form, _)| {
+            form.size() == size
+                || (form.size().is_none()
+                    && size.is_some_and(|size| form.takes_vector_memory(size)))
+        })
+        .find_map(|(form, cond)| {
+            let operands = form.complete(&operands)?;
+            match form.matches(&operands) {
+                true => Some(Opcode::new(form, *cond, operands)),
+                false => None,
+            }
+        })
+        .ok_or(InstError::InvalidOperands)
+}
+
+/// REX prefix があると, `%ah` 等は `%spl` 等と解釈されてしまう
+/// `operands` は AT&T 構文の順
+fn reject_high_byte_with_rex(opcode: Opcode, operands: &[Operand]) -> Result<Opcode, InstError> {
+    if opcode.rex_prefix().is_none() {
+        return Ok(opcode);
+    }
+
+    match operands
+        .iter()
+        .position(|op| matches!(op, Operand::GENERALREGISTER(r) if r.is_high_byte()))
+    {
+        Some(i) => Err(InstError::HighByteWithRex(i)),
+        None => Ok(opcode),
+    }
+}
+
+/// `movzbl`, `movsx` 等
+struct Extension {
+    signed: bool,
+    /// 転送元のサイズ( `movzx` のように省略されることもある)
+    rm_size: Option<OperandSize>,
+    /// 転送先のサイズ
+    size: Option<OperandSize>,
+}
+
+impl Extension {
+    /// `movzbl` -> (zero, BYTE, DWORD)
+    fn from_mnemonic(name: &str) -> Option<Self> {
+        let (signed, suffix) = match name {
+            "movzx" => (false, ""),
+            "movsx" => (true, ""),
+            "movsxd" => (true, "l"),
+            _ => match (name.strip_prefix("movz"), name.strip_prefix("movs")) {
+                (Some(suffix), _) => (false, suffix),
+                (_, Some(suffix)) => (true, suffix),
+                _ => return None,
+            },
+        };
+
+        let sizes = suffix
+            .chars()
+            .map(|c| match c {
+                'b' => Some(OperandSize::BYTE),
+                'w' => Some(OperandSize::WORD),
+                'l' => Some(OperandSize::DWORD),
+                'q' => Some(OperandSize::QWORD),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        // `movzb` や `movzbwl` は無い
+        let (rm_size, size) = match (name, sizes.as_slice()) {
+            (_, []) => (None, None),
+            ("movsxd", [rm_size]) => (Some(*rm_size), None),
+            (_, [rm_size, size]) => (Some(*rm_size), Some(*size)),
+            _ => return None,
+        };
+
+        Some(Self {
+            signed,
+            rm_size,
+            size,
+        })
+    }
+}
+
+/// `size` は Intel構文の `BYTE PTR` 等,または転送先のレジスタのサイズ
+fn build_extension(
+    extension: Extension,
+    size: Option<OperandSize>,
+    operands: &[Operand],
+) -> Result<Opcode, InstError> {
+    if let Some(i) = operands
+        .iter()
+        .position(|op| matches!(op, Operand::LABEL { .. }))
+    {
+        return Err(InstError::SymbolOperand(i));
+    }
+
+    let (src, dst) = (&operands[0], &operands[1]);
+    let dst_size = match dst {
+        Operand::GENERALREGISTER(_) => dst.size(),
+        _ => return Err(InstError::InvalidOperands),
+    };
+    if extension.size.is_some_and(|size| size != dst_size) {
+        return Err(InstError::IncorrectRegister(1));
+    }
+
+    let rm_size = match src {
+        Operand::GENERALREGISTER(_) if extension.rm_size.is_some_and(|s| s != src.size()) => {
+            return Err(InstError::IncorrectRegister(0))
+        }
+        Operand::GENERALREGISTER(_) => src.size(),
+        _ => match extension
+            .rm_size
+            .or_else(|| size.filter(|size| *size != dst_size))
+        {
+            Some(rm_size) => rm_size,
+            None => return Err(InstError::AmbiguousSize),
+        },
+    };
+
+    // `movslq` は `movsxd`
+    let mnemonic = match (extension.signed, rm_size) {
+        (true, OperandSize::DWORD) => "movsxd",
+        (true, _) => "movsx",
+        (false, _) => "movzx",
+    };
+    let kinds = [OperandKind::Reg(dst_size), OperandKind::RegMem(rm_size)];
+    if !kinds[1].matches(src) {
+        return Err(InstError::InvalidOperands);
+    }
+
+    Opcode::with_form(mnemonic, &kinds, vec![dst.clone(), src.clone()])
+        .ok_or(InstError::InvalidOperands)
+}
+
+/// オペランドサイズを持つ命令かどうか
+/// `setl` や `cmovl` は `l` サフィックスではなく条件
+pub fn is_sized_instruction(name: &str, operand_count: usize) -> bool {
+    InstructionForm::candidates(name, operand_count)
+        .iter()
+        .any(|(form, _)| form.size().is_some())
+}
+
+/// XMM, YMM レジスタを取る命令かどうか
+pub fn is_simd_instruction(name: &str, operand_count: usize) -> bool {
+    InstructionForm::candidates(name, operand_count)
+        .iter()
+        .any(|(form, _)| form.is_simd())
+}
+
+/// レジスタオペランドからサイズを推論する
+/// AT&T 構文の順で最初のレジスタを使う
+fn infer_size(operands: &[Operand], sized: &[bool]) -> Option<OperandSize> {
+    operands
+        .iter()
+        .zip(sized.iter())
+        .rev()
+        .find_map(|(operand, sized)| match operand {
+            Operand::GENERALREGISTER(_) if *sized => Some(operand.size()),
+            _ => None,
+        })
+}
+
+#[cfg(test)]
+mod context_tests {
+    use super::*;
+
+    #[test]
+    fn parse_symname_test() {
+        let mut ctxt = new_context();
+        ctxt.toplevel("main:    \n").unwrap();
+
+        assert!(!ctxt.syms.is_empty());
+        assert_eq!(State::InSymbol("main".to_string()), ctxt.state);
+        assert!(ctxt.syms.get("main").is_some());
+
+        ctxt.toplevel("\"aarch64::main\":    \n").unwrap();
+
+        assert!(!ctxt.syms.is_empty());
+        assert_eq!(State::InSymbol("aarch64::main".to_string()), ctxt.state);
+        assert!(ctxt.syms.get("aarch64::main").is_some());
+    }
+
+    #[test]
+    fn parse_global_directive_test() {
+        let mut ctxt = new_context();
+        ctxt.toplevel("    .global main    \n").unwrap();
+
+        assert!(!ctxt.syms.is_empty());
+        assert_eq!(State::TopLevel, ctxt.state);
+        assert_eq!(
+            elf_utilities::symbol::Bind::Global,
+            ctxt.syms.get("main").unwrap().bind
+        );
+    }
+
+    #[test]
+    fn parse_type_directive_test() {
+        let mut ctxt = new_context();
+        ctxt.toplevel("    .type    main, @function    \n").unwrap();
+
+        assert!(!ctxt.syms.is_empty());
+        assert_eq!(State::TopLevel, ctxt.state);
+        assert_eq!(
+            elf_utilities::symbol::Type::Func,
+            ctxt.syms.get("main").unwrap().ty
+        );
+    }
+
+    #[test]
+    fn parse_object_type_directive_test() {
+        let mut ctxt = new_context();
+        ctxt.toplevel("    .type    x, @object    \n").unwrap();
+
+        assert_ne!(
+            elf_utilities::symbol::Type::Func,
+            ctxt.syms.get("x").unwrap().ty
+        );
+        assert!(ctxt.toplevel("    .type    x, @foo    \n").is_err());
+    }
+
+    #[test]
+    fn parse_bind_directive_test() {
+        let mut ctxt = new_context();
+        ctxt.toplevel("    .weak    foo\n").unwrap();
+        ctxt.toplevel("    .globl    bar\n").unwrap();
+        ctxt.toplevel("    .local    bar\n").unwrap();
+
+        assert_eq!(
+            elf_utilities::symbol::Bind::Weak,
+            ctxt.syms.get("foo").unwrap().bind
+        );
+        assert!(ctxt.syms.get("bar").unwrap().is_local());
+    }
+
+    #[test]
+    fn undefined_local_symbol_test() {
+        let mut ctxt = new_context();
+        ctxt.toplevel("    .local foo, bar, baz, counter").unwrap();
+        ctxt.toplevel("    .local foo").unwrap();
+        ctxt.toplevel("    .comm counter,4,4").unwrap();
+        ctxt.toplevel("    .globl baz").unwrap();
+        ctxt.toplevel("bar:").unwrap();
+
+        // 宣言した束縛のまま,定義されていないことを報告する
+        let errors = ctxt.undefined_local_symbols();
+        assert_eq!(1, errors.len());
+        assert_eq!("local symbol 'foo' is never defined", errors[0].message);
+        assert_eq!(12, errors[0].column);
+        assert!(ctxt.syms["foo"].is_local());
+    }
+
+    #[test]
+    fn parse_size_directive_test() {
+        let mut ctxt = new_context();
+        ctxt.toplevel("main:\n").unwrap();
+        ctxt.in_symbol("    .size    main, .-main\n", "main")
+            .unwrap();
+        ctxt.toplevel("    .data\n").unwrap();
+        ctxt.toplevel("x:\n").unwrap();
+        ctxt.in_symbol("    .quad    1\n", "x").unwrap();
+        ctxt.in_symbol("    .size    x, . - x\n", "x").unwrap();
+        ctxt.in_symbol("    .size    y, 4\n", "x").unwrap();
+
+        assert_eq!(
+            Some(SymbolSize::CodeLength),
+            ctxt.syms.get("main").unwrap().size
+        );
+        assert_eq!(Some(SymbolSize::Bytes(8)), ctxt.syms.get("x").unwrap().size);
+        assert_eq!(Some(SymbolSize::Bytes(4)), ctxt.syms.get("y").unwrap().size);
+        assert!(ctxt.in_symbol("    .size    x, .-y\n", "x").is_err());
+    }
+
+    #[test]
+    fn parse_syntax_directive_test() {
+        let mut ctxt = new_context();
+        ctxt.toplevel("    .intel_syntax noprefix\n").unwrap();
+        assert_eq!(Syntax::INTEL, ctxt.syntax);
+
+        ctxt.toplevel("    .att_syntax\n").unwrap();
+        assert_eq!(Syntax::ATANDT, ctxt.syntax);
+
+        ctxt.toplevel("    .intel_syntax\n").unwrap();
+        assert_eq!(Syntax::INTEL, ctxt.syntax);
+
+        assert!(ctxt.toplevel("    .intel_syntax prefix\n").is_err());
+    }
+
+    #[test]
+    fn is_blank_line_test() {
+        assert!(Context::is_blank_line("\n"));
+        assert!(Context::is_blank_line("        \n"));
+        assert!(Context::is_blank_line("\t\t\t\t\n"));
+    }
+
+    #[test]
+    fn remove_double_quote_test() {
+        assert_eq!(
+            "main::entry".to_string(),
+            Context::remove_double_quote("\"main::entry\"")
+        );
+    }
+
+    #[test]
+    fn split_words_test() {
+        assert_eq!(
+            vec![(5, "movq"), (10, "$42,"), (15, "%rax")],
+            Context::split_words("    movq $42, %rax")
+        );
+    }
+
+    fn new_context() -> Context {
+        Context::new("test.s", Syntax::ATANDT)
+    }
+}
@@ -0,0 +1,873 @@
+use super::context::*;
+use super::expr::Value;
+use super::lexer::split_operands;
+use crate::assembler::resource::{CustomSection, Fixup, Symbol, SymbolSize, BUILTIN_SECTIONS};
+use crate::assembler::AsmError;
+
+impl Context {
+    /// `.text`, `.data`, `.bss`
+    /// 命令を置けるのは .text だけなので, `.text.startup` のようなサブセクションは `.text` にまとめる.
+    /// それ以外のセクションは `.section` で登録したものを出力する.
+    /// 以前に切り替えたセクションに戻った場合は,そのとき定義中だったシンボルの続きになる.
+    pub fn switch_section(&mut self, name: &str) {
+        let section = match name.starts_with(".text.") {
+            true => ".text".to_string(),
+            false => name.to_string(),
+        };
+
+        let state = std::mem::replace(&mut self.state, State::TopLevel);
+        self.section_states
+            .insert(std::mem::replace(&mut self.section, section), state);
+        if let Some(state) = self.section_states.get(&self.section) {
+            self.state = state.clone();
+        }
+        self.pending_align = 1;
+    }
+
+    /// `.section .rodata.str1.1,"aMS",@progbits,1` みたいなやつ
+    /// `.init_array` 等のフラグを省略したセクションには, GNU as と同じ属性を与える
+    pub fn parse_section_directive(&mut self, line: &str, words: &Words) -> Result<(), AsmError> {
+        let args = Self::split_arguments(line, words);
+        let (column, name) = match args.first() {
+            Some(&(column, name)) if !name.is_empty() => (column, name),
+            _ => {
+                return Err(self.error_at(words[0].0, "'.section' expects a name".to_string()));
+            }
+        };
+
+        let name = Self::remove_double_quote(name);
+        if name.contains(char::is_whitespace) {
+            return Err(self.error_at(column, format!("invalid section name '{}'", name)));
+        }
+
+        self.switch_section(&name);
+        if self.section != name {
+            let message = format!("section '{}' is merged into '{}'", name, self.section);
+            self.warnings
+                .push(AsmError::warning(&self.file, self.line, column, message));
+        }
+        if BUILTIN_SECTIONS.contains(&self.section.as_str()) {
+            return Ok(());
+        }
+
+        let mut attributes = CustomSection::with_name(&name);
+        if let Some(&(column, flags)) = args.get(1) {
+            let flags =
+                parse_string_literal(flags).map_err(|message| self.error_at(column, message))?;
+            attributes.flags = CustomSection::parse_flags(&String::from_utf8_lossy(&flags))
+                .map_err(|c| self.error_at(column, format!("unsupported section flag '{}'", c)))?;
+        }
+        if let Some(&(column, ty)) = args.get(2) {
+            attributes.ty = CustomSection::parse_type(ty)
+                .ok_or_else(|| self.error_at(column, format!("unknown section type '{}'", ty)))?;
+        }
+        if let Some(&(column, entsize)) = args.get(3) {
+            attributes.entsize = self.evaluate_constant(column, entsize)? as u64;
+        }
+
+        // 属性は最初に現れた `.section` のものを使う
+        self.sections.entry(name).or_insert(attributes);
+        Ok(())
+    }
+
+    /// `.long 1, 2` みたいなやつ
+    pub fn parse_integer_directive(
+        &mut self,
+        line: &str,
+        words: &Words,
+        size: usize,
+    ) -> Result<(), AsmError> {
+        for (column, arg) in Self::split_arguments(line, words) {
+            if arg.is_empty() {
+                return Err(self.error_at(column, "missing value".to_string()));
+            }
+
+            // `.` は引数ごとに進むので,1つずつ追加する
+            let value = self
+                .evaluate(arg)
+                .map_err(|message| self.error_at(column, message))?;
+            let value = match value.as_constant() {
+                Some(v) => v,
+                None => {
+                    self.push_fixup(words[0], column, size, value)?;
+                    continue;
+                }
+            };
+
+            // 符号付き/符号無しのどちらかで表現できればよい
+            let bits = size as u32 * 8;
+            if bits < 64 && (value >= 1 << bits || value < -(1 << (bits - 1))) {
+                return Err(self.error_at(
+                    column,
+                    format!("value {} doesn't fit in {} byte(s)", value, size),
+                ));
+            }
+
+            self.push_data(words[0], value.to_le_bytes()[..size].to_vec())?;
+        }
+
+        Ok(())
+    }
+
+    /// `.quad .LC0+8`, `.long .L3-.L2` のような,レイアウト後に決まる値
+    /// 0で埋めておき,コード生成時に値か再配置で埋める
+    fn push_fixup(
+        &mut self,
+        directive: (usize, &str),
+        column: usize,
+        size: usize,
+        value: Value,
+    ) -> Result<(), AsmError> {
+        if self.in_nobits_section() {
+            return Err(self.error_at(
+                column,
+                format!("symbols can't be used in section '{}'", self.section),
+            ));
+        }
+
+        let symbol = self.resolve_numeric_reference(column, value.symbol.unwrap())?;
+        let subtrahend = match value.subtrahend {
+            Some(name) => Some(self.resolve_numeric_reference(column, name)?),
+            None => None,
+        };
+
+        self.push_data(directive, vec![0x00; size])?;
+
+        let sym_name = match &self.state {
+            State::InSymbol(sym_name) => sym_name.to_string(),
+            State::TopLevel => unreachable!(),
+        };
+
+        // 差のシンボルが解決できるかは,すべてのラベルが定義されてから確かめる
+        if let Some(subtrahend) = &subtrahend {
+            let error = self.error_at(
+                column,
+                format!("can't resolve '{}' - '{}'", symbol, subtrahend),
+            );
+            self.symbol_differences.push((
+                sym_name.to_string(),
+                symbol.to_string(),
+                subtrahend.to_string(),
+                error,
+            ));
+        }
+
+        let sym = self.syms.get_mut(&sym_name).unwrap();
+        sym.fixups.push(Fixup {
+            offset: sym.codes.len() - size,
+            size,
+            symbol,
+            subtrahend,
+            addend: value.constant as i64,
+            signed: false,
+        });
+
+        Ok(())
+    }
+
+    /// `a - b` は, `a` と `b` が同じセクションにあるか,
+    /// `b` が値を書き込むシンボルと同じセクションにある( PC相対 )場合のみ解決できる
+    pub fn unresolved_differences(&mut self) -> Vec<AsmError> {
+        let differences = std::mem::take(&mut self.symbol_differences);
+
+        differences
+            .into_iter()
+            .filter(|(sym_name, minuend, subtrahend, _)| {
+                let section = self.syms[sym_name].section.as_str();
+                match self.label_section(subtrahend) {
+                    Some(sub_section) => {
+                        self.label_section(minuend) != Some(sub_section) && sub_section != section
+                    }
+                    None => true,
+                }
+            })
+            .map(|(_, _, _, error)| error)
+            .collect()
+    }
+
+    /// ラベルが定義されているセクション
+    /// .text のラベル( `.L2` 等)はシンボルではなくグループになっている
+    fn label_section(&self, name: &str) -> Option<&str> {
+        match self.syms.get(name) {
+            Some(sym) if !sym.section.is_empty() => Some(sym.section.as_str()),
+            _ => self
+                .syms
+                .values()
+                .find(|sym| sym.groups.iter().any(|group| group.label == name))
+                .map(|sym| sym.section.as_str()),
+        }
+    }
+
+    /// `.zero 4` みたいなやつ
+    pub fn parse_zero_directive(&mut self, line: &str, words: &Words) -> Result<(), AsmError> {
+        let args = Self::split_arguments(line, words);
+
+        let (column, arg) = match args.first() {
+            Some(&arg) => arg,
+            None => {
+                return Err(self.error_at(words[0].0, format!("'{}' expects a size", words[0].1)));
+            }
+        };
+        let length = match self.evaluate_constant(column, arg)? {
+            v if v >= 0 => v as usize,
+            _ => return Err(self.error_at(column, format!("invalid size '{}'", arg))),
+        };
+
+        // 埋める値を指定できる
+        let fill = match args.get(1) {
+            Some(&(column, arg)) => self.evaluate_constant(column, arg)? as u8,
+            None => 0,
+        };
+
+        self.push_data(words[0], vec![fill; length])
+    }
+
+    /// `.fill 200, 1, 0x90` みたいなやつ
+    /// GNU as と同様に, `size` が4バイトを超える場合は上位を0で埋める
+    pub fn parse_fill_directive(&mut self, line: &str, words: &Words) -> Result<(), AsmError> {
+        let args = Self::split_arguments(line, words);
+
+        let (column, arg) = match args.first() {
+            Some(&arg) => arg,
+            None => {
+                return Err(self.error_at(words[0].0, "'.fill' expects a count".to_string()));
+            }
+        };
+        let repeat = match self.evaluate_constant(column, arg)? {
+            v if v >= 0 => v as usize,
+            _ => return Err(self.error_at(column, format!("invalid count '{}'", arg))),
+        };
+
+        let size = match args.get(1) {
+            Some(&(column, arg)) => match self.evaluate_constant(column, arg)? {
+                v if (0..=8).contains(&v) => v as usize,
+                _ => return Err(self.error_at(column, format!("invalid size '{}'", arg))),
+            },
+            None => 1,
+        };
+        let value = match args.get(2) {
+            Some(&(column, arg)) => self.evaluate_constant(column, arg)?,
+            None => 0,
+        };
+
+        let unit = (value as u32 as u64).to_le_bytes()[..size].to_vec();
+        self.push_data(words[0], unit.repeat(repeat))
+    }
+
+    /// `.comm counter, 4, 4`, `.lcomm buf, 64` みたいなやつ
+    /// `.comm` のシンボルは,GNU as と同様にグローバルな COMMON シンボルになる.
+    /// `.lcomm` と, `.local` で宣言したシンボルの `.comm` は .bss に確保する.
+    pub fn parse_common_directive(
+        &mut self,
+        line: &str,
+        words: &Words,
+        local: bool,
+    ) -> Result<(), AsmError> {
+        let args = Self::split_arguments(line, words);
+        let (sym_name, (column, size), align) = match args.as_slice() {
+            [(_, sym_name), size] if !sym_name.is_empty() => (*sym_name, *size, None),
+            [(_, sym_name), size, align] if !sym_name.is_empty() => {
+                (*sym_name, *size, Some(*align))
+            }
+            _ => {
+                return Err(self.error_at(
+                    words[0].0,
+                    format!("'{}' expects a symbol and its size", words[0].1),
+                ));
+            }
+        };
+
+        let sym_name = Self::remove_double_quote(sym_name);
+        if self.is_defined(&sym_name) {
+            return Err(self.error_at(
+                args[0].0,
+                format!("symbol '{}' is already defined", sym_name),
+            ));
+        }
+
+        let size = match self.evaluate_constant(column, size)? {
+            v if v >= 0 => v as u64,
+            _ => return Err(self.error_at(column, format!("invalid size '{}'", size))),
+        };
+        // 省略した場合は,サイズ以下で最大の2の冪( 16 まで )
+        let align = match align {
+            Some((column, arg)) => match self.evaluate_constant(column, arg)? {
+                v if v > 0 && (v as u64).is_power_of_two() => v as u64,
+                _ => return Err(self.error_at(column, format!("invalid alignment '{}'", arg))),
+            },
+            None => (1..=16)
+                .rev()
+                .find(|a: &u64| a.is_power_of_two() && *a <= size.max(1))
+                .unwrap(),
+        };
+
+        let sym = self.syms.entry(sym_name).or_insert_with(Symbol::declared);
+        if sym.ty == elf_utilities::symbol::Type::NoType {
+            sym.as_object();
+        }
+        if local || sym.declared_local {
+            sym.section = ".bss".to_string();
+            sym.codes = vec![0x00; size as usize];
+            sym.align = align;
+            if sym.size.is_none() {
+                sym.size = Some(SymbolSize::Bytes(size));
+            }
+            return Ok(());
+        }
+
+        sym.as_common(size, align);
+        if sym.is_local() {
+            sym.as_global();
+        }
+
+        Ok(())
+    }
+
+    /// `.string "Hello, world\n"` みたいなやつ
+    pub fn parse_string_directive(
+        &mut self,
+        line: &str,
+        words: &Words,
+        null_terminated: bool,
+    ) -> Result<(), AsmError> {
+        let mut bytes = Vec::new();
+
+        for (column, arg) in Self::split_arguments(line, words) {
+            let mut s =
+                parse_string_literal(arg).map_err(|message| self.error_at(column, message))?;
+            bytes.append(&mut s);
+
+            if null_terminated {
+                bytes.push(0x00);
+            }
+        }
+
+        self.push_data(words[0], bytes)
+    }
+
+    /// `.align 8`, `.p2align 3` みたいなやつ
+    /// シンボルの途中で現れた場合は境界までパディングし,次のシンボルの先頭も揃える.
+    /// ( シンボルの先頭を動かすと, `.long y-x` 等の値が変わってしまう )
+    pub fn parse_align_directive(
+        &mut self,
+        line: &str,
+        words: &Words,
+        power_of_two: bool,
+    ) -> Result<(), AsmError> {
+        let args = Self::split_arguments(line, words);
+        let (column, arg) = match args.first() {
+            Some(&arg) => arg,
+            None => {
+                return Err(
+                    self.error_at(words[0].0, format!("'{}' expects an alignment", words[0].1))
+                );
+            }
+        };
+
+        let align = match self.evaluate_constant(column, arg)? {
+            v if power_of_two && (0..64).contains(&v) => 1 << v,
+            v if !power_of_two && v > 0 && (v as u64).is_power_of_two() => v as u64,
+            _ => return Err(self.error_at(column, format!("invalid alignment '{}'", arg))),
+        };
+
+        self.pending_align = self.pending_align.max(align);
+
+        // .text では命令の間に埋めると実行されてしまうので,次のシンボルの先頭だけを揃える
+        match self.state.clone() {
+            State::InSymbol(sym_name) if self.section != ".text" => {
+                let sym = self.syms.get_mut(&sym_name).unwrap();
+                let padding = (align - sym.codes.len() as u64 % align) % align;
+                let start = sym.codes.len();
+                sym.codes.append(&mut vec![0x00; padding as usize]);
+                sym.push_line(self.listing_line, start, sym.codes.len());
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// 現在のシンボルにデータを追加する
+    /// ラベルより前のデータ( `.init_array` の `.quad` 等)は,名前の無いシンボルに追加する
+    pub fn push_data(
+        &mut self,
+        (column, directive): (usize, &str),
+        bytes: Vec<u8>,
+    ) -> Result<(), AsmError> {
+        if self.section == ".text" {
+            return Err(self.error_at(
+                column,
+                format!("'{}' in '.text' section is not supported", directive),
+            ));
+        }
+        if self.in_nobits_section() && bytes.iter().any(|b| *b != 0x00) {
+            return Err(self.error_at(
+                column,
+                format!(
+                    "attempt to store non-zero value in section '{}'",
+                    self.section
+                ),
+            ));
+        }
+
+        let sym_name = match &self.state {
+            State::InSymbol(sym_name) => sym_name.to_string(),
+            State::TopLevel => self.define_anonymous_symbol(),
+        };
+
+        let sym = self.syms.get_mut(&sym_name).unwrap();
+        let start = sym.codes.len();
+        sym.codes.extend(bytes);
+        sym.push_line(self.listing_line, start, sym.codes.len());
+        Ok(())
+    }
+
+    /// `.L` から始まる名前はシンボルテーブルに出力しない
+    fn define_anonymous_symbol(&mut self) -> String {
+        let sym_name = format!(".L{}.{}", self.section, self.syms.len());
+        self.state = State::InSymbol(sym_name.clone());
+
+        let align = std::mem::replace(&mut self.pending_align, 1);
+        let sym = self.syms.entry(sym_name.clone()).or_default();
+        sym.section = self.section.clone();
+        sym.align = align;
+
+        sym_name
+    }
+
+    /// `.bss` や `@nobits` のセクションはバイト列を持たない
+    pub fn in_nobits_section(&self) -> bool {
+        self.section == ".bss"
+            || self
+                .sections
+                .get(&self.section)
+                .is_some_and(|section| section.is_nobits())
+    }
+
+    /// ディレクティブの引数をカンマで区切り,桁位置とともに返す
+    /// 文字列リテラル中のカンマでは区切らない
+    pub fn split_arguments<'a>(line: &'a str, words: &Words) -> Vec<(usize, &'a str)> {
+        let (column, directive) = words[0];
+        split_operands(line, column - 1 + directive.len())
+    }
+}
+
+/// `42`, `-1`, `0x2a`, `0b101010`, `052` を解釈する
+pub fn parse_integer(s: &str) -> Option<i128> {
+    let (negative, digits) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s),
+    };
+
+    let value = if let Some(hex) = digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        u64::from_str_radix(hex, 16).ok()?
+    } else if let Some(bin) = digits
+        .strip_prefix("0b")
+        .or_else(|| digits.strip_prefix("0B"))
+    {
+        u64::from_str_radix(bin, 2).ok()?
+    } else if digits.len() > 1 && digits.starts_with('0') {
+        u64::from_str_radix(&digits[1..], 8).ok()?
+    } else if digits.starts_with(|c: char| c.is_ascii_digit()) {
+        digits.parse::<u64>().ok()?
+    } else {
+        return None;
+    };
+
+    if negative {
+        Some(-(value as i128))
+    } else {
+        Some(value as i128)
+    }
+}
+
+/// `"Hello\n"` -> b"Hello\n"
+pub fn parse_string_literal(s: &str) -> Result<Vec<u8>, String> {
+    if s.len() < 2 || !s.starts_with('"') || !s.ends_with('"') {
+        return Err(format!("expected a string literal, but got '{}'", s));
+    }
+
+    let mut bytes = Vec::new();
+    let mut chars = s[1..s.len() - 1].chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            let mut buf = [0; 4];
+            bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            continue;
+        }
+
+        let escaped = match chars.next() {
+            Some(c) => c,
+            None => return Err(format!("unterminated escape sequence in {}", s)),
+        };
+
+        match escaped {
+            'n' => bytes.push(b'\n'),
+            't' => bytes.push(b'\t'),
+            'r' => bytes.push(b'\r'),
+            'b' => bytes.push(0x08),
+            'f' => bytes.push(0x0c),
+            '\\' => bytes.push(b'\\'),
+            '"' => bytes.push(b'"'),
+            '\'' => bytes.push(b'\''),
+            // 8進数
+            '0'..='7' => {
+                let mut value = escaped.to_digit(8).unwrap();
+                for _ in 0..2 {
+                    match chars.peek().and_then(|c| c.to_digit(8)) {
+                        Some(d) => {
+                            value = value * 8 + d;
+                            chars.next();
+                        }
+                        None => break,
+                    }
+                }
+                bytes.push(value as u8);
+            }
+            // 16進数
+            'x' => {
+                let mut value = 0;
+                while let Some(d) = chars.peek().and_then(|c| c.to_digit(16)) {
+                    value = (value * 16 + d) & 0xff;
+                    chars.next();
+                }
+                bytes.push(value as u8);
+            }
+            _ => return Err(format!("unknown escape sequence '\\{}'", escaped)),
+        }
+    }
+
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod data_tests {
+    use super::*;
+    use crate::assembler::resource::*;
+
+    #[test]
+    fn parse_data_section_test() {
+        let mut ctxt = new_context();
+        ctxt.toplevel("    .data").unwrap();
+        ctxt.toplevel("    .align 8").unwrap();
+        ctxt.toplevel("x:").unwrap();
+        ctxt.in_symbol("    .long 5, -1", "x").unwrap();
+        ctxt.in_symbol("    .align 8", "x").unwrap();
+        ctxt.in_symbol("    .byte 0x2a", "x").unwrap();
+
+        let x = ctxt.syms.get("x").unwrap();
+        assert_eq!(".data", x.section);
+        assert_eq!(8, x.align);
+        assert_eq!(elf_utilities::symbol::Type::Object, x.ty);
+        assert_eq!(
+            vec![0x05, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff, 0x2a],
+            x.codes
+        );
+    }
+
+    #[test]
+    fn align_in_symbol_test() {
+        let mut ctxt = new_context();
+        ctxt.toplevel("    .data").unwrap();
+        ctxt.toplevel("x:").unwrap();
+        ctxt.in_symbol("    .long 0", "x").unwrap();
+        ctxt.in_symbol("y:", "x").unwrap();
+        ctxt.in_symbol("    .byte 1", "y").unwrap();
+        ctxt.in_symbol("    .align 16", "y").unwrap();
+        ctxt.in_symbol("    .long 2", "y").unwrap();
+
+        // y の先頭は動かさず,境界までパディングする
+        let y = ctxt.syms.get("y").unwrap();
+        assert_eq!(1, y.align);
+        let mut expected = vec![0x01];
+        expected.extend(vec![0x00; 15]);
+        expected.extend(vec![0x02, 0x00, 0x00, 0x00]);
+        assert_eq!(expected, y.codes);
+
+        // 次のラベルはアラインメントに従う
+        ctxt.in_symbol("z:", "y").unwrap();
+        assert_eq!(16, ctxt.syms.get("z").unwrap().align);
+    }
+
+    #[test]
+    fn parse_rodata_section_test() {
+        let mut ctxt = new_context();
+        ctxt.toplevel("    .section .rodata.str1.1,\"aMS\",@progbits,1")
+            .unwrap();
+        ctxt.toplevel(".LC0:").unwrap();
+        ctxt.in_symbol("    .string \"a, b\\n\"", ".LC0").unwrap();
+        ctxt.in_symbol(".LC1:", ".LC0").unwrap();
+        ctxt.in_symbol("    .ascii \"\\101\\x42\"", ".LC1").unwrap();
+
+        // サブセクションはまとめずに出力する
+        let lc0 = ctxt.syms.get(".LC0").unwrap();
+        assert_eq!(".rodata.str1.1", lc0.section);
+        assert_eq!(1, ctxt.sections[".rodata.str1.1"].entsize);
+        assert_eq!(b"a, b\n\0".to_vec(), lc0.codes);
+        assert_eq!(b"AB".to_vec(), ctxt.syms.get(".LC1").unwrap().codes);
+    }
+
+    #[test]
+    fn parse_bss_section_test() {
+        let mut ctxt = new_context();
+        ctxt.toplevel("    .bss").unwrap();
+        ctxt.toplevel("z:").unwrap();
+        ctxt.in_symbol("    .zero 4", "z").unwrap();
+        assert!(ctxt.in_symbol("    .long 1", "z").is_err());
+
+        let z = ctxt.syms.get("z").unwrap();
+        assert_eq!(".bss", z.section);
+        assert_eq!(vec![0x00; 4], z.codes);
+    }
+
+    #[test]
+    fn switch_back_section_test() {
+        let mut ctxt = new_context();
+        ctxt.toplevel("main:").unwrap();
+        ctxt.in_symbol("    pushq %rbp", "main").unwrap();
+        ctxt.in_symbol("    .section .rodata", "main").unwrap();
+        ctxt.toplevel(".LC0:").unwrap();
+        ctxt.in_symbol("    .long 1", ".LC0").unwrap();
+
+        // 切り替える前のシンボルの続きになる
+        ctxt.in_symbol("    .text", ".LC0").unwrap();
+        assert_eq!(State::InSymbol("main".to_string()), ctxt.state);
+        ctxt.in_symbol(".L2:", "main").unwrap();
+        ctxt.in_symbol("    ret", "main").unwrap();
+        assert!(!ctxt.syms.contains_key(".L2"));
+        assert_eq!(".L2", ctxt.syms["main"].groups[1].label);
+
+        ctxt.in_symbol("    .section .rodata", "main").unwrap();
+        assert_eq!(State::InSymbol(".LC0".to_string()), ctxt.state);
+        ctxt.in_symbol("    .long 2", ".LC0").unwrap();
+        assert_eq!(vec![1, 0, 0, 0, 2, 0, 0, 0], ctxt.syms[".LC0"].codes);
+
+        // .text ではシンボルの途中に埋めず,次のシンボルを揃える
+        ctxt.in_symbol("    .text", ".LC0").unwrap();
+        ctxt.in_symbol("    .p2align 4", "main").unwrap();
+        ctxt.in_symbol("foo:", "main").unwrap();
+        assert_eq!(16, ctxt.syms["foo"].align);
+        assert_eq!(1, ctxt.syms["main"].align);
+    }
+
+    #[test]
+    fn parse_custom_section_test() {
+        let mut ctxt = new_context();
+        ctxt.toplevel("    .section .init_array,\"aw\"").unwrap();
+        ctxt.toplevel("    .align 8").unwrap();
+        // ラベルの無いデータ
+        ctxt.toplevel("    .quad ctor").unwrap();
+        ctxt.toplevel("    .section .note.GNU-stack,\"\",@progbits")
+            .unwrap();
+        ctxt.toplevel("    .section .tbss,\"awT\",@nobits").unwrap();
+        ctxt.toplevel("x:").unwrap();
+        ctxt.in_symbol("    .zero 4", "x").unwrap();
+        assert!(ctxt.in_symbol("    .long 1", "x").is_err());
+
+        let (sym_name, sym) = ctxt.syms.get_index(0).unwrap();
+        assert!(Symbol::is_local_label(sym_name));
+        assert_eq!(".init_array", sym.section);
+        assert_eq!(8, sym.align);
+        assert_eq!(vec![0x00; 8], sym.codes);
+        assert_eq!("ctor", sym.fixups[0].symbol);
+
+        let init_array = ctxt.sections[".init_array"];
+        assert_eq!(
+            elf_utilities::section::Type::InitArray.to_bytes(),
+            init_array.ty
+        );
+        assert_eq!(
+            elf_utilities::section::SHF_ALLOC | SHF_WRITE,
+            init_array.flags
+        );
+        assert_eq!(
+            CustomSection::with_name(".foo"),
+            ctxt.sections[".note.GNU-stack"]
+        );
+        assert!(ctxt.sections[".tbss"].is_nobits());
+        assert_eq!(".tbss", ctxt.syms["x"].section);
+
+        // サブセクションもそれぞれ出力する
+        ctxt.toplevel("    .section .rodata.cst8,\"aM\",@progbits,8")
+            .unwrap();
+        assert_eq!(".rodata.cst8", ctxt.section);
+        assert_eq!(8, ctxt.sections[".rodata.cst8"].entsize);
+        ctxt.toplevel("    .section .data.rel.local").unwrap();
+        assert_eq!(
+            CustomSection::with_name(".data"),
+            ctxt.sections[".data.rel.local"]
+        );
+        ctxt.toplevel("    .section .bss.counter").unwrap();
+        assert!(ctxt.sections[".bss.counter"].is_nobits());
+        assert_eq!(6, ctxt.sections.len());
+
+        // 命令は .text にしか置けないので, .text のサブセクションはまとめる
+        ctxt.toplevel("    .section .text.startup,\"ax\",@progbits")
+            .unwrap();
+        assert_eq!(".text", ctxt.section);
+        assert_eq!(
+            "section '.text.startup' is merged into '.text'",
+            ctxt.warnings[0].message
+        );
+        assert_eq!(6, ctxt.sections.len());
+
+        assert!(ctxt.toplevel("    .section .foo,\"aG\"").is_err());
+        assert!(ctxt.toplevel("    .section .foo,\"a\",@foo").is_err());
+    }
+
+    #[test]
+    fn parse_fill_test() {
+        let mut ctxt = new_context();
+        ctxt.toplevel("    .data").unwrap();
+        ctxt.toplevel("x:").unwrap();
+        ctxt.in_symbol("    .fill 2, 2, 0x1234", "x").unwrap();
+        ctxt.in_symbol("    .fill 1, 8, -1", "x").unwrap();
+        ctxt.in_symbol("    .fill 3", "x").unwrap();
+        assert!(ctxt.in_symbol("    .fill -1", "x").is_err());
+        assert!(ctxt.in_symbol("    .fill 1, 9", "x").is_err());
+
+        assert_eq!(
+            vec![
+                0x34, 0x12, 0x34, 0x12, 0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                0x00
+            ],
+            ctxt.syms["x"].codes
+        );
+    }
+
+    #[test]
+    fn parse_common_test() {
+        let mut ctxt = new_context();
+        // gcc の `static int counter;`
+        ctxt.toplevel("    .local counter").unwrap();
+        ctxt.toplevel("    .comm counter,4,4").unwrap();
+        ctxt.toplevel("    .comm g,8").unwrap();
+        ctxt.toplevel("    .lcomm buf,64,32").unwrap();
+        assert!(ctxt.toplevel("    .comm g,8").is_err());
+        assert!(ctxt.toplevel("    .comm h,4,3").is_err());
+        assert!(ctxt.toplevel("    .comm h").is_err());
+
+        let counter = &ctxt.syms["counter"];
+        assert_eq!(".bss", counter.section);
+        assert_eq!(vec![0x00; 4], counter.codes);
+        assert_eq!(4, counter.align);
+        assert_eq!(4, counter.st_size());
+        assert!(counter.is_local() && counter.ty == elf_utilities::symbol::Type::Object);
+
+        // st_value はアラインメント
+        let g = &ctxt.syms["g"];
+        assert!(
+            g.is_common()
+                && g.bind == elf_utilities::symbol::Bind::Global
+                && g.ty == elf_utilities::symbol::Type::Object
+        );
+        assert_eq!(8, g.value);
+        assert_eq!(8, g.st_size());
+        assert!(g.codes.is_empty());
+
+        let buf = &ctxt.syms["buf"];
+        assert_eq!(32, buf.align);
+        assert!(buf.is_local());
+
+        // セクションは切り替えない
+        assert_eq!(".text", ctxt.section);
+    }
+
+    #[test]
+    fn unknown_directive_test() {
+        let mut ctxt = new_context();
+        let e = ctxt.toplevel("    .bogusdirective 1").unwrap_err();
+        assert_eq!("unknown pseudo-op '.bogusdirective'", e.message);
+        assert_eq!(5, e.column);
+
+        // ソースファイル名等は無視する
+        ctxt.toplevel("    .file \"foo.c\"").unwrap();
+        ctxt.toplevel("    .ident \"GCC\"").unwrap();
+    }
+
+    #[test]
+    fn invalid_data_test() {
+        let mut ctxt = new_context();
+        ctxt.toplevel("    .data").unwrap();
+        ctxt.toplevel("x:").unwrap();
+        assert!(ctxt.in_symbol("    .byte 256", "x").is_err());
+        assert!(ctxt.in_symbol("    .quad foo*2", "x").is_err());
+        assert!(ctxt.in_symbol("    .quad foo+bar", "x").is_err());
+        assert!(ctxt.in_symbol("    .string \"abc", "x").is_err());
+        assert!(ctxt.in_symbol("    .align 3", "x").is_err());
+        assert!(ctxt.in_symbol("    movq %rax, %rbx", "x").is_err());
+    }
+
+    #[test]
+    fn parse_expression_data_test() {
+        let mut ctxt = new_context();
+        ctxt.toplevel("    .data").unwrap();
+        ctxt.toplevel("x:").unwrap();
+        ctxt.in_symbol("    .byte (1<<4)|2, -1", "x").unwrap();
+        ctxt.in_symbol("    .zero 2*2", "x").unwrap();
+        ctxt.in_symbol("    .quad y+8, .-x", "x").unwrap();
+        ctxt.in_symbol("    .long y-.", "x").unwrap();
+
+        let x = ctxt.syms.get("x").unwrap();
+        assert_eq!(26, x.codes.len());
+        assert_eq!(vec![0x12, 0xff, 0x00, 0x00, 0x00, 0x00], x.codes[..6]);
+        // `.-x` は定数
+        assert_eq!(14u64.to_le_bytes(), x.codes[14..22]);
+        assert_eq!(
+            vec![
+                Fixup {
+                    offset: 6,
+                    size: 8,
+                    symbol: "y".to_string(),
+                    subtrahend: None,
+                    addend: 8,
+                    signed: false,
+                },
+                Fixup {
+                    offset: 22,
+                    size: 4,
+                    symbol: "y".to_string(),
+                    subtrahend: Some("x".to_string()),
+                    addend: -22,
+                    signed: false,
+                },
+            ],
+            x.fixups
+        );
+        assert!(ctxt.unresolved_differences().is_empty());
+    }
+
+    #[test]
+    fn unresolved_difference_test() {
+        let mut ctxt = new_context();
+        ctxt.toplevel("    .data").unwrap();
+        ctxt.toplevel("x:").unwrap();
+        ctxt.in_symbol("    .long y-z", "x").unwrap();
+        ctxt.in_symbol("    .section .rodata", "x").unwrap();
+        ctxt.toplevel("z:").unwrap();
+
+        let errors = ctxt.unresolved_differences();
+        assert_eq!(1, errors.len());
+        assert_eq!("can't resolve 'y' - 'z'", errors[0].message);
+    }
+
+    #[test]
+    fn parse_integer_test() {
+        assert_eq!(Some(42), parse_integer("42"));
+        assert_eq!(Some(-42), parse_integer("-42"));
+        assert_eq!(Some(42), parse_integer("0x2a"));
+        assert_eq!(Some(42), parse_integer("0b101010"));
+        assert_eq!(Some(42), parse_integer("052"));
+        assert_eq!(Some(0), parse_integer("0"));
+        assert_eq!(None, parse_integer("foo"));
+        assert_eq!(None, parse_integer("08"));
+    }
+
+    fn new_context() -> Context {
+        Context::new("test.s", Syntax::ATANDT)
+    }
+}
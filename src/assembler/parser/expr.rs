@@ -0,0 +1,497 @@
+use super::context::*;
+use super::data::{parse_integer, parse_string_literal};
+use crate::assembler::resource::{Displacement, Immediate, Operand};
+use crate::assembler::AsmError;
+
+impl Context {
+    /// 式を評価する
+    pub fn evaluate(&self, expr: &str) -> Result<Value, String> {
+        evaluate(expr, &mut |name| self.symbol_value(name))
+    }
+
+    /// アセンブル時に値が決まる式( `.zero 4*8` 等)
+    pub fn evaluate_constant(&self, column: usize, expr: &str) -> Result<i128, AsmError> {
+        let value = self
+            .evaluate(expr)
+            .map_err(|message| self.error_at(column, message))?;
+
+        value
+            .as_constant()
+            .ok_or_else(|| self.error_at(column, format!("'{}' is not a constant", expr)))
+    }
+
+    /// `.` はデータセクション内の現在位置
+    /// `.set` 等で定義されたシンボルはその値
+    fn symbol_value(&self, name: &str) -> Result<Value, String> {
+        if let Some(value) = self.equates.get(name) {
+            return Ok(value.clone());
+        }
+        if name != "." {
+            return Ok(Value::symbol(name));
+        }
+
+        match &self.state {
+            State::InSymbol(sym_name) if self.section != ".text" => Ok(Value {
+                symbol: Some(sym_name.to_string()),
+                subtrahend: None,
+                constant: self.syms[sym_name].codes.len() as i128,
+            }),
+            _ => Err("'.' can be used only in data sections".to_string()),
+        }
+    }
+}
+
+/// 式の値
+/// リンク時まで決まらない値は,シンボル(とその差)と定数の組で表す
+#[derive(Eq, PartialEq, Debug, Clone, Default)]
+pub struct Value {
+    /// `sym+8` の `sym`
+    pub symbol: Option<String>,
+    /// `.L3-.L2` の `.L2`
+    pub subtrahend: Option<String>,
+    pub constant: i128,
+}
+
+/// 式を評価する
+/// `resolve` はシンボル( `.` を含む)の値を返す
+pub fn evaluate(
+    expr: &str,
+    resolve: &mut dyn FnMut(&str) -> Result<Value, String>,
+) -> Result<Value, String> {
+    let tokens = tokenize(expr)?;
+    if tokens.is_empty() {
+        return Err("missing expression".to_string());
+    }
+
+    let mut parser = ExprParser {
+        tokens,
+        pos: 0,
+        resolve,
+    };
+    let value = parser.parse_binary(1)?;
+
+    match parser.tokens.get(parser.pos) {
+        None => Ok(value),
+        Some(token) => Err(format!("junk '{}' in expression '{}'", token, expr)),
+    }
+}
+
+impl Value {
+    pub fn constant(v: i128) -> Self {
+        Self {
+            constant: v,
+            ..Default::default()
+        }
+    }
+
+    pub fn symbol(name: &str) -> Self {
+        Self {
+            symbol: Some(name.to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// アセンブル時に値が決まる場合はその値
+    pub fn as_constant(&self) -> Option<i128> {
+        match (&self.symbol, &self.subtrahend) {
+            (None, None) => Some(self.constant),
+            _ => None,
+        }
+    }
+
+    /// GNU as と同様に,値は 64bit として扱う( `$0xfffffffffffffff0` は -16 )
+    /// imm32 に収まるかは,命令のサイズが決まってから確かめる
+    pub fn to_immediate(&self) -> Result<Immediate, String> {
+        let v = match self.as_constant() {
+            Some(v) if i64::MIN as i128 <= v && v <= u64::MAX as i128 => v as i64,
+            Some(v) => return Err(format!("immediate {} is out of range", v)),
+            None => return Err("symbol in an immediate is not supported".to_string()),
+        };
+
+        if i8::MIN as i64 <= v && v <= i8::MAX as i64 {
+            Ok(Immediate::I8(v as i8))
+        } else if i32::MIN as i64 <= v && v <= i32::MAX as i64 {
+            Ok(Immediate::I32(v as i32))
+        } else {
+            Ok(Immediate::I64(v))
+        }
+    }
+
+    /// `$sym+4`, `$.L2-.L1` のようにシンボルを含む即値は,レイアウト後か再配置で埋める
+    pub fn into_immediate_operand(self) -> Result<Operand, String> {
+        match self.symbol {
+            None => self.to_immediate().map(Operand::Immediate),
+            Some(symbol)
+                if i32::MIN as i128 <= self.constant && self.constant <= i32::MAX as i128 =>
+            {
+                Ok(Operand::SYMBOLIC {
+                    symbol,
+                    subtrahend: self.subtrahend,
+                    addend: self.constant as i64,
+                })
+            }
+            Some(_) => Err(format!(
+                "immediate offset {} is out of range",
+                self.constant
+            )),
+        }
+    }
+
+    /// `0` の場合も displacement として扱う( `0(%rbp)` )
+    pub fn to_displacement(&self) -> Result<Displacement, String> {
+        match self.as_constant() {
+            Some(v) if i8::MIN as i128 <= v && v <= i8::MAX as i128 => {
+                Ok(Displacement::DISP8(v as i8))
+            }
+            Some(v) if i32::MIN as i128 <= v && v <= i32::MAX as i128 => {
+                Ok(Displacement::DISP32(v as i32))
+            }
+            Some(v) => Err(format!("displacement {} is out of range", v)),
+            None => Err("symbol in a displacement is only supported with RIP".to_string()),
+        }
+    }
+
+    /// ベースレジスタの無いメモリオペランド( `0x1000`, `0(,%rax,4)` )の displacement
+    /// disp32 は符号拡張されるので, i32 に収まる必要がある
+    pub fn to_absolute_displacement(&self) -> Result<i32, String> {
+        match self.as_constant() {
+            Some(v) if i32::MIN as i128 <= v && v <= i32::MAX as i128 => Ok(v as i32),
+            Some(v) => Err(format!("absolute address {} is out of range", v)),
+            None => {
+                Err("symbol in a displacement without a base register is not supported".to_string())
+            }
+        }
+    }
+
+    fn add(self, rhs: Self) -> Result<Self, String> {
+        let constant = self.constant + rhs.constant;
+
+        match (self.as_constant(), rhs.as_constant()) {
+            (_, Some(_)) => Ok(Self { constant, ..self }),
+            (Some(_), None) => Ok(Self { constant, ..rhs }),
+            (None, None) => Err("symbols can't be added".to_string()),
+        }
+    }
+
+    fn sub(self, rhs: Self) -> Result<Self, String> {
+        let constant = self.constant - rhs.constant;
+
+        match (&self.subtrahend, rhs.as_constant(), rhs.symbol) {
+            (_, Some(_), _) => Ok(Self { constant, ..self }),
+            // 同じシンボル同士の差は定数
+            (None, None, Some(sym))
+                if rhs.subtrahend.is_none() && self.symbol == Some(sym.clone()) =>
+            {
+                Ok(Self::constant(constant))
+            }
+            (None, None, Some(sym)) if rhs.subtrahend.is_none() && self.symbol.is_some() => {
+                Ok(Self {
+                    symbol: self.symbol,
+                    subtrahend: Some(sym),
+                    constant,
+                })
+            }
+            _ => Err("symbols can't be subtracted here".to_string()),
+        }
+    }
+}
+
+#[derive(Eq, PartialEq, Debug, Clone)]
+enum Token {
+    Number(i128),
+    Symbol(String),
+    Operator(&'static str),
+    Open,
+    Close,
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Number(v) => write!(f, "{}", v),
+            Token::Symbol(name) => write!(f, "{}", name),
+            Token::Operator(op) => write!(f, "{}", op),
+            Token::Open => write!(f, "("),
+            Token::Close => write!(f, ")"),
+        }
+    }
+}
+
+/// 長いものから順に並べる
+const OPERATORS: [&str; 21] = [
+    "<<", ">>", "<=", ">=", "==", "!=", "<>", "&&", "||", "+", "-", "*", "/", "%", "|", "&", "^",
+    "!", "~", "<", ">",
+];
+
+fn tokenize(expr: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = expr.trim_start();
+
+    while let Some(c) = rest.chars().next() {
+        let length = if c == '(' {
+            tokens.push(Token::Open);
+            1
+        } else if c == ')' {
+            tokens.push(Token::Close);
+            1
+        } else if c == '"' {
+            // `"foo bar"` のようなシンボル
+            let end = match rest[1..].find('"') {
+                Some(end) => end + 1,
+                None => return Err(format!("unterminated symbol name {}", rest)),
+            };
+            tokens.push(Token::Symbol(rest[1..end].to_string()));
+            end + 1
+        } else if c == '\'' {
+            // `'a'`, `'\n'` のような文字定数
+            let (body, length) = super::lexer::char_constant(rest);
+            match parse_string_literal(&format!("\"{}\"", body))?[..] {
+                [byte] => tokens.push(Token::Number(byte as i128)),
+                _ => return Err(format!("invalid character constant {}", &rest[..length])),
+            }
+            length
+        } else if c.is_ascii_digit() {
+            let length = rest
+                .find(|c: char| !c.is_ascii_alphanumeric())
+                .unwrap_or(rest.len());
+            let word = &rest[..length];
+
+            match parse_integer(word) {
+                Some(v) => tokens.push(Token::Number(v)),
+                // `1b`, `1f` は数値ラベルへの参照
+                None if super::context::Context::is_numeric_reference(word) => {
+                    tokens.push(Token::Symbol(word.to_string()))
+                }
+                None => return Err(format!("invalid number '{}'", word)),
+            }
+            length
+        } else if super::lexer::is_symbol_char(c) {
+            let length = rest
+                .find(|c: char| !super::lexer::is_symbol_char(c))
+                .unwrap_or(rest.len());
+            tokens.push(Token::Symbol(rest[..length].to_string()));
+            length
+        } else {
+            match OPERATORS.iter().find(|op| rest.starts_with(**op)) {
+                Some(op) => {
+                    tokens.push(Token::Operator(op));
+                    op.len()
+                }
+                None => return Err(format!("unexpected character '{}' in expression", c)),
+            }
+        };
+
+        rest = rest[length..].trim_start();
+    }
+
+    Ok(tokens)
+}
+
+/// GNU as と同じ優先順位
+/// 1. `&&`, `||`
+/// 2. `+`, `-`, 比較演算子
+/// 3. `|`, `&`, `^`, `!`
+/// 4. `*`, `/`, `%`, `<<`, `>>`
+fn precedence(op: &str) -> usize {
+    match op {
+        "&&" | "||" => 1,
+        "+" | "-" | "==" | "!=" | "<>" | "<" | ">" | "<=" | ">=" => 2,
+        "|" | "&" | "^" | "!" => 3,
+        "*" | "/" | "%" | "<<" | ">>" => 4,
+        _ => 0,
+    }
+}
+
+struct ExprParser<'a> {
+    tokens: Vec<Token>,
+    pos: usize,
+    resolve: &'a mut dyn FnMut(&str) -> Result<Value, String>,
+}
+
+impl ExprParser<'_> {
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    /// 優先順位が `min_precedence` 以上の二項演算子からなる式
+    fn parse_binary(&mut self, min_precedence: usize) -> Result<Value, String> {
+        let mut lhs = self.parse_unary()?;
+
+        while let Some(Token::Operator(op)) = self.tokens.get(self.pos).cloned() {
+            let prec = precedence(op);
+            if prec < min_precedence {
+                break;
+            }
+
+            self.pos += 1;
+            let rhs = self.parse_binary(prec + 1)?;
+            lhs = apply_binary(op, lhs, rhs)?;
+        }
+
+        Ok(lhs)
+    }
+
+    fn parse_unary(&mut self) -> Result<Value, String> {
+        match self.next() {
+            Some(Token::Operator(op)) if matches!(op, "-" | "+" | "~" | "!") => {
+                let operand = self.parse_unary()?;
+                if op == "+" {
+                    return Ok(operand);
+                }
+
+                let v = match operand.as_constant() {
+                    Some(v) => v,
+                    None => return Err(format!("'{}' can't be applied to symbols", op)),
+                };
+                let result = match op {
+                    "-" => -v,
+                    "~" => !v,
+                    _ => (v == 0) as i128,
+                };
+
+                Ok(Value::constant(result))
+            }
+            Some(Token::Open) => {
+                let value = self.parse_binary(1)?;
+                match self.next() {
+                    Some(Token::Close) => Ok(value),
+                    _ => Err("missing ')' in expression".to_string()),
+                }
+            }
+            Some(Token::Number(v)) => Ok(Value::constant(v)),
+            Some(Token::Symbol(name)) => (self.resolve)(&name),
+            Some(token) => Err(format!("unexpected '{}' in expression", token)),
+            None => Err("missing operand in expression".to_string()),
+        }
+    }
+}
+
+fn apply_binary(op: &str, lhs: Value, rhs: Value) -> Result<Value, String> {
+    match op {
+        "+" => return lhs.add(rhs),
+        "-" => return lhs.sub(rhs),
+        _ => {}
+    }
+
+    let (a, b) = match (lhs.as_constant(), rhs.as_constant()) {
+        (Some(a), Some(b)) => (a, b),
+        _ => return Err(format!("'{}' can't be applied to symbols", op)),
+    };
+
+    // 比較演算子は,真なら -1 を返す
+    let compare = |result: bool| -(result as i128);
+
+    let result = match op {
+        "*" => a.wrapping_mul(b),
+        "/" | "%" if b == 0 => return Err("division by zero".to_string()),
+        "/" => a / b,
+        "%" => a % b,
+        "<<" | ">>" if !(0..128).contains(&b) => {
+            return Err(format!("invalid shift amount {}", b));
+        }
+        "<<" => a.wrapping_shl(b as u32),
+        // GNU as と同様に,64bit の論理シフト( `-1>>60` は 15 )
+        ">>" => (a as u64).checked_shr(b as u32).unwrap_or(0) as i64 as i128,
+        "|" => a | b,
+        "&" => a & b,
+        "^" => a ^ b,
+        "!" => a | !b,
+        "==" => compare(a == b),
+        "!=" | "<>" => compare(a != b),
+        "<" => compare(a < b),
+        ">" => compare(a > b),
+        "<=" => compare(a <= b),
+        ">=" => compare(a >= b),
+        "&&" => (a != 0 && b != 0) as i128,
+        "||" => (a != 0 || b != 0) as i128,
+        _ => unreachable!(),
+    };
+
+    Ok(Value::constant(result))
+}
+
+#[cfg(test)]
+mod expr_tests {
+    use super::*;
+
+    #[test]
+    fn constant_test() {
+        assert_eq!(Ok(4104), constant("(1<<12)+8"));
+        assert_eq!(Ok(0x7fff), constant("0x7fff"));
+        assert_eq!(Ok(-8), constant("-16+8"));
+        assert_eq!(Ok(7), constant("1 + 2 * 3"));
+        // `|` は `+` より優先順位が高い
+        assert_eq!(Ok(6), constant("1 | 2 + 3"));
+        assert_eq!(Ok(-1), constant("3 > 2"));
+        assert_eq!(Ok(1), constant("!0 && ~0"));
+        assert_eq!(Ok(-3), constant("-(7 / 2)"));
+        // `>>` は論理シフト
+        assert_eq!(Ok(15), constant("-1>>60"));
+        assert_eq!(Ok(0x3ffffffffffffffc), constant("-16>>2"));
+        assert_eq!(Ok(2), constant("8>>2"));
+        assert!(constant("1 / 0").is_err());
+        assert!(constant("(1 + 2").is_err());
+        assert!(constant("1 2").is_err());
+    }
+
+    #[test]
+    fn character_constant_test() {
+        assert_eq!(Ok(97), constant("'a'"));
+        assert_eq!(Ok(98), constant("'a' + 1"));
+        // 閉じる `'` は省略できる
+        assert_eq!(Ok(97), constant("'a"));
+        assert_eq!(Ok(59), constant("';'"));
+        assert_eq!(Ok(10), constant("'\\n'"));
+        assert_eq!(Ok(39), constant("'\\''"));
+        assert_eq!(Ok(65), constant("'\\101'"));
+        assert_eq!(Ok(0x7f), constant("'\\x7f'"));
+        assert!(constant("'ab'").is_err());
+        assert!(constant("'").is_err());
+    }
+
+    #[test]
+    fn symbolic_test() {
+        assert_eq!(
+            Ok(Value {
+                symbol: Some("sym".to_string()),
+                subtrahend: None,
+                constant: 8,
+            }),
+            symbolic("sym+8")
+        );
+        assert_eq!(
+            Ok(Value {
+                symbol: Some(".L3".to_string()),
+                subtrahend: Some(".L2".to_string()),
+                constant: 0,
+            }),
+            symbolic(".L3-.L2")
+        );
+        assert_eq!(
+            Ok(Value {
+                symbol: Some("a b".to_string()),
+                subtrahend: None,
+                constant: -4,
+            }),
+            symbolic("-4 + \"a b\"")
+        );
+        assert_eq!(Ok(Value::constant(0)), symbolic("x - x"));
+        assert_eq!(Ok(Value::symbol("1b")), symbolic("1b"));
+        assert!(symbolic("a + b").is_err());
+        assert!(symbolic("4 - a").is_err());
+        assert!(symbolic("a * 2").is_err());
+    }
+
+    fn constant(expr: &str) -> Result<i128, String> {
+        let value = symbolic(expr)?;
+        value
+            .as_constant()
+            .ok_or_else(|| "not a constant".to_string())
+    }
+
+    fn symbolic(expr: &str) -> Result<Value, String> {
+        evaluate(expr, &mut |name| Ok(Value::symbol(name)))
+    }
+}
@@ -0,0 +1,270 @@
+use super::context::*;
+use super::data::parse_string_literal;
+use super::lexer::{split_statements, Statement};
+use crate::assembler::AsmError;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// ソースファイル,または `.include` で読み込んだファイル
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub struct SourceFile {
+    /// エラーメッセージに使う名前
+    pub name: String,
+    /// インクルード元( `Context::sources` の添字 )
+    pub parent: Option<usize>,
+    /// インクルード元で `.include` を書いた行
+    pub included_at: usize,
+    /// 循環の検出に使う絶対パス( `assemble_code` の入力等,ファイルでなければ None )
+    path: Option<PathBuf>,
+    /// ファイルの内容( リスティングに使う )
+    pub text: String,
+}
+
+impl SourceFile {
+    pub fn new(name: &str, parent: Option<usize>) -> Self {
+        Self {
+            name: name.to_string(),
+            parent,
+            included_at: 0,
+            path: fs::canonicalize(name).ok(),
+            text: String::new(),
+        }
+    }
+}
+
+impl Context {
+    /// `.include "file.s"`
+    /// 読み込んだ文を `pending` に積む
+    pub fn include_file(
+        &mut self,
+        statement: &Statement,
+        column: usize,
+        rest: &str,
+        pending: &mut Vec<Statement>,
+    ) -> Result<(), AsmError> {
+        let name = self.parse_file_name(column, ".include", rest.trim())?;
+        let included = self.find_file(statement.source, column, &name)?;
+
+        // インクルード元をたどり,同じファイルがあれば循環している
+        let mut included = SourceFile::new(&included, Some(statement.source));
+        let mut chain = vec![included.name.clone()];
+        let mut source = Some(statement.source);
+        while let Some(idx) = source {
+            chain.push(self.sources[idx].name.clone());
+            if included.path.is_some() && self.sources[idx].path == included.path {
+                chain.reverse();
+                return Err(self.error_at(
+                    column,
+                    format!("include cycle detected: {}", chain.join(" -> ")),
+                ));
+            }
+            source = self.sources[idx].parent;
+        }
+
+        included.text = fs::read_to_string(&included.name)
+            .map_err(|e| self.error_at(column, format!("can't read '{}': {}", included.name, e)))?;
+        included.included_at = statement.line;
+        let (statements, unterminated_comment) = split_statements(&included.text);
+
+        let source = self.sources.len();
+        let file_name = included.name.clone();
+        self.sources.push(included);
+
+        pending.extend(statements.into_iter().rev().map(|s| Statement {
+            depth: statement.depth,
+            source,
+            ..s
+        }));
+
+        // 読み込んだ文は処理を続ける
+        match unterminated_comment {
+            Some((line, column)) => Err(AsmError::new(
+                &file_name,
+                line,
+                column,
+                "unterminated comment".to_string(),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// `.incbin "file"[, skip[, count]]`
+    pub fn parse_incbin_directive(&mut self, line: &str, words: &Words) -> Result<(), AsmError> {
+        let args = Self::split_arguments(line, words);
+        let (column, name) = match args.first() {
+            Some(&arg) => arg,
+            None => {
+                return Err(self.error_at(words[0].0, "'.incbin' expects a file name".to_string()));
+            }
+        };
+        let name = self.parse_file_name(column, ".incbin", name)?;
+
+        let path = self.find_file(self.source, column, &name)?;
+        let bytes = fs::read(&path)
+            .map_err(|e| self.error_at(column, format!("can't read '{}': {}", path, e)))?;
+
+        let skip = match args.get(1) {
+            Some(&(column, arg)) => match self.evaluate_constant(column, arg)? {
+                skip if skip >= 0 && skip as usize <= bytes.len() => skip as usize,
+                skip => {
+                    return Err(self.error_at(
+                        column,
+                        format!(
+                            "skip ({}) is out of '{}' ({} bytes)",
+                            skip,
+                            path,
+                            bytes.len()
+                        ),
+                    ));
+                }
+            },
+            None => 0,
+        };
+        let count = match args.get(2) {
+            Some(&(column, arg)) => match self.evaluate_constant(column, arg)? {
+                count if count >= 0 && skip + count as usize <= bytes.len() => count as usize,
+                count => {
+                    return Err(self.error_at(
+                        column,
+                        format!(
+                            "count ({}) is out of '{}' ({} bytes)",
+                            count,
+                            path,
+                            bytes.len()
+                        ),
+                    ));
+                }
+            },
+            None => bytes.len() - skip,
+        };
+
+        self.push_data(words[0], bytes[skip..skip + count].to_vec())
+    }
+
+    /// `"file.s"` -> `file.s`
+    fn parse_file_name(
+        &self,
+        column: usize,
+        directive: &str,
+        literal: &str,
+    ) -> Result<String, AsmError> {
+        if !literal.starts_with('"') {
+            return Err(self.error_at(column, format!("'{}' expects a file name", directive)));
+        }
+
+        let bytes =
+            parse_string_literal(literal).map_err(|message| self.error_at(column, message))?;
+        Ok(String::from_utf8_lossy(&bytes).to_string())
+    }
+
+    /// インクルード元のディレクトリ, `-I` で指定したディレクトリの順に探す
+    fn find_file(&self, source: usize, column: usize, name: &str) -> Result<String, AsmError> {
+        let base = Path::new(&self.sources[source].name)
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+
+        std::iter::once(base)
+            .chain(self.include_paths.iter().map(PathBuf::from))
+            .map(|dir| dir.join(name))
+            .find(|path| path.is_file())
+            .map(|path| path.to_string_lossy().to_string())
+            .ok_or_else(|| self.error_at(column, format!("can't find file '{}'", name)))
+    }
+}
+
+#[cfg(test)]
+mod include_tests {
+    use crate::assembler::resource::Syntax;
+    use crate::assembler::{AsmError, Options};
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn include_test() {
+        let dir = test_dir("include_test");
+        write(&dir, "inc/defs.inc", ".macro three\n    .byte 3\n.endm\n");
+        write(&dir, "sub/lib.s", "    .include \"defs.inc\"\n    three\n");
+        write(&dir, "table.bin", "\x01\x02\x03\x04");
+        write(
+            &dir,
+            "main.s",
+            "    .data\nx:\n    .include \"sub/lib.s\"\n    .incbin \"table.bin\", 1, 2\n",
+        );
+
+        let include_path = dir.join("inc");
+        let bytes = data_bytes(&dir, &[include_path.to_str().unwrap()]).unwrap();
+        assert_eq!(vec![3, 2, 3], bytes);
+
+        // `-I` が無ければ見つからない
+        let errors = data_bytes(&dir, &[]).unwrap_err();
+        assert_eq!(2, errors.len());
+        assert!(errors[0].file.ends_with("lib.s"));
+        assert_eq!((1, 5), (errors[0].line, errors[0].column));
+        assert_eq!("can't find file 'defs.inc'", errors[0].message);
+    }
+
+    #[test]
+    fn include_cycle_test() {
+        let dir = test_dir("include_cycle_test");
+        write(&dir, "a.s", "    .include \"b.s\"\n");
+        write(&dir, "b.s", "    .include \"a.s\"\n");
+        write(&dir, "main.s", "    .include \"a.s\"\n");
+
+        let errors = data_bytes(&dir, &[]).unwrap_err();
+        assert_eq!(1, errors.len());
+        assert!(errors[0].file.ends_with("b.s"));
+        let (a, b) = (dir.join("a.s"), dir.join("b.s"));
+        assert_eq!(
+            format!(
+                "include cycle detected: {} -> {} -> {}",
+                a.display(),
+                b.display(),
+                a.display()
+            ),
+            errors[0].message
+        );
+    }
+
+    #[test]
+    fn invalid_incbin_test() {
+        let dir = test_dir("invalid_incbin_test");
+        write(&dir, "table.bin", "\x01\x02");
+        write(
+            &dir,
+            "main.s",
+            "    .data\nx:\n    .incbin \"table.bin\", 3\n    .incbin \"table.bin\", 1, 2\n    .incbin table.bin\n",
+        );
+
+        let errors = data_bytes(&dir, &[]).unwrap_err();
+        assert_eq!(3, errors.len());
+        assert_eq!((3, 26), (errors[0].line, errors[0].column));
+        assert_eq!((4, 29), (errors[1].line, errors[1].column));
+        assert_eq!("'.incbin' expects a file name", errors[2].message);
+    }
+
+    /// `dir/main.s` の `x` の中身
+    fn data_bytes(dir: &Path, include_paths: &[&str]) -> Result<Vec<u8>, Vec<AsmError>> {
+        let main = dir.join("main.s");
+        let mut options = Options::new(Syntax::ATANDT);
+        for path in include_paths.iter() {
+            options.add_include_path(path);
+        }
+
+        let source = fs::read_to_string(&main).unwrap();
+        let (syms, _, _, _) = super::super::parse(source, main.to_str().unwrap(), &options)?;
+        Ok(syms.get("x").map(|x| x.codes.clone()).unwrap_or_default())
+    }
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("asmpeach_{}", name));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn write(dir: &Path, name: &str, content: &str) {
+        let path = dir.join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+}
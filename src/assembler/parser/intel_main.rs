@@ -0,0 +1,651 @@
+use super::context::*;
+use super::expr::Value;
+use super::lexer::split_operands;
+use crate::assembler::resource::*;
+use crate::assembler::AsmError;
+
+impl Context {
+    /// `mov rax, QWORD PTR [rbp-8]` みたいなやつ
+    pub fn parse_intel_instruction(
+        &mut self,
+        sym_name: &str,
+        line: &str,
+        (column, mnemonic): (usize, &str),
+    ) -> Result<(), AsmError> {
+        // オペランドは空白を含みうるので,カンマで区切る
+        let operands_start = column - 1 + mnemonic.len();
+
+        let mut size = None;
+        let mut operands = Vec::new();
+        for (op_column, op_str) in split_operands(line, operands_start) {
+            let (operand, ptr_size) = self
+                .parse_intel_operand(op_str)
+                .map_err(|message| self.error_at(op_column, message))?;
+
+            // `QWORD PTR` 等でサイズを指定している場合
+            size = size.or(ptr_size);
+            operands.push((op_column, operand));
+        }
+
+        // サイズ指定がなければ,先頭( dst 側)のレジスタから推論する
+        if size.is_none() {
+            size = operands.iter().find_map(|(_, operand)| match operand {
+                Operand::GENERALREGISTER(_) => Some(operand.size()),
+                _ => None,
+            });
+        }
+
+        // Intel構文は dst, src の順
+        operands.reverse();
+
+        let name = mnemonic.to_ascii_lowercase();
+        self.push_instruction(sym_name, (column, mnemonic), &name, size, operands)
+    }
+
+    /// `QWORD PTR` のようなサイズ指定があれば,そのサイズも返す
+    fn parse_intel_operand(&self, operand: &str) -> Result<(Operand, Option<OperandSize>), String> {
+        if operand.is_empty() {
+            return Err("missing operand".to_string());
+        }
+
+        let (size, operand) = Self::strip_size_ptr(operand);
+
+        // メモリオペランド
+        if operand.contains('[') {
+            return self.parse_intel_memory(operand).map(|m| (m, size));
+        }
+
+        if size.is_some() {
+            return Err(format!(
+                "'PTR' must be used with a memory operand: '{}'",
+                operand
+            ));
+        }
+
+        // レジスタの場合
+        if let Some(reg) = GeneralPurposeRegister::from_intel_string(&operand.to_ascii_lowercase())
+        {
+            return Ok((Operand::GENERALREGISTER(reg), None));
+        }
+        if let Some(xmm) = XMMRegister::from_intel_string(&operand.to_ascii_lowercase()) {
+            return Ok((Operand::XMMREGISTER(xmm), None));
+        }
+        if let Some(ymm) = YMMRegister::from_intel_string(&operand.to_ascii_lowercase()) {
+            return Ok((Operand::YMMREGISTER(ymm), None));
+        }
+
+        // 即値かラベル
+        // `1b`, `1f` は数値ラベルへの参照
+        match self.evaluate(operand)? {
+            Value {
+                symbol: Some(label),
+                subtrahend: None,
+                constant,
+            } => Ok((
+                Operand::LABEL {
+                    label,
+                    addend: constant as i64,
+                },
+                None,
+            )),
+            value => value
+                .to_immediate()
+                .map(|imm| (Operand::Immediate(imm), None)),
+        }
+    }
+
+    /// `QWORD PTR [rax]` -> (Some(QWORD), "[rax]")
+    fn strip_size_ptr(operand: &str) -> (Option<OperandSize>, &str) {
+        let mut words = operand.splitn(2, char::is_whitespace);
+
+        let size = match words.next().unwrap().to_ascii_uppercase().as_str() {
+            "BYTE" => OperandSize::BYTE,
+            "WORD" => OperandSize::WORD,
+            "DWORD" => OperandSize::DWORD,
+            "QWORD" => OperandSize::QWORD,
+            "XMMWORD" => OperandSize::XMMWORD,
+            "YMMWORD" => OperandSize::YMMWORD,
+            _ => return (None, operand),
+        };
+
+        let rest = words.next().unwrap_or("").trim_start();
+        match rest.get(..3) {
+            Some(ptr) if ptr.eq_ignore_ascii_case("PTR") => (Some(size), rest[3..].trim_start()),
+            _ => (None, operand),
+        }
+    }
+
+    /// `[rbp-8]`, `-8[rbp]`, `[rax+rbx*4+16]`, `[rip+.LC0]` みたいなやつ
+    fn parse_intel_memory(&self, operand: &str) -> Result<Operand, String> {
+        let open = operand.find('[').unwrap();
+        if !operand.ends_with(']') {
+            return Err(format!("junk after memory operand '{}'", operand));
+        }
+
+        // `-8[rbp]` のように,括弧の前にもdisplacementを書ける
+        let outer = operand[..open].trim();
+        let inner = &operand[open + 1..operand.len() - 1];
+
+        let mut base = None;
+        let mut index = None;
+        let mut scale = None;
+        // レジスタ以外の項をまとめた式
+        let mut disp = String::new();
+        let mut rip = false;
+
+        let mut terms = Self::split_intel_terms(inner);
+        if !outer.is_empty() {
+            terms.push((false, outer));
+        }
+
+        for (negative, term) in terms {
+            if term.is_empty() {
+                return Err(format!("invalid memory operand '{}'", operand));
+            }
+
+            // index*scale
+            if let Some((reg, factor)) = Self::split_index_scale(term) {
+                if !matches!(factor, Some(1 | 2 | 4 | 8)) {
+                    return Err(format!("scale must be 1, 2, 4 or 8 in '{}'", operand));
+                }
+                if negative || index.is_some() {
+                    return Err(format!("invalid index register in '{}'", operand));
+                }
+
+                index = Some(reg);
+                scale = factor;
+                continue;
+            }
+
+            let lower = term.to_ascii_lowercase();
+            if lower == "rip" && !negative {
+                rip = true;
+                continue;
+            }
+
+            if let Some(reg) = GeneralPurposeRegister::from_intel_string(&lower) {
+                if negative {
+                    return Err(format!("register can't be subtracted in '{}'", operand));
+                }
+
+                if base.is_none() {
+                    base = Some(reg);
+                } else if index.is_none() {
+                    index = Some(reg);
+                } else {
+                    return Err(format!("too many registers in '{}'", operand));
+                }
+                continue;
+            }
+
+            disp.push_str(if negative { "-" } else { "+" });
+            disp.push_str(&format!("({})", term));
+        }
+
+        let disp = match disp.as_str() {
+            "" => None,
+            expr => Some(self.evaluate(expr)?),
+        };
+
+        // RIP相対
+        if rip {
+            return match (disp, base, index) {
+                (
+                    Some(Value {
+                        symbol: Some(label),
+                        subtrahend: None,
+                        constant,
+                    }),
+                    None,
+                    None,
+                ) => Ok(Operand::RIPRELATIVE {
+                    label,
+                    addend: constant as i64,
+                }),
+                _ => Err(format!("unsupported RIP-relative operand '{}'", operand)),
+            };
+        }
+
+        // `[rax*4]`, `[0x1000]` のようにベースレジスタが無い場合
+        let base = match base {
+            Some(base) => base,
+            None => {
+                return Ok(Operand::ABSOLUTE {
+                    index,
+                    disp: disp.unwrap_or_default().to_absolute_displacement()?,
+                    scale,
+                })
+            }
+        };
+
+        let displacement = match disp {
+            None => None,
+            Some(value) => Some(value.to_displacement()?),
+        };
+
+        Ok(Operand::ADDRESSING {
+            base,
+            index,
+            disp: displacement,
+            scale,
+        })
+    }
+
+    /// `rbx*4` -> Some((RBX, Some(4)))
+    /// レジスタを含まない `(1+2)*4` はdisplacementの式なので None
+    fn split_index_scale(term: &str) -> Option<(GeneralPurposeRegister, Option<u8>)> {
+        let (lhs, rhs) = term.split_once('*')?;
+        let (lhs, rhs) = (lhs.trim(), rhs.trim());
+
+        let (reg, factor) = match Self::parse_intel_register(lhs) {
+            Ok(reg) => (reg, rhs),
+            Err(_e) => (Self::parse_intel_register(rhs).ok()?, lhs),
+        };
+
+        Some((reg, factor.parse::<u8>().ok()))
+    }
+
+    /// `rbp-8` -> [(false, "rbp"), (true, "8")]
+    /// 各項と,その項が引かれるかどうかを返す
+    fn split_intel_terms(s: &str) -> Vec<(bool, &str)> {
+        let mut terms = Vec::new();
+        let mut negative = false;
+        let mut start = 0;
+        let mut depth = 0;
+
+        for (idx, c) in s.char_indices() {
+            match c {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                _ => {}
+            }
+            if (c != '+' && c != '-') || depth != 0 {
+                continue;
+            }
+
+            // 先頭の符号
+            if s[start..idx].trim().is_empty() && idx == start {
+                negative = c == '-';
+                start = idx + 1;
+                continue;
+            }
+
+            terms.push((negative, s[start..idx].trim()));
+            negative = c == '-';
+            start = idx + 1;
+        }
+        terms.push((negative, s[start..].trim()));
+
+        terms
+    }
+
+    fn parse_intel_register(s: &str) -> Result<GeneralPurposeRegister, String> {
+        GeneralPurposeRegister::from_intel_string(&s.to_ascii_lowercase())
+            .ok_or_else(|| format!("bad register name '{}'", s))
+    }
+}
+
+#[cfg(test)]
+mod parse_tests {
+    use super::*;
+    use OperandKind::*;
+    use OperandSize::*;
+
+    #[test]
+    fn parse_mov_test() {
+        let mut ctxt = new_context();
+        ctxt.toplevel("main:\n").unwrap();
+        ctxt.in_symbol("    mov rax, 42", "main").unwrap();
+        ctxt.in_symbol("    mov QWORD PTR [rbp-24], 3", "main")
+            .unwrap();
+        ctxt.in_symbol("    mov rax, QWORD PTR -8[rbp]", "main")
+            .unwrap();
+
+        let insts = &ctxt.syms.get("main").unwrap().groups[0].insts;
+        assert_eq!(
+            opcode(
+                "mov",
+                &[RegMem(QWORD), Imm(DWORD)],
+                vec![
+                    Operand::GENERALREGISTER(GeneralPurposeRegister::RAX),
+                    Operand::Immediate(Immediate::I32(42))
+                ]
+            ),
+            insts[0].opcode
+        );
+        assert_eq!(
+            opcode(
+                "mov",
+                &[RegMem(QWORD), Imm(DWORD)],
+                vec![
+                    Operand::ADDRESSING {
+                        base: GeneralPurposeRegister::RBP,
+                        index: None,
+                        disp: Some(Displacement::DISP8(-24)),
+                        scale: None
+                    },
+                    Operand::Immediate(Immediate::I32(3))
+                ]
+            ),
+            insts[1].opcode
+        );
+        assert_eq!(
+            opcode(
+                "mov",
+                &[Reg(QWORD), RegMem(QWORD)],
+                vec![
+                    Operand::GENERALREGISTER(GeneralPurposeRegister::RAX),
+                    Operand::ADDRESSING {
+                        base: GeneralPurposeRegister::RBP,
+                        index: None,
+                        disp: Some(Displacement::DISP8(-8)),
+                        scale: None
+                    }
+                ]
+            ),
+            insts[2].opcode
+        );
+    }
+
+    #[test]
+    fn parse_extension_test() {
+        let mut ctxt = new_context();
+        ctxt.toplevel("main:\n").unwrap();
+        ctxt.in_symbol("    movzx eax, BYTE PTR [rbp-1]", "main")
+            .unwrap();
+        ctxt.in_symbol("    movsxd rax, ecx", "main").unwrap();
+        ctxt.in_symbol("    cdqe", "main").unwrap();
+
+        let insts = &ctxt.syms.get("main").unwrap().groups[0].insts;
+        assert_eq!(
+            opcode(
+                "movzx",
+                &[Reg(DWORD), RegMem(BYTE)],
+                vec![
+                    Operand::GENERALREGISTER(GeneralPurposeRegister::EAX),
+                    Operand::ADDRESSING {
+                        base: GeneralPurposeRegister::RBP,
+                        index: None,
+                        disp: Some(Displacement::DISP8(-1)),
+                        scale: None
+                    }
+                ]
+            ),
+            insts[0].opcode
+        );
+        assert_eq!(
+            opcode(
+                "movsxd",
+                &[Reg(QWORD), RegMem(DWORD)],
+                vec![
+                    Operand::GENERALREGISTER(GeneralPurposeRegister::RAX),
+                    Operand::GENERALREGISTER(GeneralPurposeRegister::ECX)
+                ]
+            ),
+            insts[1].opcode
+        );
+        assert_eq!(opcode("cdqe", &[], vec![]), insts[2].opcode);
+
+        // 転送元のサイズが分からない
+        let err = ctxt.in_symbol("    movzx eax, [rax]", "main").unwrap_err();
+        assert_eq!("ambiguous operand size for 'movzx'", err.message);
+    }
+
+    #[test]
+    fn parse_sse_test() {
+        let mut ctxt = new_context();
+        ctxt.toplevel("main:\n").unwrap();
+        ctxt.in_symbol("    movsd xmm0, QWORD PTR [rbp-8]", "main")
+            .unwrap();
+        ctxt.in_symbol("    cvttsd2si eax, xmm15", "main").unwrap();
+        ctxt.in_symbol("    movaps XMMWORD PTR [rsp], xmm1", "main")
+            .unwrap();
+
+        let insts = &ctxt.syms.get("main").unwrap().groups[0].insts;
+        assert_eq!(
+            opcode(
+                "movsd",
+                &[Xmm, XmmMem(QWORD)],
+                vec![
+                    Operand::XMMREGISTER(XMMRegister::XMM0),
+                    Operand::ADDRESSING {
+                        base: GeneralPurposeRegister::RBP,
+                        index: None,
+                        disp: Some(Displacement::DISP8(-8)),
+                        scale: None
+                    }
+                ]
+            ),
+            insts[0].opcode
+        );
+        assert_eq!(
+            opcode(
+                "cvttsd2si",
+                &[Reg(DWORD), XmmMem(QWORD)],
+                vec![
+                    Operand::GENERALREGISTER(GeneralPurposeRegister::EAX),
+                    Operand::XMMREGISTER(XMMRegister::XMM15)
+                ]
+            ),
+            insts[1].opcode
+        );
+        assert_eq!(&[XmmMem(XMMWORD), Xmm], insts[2].opcode.form.operands);
+
+        // メモリオペランドのサイズが異なる
+        let err = ctxt
+            .in_symbol("    movsd xmm0, DWORD PTR [rbp-8]", "main")
+            .unwrap_err();
+        assert_eq!("invalid operand combination for 'movsd'", err.message);
+    }
+
+    #[test]
+    fn parse_avx_test() {
+        let mut ctxt = new_context();
+        ctxt.toplevel("main:\n").unwrap();
+        ctxt.in_symbol("    vmovdqu YMMWORD PTR [rdi+32], ymm8", "main")
+            .unwrap();
+        ctxt.in_symbol("    vbroadcastss ymm1, DWORD PTR [rip+.LC0]", "main")
+            .unwrap();
+        ctxt.in_symbol("    vzeroupper", "main").unwrap();
+
+        let insts = &ctxt.syms.get("main").unwrap().groups[0].insts;
+        assert_eq!(
+            opcode(
+                "vmovdqu",
+                &[YmmMem(YMMWORD), Ymm],
+                vec![
+                    Operand::ADDRESSING {
+                        base: GeneralPurposeRegister::RDI,
+                        index: None,
+                        disp: Some(Displacement::DISP8(32)),
+                        scale: None
+                    },
+                    Operand::YMMREGISTER(YMMRegister::YMM8)
+                ]
+            ),
+            insts[0].opcode
+        );
+        assert_eq!(&[Ymm, XmmMem(DWORD)], insts[1].opcode.form.operands);
+        assert_eq!(opcode("vzeroupper", &[], vec![]), insts[2].opcode);
+
+        let err = ctxt
+            .in_symbol("    vmovdqu ymm0, XMMWORD PTR [rdi]", "main")
+            .unwrap_err();
+        assert_eq!("invalid operand combination for 'vmovdqu'", err.message);
+    }
+
+    #[test]
+    fn parse_ambiguous_size_test() {
+        let mut ctxt = new_context();
+        ctxt.toplevel("main:\n").unwrap();
+
+        let err = ctxt.in_symbol("    mov [rbp-8], 3", "main").unwrap_err();
+        assert_eq!(5, err.column);
+
+        let err = ctxt.in_symbol("    mov eax, rbx", "main").unwrap_err();
+        assert_eq!(14, err.column);
+    }
+
+    #[test]
+    fn parse_intel_operand_test() {
+        assert_eq!(
+            Ok((Operand::GENERALREGISTER(GeneralPurposeRegister::RAX), None)),
+            new_context().parse_intel_operand("RAX")
+        );
+        assert_eq!(
+            Ok((Operand::Immediate(Immediate::I8(-30)), None)),
+            new_context().parse_intel_operand("-30")
+        );
+        assert_eq!(
+            Ok((
+                Operand::LABEL {
+                    label: ".L2".to_string(),
+                    addend: 0
+                },
+                None
+            )),
+            new_context().parse_intel_operand(".L2")
+        );
+        assert_eq!(
+            Ok((
+                Operand::LABEL {
+                    label: "1b".to_string(),
+                    addend: 0
+                },
+                None
+            )),
+            new_context().parse_intel_operand("1b")
+        );
+        assert_eq!(
+            Ok((
+                Operand::ADDRESSING {
+                    base: GeneralPurposeRegister::RAX,
+                    index: Some(GeneralPurposeRegister::RBX),
+                    disp: Some(Displacement::DISP8(16)),
+                    scale: Some(4),
+                },
+                Some(OperandSize::DWORD)
+            )),
+            new_context().parse_intel_operand("DWORD PTR [rax + rbx*4 + 16]")
+        );
+        assert_eq!(
+            Ok((
+                Operand::ADDRESSING {
+                    base: GeneralPurposeRegister::RAX,
+                    index: Some(GeneralPurposeRegister::RBX),
+                    disp: None,
+                    scale: None,
+                },
+                None
+            )),
+            new_context().parse_intel_operand("[rax+rbx]")
+        );
+        assert_eq!(
+            Ok((
+                Operand::ADDRESSING {
+                    base: GeneralPurposeRegister::RBP,
+                    index: None,
+                    disp: Some(Displacement::DISP32(-200)),
+                    scale: None,
+                },
+                Some(OperandSize::QWORD)
+            )),
+            new_context().parse_intel_operand("qword ptr [rbp-100-100]")
+        );
+        assert_eq!(
+            Ok((
+                Operand::ADDRESSING {
+                    base: GeneralPurposeRegister::RBP,
+                    index: None,
+                    disp: Some(Displacement::DISP8(-24)),
+                    scale: None,
+                },
+                None
+            )),
+            new_context().parse_intel_operand("[rbp-(1+2)*8]")
+        );
+        assert_eq!(
+            Ok((
+                Operand::ABSOLUTE {
+                    index: Some(GeneralPurposeRegister::RAX),
+                    disp: 0,
+                    scale: Some(4),
+                },
+                None
+            )),
+            new_context().parse_intel_operand("[rax*4]")
+        );
+        assert_eq!(
+            Ok((
+                Operand::ABSOLUTE {
+                    index: None,
+                    disp: 0x1000,
+                    scale: None,
+                },
+                Some(OperandSize::DWORD)
+            )),
+            new_context().parse_intel_operand("DWORD PTR [0x1000]")
+        );
+        assert_eq!(
+            Ok((Operand::Immediate(Immediate::I32(4104)), None)),
+            new_context().parse_intel_operand("(1<<12)+8")
+        );
+    }
+
+    #[test]
+    fn parse_rip_relative_operand_test() {
+        assert_eq!(
+            Ok((
+                Operand::RIPRELATIVE {
+                    label: ".LC0".to_string(),
+                    addend: 0
+                },
+                None
+            )),
+            new_context().parse_intel_operand("[rip+.LC0]")
+        );
+        assert_eq!(
+            Ok((
+                Operand::RIPRELATIVE {
+                    label: ".LC0".to_string(),
+                    addend: 0
+                },
+                None
+            )),
+            new_context().parse_intel_operand(".LC0[rip]")
+        );
+        assert_eq!(
+            Ok((
+                Operand::RIPRELATIVE {
+                    label: "sym".to_string(),
+                    addend: -8
+                },
+                None
+            )),
+            new_context().parse_intel_operand("[rip+sym-8]")
+        );
+        assert!(new_context().parse_intel_operand("[rip+8]").is_err());
+        assert!(new_context().parse_intel_operand("[rip+rax+.LC0]").is_err());
+    }
+
+    #[test]
+    fn parse_invalid_operand_test() {
+        assert!(new_context().parse_intel_operand("").is_err());
+        assert!(new_context().parse_intel_operand("QWORD PTR rax").is_err());
+        assert!(new_context().parse_intel_operand("[rax*3]").is_err());
+        assert!(new_context().parse_intel_operand("[rbp-8").is_err());
+        assert!(new_context().parse_intel_operand("[rax+rbx+rcx]").is_err());
+        assert!(new_context().parse_intel_operand("[.LC0]").is_err());
+        assert!(new_context()
+            .parse_intel_operand("0x10000000000000000")
+            .is_err());
+    }
+
+    fn new_context() -> Context {
+        Context::new("test.s", Syntax::INTEL)
+    }
+
+    fn opcode(mnemonic: &str, kinds: &[OperandKind], operands: Vec<Operand>) -> Opcode {
+        Opcode::with_form(mnemonic, kinds, operands).unwrap()
+    }
+}
@@ -0,0 +1,139 @@
+#[cfg(test)]
+mod parser_tests {
+    use super::super::context::parse;
+    use crate::assembler::resource::{Symbol, Syntax};
+    use crate::assembler::{AsmError, Options};
+    use indexmap::map::IndexMap;
+
+    #[test]
+    fn case1_test() {
+        let s = "	.intel_syntax noprefix
+	.globl	foo
+    .type	foo, @function
+foo:
+    push	rbp
+    mov	rbp, rsp
+    mov	rax, 30
+    pop	rbp
+    ret
+    .globl	main
+    .type	main, @function
+main:
+    push	rbp
+    mov	rbp, rsp
+    mov	rax, 0
+    call	foo
+    pop	rbp
+    ret"
+        .to_string();
+
+        let syms = parse_intel(s, "test.s").unwrap();
+
+        assert_eq!(2, syms.len());
+        assert!(
+            syms.get("foo").unwrap().bind == elf_utilities::symbol::Bind::Global
+                && syms.get("foo").unwrap().ty == elf_utilities::symbol::Type::Func
+        );
+        assert!(
+            syms.get("main").unwrap().bind == elf_utilities::symbol::Bind::Global
+                && syms.get("main").unwrap().ty == elf_utilities::symbol::Type::Func
+        );
+
+        assert_eq!(5, syms.get("foo").unwrap().groups[0].insts.len());
+        assert_eq!(6, syms.get("main").unwrap().groups[0].insts.len());
+    }
+
+    #[test]
+    fn same_as_atandt_test() {
+        let intel = "	.globl	main
+    .type	main, @function
+main:
+    push	rbp
+    mov	rbp, rsp
+    mov	QWORD PTR [rbp-24], 3
+    mov	QWORD PTR -16[rbp], 5
+    mov	rdx, QWORD PTR [rbp-24]
+    mov	rax, QWORD PTR [rbp - 16]
+    add	rax, rdx
+    imul	rax, QWORD PTR [rbp+rdx*8-8]
+    lea	rax, [rbp-8]
+    sub	rsp, 16
+    cmp	rax, 3
+    mov	QWORD PTR [rbp-8], rax
+    pop	rbp
+    ret"
+        .to_string();
+        let atandt = "	.globl	main
+    .type	main, @function
+main:
+    pushq	%rbp
+    movq	%rsp, %rbp
+    movq	$3, -24(%rbp)
+    movq	$5, -16(%rbp)
+    movq	-24(%rbp), %rdx
+    movq	-16(%rbp), %rax
+    addq	%rdx, %rax
+    imulq	-8(%rbp,%rdx,8), %rax
+    leaq	-8(%rbp), %rax
+    subq	$16, %rsp
+    cmpq	$3, %rax
+    movq	%rax, -8(%rbp)
+    popq	%rbp
+    ret"
+        .to_string();
+
+        let intel_syms = parse_intel(intel, "test.s").unwrap();
+        let atandt_syms = parse_atandt(atandt, "test.s").unwrap();
+
+        let intel_insts = &intel_syms.get("main").unwrap().groups[0].insts;
+        let atandt_insts = &atandt_syms.get("main").unwrap().groups[0].insts;
+        assert_eq!(atandt_insts.len(), intel_insts.len());
+        for (intel_inst, atandt_inst) in intel_insts.iter().zip(atandt_insts.iter()) {
+            assert_eq!(atandt_inst.opcode, intel_inst.opcode);
+        }
+    }
+
+    #[test]
+    fn switch_syntax_test() {
+        let s = "	.globl	main
+    .type	main, @function
+main:
+    pushq	%rbp
+    .intel_syntax noprefix
+    mov	rbp, rsp
+    .att_syntax prefix
+    movq	$0, %rax
+    popq	%rbp
+    ret"
+        .to_string();
+
+        let syms = parse_atandt(s, "test.s").unwrap();
+        assert_eq!(5, syms.get("main").unwrap().groups[0].insts.len());
+    }
+
+    #[test]
+    fn collect_all_errors_test() {
+        let s = "	.intel_syntax noprefix
+	.globl	main
+    .type	main, @function
+main:
+    mov	rax, QWORD PTR [rbp-8
+    mov	QWORD PTR [rbp-8], 3
+    add	rax, eax
+    mov	[rbp-8], 3
+    ret"
+        .to_string();
+
+        let errors = parse_intel(s, "test.s").unwrap_err();
+        let locations: Vec<(usize, usize)> = errors.iter().map(|e| (e.line, e.column)).collect();
+        assert_eq!(vec![(5, 14), (7, 14), (8, 5)], locations);
+    }
+
+    fn parse_atandt(s: String, file_name: &str) -> Result<IndexMap<String, Symbol>, Vec<AsmError>> {
+        parse(s, file_name, &Options::new(Syntax::ATANDT)).map(|(syms, _, _, _)| syms)
+    }
+
+    fn parse_intel(s: String, file_name: &str) -> Result<IndexMap<String, Symbol>, Vec<AsmError>> {
+        parse(s, file_name, &Options::new(Syntax::INTEL)).map(|(syms, _, _, _)| syms)
+    }
+}
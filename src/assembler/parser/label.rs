@@ -0,0 +1,159 @@
+use super::context::*;
+use crate::assembler::resource::Operand;
+use crate::assembler::AsmError;
+
+impl Context {
+    /// `1`, `42` のような数値ラベルかどうか
+    pub fn is_numeric_label(name: &str) -> bool {
+        !name.is_empty() && name.chars().all(|c| c.is_ascii_digit())
+    }
+
+    /// `1b`, `1f` のような数値ラベルへの参照かどうか
+    pub fn is_numeric_reference(name: &str) -> bool {
+        match name.strip_suffix(|c| c == 'b' || c == 'f') {
+            Some(label) => Self::is_numeric_label(label),
+            None => false,
+        }
+    }
+
+    /// `1:` の定義
+    /// 数値ラベルは何度でも定義できるので,定義ごとに一意な `.L` ラベル名をつける
+    pub fn define_numeric_label(&mut self, label: &str) -> String {
+        let count = self.numeric_labels.entry(label.to_string()).or_insert(0);
+        *count += 1;
+
+        Self::numeric_label_name(label, *count)
+    }
+
+    /// GNU as と同様に, `N` の `i` 番目の定義を `.LN\x02i` と名付ける
+    fn numeric_label_name(label: &str, instance: usize) -> String {
+        format!(".L{}\u{2}{}", label, instance)
+    }
+
+    /// オペランド中の `1b`, `1f` を,それが指すラベル名に置き換える
+    pub fn resolve_numeric_operand(
+        &mut self,
+        column: usize,
+        operand: Operand,
+    ) -> Result<Operand, AsmError> {
+        let resolved = match operand {
+            Operand::LABEL { label, addend } => Operand::LABEL {
+                label: self.resolve_numeric_reference(column, label)?,
+                addend,
+            },
+            Operand::RIPRELATIVE { label, addend } => Operand::RIPRELATIVE {
+                label: self.resolve_numeric_reference(column, label)?,
+                addend,
+            },
+            Operand::SYMBOLIC {
+                symbol,
+                subtrahend,
+                addend,
+            } => Operand::SYMBOLIC {
+                symbol: self.resolve_numeric_reference(column, symbol)?,
+                subtrahend: match subtrahend {
+                    Some(name) => Some(self.resolve_numeric_reference(column, name)?),
+                    None => None,
+                },
+                addend,
+            },
+            _ => operand,
+        };
+
+        Ok(resolved)
+    }
+
+    /// `1b` は直前の定義, `1f` は次の定義を指す
+    pub fn resolve_numeric_reference(
+        &mut self,
+        column: usize,
+        label: String,
+    ) -> Result<String, AsmError> {
+        if !Self::is_numeric_reference(&label) {
+            return Ok(label);
+        }
+        let (number, direction) = label.split_at(label.len() - 1);
+
+        let defined = self.numeric_labels.get(number).copied().unwrap_or(0);
+        match direction {
+            "b" if defined == 0 => {
+                Err(self.error_at(column, format!("undefined local label '{}'", label)))
+            }
+            "b" => Ok(Self::numeric_label_name(number, defined)),
+            _ => {
+                // 後で定義されなければエラーにする
+                let error = self.error_at(column, format!("undefined local label '{}'", label));
+                self.forward_references
+                    .push((number.to_string(), defined + 1, error));
+                Ok(Self::numeric_label_name(number, defined + 1))
+            }
+        }
+    }
+
+    /// 定義されなかった前方参照 `1f` のエラー
+    pub fn undefined_forward_references(&mut self) -> Vec<AsmError> {
+        let references = std::mem::take(&mut self.forward_references);
+
+        references
+            .into_iter()
+            .filter(|(label, instance, _)| {
+                self.numeric_labels.get(label).copied().unwrap_or(0) < *instance
+            })
+            .map(|(_, _, error)| error)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod label_tests {
+    use super::*;
+    use crate::assembler::resource::Syntax;
+
+    #[test]
+    fn resolve_numeric_reference_test() {
+        let mut ctxt = Context::new("a.s", Syntax::ATANDT);
+
+        let first = ctxt.define_numeric_label("1");
+        let forward = ctxt.resolve_numeric_reference(5, "1f".to_string()).unwrap();
+        assert_eq!(
+            first,
+            ctxt.resolve_numeric_reference(5, "1b".to_string()).unwrap()
+        );
+
+        let second = ctxt.define_numeric_label("1");
+        assert_ne!(first, second);
+        assert_eq!(second, forward);
+        assert_eq!(
+            second,
+            ctxt.resolve_numeric_reference(5, "1b".to_string()).unwrap()
+        );
+
+        // 数値ラベルでないものはそのまま
+        assert_eq!(
+            "foo",
+            ctxt.resolve_numeric_reference(5, "foo".to_string())
+                .unwrap()
+        );
+        assert_eq!(
+            "10",
+            ctxt.resolve_numeric_reference(5, "10".to_string()).unwrap()
+        );
+        assert!(ctxt.undefined_forward_references().is_empty());
+    }
+
+    #[test]
+    fn undefined_numeric_reference_test() {
+        let mut ctxt = Context::new("a.s", Syntax::ATANDT);
+        ctxt.line = 3;
+
+        let err = ctxt
+            .resolve_numeric_reference(9, "2b".to_string())
+            .unwrap_err();
+        assert_eq!((3, 9), (err.line, err.column));
+
+        ctxt.resolve_numeric_reference(7, "2f".to_string()).unwrap();
+        let errors = ctxt.undefined_forward_references();
+        assert_eq!(1, errors.len());
+        assert_eq!("undefined local label '2f'", errors[0].message);
+    }
+}
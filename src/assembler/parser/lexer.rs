@@ -0,0 +1,322 @@
+/// 1つの文( `;` で区切られた命令,ラベル定義等)
+/// 桁位置を保つため,行内の他の文やコメントは空白に置き換えてある
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub struct Statement {
+    /// 1始まりの行番号
+    pub line: usize,
+    pub text: String,
+    /// マクロ展開のネストの深さ(ソース中の文は0)
+    pub depth: usize,
+    /// 文を含むファイル( `Context::sources` の添字 )
+    pub source: usize,
+}
+
+/// ソースを文に分割する
+/// - `#` から行末まで, `/* */` の間はコメント
+/// - `;` は文の区切り
+/// - 文字列( `"..."` )や文字定数( `';'` )の中の `#`, `;` はそのまま
+/// - `main: ret` のような行は,ラベルと命令の2つの文に分ける
+///
+/// 閉じられていない `/*` があれば,その位置(行, 桁)も返す
+pub fn split_statements(source: &str) -> (Vec<Statement>, Option<(usize, usize)>) {
+    let mut statements = Vec::new();
+    let mut in_comment = None;
+
+    for (line_idx, line) in source.lines().enumerate() {
+        let line_number = line_idx + 1;
+        let (cleaned, separators) = remove_comments(line, line_number, &mut in_comment);
+
+        let mut start = 0;
+        for end in separators.into_iter().chain(std::iter::once(cleaned.len())) {
+            push_statements(&mut statements, line_number, &cleaned, start, end);
+            start = end + 1;
+        }
+    }
+
+    (statements, in_comment)
+}
+
+/// コメントと `;` を空白に置き換えた行と, `;` の位置を返す
+/// `in_comment` は行をまたぐ `/*` の開始位置
+fn remove_comments(
+    line: &str,
+    line_number: usize,
+    in_comment: &mut Option<(usize, usize)>,
+) -> (String, Vec<usize>) {
+    let mut cleaned = String::with_capacity(line.len());
+    let mut separators = Vec::new();
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut char_end = 0;
+    let mut chars = line.char_indices().peekable();
+
+    while let Some((idx, c)) = chars.next() {
+        if in_comment.is_some() {
+            if c == '*' && matches!(chars.peek(), Some((_, '/'))) {
+                chars.next();
+                cleaned.push_str("  ");
+                *in_comment = None;
+            } else {
+                push_blank(&mut cleaned, c);
+            }
+            continue;
+        }
+
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            _ if idx < char_end => {}
+            '"' => in_string = !in_string,
+            _ if in_string => {}
+            '\'' => char_end = idx + char_constant(&line[idx..]).1,
+            '#' => {
+                // 行末までコメント
+                for (_, c) in std::iter::once((idx, c)).chain(chars.by_ref()) {
+                    push_blank(&mut cleaned, c);
+                }
+                break;
+            }
+            '/' if matches!(chars.peek(), Some((_, '*'))) => {
+                chars.next();
+                cleaned.push_str("  ");
+                *in_comment = Some((line_number, idx + 1));
+                continue;
+            }
+            ';' => {
+                separators.push(idx);
+                cleaned.push(' ');
+                continue;
+            }
+            _ => {}
+        }
+
+        cleaned.push(c);
+    }
+
+    (cleaned, separators)
+}
+
+/// 桁位置を保つため,文字のバイト数分の空白にする
+fn push_blank(s: &mut String, c: char) {
+    for _ in 0..c.len_utf8() {
+        s.push(' ');
+    }
+}
+
+/// `line[start..end]` を文として追加する
+/// 先頭のラベル定義( `1:`, `main:`, `"a::b":` )は別の文にする
+fn push_statements(
+    statements: &mut Vec<Statement>,
+    line_number: usize,
+    line: &str,
+    mut start: usize,
+    end: usize,
+) {
+    while let Some(colon) = label_end(&line[start..end]) {
+        statements.push(new_statement(line_number, line, start, start + colon + 1));
+        start += colon + 1;
+    }
+
+    if !line[start..end].trim().is_empty() {
+        statements.push(new_statement(line_number, line, start, end));
+    }
+}
+
+fn new_statement(line_number: usize, line: &str, start: usize, end: usize) -> Statement {
+    Statement {
+        line: line_number,
+        text: format!("{}{}", " ".repeat(start), &line[start..end]),
+        depth: 0,
+        source: 0,
+    }
+}
+
+/// 文がラベル定義で始まる場合,その `:` の位置を返す
+pub fn label_end(s: &str) -> Option<usize> {
+    let name_start = s.len() - s.trim_start().len();
+    let rest = &s[name_start..];
+
+    let name_length = if let Some(quoted) = rest.strip_prefix('"') {
+        quoted.find('"')? + 2
+    } else {
+        rest.find(|c: char| !is_symbol_char(c))
+            .unwrap_or(rest.len())
+    };
+
+    match rest[name_length..].starts_with(':') {
+        true if name_length != 0 => Some(name_start + name_length),
+        _ => None,
+    }
+}
+
+/// シンボル名に使える文字
+pub fn is_symbol_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '$'
+}
+
+/// `'a'`, `'\n'`, `'\101'` のような文字定数( `s` は `'` で始まる )の中身と長さ
+/// GNU as と同様に,閉じる `'` は省略できる( `'a` )
+pub fn char_constant(s: &str) -> (&str, usize) {
+    let body = &s[1..];
+    let length = match body.strip_prefix('\\') {
+        Some(escape) => {
+            let escape_length = match escape.chars().next() {
+                Some('0'..='7') => escape.chars().take(3).take_while(|c| c.is_digit(8)).count(),
+                Some('x') => {
+                    escape[1..]
+                        .find(|c: char| !c.is_ascii_hexdigit())
+                        .unwrap_or(escape.len() - 1)
+                        + 1
+                }
+                Some(c) => c.len_utf8(),
+                None => 0,
+            };
+            1 + escape_length
+        }
+        None => body.chars().next().map_or(0, |c| c.len_utf8()),
+    };
+    let closing = body[length..].starts_with('\'') as usize;
+
+    (&body[..length], 1 + length + closing)
+}
+
+/// `line[start..]` をカンマで区切り,各オペランドを桁位置とともに返す
+/// `(%rbp, %rax, 8)`, `[rax + rbx*4]`, 文字列中のカンマでは区切らない
+pub fn split_operands(line: &str, start: usize) -> Vec<(usize, &str)> {
+    let rest = &line[start..];
+    if rest.trim().is_empty() {
+        return Vec::new();
+    }
+
+    let mut operands = Vec::new();
+    let mut op_start = start;
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut char_end = 0;
+
+    for (idx, c) in rest.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            _ if idx < char_end => {}
+            '"' => in_string = !in_string,
+            _ if in_string => {}
+            '\'' => char_end = idx + char_constant(&rest[idx..]).1,
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth -= 1,
+            ',' if depth == 0 => {
+                operands.push(trim_with_column(line, op_start, start + idx));
+                op_start = start + idx + 1;
+            }
+            _ => {}
+        }
+    }
+    operands.push(trim_with_column(line, op_start, line.len()));
+
+    operands
+}
+
+/// `line[start..end]` の前後の空白を取り除き,桁位置とともに返す
+fn trim_with_column(line: &str, start: usize, end: usize) -> (usize, &str) {
+    let s = &line[start..end];
+    let leading = s.len() - s.trim_start().len();
+
+    (start + leading + 1, s.trim())
+}
+
+#[cfg(test)]
+mod lexer_tests {
+    use super::*;
+
+    #[test]
+    fn split_statements_test() {
+        let source = "main: # entry
+    movq $1, %rax; ret
+1: 2: jmp 1b /* back */";
+
+        let (statements, unterminated) = split_statements(source);
+        assert_eq!(None, unterminated);
+
+        let texts: Vec<(usize, &str)> = statements
+            .iter()
+            .map(|s| (s.line, s.text.as_str()))
+            .collect();
+        assert_eq!(
+            vec![
+                (1, "main:"),
+                (2, "    movq $1, %rax"),
+                (2, "                   ret"),
+                (3, "1:"),
+                (3, "   2:"),
+                (3, "      jmp 1b           "),
+            ],
+            texts
+        );
+    }
+
+    #[test]
+    fn comment_in_string_test() {
+        let source = r#"    .string "a#b;c:/*\"" # comment"#;
+
+        let (statements, _) = split_statements(source);
+        assert_eq!(1, statements.len());
+        assert_eq!(r#"    .string "a#b;c:/*\""          "#, statements[0].text);
+
+        let (statements, _) = split_statements("    movb $';', %al; cmpb $'#', %cl # comment");
+        assert_eq!(2, statements.len());
+        assert_eq!("    movb $';', %al", statements[0].text);
+        assert_eq!(
+            "                    cmpb $'#', %cl          ",
+            statements[1].text
+        );
+    }
+
+    #[test]
+    fn multiline_comment_test() {
+        let source = "/* start
+    movq $1, %rax
+end */ ret
+    /* unterminated";
+
+        let (statements, unterminated) = split_statements(source);
+        assert_eq!(1, statements.len());
+        assert_eq!(
+            (3, "       ret"),
+            (statements[0].line, statements[0].text.as_str())
+        );
+        assert_eq!(Some((4, 5)), unterminated);
+    }
+
+    #[test]
+    fn label_end_test() {
+        assert_eq!(Some(4), label_end("main: ret"));
+        assert_eq!(Some(17), label_end("  \"aarch64::main\":"));
+        assert_eq!(None, label_end("movq %fs:0, %rax"));
+        assert_eq!(None, label_end(".ident \"GCC: (GNU) 10.2.0\""));
+        assert_eq!(None, label_end(":"));
+    }
+
+    #[test]
+    fn split_operands_test() {
+        let line = "    movq -8( %rbp , %rax, 8 ),%rbx";
+
+        assert_eq!(
+            vec![(10, "-8( %rbp , %rax, 8 )"), (31, "%rbx")],
+            split_operands(line, 8)
+        );
+        assert_eq!(
+            vec![(9, "\"a,b\""), (16, "1")],
+            split_operands(".ascii  \"a,b\", 1", 6)
+        );
+        assert_eq!(
+            vec![(9, "QWORD PTR [rbp - 8]"), (30, "rax")],
+            split_operands("    mov QWORD PTR [rbp - 8] ,rax", 7)
+        );
+        assert_eq!(
+            vec![(10, "$','"), (16, "%al")],
+            split_operands("    movb $',', %al", 8)
+        );
+        assert!(split_operands("    ret   ", 7).is_empty());
+    }
+}
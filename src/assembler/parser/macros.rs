@@ -0,0 +1,553 @@
+use super::context::*;
+use super::lexer::{split_operands, split_statements, Statement};
+use crate::assembler::AsmError;
+
+/// マクロ展開のネストの上限( GNU as と同じ )
+const MAX_EXPANSION_DEPTH: usize = 100;
+
+/// `.macro` で定義されたマクロ
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub struct Macro {
+    params: Vec<MacroParam>,
+    body: Vec<Statement>,
+}
+
+/// `.macro name a, b=1, c:req, rest:vararg` の各パラメータ
+#[derive(Eq, PartialEq, Debug, Clone)]
+struct MacroParam {
+    name: String,
+    default: String,
+    required: bool,
+    vararg: bool,
+}
+
+impl Context {
+    /// マクロ関連のディレクティブを処理し,展開した文を `pending` に積む
+    /// それ以外の文はそのまま返す
+    /// `pending` は末尾が次に処理する文
+    pub fn expand_statement(
+        &mut self,
+        statement: Statement,
+        pending: &mut Vec<Statement>,
+    ) -> Result<Option<Statement>, AsmError> {
+        let words = Self::split_words(&statement.text);
+        let (column, first) = match words.first() {
+            Some(&word) => word,
+            None => return Ok(Some(statement)),
+        };
+        let rest = &statement.text[column - 1 + first.len()..];
+
+        // 条件が成り立たない分岐の中では,マクロの定義等も無視する
+        if let Some(result) =
+            self.parse_conditional_directive((column, first), rest, statement.depth)
+        {
+            return result.map(|_| None);
+        }
+        if !self.is_assembling() {
+            return Ok(None);
+        }
+        if let Some(result) = self.parse_diagnostic_directive((column, first), rest) {
+            return result.map(|_| None);
+        }
+
+        match first {
+            ".macro" => self.define_macro(&statement, column, rest, pending)?,
+            ".include" => self.include_file(&statement, column, rest, pending)?,
+            ".purgem" => {
+                let name = rest.trim().to_ascii_lowercase();
+                if self.macros.shift_remove(&name).is_none() {
+                    return Err(self.error_at(column, format!("macro '{}' is not defined", name)));
+                }
+            }
+            ".rept" => {
+                let body = self.collect_body(&statement, column, pending, ".endr")?;
+                let count = self.evaluate_constant(column + first.len(), rest.trim())?;
+                for _ in 0..count.max(0) {
+                    Self::push_expansion(pending, &body, statement.depth, |text| text.to_string());
+                }
+            }
+            ".irp" | ".irpc" => {
+                let body = self.collect_body(&statement, column, pending, ".endr")?;
+                let args = split_operands(&statement.text, column - 1 + first.len());
+                let (name, values) = match args.split_first() {
+                    Some(((_, name), values)) if !name.is_empty() => (name.to_string(), values),
+                    _ => {
+                        return Err(
+                            self.error_at(column, format!("'{}' expects a parameter name", first))
+                        );
+                    }
+                };
+
+                let values: Vec<String> = match first {
+                    ".irp" => values.iter().map(|(_, v)| unquote(v)).collect(),
+                    _ => values
+                        .iter()
+                        .flat_map(|(_, v)| unquote(v).chars().collect::<Vec<char>>())
+                        .map(|c| c.to_string())
+                        .collect(),
+                };
+
+                // 値が無ければ,空文字列で1回展開する
+                let values = if values.is_empty() {
+                    vec![String::new()]
+                } else {
+                    values
+                };
+
+                for value in values.iter().rev() {
+                    let args = [(name.as_str(), value.as_str())];
+                    Self::push_expansion(pending, &body, statement.depth, |text| {
+                        substitute(text, &args, None)
+                    });
+                }
+            }
+            ".exitm" => {
+                if statement.depth == 0 {
+                    return Err(self.error_at(column, "'.exitm' outside of a macro".to_string()));
+                }
+
+                // 展開中のマクロの残りと,その中で始まった `.if` を捨てる
+                while matches!(pending.last(), Some(s) if s.depth >= statement.depth) {
+                    pending.pop();
+                }
+                self.exit_conditionals(statement.depth);
+            }
+            ".endm" | ".endr" => {
+                let start = if first == ".endm" { ".macro" } else { ".rept" };
+                return Err(self.error_at(column, format!("'{}' without '{}'", first, start)));
+            }
+            _ => match self.macros.get(&first.to_ascii_lowercase()) {
+                Some(m) => {
+                    let m = m.clone();
+                    self.invoke_macro(&statement, column, rest, &m, pending)?;
+                }
+                None => return Ok(Some(statement)),
+            },
+        }
+
+        Ok(None)
+    }
+
+    /// `.macro name a, b=1` から `.endm` までを定義する
+    fn define_macro(
+        &mut self,
+        statement: &Statement,
+        column: usize,
+        header: &str,
+        pending: &mut Vec<Statement>,
+    ) -> Result<(), AsmError> {
+        let body = self.collect_body(statement, column, pending, ".endm")?;
+
+        let header = header.trim();
+        let name_length = header
+            .find(|c: char| c.is_whitespace() || c == ',')
+            .unwrap_or(header.len());
+        let name = &header[..name_length];
+        if name.is_empty() {
+            return Err(self.error_at(column, "'.macro' expects a name".to_string()));
+        }
+
+        let mut params = Vec::new();
+        let rest = header[name_length..].trim_start().trim_start_matches(',');
+        for param in split_macro_arguments(rest, true).iter() {
+            let (param, default) = match param.split_once('=') {
+                Some((param, default)) => (param.trim(), unquote(default.trim())),
+                None => (param.as_str(), String::new()),
+            };
+            let (param_name, qualifier) = match param.split_once(':') {
+                Some((param_name, qualifier)) => (param_name.trim(), Some(qualifier.trim())),
+                None => (param, None),
+            };
+
+            if param_name.is_empty() || !param_name.chars().all(is_param_char) {
+                return Err(self.error_at(column, format!("invalid parameter '{}'", param)));
+            }
+            if !matches!(qualifier, None | Some("req") | Some("vararg")) {
+                return Err(self.error_at(
+                    column,
+                    format!("invalid qualifier '{}'", qualifier.unwrap()),
+                ));
+            }
+
+            params.push(MacroParam {
+                name: param_name.to_string(),
+                default,
+                required: qualifier == Some("req"),
+                vararg: qualifier == Some("vararg"),
+            });
+        }
+
+        if params.iter().rev().skip(1).any(|param| param.vararg) {
+            return Err(self.error_at(
+                column,
+                "only the last parameter can be ':vararg'".to_string(),
+            ));
+        }
+
+        self.macros
+            .insert(name.to_ascii_lowercase(), Macro { params, body });
+        Ok(())
+    }
+
+    /// 引数をパラメータに割り当てて,マクロの本体を展開する
+    fn invoke_macro(
+        &mut self,
+        statement: &Statement,
+        column: usize,
+        rest: &str,
+        m: &Macro,
+        pending: &mut Vec<Statement>,
+    ) -> Result<(), AsmError> {
+        if statement.depth >= MAX_EXPANSION_DEPTH {
+            return Err(self.error_at(column, "macros nested too deeply".to_string()));
+        }
+
+        // パラメータが1つなら,空白を含む引数もそのまま渡す
+        let args = split_macro_arguments(rest.trim(), m.params.len() > 1);
+        let mut values: Vec<Option<String>> = vec![None; m.params.len()];
+        let mut position = 0;
+
+        for (idx, arg) in args.iter().enumerate() {
+            // `name=value` のキーワード引数
+            let keyword = arg.split_once('=').and_then(|(name, value)| {
+                m.params
+                    .iter()
+                    .position(|param| param.name == name.trim())
+                    .map(|idx| (idx, value.trim()))
+            });
+            if let Some((param_idx, value)) = keyword {
+                values[param_idx] = Some(unquote(value));
+                continue;
+            }
+
+            match m.params.get(position) {
+                // 可変長引数は残りをすべて受け取る
+                Some(param) if param.vararg => {
+                    values[position] = Some(args[idx..].join(", "));
+                    break;
+                }
+                Some(_param) => values[position] = Some(unquote(arg)),
+                None => {
+                    return Err(self.error_at(column, "too many positional arguments".to_string()));
+                }
+            }
+            position += 1;
+        }
+
+        let mut substitutions = Vec::new();
+        for (param, value) in m.params.iter().zip(values) {
+            let value = match value {
+                Some(value) if !value.is_empty() => value,
+                _ if param.required => {
+                    return Err(self.error_at(
+                        column,
+                        format!("missing value for required parameter '{}'", param.name),
+                    ));
+                }
+                _ => param.default.to_string(),
+            };
+            substitutions.push((param.name.as_str(), value));
+        }
+
+        let substitutions: Vec<(&str, &str)> = substitutions
+            .iter()
+            .map(|(name, value)| (*name, value.as_str()))
+            .collect();
+        let count = self.macro_expansions;
+        self.macro_expansions += 1;
+
+        Self::push_expansion(pending, &m.body, statement.depth + 1, |text| {
+            substitute(text, &substitutions, Some(count))
+        });
+        Ok(())
+    }
+
+    /// `end` ( `.endm`, `.endr` ) までの文を取り出す
+    /// 入れ子になった `.macro`, `.rept` 等も考慮する
+    fn collect_body(
+        &self,
+        statement: &Statement,
+        column: usize,
+        pending: &mut Vec<Statement>,
+        end: &str,
+    ) -> Result<Vec<Statement>, AsmError> {
+        let mut body = Vec::new();
+        let mut nest = 0;
+
+        while let Some(s) = pending.pop() {
+            let first = Self::split_words(&s.text).first().map(|(_, word)| *word);
+
+            match first {
+                Some(".macro") if end == ".endm" => nest += 1,
+                Some(".rept") | Some(".irp") | Some(".irpc") if end == ".endr" => nest += 1,
+                Some(word) if word == end && nest == 0 => return Ok(body),
+                Some(word) if word == end => nest -= 1,
+                _ => {}
+            }
+
+            body.push(s);
+        }
+
+        Err(AsmError::new(
+            &self.file,
+            statement.line,
+            column,
+            format!("missing '{}'", end),
+        ))
+    }
+
+    /// 置換した本体を, `pending` の先頭(末尾)に積む
+    /// 置換によって現れた `;` やラベルで文を分け直す
+    fn push_expansion(
+        pending: &mut Vec<Statement>,
+        body: &[Statement],
+        depth: usize,
+        substitute: impl Fn(&str) -> String,
+    ) {
+        let mut expanded = Vec::new();
+
+        for statement in body.iter() {
+            let (statements, _) = split_statements(&substitute(&statement.text));
+            expanded.extend(statements.into_iter().map(|s| Statement {
+                line: statement.line,
+                depth,
+                source: statement.source,
+                ..s
+            }));
+        }
+
+        pending.extend(expanded.into_iter().rev());
+    }
+}
+
+/// マクロのパラメータ名に使える文字
+fn is_param_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '$'
+}
+
+/// `"a b"` -> `a b`
+fn unquote(s: &str) -> String {
+    match s.strip_prefix('"').and_then(|s| s.strip_suffix('"')) {
+        Some(inner) => inner.to_string(),
+        None => s.to_string(),
+    }
+}
+
+/// 引数はカンマか,( `by_whitespace` なら)空白で区切る
+fn split_macro_arguments(s: &str, by_whitespace: bool) -> Vec<String> {
+    if s.is_empty() {
+        return Vec::new();
+    }
+
+    let args = split_operands(s, 0);
+    if args.len() > 1 || !by_whitespace {
+        return args.into_iter().map(|(_, arg)| arg.to_string()).collect();
+    }
+
+    // 文字列中の空白では区切らない
+    let mut args = vec![String::new()];
+    let mut in_string = false;
+    for c in s.chars() {
+        match c {
+            '"' => in_string = !in_string,
+            _ if c.is_whitespace() && !in_string => {
+                if !args.last().unwrap().is_empty() {
+                    args.push(String::new());
+                }
+                continue;
+            }
+            _ => {}
+        }
+        args.last_mut().unwrap().push(c);
+    }
+
+    args
+}
+
+/// `\name` を引数に, `\@` を展開回数に置き換え, `\()` を取り除く
+fn substitute(text: &str, args: &[(&str, &str)], count: Option<usize>) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(idx) = rest.find('\\') {
+        result.push_str(&rest[..idx]);
+        let after = &rest[idx + 1..];
+
+        if let (Some(count), Some(after)) = (count, after.strip_prefix('@')) {
+            result.push_str(&count.to_string());
+            rest = after;
+            continue;
+        }
+        if let Some(after) = after.strip_prefix("()") {
+            rest = after;
+            continue;
+        }
+
+        let name_length = after
+            .find(|c: char| !is_param_char(c))
+            .unwrap_or(after.len());
+        match args.iter().find(|(name, _)| *name == &after[..name_length]) {
+            Some((_, value)) if name_length != 0 => {
+                result.push_str(value);
+                rest = &after[name_length..];
+            }
+            // パラメータでなければそのまま
+            _ => {
+                result.push('\\');
+                rest = after;
+            }
+        }
+    }
+    result.push_str(rest);
+
+    result
+}
+
+#[cfg(test)]
+mod macro_tests {
+    use super::*;
+    use crate::assembler::resource::Syntax;
+
+    #[test]
+    fn substitute_test() {
+        let args = [("reg", "%rax"), ("n", "8")];
+        assert_eq!(
+            "movq \\x, %rax; .L8_3:",
+            substitute("movq \\x, \\reg; .L\\n\\()_\\@:", &args, Some(3))
+        );
+        assert_eq!("\\@", substitute("\\@", &args, None));
+    }
+
+    #[test]
+    fn expand_macro_test() {
+        let source = ".macro str s, n=3, rest:vararg
+    .string \"\\s\"
+    .byte \\n
+    .byte \\rest
+.endm
+.macro twice a b
+    .byte \\a, \\b, \\@
+.endm
+    str \"a b\", 5, 1, 2
+    str \"c\"  ,, 9
+    twice 1 2
+    twice b=4, a=6";
+
+        assert_eq!(
+            lines(vec![
+                (2, "    .string \"a b\""),
+                (3, "    .byte 5"),
+                (4, "    .byte 1, 2"),
+                (2, "    .string \"c\""),
+                (3, "    .byte 3"),
+                (4, "    .byte 9"),
+                (7, "    .byte 1, 2, 2"),
+                (7, "    .byte 6, 4, 3"),
+            ]),
+            expand(source).unwrap()
+        );
+    }
+
+    #[test]
+    fn expand_rept_test() {
+        let source = "    .irp r, rax, rbx
+    .rept 2
+    pushq %\\r
+    .endr
+    .endr
+    .irpc c, 12
+    .byte \\c
+    .endr
+    .rept 0
+    .byte 0
+    .endr";
+
+        assert_eq!(
+            lines(vec![
+                (3, "    pushq %rax"),
+                (3, "    pushq %rax"),
+                (3, "    pushq %rbx"),
+                (3, "    pushq %rbx"),
+                (7, "    .byte 1"),
+                (7, "    .byte 2"),
+            ]),
+            expand(source).unwrap()
+        );
+    }
+
+    #[test]
+    fn exitm_and_purgem_test() {
+        let source = ".macro m a
+    .byte \\a
+    .exitm
+    .byte 9
+.endm
+    m 1
+    m 2
+    .purgem m
+    m 3";
+
+        assert_eq!(
+            lines(vec![(2, "    .byte 1"), (2, "    .byte 2"), (9, "    m 3")]),
+            expand(source).unwrap()
+        );
+
+        // `.exitm` は展開中に始まった `.if` も閉じる
+        let source = ".macro m n
+    .if \\n == 0
+    .exitm
+    .endif
+    .byte \\n
+.endm
+    .if 1
+    m 0
+    m 1
+    .endif";
+
+        assert_eq!(lines(vec![(5, "    .byte 1")]), expand(source).unwrap());
+    }
+
+    #[test]
+    fn invalid_macro_test() {
+        let err = expand(".macro m a:req\n.endm\n  m").unwrap_err();
+        assert_eq!((3, 3), (err.line, err.column));
+        assert_eq!("missing value for required parameter 'a'", err.message);
+
+        let err = expand("\n  .rept 3\n  .byte 1").unwrap_err();
+        assert_eq!((2, 3), (err.line, err.column));
+        assert_eq!("missing '.endr'", err.message);
+
+        assert!(expand(".macro m a\n.endm\n  m 1, 2").is_err());
+        assert!(expand(".macro r\n  r\n.endm\n  r").is_err());
+        assert!(expand("  .exitm").is_err());
+        assert!(expand("  .endm").is_err());
+        assert!(expand("  .purgem m").is_err());
+    }
+
+    fn lines(expected: Vec<(usize, &str)>) -> Vec<(usize, String)> {
+        expected
+            .into_iter()
+            .map(|(line, text)| (line, text.to_string()))
+            .collect()
+    }
+
+    /// 展開後の (行番号, 文)
+    fn expand(source: &str) -> Result<Vec<(usize, String)>, AsmError> {
+        let mut ctxt = Context::new("test.s", Syntax::ATANDT);
+        let (statements, _) = split_statements(source);
+        let mut pending: Vec<Statement> = statements.into_iter().rev().collect();
+        let mut expanded = Vec::new();
+
+        while let Some(statement) = pending.pop() {
+            ctxt.line = statement.line;
+            if let Some(s) = ctxt.expand_statement(statement, &mut pending)? {
+                expanded.push((s.line, s.text.trim_end().to_string()));
+            }
+        }
+
+        match ctxt.unterminated_conditionals().into_iter().next() {
+            Some(e) => Err(e),
+            None => Ok(expanded),
+        }
+    }
+}
@@ -1,3 +1,4 @@
+mod condition;
 mod elf_builder;
 mod encoding;
 mod group;
@@ -7,10 +8,13 @@ mod opcode;
 mod operand;
 mod relocation;
 mod rex_prefix;
+mod section;
 mod sib_byte;
 mod symbol;
 mod syntax;
+mod vex_prefix;
 
+pub use condition::*;
 pub use elf_builder::*;
 pub use encoding::*;
 pub use group::*;
@@ -20,6 +24,8 @@ pub use opcode::*;
 pub use operand::*;
 pub use relocation::*;
 pub use rex_prefix::*;
+pub use section::*;
 pub use sib_byte::*;
 pub use symbol::*;
 pub use syntax::*;
+pub use vex_prefix::*;
@@ -0,0 +1,162 @@
+/// x86_64 condition codes(the `cc` of Jcc/SETcc/CMOVcc).
+#[derive(Eq, Ord, PartialOrd, PartialEq, Debug, Clone, Copy)]
+pub enum ConditionCode {
+    /// Overflow(OF=1)
+    O,
+    /// Not Overflow(OF=0)
+    NO,
+    /// Below(CF=1)
+    B,
+    /// Above or Equal(CF=0)
+    AE,
+    /// Equal(ZF=1)
+    E,
+    /// Not Equal(ZF=0)
+    NE,
+    /// Below or Equal(CF=1 or ZF=1)
+    BE,
+    /// Above(CF=0 and ZF=0)
+    A,
+    /// Sign(SF=1)
+    S,
+    /// Not Sign(SF=0)
+    NS,
+    /// Parity(PF=1)
+    P,
+    /// Not Parity(PF=0)
+    NP,
+    /// Less(SF!=OF)
+    L,
+    /// Greater or Equal(SF=OF)
+    GE,
+    /// Less or Equal(ZF=1 or SF!=OF)
+    LE,
+    /// Greater(ZF=0 and SF=OF)
+    G,
+}
+
+impl ConditionCode {
+    /// the lower 4 bits of the opcode(e.g. `0x74` for `je`)
+    pub fn number(&self) -> u8 {
+        match self {
+            Self::O => 0x0,
+            Self::NO => 0x1,
+            Self::B => 0x2,
+            Self::AE => 0x3,
+            Self::E => 0x4,
+            Self::NE => 0x5,
+            Self::BE => 0x6,
+            Self::A => 0x7,
+            Self::S => 0x8,
+            Self::NS => 0x9,
+            Self::P => 0xa,
+            Self::NP => 0xb,
+            Self::L => 0xc,
+            Self::GE => 0xd,
+            Self::LE => 0xe,
+            Self::G => 0xf,
+        }
+    }
+
+    /// the inverse of `number()`(e.g. `0x4` -> E).
+    pub fn from_number(number: u8) -> Option<Self> {
+        let cond = match number {
+            0x0 => Self::O,
+            0x1 => Self::NO,
+            0x2 => Self::B,
+            0x3 => Self::AE,
+            0x4 => Self::E,
+            0x5 => Self::NE,
+            0x6 => Self::BE,
+            0x7 => Self::A,
+            0x8 => Self::S,
+            0x9 => Self::NS,
+            0xa => Self::P,
+            0xb => Self::NP,
+            0xc => Self::L,
+            0xd => Self::GE,
+            0xe => Self::LE,
+            0xf => Self::G,
+            _ => return None,
+        };
+
+        Some(cond)
+    }
+
+    /// `e` -> E, `z` -> E, `nae` -> B ...
+    /// accepts all aliases GNU as accepts.
+    pub fn from_suffix(s: &str) -> Option<Self> {
+        let cond = match s {
+            "o" => Self::O,
+            "no" => Self::NO,
+            "b" | "c" | "nae" => Self::B,
+            "ae" | "nb" | "nc" => Self::AE,
+            "e" | "z" => Self::E,
+            "ne" | "nz" => Self::NE,
+            "be" | "na" => Self::BE,
+            "a" | "nbe" => Self::A,
+            "s" => Self::S,
+            "ns" => Self::NS,
+            "p" | "pe" => Self::P,
+            "np" | "po" => Self::NP,
+            "l" | "nge" => Self::L,
+            "ge" | "nl" => Self::GE,
+            "le" | "ng" => Self::LE,
+            "g" | "nle" => Self::G,
+            _ => return None,
+        };
+
+        Some(cond)
+    }
+
+    /// E -> `e`, B -> `b` ...
+    /// the canonical suffix objdump prints.
+    pub fn to_suffix(self) -> &'static str {
+        match self {
+            Self::O => "o",
+            Self::NO => "no",
+            Self::B => "b",
+            Self::AE => "ae",
+            Self::E => "e",
+            Self::NE => "ne",
+            Self::BE => "be",
+            Self::A => "a",
+            Self::S => "s",
+            Self::NS => "ns",
+            Self::P => "p",
+            Self::NP => "np",
+            Self::L => "l",
+            Self::GE => "ge",
+            Self::LE => "le",
+            Self::G => "g",
+        }
+    }
+}
+
+#[cfg(test)]
+mod condition_tests {
+    use super::*;
+
+    #[test]
+    fn from_suffix_test() {
+        assert_eq!(Some(ConditionCode::E), ConditionCode::from_suffix("z"));
+        assert_eq!(Some(ConditionCode::B), ConditionCode::from_suffix("nae"));
+        assert_eq!(Some(ConditionCode::NP), ConditionCode::from_suffix("po"));
+        assert_eq!(Some(ConditionCode::G), ConditionCode::from_suffix("nle"));
+        assert_eq!(None, ConditionCode::from_suffix("mp"));
+    }
+
+    #[test]
+    fn number_test() {
+        let all = [
+            "o", "no", "b", "ae", "e", "ne", "be", "a", "s", "ns", "p", "np", "l", "ge", "le", "g",
+        ];
+        for (n, suffix) in all.iter().enumerate() {
+            let cond = ConditionCode::from_suffix(suffix).unwrap();
+            assert_eq!(n as u8, cond.number());
+            assert_eq!(*suffix, cond.to_suffix());
+            assert_eq!(Some(cond), ConditionCode::from_number(n as u8));
+        }
+        assert_eq!(None, ConditionCode::from_number(0x10));
+    }
+}
@@ -1,13 +1,21 @@
 use elf_utilities::file::ELF64;
+use indexmap::map::IndexMap;
 
 pub struct ELFBuilder {
     pub file: ELF64,
+    /// セクション名 -> セクションヘッダテーブル内のインデックス
+    sections: IndexMap<String, usize>,
+    /// シンボル名 -> シンボルテーブル内のインデックス
+    /// セクションシンボルはセクション名で登録する
+    symbols: IndexMap<String, usize>,
 }
 
 impl ELFBuilder {
     pub fn new() -> Self {
         Self {
             file: ELF64::new(Self::initialize_elf64_header()),
+            sections: IndexMap::new(),
+            symbols: IndexMap::new(),
         }
     }
     pub fn give_file(self) -> elf_utilities::file::ELF64 {
@@ -15,9 +23,58 @@ impl ELFBuilder {
     }
 
     pub fn add_section(&mut self, section: elf_utilities::section::Section64) {
+        self.sections
+            .insert(section.name.to_string(), self.file.sections.len());
         self.file.add_section(section);
     }
 
+    /// 追加済みのセクションのインデックス
+    pub fn section_index(&self, name: &str) -> Option<usize> {
+        self.sections.get(name).copied()
+    }
+
+    /// 追加した順のセクション名(NULLセクションを除く)
+    pub fn section_names(&self) -> Vec<&str> {
+        self.sections
+            .keys()
+            .filter(|name| !name.is_empty())
+            .map(|name| name.as_str())
+            .collect()
+    }
+
+    pub fn register_symbol(&mut self, name: &str, index: usize) {
+        self.symbols.insert(name.to_string(), index);
+    }
+
+    /// シンボル名 -> シンボルテーブル内のインデックス
+    pub fn symbol_indices(&self) -> &IndexMap<String, usize> {
+        &self.symbols
+    }
+
+    /// シンボルテーブル,再配置テーブルの sh_link/sh_info を設定する
+    /// すべてのセクションを追加してから呼び出す
+    pub fn link_sections(&mut self) {
+        let strtab_index = self.section_index(".strtab").unwrap_or(0);
+        let symtab_index = self.section_index(".symtab").unwrap_or(0);
+
+        for section in self.file.sections.iter_mut() {
+            match section.header.get_type() {
+                elf_utilities::section::Type::SymTab => {
+                    section.header.sh_link = strtab_index as elf_utilities::Elf64Word;
+                }
+                elf_utilities::section::Type::Rela => {
+                    // .rela.text -> .text
+                    let target = section.name.trim_start_matches(".rela");
+                    let target_index = self.sections.get(target).copied().unwrap_or(0);
+
+                    section.header.sh_link = symtab_index as elf_utilities::Elf64Word;
+                    section.header.sh_info = target_index as elf_utilities::Elf64Word;
+                }
+                _ => {}
+            }
+        }
+    }
+
     fn initialize_elf64_header() -> elf_utilities::header::Ehdr64 {
         let mut ehdr: elf_utilities::header::Ehdr64 = Default::default();
 
@@ -39,3 +96,38 @@ impl ELFBuilder {
         ehdr
     }
 }
+
+#[cfg(test)]
+mod elf_builder_tests {
+    use super::*;
+    use elf_utilities::section::{Section64, Shdr64, Type};
+
+    #[test]
+    fn section_registry_test() {
+        let mut builder = ELFBuilder::new();
+        builder.add_section(Section64::new_null_section());
+        builder.add_section(new_section(".text", Type::ProgBits));
+        builder.add_section(new_section(".rela.text", Type::Rela));
+        builder.add_section(new_section(".symtab", Type::SymTab));
+        builder.add_section(new_section(".strtab", Type::StrTab));
+
+        assert_eq!(Some(3), builder.section_index(".symtab"));
+        assert_eq!(None, builder.section_index(".data"));
+        assert_eq!(
+            vec![".text", ".rela.text", ".symtab", ".strtab"],
+            builder.section_names()
+        );
+
+        builder.link_sections();
+        let sections = &builder.file.sections;
+        assert_eq!(3, sections[2].header.sh_link);
+        assert_eq!(1, sections[2].header.sh_info);
+        assert_eq!(4, sections[3].header.sh_link);
+    }
+
+    fn new_section(name: &str, ty: Type) -> Section64 {
+        let mut shdr: Shdr64 = Default::default();
+        shdr.set_type(ty);
+        Section64::new(name.to_string(), shdr)
+    }
+}
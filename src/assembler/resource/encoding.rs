@@ -1,4 +1,4 @@
-#[allow(dead_code)]
+#[allow(dead_code, clippy::upper_case_acronyms)]
 #[derive(Eq, Ord, PartialOrd, PartialEq, Debug, Clone, Copy)]
 pub enum Encoding {
     ZO,
@@ -18,4 +18,16 @@ pub enum Encoding {
     I,
     /// Ope1 -> ModRM:r/m
     M,
+    /// Ope1 -> ModRM:r/m,   Ope2 -> 1
+    M1,
+    /// Ope1 -> ModRM:r/m,   Ope2 -> CL
+    MC,
+    /// Ope1 -> ModRM:r/m,   Ope2 -> ModRM:reg, Ope3 -> imm8
+    MRI,
+    /// Ope1 -> ModRM:r/m,   Ope2 -> ModRM:reg, Ope3 -> CL
+    MRC,
+    /// Ope1 -> ModRM:reg,   Ope2 -> ModRM:r/m, Ope3 -> imm8
+    RMI,
+    /// Ope1 -> ModRM:reg,   Ope2 -> VEX.vvvv,  Ope3 -> ModRM:r/m
+    RVM,
 }
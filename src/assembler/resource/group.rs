@@ -1,6 +1,6 @@
 use crate::assembler::resource::Instruction;
 
-#[derive(Eq, Ord, PartialOrd, PartialEq, Debug, Clone)]
+#[derive(Eq, Ord, PartialOrd, PartialEq, Debug, Clone, Default)]
 pub struct Group {
     pub label: String,
     pub insts: Vec<Instruction>,
@@ -16,12 +16,3 @@ impl Group {
         }
     }
 }
-
-impl Default for Group {
-    fn default() -> Self {
-        Self {
-            label: String::new(),
-            insts: Vec::new(),
-        }
-    }
-}
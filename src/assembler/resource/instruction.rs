@@ -1,7 +1,6 @@
-use crate::assembler::resource::Opcode;
+use crate::assembler::resource::{Opcode, Operand, OperandKind};
 
 /// An implementation of x64 instruction.
-#[allow(dead_code)]
 #[derive(Eq, Ord, PartialOrd, PartialEq, Debug, Clone)]
 pub struct Instruction {
     pub opcode: Opcode,
@@ -13,10 +12,19 @@ impl Instruction {
     pub fn to_bytes(&self) -> Vec<u8> {
         let mut codes = Vec::new();
 
+        // REX prefix より前に置く
+        if let Some(prefix) = self.opcode.prefix() {
+            codes.push(prefix);
+        }
+
         if let Some(rex_prefix) = self.opcode.rex_prefix() {
             codes.push(rex_prefix.to_byte());
         }
 
+        if let Some(vex_prefix) = self.opcode.vex_prefix() {
+            codes.append(&mut vex_prefix.to_bytes());
+        }
+
         codes.append(&mut self.opcode.to_bytes());
 
         if let Some(modrm) = self.opcode.modrm() {
@@ -36,4 +44,56 @@ impl Instruction {
         }
         codes
     }
+
+    /// `movq -8(%rbp), %rax`
+    pub fn to_at_string(&self) -> String {
+        let operands = self
+            .printed_operands()
+            .rev()
+            .map(|(_, operand)| operand.to_at_string())
+            .collect::<Vec<String>>();
+
+        Self::join(self.opcode.at_mnemonic(), operands)
+    }
+
+    /// `mov rax, QWORD PTR -8[rbp]`
+    pub fn to_intel_string(&self) -> String {
+        let operands = self
+            .printed_operands()
+            .map(|(kind, operand)| match (kind, operand) {
+                (
+                    OperandKind::RegMem(size)
+                    | OperandKind::XmmMem(size)
+                    | OperandKind::YmmMem(size),
+                    Operand::ADDRESSING { .. }
+                    | Operand::ABSOLUTE { .. }
+                    | Operand::RIPRELATIVE { .. },
+                ) => format!(
+                    "{} PTR {}",
+                    size.to_intel_string(),
+                    operand.to_intel_address()
+                ),
+                _ => operand.to_intel_address(),
+            })
+            .collect::<Vec<String>>();
+
+        Self::join(self.opcode.mnemonic(), operands)
+    }
+
+    /// `shl %rax` のように,省略できる 1 は表示しない
+    fn printed_operands(&self) -> impl DoubleEndedIterator<Item = (&OperandKind, &Operand)> {
+        self.opcode
+            .form
+            .operands
+            .iter()
+            .zip(self.opcode.operands.iter())
+            .filter(|(kind, _)| **kind != OperandKind::One)
+    }
+
+    fn join(mnemonic: String, operands: Vec<String>) -> String {
+        match operands.is_empty() {
+            true => mnemonic,
+            false => format!("{} {}", mnemonic, operands.join(", ")),
+        }
+    }
 }
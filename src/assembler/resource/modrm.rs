@@ -1,4 +1,4 @@
-use crate::assembler::resource::{GeneralPurposeRegister, Operand};
+use crate::assembler::resource::Operand;
 
 #[derive(Eq, Ord, PartialOrd, PartialEq, Debug, Clone, Copy)]
 pub struct ModRM {
@@ -8,38 +8,13 @@ pub struct ModRM {
 }
 
 impl ModRM {
-    pub fn to_byte(&self) -> u8 {
+    pub fn to_byte(self) -> u8 {
         Self::mode_field(self.mode.to_byte()) | self.reg | self.rm
     }
 
-    /// new MI Encoding.
-    pub fn new_mi(mode: AddressingMode, rm: &Operand) -> Self {
-        let rm_byte = if rm.req_sib_byte() {
-            0x04
-        } else {
-            rm.number() & 0b111
-        };
-        Self {
-            mode,
-            rm: Self::rm_field(rm_byte),
-            reg: 0,
-        }
-    }
     /// new MR Encoding.
-    pub fn new_mr(mode: AddressingMode, rm: &Operand, reg: &GeneralPurposeRegister) -> Self {
-        let rm_byte = if rm.req_sib_byte() {
-            0x04
-        } else {
-            rm.number() & 0b111
-        };
-        Self {
-            mode,
-            rm: Self::rm_field(rm_byte),
-            reg: Self::reg_field(reg.number()),
-        }
-    }
-    /// new RM Encoding.
-    pub fn new_rm(mode: AddressingMode, reg: &GeneralPurposeRegister, rm: &Operand) -> Self {
+    /// `reg` is the register code(or the opcode extension `/digit`) in ModRM:reg.
+    pub fn new_mr(mode: AddressingMode, rm: &Operand, reg: u8) -> Self {
         let rm_byte = if rm.req_sib_byte() {
             0x04
         } else {
@@ -48,7 +23,7 @@ impl ModRM {
         Self {
             mode,
             rm: Self::rm_field(rm_byte),
-            reg: Self::reg_field(reg.number() & 0b111),
+            reg: Self::reg_field(reg & 0b111),
         }
     }
     pub fn mode_field(byte: u8) -> u8 {
@@ -62,6 +37,7 @@ impl ModRM {
     }
 }
 
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Eq, Ord, PartialOrd, PartialEq, Debug, Clone, Copy)]
 pub enum AddressingMode {
     /// [reg + disp8]
@@ -75,7 +51,7 @@ pub enum AddressingMode {
 }
 
 impl AddressingMode {
-    pub fn to_byte(&self) -> u8 {
+    pub fn to_byte(self) -> u8 {
         match self {
             Self::REGISTER => 0b00,
             Self::DISP8 => 0b01,
@@ -1,21 +1,7 @@
 mod base;
-pub use base::*;
+mod form;
+mod table;
 
-mod add;
-pub use add::*;
-mod call;
-pub use call::*;
-mod cmp;
-pub use cmp::*;
-mod mov;
-pub use mov::*;
-mod push;
-pub use push::*;
-mod pop;
-pub use pop::*;
-mod sub;
-pub use sub::*;
-mod lea;
-pub use lea::*;
-mod imul;
-pub use imul::*;
+pub use base::*;
+pub use form::*;
+pub use table::*;
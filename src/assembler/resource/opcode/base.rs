@@ -1,603 +1,262 @@
 use crate::assembler::resource::*;
 
-#[allow(dead_code)]
+/// an instruction form in the table with its operands.
 #[derive(Eq, Ord, PartialOrd, PartialEq, Debug, Clone)]
-pub enum Opcode {
-    // Add
-    /// Add r/m32 to r32
-    ADDRM32R32 {
-        rm32: Operand,
-        r32: GeneralPurposeRegister,
-    },
-
-    /// Add r32 to r/m32
-    ADDR32RM32 {
-        r32: GeneralPurposeRegister,
-        rm32: Operand,
-    },
-    /// Add r/m64 to r64
-    ADDRM64R64 {
-        rm64: Operand,
-        r64: GeneralPurposeRegister,
-    },
-
-    /// Add r64 to r/m64
-    ADDR64RM64 {
-        r64: GeneralPurposeRegister,
-        rm64: Operand,
-    },
-
-    // Call
-    /// CALL Function (abstraction)
-    CALLFUNC(Operand),
-
-    // Convert Word to Doubleword/Convert Doubleword to Quadword
-    /// DX:AX := Sign-extended of AX
-    CWD,
-    /// EDX:EAX := Sign-extended of EAX
-    CDQ,
-    /// RDX:RAX := Sign-extended of RAX
-    CQO,
-
-    // Compare Two Operands
-    /// Compare imm32 with r/m64
-    CMPRM64IMM32 { imm: Immediate, rm64: Operand },
-    /// Compare imm32 with RAX.
-    CMPRAXIMM32 { imm: Immediate },
-
-    /// End Branch 64bit
-    ENDBR64,
-
-    // (signed) Integer Divide
-    /// signed divide RDX:RAX by r/m64;
-    /// result stored in RAX := Quotient, RDX := Remainder.
-    IDIVRM64 { rm64: Operand },
-
-    // (signed) Integer Multiply
-    /// Quadword register := Quadword register * r/m64
-    IMULR64RM64 {
-        r64: GeneralPurposeRegister,
-        rm64: Operand,
-    },
-
-    // Increment
-    /// increment r/m64 by one.
-    INCRM64 { rm64: Operand },
-
-    // Jump
-    /// Jump Label
-    JMPLABEL { label: String },
-
-    /// Jump Equal Label
-    JELABEL { label: String },
-
-    /// Jump Less or Equal Label
-    JLELABEL { label: String },
-
-    // Load Effective Address
-    /// Store effective address for m in register r64
-    LEAR64M {
-        r64: GeneralPurposeRegister,
-        m: Operand,
-    },
-
-    // Move
-    /// Move r8 to r/m8
-    MOVRM8R8 {
-        r8: GeneralPurposeRegister,
-        rm8: Operand,
-    },
-
-    /// Move r32 to r/m32
-    MOVRM32R32 {
-        r32: GeneralPurposeRegister,
-        rm32: Operand,
-    },
-
-    /// Move r/m32 to r32
-    MOVR32RM32 {
-        r32: GeneralPurposeRegister,
-        rm32: Operand,
-    },
-
-    /// Move imm32 to r/m32
-    MOVRM32IMM32 { imm: Immediate, rm32: Operand },
-
-    /// Move r64 to r/m64
-    MOVRM64R64 {
-        r64: GeneralPurposeRegister,
-        rm64: Operand,
-    },
-
-    /// Move r/m64 to r64
-    MOVR64RM64 {
-        r64: GeneralPurposeRegister,
-        rm64: Operand,
-    },
-
-    /// Move imm32 to r/m64
-    MOVRM64IMM32 { imm: Immediate, rm64: Operand },
-
-    // Neg
-    /// Two's complement negate r/m64
-    NEGRM64 { rm64: Operand },
-
-    // Pop
-    /// Pop top of stack into r64; increment stack pointer; Cannot encode 32-bit operand size.
-    POPR64 { r64: GeneralPurposeRegister },
-
-    // Push
-    /// Push r/m64
-    PUSHRM64 { rm64: Operand },
-
-    /// Push r64,
-    PUSHR64 { r64: GeneralPurposeRegister },
-    /// Push imm32
-    PUSHIMM32 { imm: Immediate },
-
-    // Return from procedure
-    /// Near Return
-    RET,
-
-    // Sub
-    /// Subtract r/m64 from r64
-    SUBR64RM64 {
-        r64: GeneralPurposeRegister,
-        rm64: Operand,
-    },
-
-    /// Subtract r64 from r/m64
-    SUBRM64R64 {
-        rm64: Operand,
-        r64: GeneralPurposeRegister,
-    },
-
-    /// Subtract imm32 from r/m64
-    SUBRM64IMM32 { rm64: Operand, imm: Immediate },
-
-    /// Fast System Call
-    SYSCALL,
-
-    // etc
-    /// for comments
-    COMMENT(String),
+pub struct Opcode {
+    pub form: &'static InstructionForm,
+    /// 条件付きの命令の条件( `sete` の E 等 )
+    pub cond: Option<ConditionCode>,
+    /// Intel SDM の順( dst, src )
+    /// `shl %rax` の 1 のように,省略されたオペランドも含む
+    pub operands: Vec<Operand>,
 }
-#[allow(dead_code)]
-impl Opcode {
-    pub fn to_bytes(&self) -> Vec<u8> {
-        match self {
-            // Add
-            Opcode::ADDRM32R32 { rm32: _, r32: _ } => vec![0x01],
-            Opcode::ADDR32RM32 { r32: _, rm32: _ } => vec![0x03],
-            Opcode::ADDRM64R64 { rm64: _, r64: _ } => vec![0x01],
-            Opcode::ADDR64RM64 { r64: _, rm64: _ } => vec![0x03],
-
-            // Call
-            Opcode::CALLFUNC(_func) => unimplemented!(),
-
-            // Convert Word to Doubleword/Convert Doubleword to Quadword
-            Opcode::CWD => vec![0x66, 0x99],
-            Opcode::CDQ | Opcode::CQO => vec![0x99],
-
-            Opcode::CMPRM64IMM32 { imm: _, rm64: _ } => vec![0x81],
-            Opcode::CMPRAXIMM32 { imm: _ } => vec![0x3d],
-
-            Opcode::ENDBR64 => vec![0xf3, 0x0f, 0x1e, 0xfa],
-
-            // (signed) Integer Divide
-            Opcode::IDIVRM64 { rm64: _ } => vec![0xf7],
 
-            // (signed) Integer Multiply
-            Opcode::IMULR64RM64 { r64: _, rm64: _ } => vec![0x0f, 0xaf],
-
-            // Increment
-            Opcode::INCRM64 { rm64: _ } => vec![0xff],
-
-            // Jump
-            Opcode::JMPLABEL { label: _ } => vec![0xe9],
-            Opcode::JELABEL { label: _ } => vec![0x0f, 0x84],
-            Opcode::JLELABEL { label: _ } => vec![0x0f, 0x8e],
-
-            // Load Effective Address
-            Opcode::LEAR64M { r64: _, m: _ } => vec![0x8d],
-
-            // Move
-            Opcode::MOVRM8R8 { r8: _, rm8: _ } => vec![0x88],
-            Opcode::MOVRM32R32 { r32: _, rm32: _ } => vec![0x89],
-            Opcode::MOVR32RM32 { r32: _, rm32: _ } => vec![0x8b],
-            Opcode::MOVRM32IMM32 { imm: _, rm32: _ } => vec![0xc7],
-            Opcode::MOVRM64R64 { r64: _, rm64: _ } => vec![0x89],
-            Opcode::MOVR64RM64 { r64: _, rm64: _ } => vec![0x8b],
-            Opcode::MOVRM64IMM32 { imm: _, rm64: _ } => vec![0xc7],
-
-            // Neg
-            Opcode::NEGRM64 { rm64: _ } => vec![0xf7],
-
-            // Pop
-            Opcode::POPR64 { r64 } => vec![0x58 + r64.number()],
-
-            // Push
-            Opcode::PUSHRM64 { rm64: _ } => vec![0xff],
-            Opcode::PUSHR64 { r64 } => vec![0x50 + r64.number()],
-            Opcode::PUSHIMM32 { imm: _ } => vec![0x68],
-
-            // Return from procedure
-            Opcode::RET => vec![0xc3],
-
-            // Sub
-            Opcode::SUBRM64IMM32 { rm64: _, imm: _ } => vec![0x81],
-            Opcode::SUBR64RM64 { r64: _, rm64: _ } => vec![0x2b],
-            Opcode::SUBRM64R64 { rm64: _, r64: _ } => vec![0x29],
-
-            // Fast System Call
-            Opcode::SYSCALL => vec![0x0f, 0x05],
-
-            // etc
-            Opcode::COMMENT(_com) => panic!("mustn't call 'to_bytes()' with COMMENT"),
+impl Opcode {
+    pub fn new(
+        form: &'static InstructionForm,
+        cond: Option<ConditionCode>,
+        operands: Vec<Operand>,
+    ) -> Self {
+        Self {
+            form,
+            cond,
+            operands,
         }
     }
 
-    pub fn encoding(&self) -> Encoding {
-        match self {
-            Opcode::ADDRM32R32 { rm32: _, r32: _ } => Encoding::MR,
-            Opcode::ADDR32RM32 { r32: _, rm32: _ } => Encoding::RM,
-            Opcode::ADDRM64R64 { rm64: _, r64: _ } => Encoding::MR,
-            Opcode::ADDR64RM64 { r64: _, rm64: _ } => Encoding::RM,
-            Opcode::CALLFUNC(_func) => unimplemented!(),
-            Opcode::CWD | Opcode::CDQ | Opcode::CQO => Encoding::ZO,
-            Opcode::CMPRM64IMM32 { imm: _, rm64: _ } => Encoding::MI,
-            Opcode::CMPRAXIMM32 { imm: _ } => Encoding::I,
-            Opcode::ENDBR64 => Encoding::ZO,
-            Opcode::IDIVRM64 { rm64: _ } => Encoding::M,
-            Opcode::IMULR64RM64 { r64: _, rm64: _ } => Encoding::RM,
-            Opcode::INCRM64 { rm64: _ } => Encoding::M,
-            Opcode::JMPLABEL { label: _ } => Encoding::D,
-            Opcode::JELABEL { label: _ } => Encoding::D,
-            Opcode::JLELABEL { label: _ } => Encoding::D,
-            Opcode::LEAR64M { r64: _, m: _ } => Encoding::RM,
-            Opcode::MOVRM8R8 { r8: _, rm8: _ } => Encoding::MR,
-            Opcode::MOVRM32R32 { r32: _, rm32: _ } => Encoding::MR,
-            Opcode::MOVR32RM32 { r32: _, rm32: _ } => Encoding::RM,
-            Opcode::MOVRM32IMM32 { rm32: _, imm: _ } => Encoding::MI,
-            Opcode::MOVRM64R64 { r64: _, rm64: _ } => Encoding::MR,
-            Opcode::MOVR64RM64 { r64: _, rm64: _ } => Encoding::RM,
-            Opcode::MOVRM64IMM32 { rm64: _, imm: _ } => Encoding::MI,
-            Opcode::NEGRM64 { rm64: _ } => Encoding::M,
-            Opcode::POPR64 { r64: _ } => Encoding::O,
-            Opcode::PUSHRM64 { rm64: _ } => Encoding::M,
-            Opcode::PUSHR64 { r64: _ } => Encoding::O,
-            Opcode::PUSHIMM32 { imm: _ } => Encoding::I,
-            Opcode::RET => Encoding::ZO,
-            Opcode::SUBRM64IMM32 { rm64: _, imm: _ } => Encoding::MI,
-            Opcode::SUBR64RM64 { r64: _, rm64: _ } => Encoding::RM,
-            Opcode::SUBRM64R64 { rm64: _, r64: _ } => Encoding::MR,
-            Opcode::SYSCALL => Encoding::ZO,
-            Opcode::COMMENT(_com) => panic!("mustn't call 'encoding()' with COMMENT"),
-        }
+    /// `add r/m64, r64` のように,命令表の形式を名前とオペランドの種類で指定して作る
+    pub fn with_form(
+        mnemonic: &str,
+        kinds: &[OperandKind],
+        operands: Vec<Operand>,
+    ) -> Option<Self> {
+        let (form, cond) = InstructionForm::find(mnemonic, kinds)?;
+        Some(Self::new(form, cond, operands))
     }
 
-    /// calculating REX-Prefix byte
-    pub fn rex_prefix(&self) -> Option<REXPrefix> {
-        match &self {
-            // Add
-            Opcode::ADDRM64R64 { rm64, r64 } => {
-                Some(REXPrefix::new_from_mem_and_reg(true, r64, rm64))
-            }
-            Opcode::ADDR64RM64 { r64, rm64 } => Some(REXPrefix::new_rm(r64, rm64)),
-
-            // Convert Word to Doubleword/Convert Doubleword to Quadword
-            Opcode::CQO => Some(REXPrefix::new(true, false, false, false)),
-
-            // Compare Two Operands
-            Opcode::CMPRM64IMM32 { imm: _, rm64 } => Some(REXPrefix::new_mi(rm64)),
-            Opcode::CMPRAXIMM32 { imm: _ } => Some(REXPrefix::new(true, false, false, false)),
-
-            // (signed) Integer Multiply
-            Opcode::IMULR64RM64 { r64, rm64 } => Some(REXPrefix::new_rm(r64, rm64)),
-
-            // (signed) Integer Divide
-            Opcode::IDIVRM64 { rm64 } => Some(REXPrefix::new_from_mem(true, rm64)),
-
-            // Increment
-            Opcode::INCRM64 { rm64 } => Some(REXPrefix::new_from_mem(true, rm64)),
-
-            // Load Effective Address
-            Opcode::LEAR64M { r64, m } => Some(REXPrefix::new(
-                true,
-                r64.is_expanded(),
-                m.index_reg_is_expanded(),
-                m.is_expanded(),
-            )),
-
-            // Move
-            Opcode::MOVRM64R64 { rm64, r64 } => Some(REXPrefix::new(
-                true,
-                r64.is_expanded(),
-                rm64.index_reg_is_expanded(),
-                rm64.is_expanded(),
-            )),
-            Opcode::MOVR64RM64 { r64, rm64 } => Some(REXPrefix::new(
-                true,
-                r64.is_expanded(),
-                rm64.index_reg_is_expanded(),
-                rm64.is_expanded(),
-            )),
-            Opcode::MOVRM64IMM32 { rm64, imm: _ } => Some(REXPrefix::new_mi(rm64)),
-
-            // Neg
-            Opcode::NEGRM64 { rm64 } => Some(REXPrefix::new_from_mem(true, rm64)),
+    /// the mnemonic in Intel syntax, including the condition(e.g. `sete`).
+    pub fn mnemonic(&self) -> String {
+        match self.cond {
+            Some(cond) => format!("{}{}", self.form.mnemonic, cond.to_suffix()),
+            None => self.form.mnemonic.to_string(),
+        }
+    }
 
-            // Pop
-            Opcode::POPR64 { r64 } => {
-                if r64.is_expanded() {
-                    Some(REXPrefix::new(false, false, false, true))
-                } else {
-                    None
-                }
-            }
+    /// the mnemonic in AT&T syntax, with the size suffix(e.g. `movq`, `movzbl`, `cltq`).
+    pub fn at_mnemonic(&self) -> String {
+        let mnemonic = self.mnemonic();
+        if let Some((_, att)) = ATT_MNEMONICS.iter().find(|(intel, _)| *intel == mnemonic) {
+            return att.to_string();
+        }
 
-            // Push
-            Opcode::PUSHRM64 { rm64: _ } => None,
-            Opcode::PUSHR64 { r64 } => {
-                if r64.is_expanded() {
-                    Some(REXPrefix::new(false, false, false, true))
-                } else {
-                    None
-                }
+        match (self.form.mnemonic, self.form.operands) {
+            // `movzx eax, bl` -> `movzbl %bl, %eax`
+            ("movzx", [OperandKind::Reg(size), OperandKind::RegMem(rm_size)]) => {
+                format!("movz{}{}", rm_size.to_at_suffix(), size.to_at_suffix())
             }
-            // Sub
-            Opcode::SUBRM64IMM32 { rm64, imm: _ } => Some(REXPrefix::new_from_mem(true, rm64)),
-            Opcode::SUBR64RM64 { r64, rm64 } => {
-                Some(REXPrefix::new_from_mem_and_reg(true, r64, rm64))
+            ("movsx" | "movsxd", [OperandKind::Reg(size), OperandKind::RegMem(rm_size)]) => {
+                format!("movs{}{}", rm_size.to_at_suffix(), size.to_at_suffix())
             }
-            Opcode::SUBRM64R64 { rm64, r64 } => {
-                Some(REXPrefix::new_from_mem_and_reg(true, r64, rm64))
-            }
-
-            _ => None,
+            // `setb` は set below と区別できない
+            ("set", _) => mnemonic,
+            // SSE, AVX の命令はサフィックスを付けない
+            // `cvtsi2sdl (%rax), %xmm0` のように,メモリオペランドのサイズが決まらない場合のみ付ける
+            _ if self.form.is_simd() => match self.ambiguous_memory_size() {
+                Some(size) => format!("{}{}", mnemonic, size.to_at_suffix()),
+                None => mnemonic,
+            },
+            _ => match (self.form.encoding, self.form.size()) {
+                (Encoding::ZO | Encoding::D, _) | (_, None) => mnemonic,
+                (_, Some(size)) => format!("{}{}", mnemonic, size.to_at_suffix()),
+            },
         }
     }
 
-    /// calculating ModR/M byte
-    #[allow(unreachable_patterns)]
-    pub fn modrm(&self) -> Option<ModRM> {
-        match &self {
-            // Add
-            Opcode::ADDRM32R32 { rm32, r32 } => {
-                // MR
-                Some(ModRM::new_mr(rm32.addressing_mode(), rm32, r32))
-            }
-            Opcode::ADDR32RM32 { r32, rm32 } => {
-                // RM
-                Some(ModRM::new_rm(rm32.addressing_mode(), r32, rm32))
-            }
-            Opcode::ADDRM64R64 { rm64, r64 } => {
-                // MR
-                Some(ModRM::new_mr(rm64.addressing_mode(), rm64, r64))
-            }
-            Opcode::ADDR64RM64 { r64, rm64 } => {
-                // RM
-                Some(ModRM::new_rm(rm64.addressing_mode(), r64, rm64))
-            }
-
-            // Compare
-            Opcode::CMPRM64IMM32 { imm: _, rm64 } => {
-                // MIだけど /7 でマスク
-                Some(ModRM::new_mr(
-                    rm64.addressing_mode(),
-                    rm64,
-                    &GeneralPurposeRegister::new_64bit_from_code(7),
-                ))
-            }
-            // (signed) Integer Divide
-            Opcode::IDIVRM64 { rm64 } => {
-                // Mだけど /7 でマスク
-                Some(ModRM::new_mr(
-                    rm64.addressing_mode(),
-                    rm64,
-                    &GeneralPurposeRegister::new_64bit_from_code(7),
-                ))
-            }
+    /// 汎用レジスタのサイズだけが異なる形式がある命令の,メモリオペランドのサイズ
+    fn ambiguous_memory_size(&self) -> Option<OperandSize> {
+        let size = self
+            .form
+            .operands
+            .iter()
+            .zip(self.operands.iter())
+            .find_map(|(kind, operand)| match (kind, operand) {
+                (OperandKind::RegMem(size), Operand::ADDRESSING { .. })
+                | (OperandKind::RegMem(size), Operand::ABSOLUTE { .. })
+                | (OperandKind::RegMem(size), Operand::RIPRELATIVE { .. }) => Some(*size),
+                _ => None,
+            })?;
+
+        InstructionForm::candidates(self.form.mnemonic, self.form.operands.len())
+            .iter()
+            .any(|(form, _)| form.size() != self.form.size())
+            .then_some(size)
+    }
 
-            // (signed) Integer Multiply
-            Opcode::IMULR64RM64 { r64, rm64 } => {
-                // RM
-                Some(ModRM::new_rm(rm64.addressing_mode(), r64, rm64))
-            }
+    pub fn to_bytes(&self) -> Vec<u8> {
+        // エスケープ( `0f` 等 )は VEX prefix に含める
+        let mut codes = match self.form.vex {
+            Some(_) => self.form.split_escape().1.to_vec(),
+            None => self.form.opcode.to_vec(),
+        };
+
+        // 条件コードやレジスタ番号は,オペコードの最後のバイトに加える
+        let last = codes.last_mut().unwrap();
+        if let Some(cond) = self.cond {
+            *last += cond.number();
+        }
+        if let Some(r) = self.opcode_register() {
+            *last += r.number() & 0b111;
+        }
 
-            // Increment
-            Opcode::INCRM64 { rm64 } => {
-                // Mだけど /0 なのでマスク
-                Some(ModRM::new_mr(
-                    rm64.addressing_mode(),
-                    rm64,
-                    &GeneralPurposeRegister::new_64bit_from_code(0),
-                ))
-            }
+        codes
+    }
 
-            // Load Effective Address
-            Opcode::LEAR64M { r64, m } => Some(ModRM::new_rm(AddressingMode::DISP8, r64, m)),
+    pub fn encoding(&self) -> Encoding {
+        self.form.encoding
+    }
 
-            // Move
-            Opcode::MOVRM8R8 { rm8, r8 } => {
-                // MR
-                Some(ModRM::new_mr(rm8.addressing_mode(), rm8, r8))
-            }
-            Opcode::MOVRM32R32 { rm32, r32 } => {
-                // MR
-                Some(ModRM::new_mr(rm32.addressing_mode(), rm32, r32))
-            }
-            Opcode::MOVR32RM32 { r32, rm32 } => {
-                // RM
-                Some(ModRM::new_rm(rm32.addressing_mode(), r32, rm32))
-            }
-            Opcode::MOVRM32IMM32 { rm32, imm: _ } => {
-                // MI( /0 マスクなのでそのままMIで )
-                Some(ModRM::new_mi(rm32.addressing_mode(), rm32))
-            }
+    /// the prefix placed before REX prefix(e.g. the operand-size override prefix `0x66`).
+    pub fn prefix(&self) -> Option<u8> {
+        match self.form.vex {
+            Some(_) => None,
+            None => self.form.prefix,
+        }
+    }
 
-            Opcode::MOVRM64R64 { rm64, r64 } => {
-                // MR
-                Some(ModRM::new_mr(rm64.addressing_mode(), rm64, r64))
-            }
-            Opcode::MOVR64RM64 { r64, rm64 } => {
-                // RM
-                Some(ModRM::new_rm(rm64.addressing_mode(), r64, rm64))
-            }
-            Opcode::MOVRM64IMM32 { rm64, imm: _ } => {
-                // MI( /0 マスクなのでそのままMIで )
-                Some(ModRM::new_mi(rm64.addressing_mode(), rm64))
-            }
+    pub fn rex_prefix(&self) -> Option<REXPrefix> {
+        if self.form.vex.is_some() {
+            return None;
+        }
 
-            // Neg
-            Opcode::NEGRM64 { rm64 } => {
-                // Mだけど /3 でマスクするのでmr
-                Some(ModRM::new_mr(
-                    rm64.addressing_mode(),
-                    rm64,
-                    &GeneralPurposeRegister::new_64bit_from_code(3),
-                ))
-            }
+        // opcode + rd のレジスタは REX.B で拡張する
+        let opcode_register = self.opcode_register().map(|r| Operand::GENERALREGISTER(*r));
+        let rm = self.rm_operand().or(opcode_register.as_ref());
 
-            // Pop
+        REXPrefix::from_operands(self.form.rex_w, self.reg_operand(), rm)
+    }
 
-            // Push
-            Opcode::PUSHRM64 { rm64 } => {
-                // Mだけど /6 でマスクするのでmr
-                Some(ModRM::new_mr(
-                    rm64.addressing_mode(),
-                    rm64,
-                    &GeneralPurposeRegister::new_64bit_from_code(6),
-                ))
-            }
+    /// VEX prefix は prefix と REX prefix の代わりに置く
+    pub fn vex_prefix(&self) -> Option<VEXPrefix> {
+        VEXPrefix::from_operands(
+            self.form,
+            self.reg_operand(),
+            self.vvvv_operand(),
+            self.rm_operand(),
+        )
+    }
 
-            // Sub
-            Opcode::SUBRM64IMM32 { rm64, imm: _ } => {
-                // MIだけど /5 でマスクするのでmr
-                Some(ModRM::new_mr(
-                    rm64.addressing_mode(),
-                    rm64,
-                    &GeneralPurposeRegister::new_64bit_from_code(5),
-                ))
-            }
-            Opcode::SUBR64RM64 { r64, rm64 } => {
-                // RM
-                Some(ModRM::new_rm(rm64.addressing_mode(), r64, rm64))
-            }
-            Opcode::SUBRM64R64 { rm64, r64 } => {
-                // MR
-                Some(ModRM::new_mr(rm64.addressing_mode(), rm64, r64))
-            }
+    pub fn modrm(&self) -> Option<ModRM> {
+        let rm = self.rm_operand()?;
+        let reg = match self.form.modrm? {
+            ModRMReg::R => self.reg_operand()?.number(),
+            // reg フィールドでオペコードを拡張する
+            ModRMReg::Digit(digit) => digit,
+        };
+
+        Some(ModRM::new_mr(rm.addressing_mode(), rm, reg))
+    }
 
+    /// the operand encoded in ModRM:r/m.
+    pub fn rm_operand(&self) -> Option<&Operand> {
+        let operands = self.explicit_operands();
+        match self.form.encoding {
+            Encoding::RM | Encoding::RMI => operands.get(1).copied(),
+            Encoding::RVM => operands.get(2).copied(),
+            Encoding::MR
+            | Encoding::MI
+            | Encoding::M
+            | Encoding::M1
+            | Encoding::MC
+            | Encoding::MRI
+            | Encoding::MRC => operands.first().copied(),
             _ => None,
         }
     }
 
-    /// get displacement
-    /// if memory operand hasn't found, it returns Option::None,
-    pub fn get_displacement(&self) -> Option<Displacement> {
-        match &self {
-            // Add
-            Opcode::ADDRM32R32 { rm32, r32: _ } => rm32.get_displacement(),
-            Opcode::ADDR32RM32 { r32: _, rm32 } => rm32.get_displacement(),
-            Opcode::ADDRM64R64 { rm64, r64: _ } => rm64.get_displacement(),
-            Opcode::ADDR64RM64 { r64: _, rm64 } => rm64.get_displacement(),
-
-            // Compare
-            Opcode::CMPRM64IMM32 { imm: _, rm64 } => rm64.get_displacement(),
-
-            // (signed) Integer Divide
-            Opcode::IDIVRM64 { rm64 } => rm64.get_displacement(),
-
-            // (signed) Integer Multiply
-            Opcode::IMULR64RM64 { r64: _, rm64 } => rm64.get_displacement(),
-
-            // Increment
-            Opcode::INCRM64 { rm64 } => rm64.get_displacement(),
-
-            // Lea
-            Opcode::LEAR64M { r64: _, m } => m.get_displacement(),
-
-            // Move
-            Opcode::MOVRM8R8 { rm8, r8: _ } => rm8.get_displacement(),
-            Opcode::MOVR32RM32 { rm32, r32: _ } => rm32.get_displacement(),
-            Opcode::MOVRM32R32 { rm32, r32: _ } => rm32.get_displacement(),
-            Opcode::MOVRM32IMM32 { rm32, imm: _ } => rm32.get_displacement(),
-            Opcode::MOVR64RM64 { rm64, r64: _ } => rm64.get_displacement(),
-            Opcode::MOVRM64R64 { rm64, r64: _ } => rm64.get_displacement(),
-            Opcode::MOVRM64IMM32 { rm64, imm: _ } => rm64.get_displacement(),
-
-            // Neg
-            Opcode::NEGRM64 { rm64 } => rm64.get_displacement(),
-
-            // Pop
-
-            // Push
-            Opcode::PUSHRM64 { rm64 } => rm64.get_displacement(),
-
-            // Sub
-            Opcode::SUBRM64R64 { rm64, r64: _ } => rm64.get_displacement(),
-            Opcode::SUBRM64IMM32 { rm64, imm: _ } => rm64.get_displacement(),
-            Opcode::SUBR64RM64 { r64: _, rm64 } => rm64.get_displacement(),
+    /// the register encoded in ModRM:reg.
+    fn reg_operand(&self) -> Option<&Operand> {
+        let operands = self.explicit_operands();
+        let reg = match self.form.encoding {
+            Encoding::RM | Encoding::RMI | Encoding::RVM => operands.first(),
+            Encoding::MR | Encoding::MRI | Encoding::MRC => operands.get(1),
+            _ => None,
+        };
+
+        match reg {
+            Some(
+                reg @ (Operand::GENERALREGISTER(_)
+                | Operand::XMMREGISTER(_)
+                | Operand::YMMREGISTER(_)),
+            ) => Some(*reg),
             _ => None,
         }
     }
 
-    pub fn get_immediate(&self) -> Option<Immediate> {
-        match &self {
-            // Compare Two Operands
-            Opcode::CMPRM64IMM32 { imm, rm64: _ } => Some(*imm),
-            Opcode::CMPRAXIMM32 { imm } => Some(*imm),
-
-            // Move
-            Opcode::MOVRM32IMM32 { rm32: _, imm } => Some(*imm),
-            Opcode::MOVRM64IMM32 { rm64: _, imm } => Some(*imm),
-
-            // Push
-            Opcode::PUSHIMM32 { imm } => Some(*imm),
-
-            // Sub
-            Opcode::SUBRM64IMM32 { rm64: _, imm } => Some(*imm),
+    /// the register encoded in VEX.vvvv.
+    fn vvvv_operand(&self) -> Option<&Operand> {
+        match self.form.encoding {
+            Encoding::RVM => self.operands.get(1),
             _ => None,
         }
     }
 
-    pub fn sib_bite(&self) -> Option<SIBByte> {
-        match &self {
-            // Add
-            Opcode::ADDRM32R32 { rm32, r32: _ } => rm32.sib_byte(),
-            Opcode::ADDR32RM32 { r32: _, rm32 } => rm32.sib_byte(),
-            Opcode::ADDRM64R64 { rm64, r64: _ } => rm64.sib_byte(),
-            Opcode::ADDR64RM64 { r64: _, rm64 } => rm64.sib_byte(),
-
-            // (signed) Integer Divide
-            Opcode::IDIVRM64 { rm64 } => rm64.sib_byte(),
-
-            // (signed) Integer Multiply
-            Opcode::IMULR64RM64 { r64: _, rm64 } => rm64.sib_byte(),
-
-            // Increment
-            Opcode::INCRM64 { rm64 } => rm64.sib_byte(),
-
-            // Move
-            Opcode::MOVRM8R8 { rm8, r8: _ } => rm8.sib_byte(),
-            Opcode::MOVRM32R32 { rm32, r32: _ } => rm32.sib_byte(),
-            Opcode::MOVRM64R64 { rm64, r64: _ } => rm64.sib_byte(),
+    /// the register encoded in the opcode(`opcode + rd`).
+    fn opcode_register(&self) -> Option<&GeneralPurposeRegister> {
+        match (self.form.encoding, self.operands.first()) {
+            (Encoding::O | Encoding::OI, Some(Operand::GENERALREGISTER(r))) => Some(r),
+            _ => None,
+        }
+    }
 
-            // Neg
-            Opcode::NEGRM64 { rm64 } => rm64.sib_byte(),
+    /// AL/AX/EAX/RAX, CL, 1 を除いたオペランド
+    fn explicit_operands(&self) -> Vec<&Operand> {
+        self.form
+            .operands
+            .iter()
+            .zip(self.operands.iter())
+            .filter(|(kind, _)| !kind.is_implicit())
+            .map(|(_, operand)| operand)
+            .collect()
+    }
 
-            // Pop
+    /// get displacement
+    /// if memory operand hasn't found, it returns Option::None,
+    pub fn get_displacement(&self) -> Option<Displacement> {
+        self.rm_operand()?.get_displacement()
+    }
 
-            // Push
-            Opcode::PUSHRM64 { rm64 } => rm64.sib_byte(),
+    pub fn get_immediate(&self) -> Option<Immediate> {
+        self.form
+            .operands
+            .iter()
+            .zip(self.operands.iter())
+            .find_map(|(kind, operand)| match (kind, operand) {
+                (
+                    OperandKind::Imm(OperandSize::BYTE) | OperandKind::SImm8,
+                    Operand::Immediate(imm),
+                ) => Some(imm.as_8bit()),
+                (OperandKind::Imm(OperandSize::WORD), Operand::Immediate(imm)) => {
+                    Some(imm.as_16bit())
+                }
+                (OperandKind::Imm(_), Operand::Immediate(imm)) => Some(imm.as_32bit()),
+                // レイアウト後に埋める
+                (OperandKind::Imm(_), Operand::SYMBOLIC { .. }) => Some(Immediate::I32(0)),
+                _ => None,
+            })
+    }
 
-            // Sub
-            Opcode::SUBRM64IMM32 { rm64, imm: _ } => rm64.sib_byte(),
-            Opcode::SUBRM64R64 { rm64, r64: _ } => rm64.sib_byte(),
-            Opcode::SUBR64RM64 { r64: _, rm64 } => rm64.sib_byte(),
+    /// the opcode of the rel8/rel32 form of a relative jump(e.g. `eb`/`e9` for `jmp`).
+    /// `call` のように rel8 の形式を持たなければ None
+    pub fn relative_opcode(&self, size: OperandSize) -> Option<Vec<u8>> {
+        let opcode = Self::with_form(
+            &self.mnemonic(),
+            &[OperandKind::Rel(size)],
+            self.operands.clone(),
+        )?;
+        Some(opcode.to_bytes())
+    }
 
-            _ => None,
-        }
+    pub fn sib_bite(&self) -> Option<SIBByte> {
+        self.rm_operand()?.sib_byte()
     }
 }
@@ -1,7 +0,0 @@
-use crate::assembler::resource::*;
-
-impl Opcode {
-    pub fn call(func: Operand) -> Self {
-        Opcode::CALLFUNC(func)
-    }
-}
@@ -0,0 +1,433 @@
+use crate::assembler::resource::*;
+
+/// an operand of an instruction form, in the notation of Intel SDM.
+#[derive(Eq, Ord, PartialOrd, PartialEq, Debug, Clone, Copy)]
+pub enum OperandKind {
+    /// r8/r16/r32/r64
+    Reg(OperandSize),
+    /// r/m8/r/m16/r/m32/r/m64
+    RegMem(OperandSize),
+    /// m( `lea` のように,サイズを持たないメモリオペランド )
+    Mem,
+    /// imm8/imm16/imm32
+    /// 64bit の形式の imm32 は符号拡張される
+    Imm(OperandSize),
+    /// imm8( オペランドサイズに符号拡張される, `83 /0 ib` 等 )
+    SImm8,
+    /// AL/AX/EAX/RAX
+    Acc(OperandSize),
+    /// CL( シフト回数 )
+    Cl,
+    /// 1( シフト回数 )
+    One,
+    /// rel8/rel32
+    Rel(OperandSize),
+    /// xmm
+    Xmm,
+    /// xmm/m32/m64/m128( メモリの場合のサイズ )
+    XmmMem(OperandSize),
+    /// ymm
+    Ymm,
+    /// ymm/m256
+    YmmMem(OperandSize),
+}
+
+impl OperandKind {
+    /// whether `operand` can be encoded as this kind.
+    pub fn matches(&self, operand: &Operand) -> bool {
+        match (self, operand) {
+            (Self::Reg(size) | Self::RegMem(size), Operand::GENERALREGISTER(_)) => {
+                operand.size() == *size
+            }
+            (
+                Self::RegMem(_) | Self::Mem,
+                Operand::ADDRESSING { .. } | Operand::ABSOLUTE { .. } | Operand::RIPRELATIVE { .. },
+            ) => true,
+            (Self::Imm(size), Operand::Immediate(imm)) => imm.fits_in(*size),
+            // シンボルを含む即値は imm32 として再配置する
+            (Self::Imm(OperandSize::DWORD), Operand::SYMBOLIC { .. }) => true,
+            (Self::SImm8, Operand::Immediate(imm)) => {
+                i8::MIN as i64 <= imm.value() && imm.value() <= i8::MAX as i64
+            }
+            (Self::Acc(size), Operand::GENERALREGISTER(r)) => {
+                r.number() == 0 && operand.size() == *size
+            }
+            (Self::Cl, Operand::GENERALREGISTER(r)) => *r == GeneralPurposeRegister::CL,
+            (Self::One, Operand::Immediate(imm)) => imm.value() == 1,
+            (Self::Rel(_), Operand::LABEL { .. }) => true,
+            (Self::Xmm, Operand::XMMREGISTER(_)) => true,
+            (
+                Self::XmmMem(_),
+                Operand::XMMREGISTER(_)
+                | Operand::ADDRESSING { .. }
+                | Operand::ABSOLUTE { .. }
+                | Operand::RIPRELATIVE { .. },
+            ) => true,
+            (Self::Ymm, Operand::YMMREGISTER(_)) => true,
+            (
+                Self::YmmMem(_),
+                Operand::YMMREGISTER(_)
+                | Operand::ADDRESSING { .. }
+                | Operand::ABSOLUTE { .. }
+                | Operand::RIPRELATIVE { .. },
+            ) => true,
+            _ => false,
+        }
+    }
+
+    /// オペコードやエンコーディングで決まるオペランド( ModRM 等には現れない )
+    pub fn is_implicit(&self) -> bool {
+        matches!(self, Self::Acc(_) | Self::Cl | Self::One)
+    }
+
+    /// シフト回数のように,即値か `%cl` しか取らない
+    pub fn is_count(&self) -> bool {
+        matches!(self, Self::Imm(_) | Self::SImm8 | Self::Cl | Self::One)
+    }
+}
+
+/// the field of ModRM:reg.
+#[derive(Eq, Ord, PartialOrd, PartialEq, Debug, Clone, Copy)]
+pub enum ModRMReg {
+    /// `/r`( レジスタオペランド )
+    R,
+    /// `/digit`( オペコードの拡張 )
+    Digit(u8),
+}
+
+/// the CPU feature an instruction requires.
+#[allow(dead_code, clippy::upper_case_acronyms)]
+#[derive(Eq, Ord, PartialOrd, PartialEq, Debug, Clone, Copy)]
+pub enum CpuFeature {
+    /// x86_64 の基本命令
+    X64,
+    /// Control-flow Enforcement Technology(Indirect Branch Tracking)
+    CET,
+    /// Streaming SIMD Extensions( 単精度浮動小数点数 )
+    SSE,
+    /// SSE2( 倍精度浮動小数点数,整数の SIMD )
+    SSE2,
+    /// Advanced Vector Extensions( VEX prefix, 256bit の浮動小数点数 )
+    AVX,
+    /// AVX2( 256bit の整数 )
+    AVX2,
+    /// Fused Multiply-Add( 3オペランド )
+    FMA,
+}
+
+/// VEX.L( the vector length of a VEX-encoded instruction ).
+#[derive(Eq, Ord, PartialOrd, PartialEq, Debug, Clone, Copy)]
+pub enum VectorLength {
+    /// `VEX.128`( スカラーの命令も含む )
+    L128,
+    /// `VEX.256`
+    L256,
+}
+
+/// one row of the instruction table.
+/// e.g. `REX.W + 01 /r` -> `ADD r/m64, r64`
+#[derive(Eq, Ord, PartialOrd, PartialEq, Debug)]
+pub struct InstructionForm {
+    /// Intel 構文での名前
+    /// 条件付きの命令は `set`, `cmov`, `j` のように条件を除いた名前
+    pub mnemonic: &'static str,
+    /// Intel SDM の順( dst, src )
+    pub operands: &'static [OperandKind],
+    pub encoding: Encoding,
+    /// REX prefix より前に置く prefix( `0x66` 等 )
+    pub prefix: Option<u8>,
+    pub rex_w: bool,
+    pub opcode: &'static [u8],
+    pub modrm: Option<ModRMReg>,
+    /// オペコードの最後のバイトに条件コードを加える( `0f 90+cc` )
+    pub cc: bool,
+    /// オペランドから決まらないオペランドサイズ( `push imm32` 等 )
+    pub operand_size: Option<OperandSize>,
+    pub feature: CpuFeature,
+    /// VEX prefix でエンコードする( `prefix`, `rex_w`, オペコードのエスケープは VEX prefix に含める )
+    pub vex: Option<VectorLength>,
+}
+
+/// a new row of the instruction table.
+pub const fn form(
+    mnemonic: &'static str,
+    operands: &'static [OperandKind],
+    encoding: Encoding,
+    opcode: &'static [u8],
+) -> InstructionForm {
+    InstructionForm {
+        mnemonic,
+        operands,
+        encoding,
+        prefix: None,
+        rex_w: false,
+        opcode,
+        modrm: None,
+        cc: false,
+        operand_size: None,
+        feature: CpuFeature::X64,
+        vex: None,
+    }
+}
+
+impl InstructionForm {
+    /// `/r`
+    pub const fn r(mut self) -> Self {
+        self.modrm = Some(ModRMReg::R);
+        self
+    }
+
+    /// `/digit`
+    pub const fn digit(mut self, digit: u8) -> Self {
+        self.modrm = Some(ModRMReg::Digit(digit));
+        self
+    }
+
+    /// `REX.W +`
+    pub const fn rex_w(mut self) -> Self {
+        self.rex_w = true;
+        self
+    }
+
+    pub const fn prefix(mut self, prefix: u8) -> Self {
+        self.prefix = Some(prefix);
+        self
+    }
+
+    /// `+cc`
+    pub const fn cc(mut self) -> Self {
+        self.cc = true;
+        self
+    }
+
+    pub const fn operand_size(mut self, size: OperandSize) -> Self {
+        self.operand_size = Some(size);
+        self
+    }
+
+    pub const fn feature(mut self, feature: CpuFeature) -> Self {
+        self.feature = feature;
+        self
+    }
+
+    /// `VEX.128`, `VEX.256`
+    pub const fn vex(mut self, length: VectorLength) -> Self {
+        self.vex = Some(length);
+        self
+    }
+
+    /// whether the instruction operates on XMM/YMM registers.
+    pub fn is_simd(&self) -> bool {
+        matches!(
+            self.feature,
+            CpuFeature::SSE
+                | CpuFeature::SSE2
+                | CpuFeature::AVX
+                | CpuFeature::AVX2
+                | CpuFeature::FMA
+        )
+    }
+
+    /// the opcode escape(`0f`, `0f 38`, `0f 3a`) and the rest of the opcode.
+    /// VEX prefix はエスケープを含む( VEX.mmmmm )
+    pub fn split_escape(&self) -> (&'static [u8], &'static [u8]) {
+        let length = match self.opcode {
+            [0x0f, 0x38 | 0x3a, _, ..] => 2,
+            [0x0f, _, ..] => 1,
+            _ => 0,
+        };
+        self.opcode.split_at(length)
+    }
+
+    /// the operand-size(the suffix in AT&T syntax, `PTR` in Intel syntax).
+    /// 最初のレジスタかメモリのオペランドのサイズ
+    pub fn size(&self) -> Option<OperandSize> {
+        self.operand_size.or_else(|| {
+            self.operands.iter().find_map(|kind| match kind {
+                OperandKind::Reg(size) | OperandKind::RegMem(size) | OperandKind::Acc(size) => {
+                    Some(*size)
+                }
+                _ => None,
+            })
+        })
+    }
+
+    /// whether the form takes a label( `jmp .L2` ).
+    pub fn takes_label(&self) -> bool {
+        self.operands
+            .iter()
+            .any(|kind| matches!(kind, OperandKind::Rel(_)))
+    }
+
+    /// whether the form takes a memory operand of `size` in place of an XMM/YMM register.
+    pub fn takes_vector_memory(&self, size: OperandSize) -> bool {
+        self.operands.iter().any(|kind| match kind {
+            OperandKind::XmmMem(s) | OperandKind::YmmMem(s) => *s == size,
+            _ => false,
+        })
+    }
+
+    /// whether the `i`-th operand follows the operand-size.
+    /// シフト回数のように,オペランドサイズに従わないものもある
+    pub fn follows_size(&self, i: usize) -> bool {
+        let size = match self.size() {
+            Some(size) => size,
+            None => return false,
+        };
+
+        match self.operands.get(i) {
+            Some(OperandKind::Reg(s) | OperandKind::RegMem(s) | OperandKind::Acc(s)) => *s == size,
+            // 64bit の形式の即値は imm32
+            Some(OperandKind::Imm(s)) => *s == size.min(OperandSize::DWORD),
+            Some(OperandKind::SImm8) => true,
+            _ => false,
+        }
+    }
+
+    /// whether `operands`(in the order of Intel SDM) can be encoded with this form.
+    pub fn matches(&self, operands: &[Operand]) -> bool {
+        self.operands.len() == operands.len()
+            && self
+                .operands
+                .iter()
+                .zip(operands.iter())
+                .all(|(kind, operand)| kind.matches(operand))
+    }
+
+    /// 省略されたオペランドを補う
+    /// `shl %rax` は `shl $1, %rax`, `shld %rbx, %rax` は `shld %cl, %rbx, %rax` と同じ
+    pub fn complete(&self, operands: &[Operand]) -> Option<Vec<Operand>> {
+        let mut operands = operands.to_vec();
+        if operands.len() + 1 == self.operands.len() {
+            operands.push(self.omitted_operand()?);
+        }
+
+        if operands.len() == self.operands.len() {
+            Some(operands)
+        } else {
+            None
+        }
+    }
+
+    fn omitted_operand(&self) -> Option<Operand> {
+        match self.operands.last()? {
+            OperandKind::One => Some(Operand::Immediate(Immediate::I8(1))),
+            OperandKind::Cl => Some(Operand::GENERALREGISTER(GeneralPurposeRegister::CL)),
+            _ => None,
+        }
+    }
+
+    /// the forms named `mnemonic` which take `operand_count` operands.
+    /// `mnemonic` may be a conditional one(e.g. `sete`) or an alias(e.g. `sal`, `cltq`).
+    pub fn candidates(
+        mnemonic: &str,
+        operand_count: usize,
+    ) -> Vec<(&'static Self, Option<ConditionCode>)> {
+        Self::named(mnemonic)
+            .filter(|(form, _)| {
+                form.operands.len() == operand_count
+                    || (form.operands.len() == operand_count + 1
+                        && form.omitted_operand().is_some())
+            })
+            .collect()
+    }
+
+    /// the form named `mnemonic` whose operands are exactly `kinds`.
+    pub fn find(
+        mnemonic: &str,
+        kinds: &[OperandKind],
+    ) -> Option<(&'static Self, Option<ConditionCode>)> {
+        Self::named(mnemonic).find(|(form, _)| form.operands == kinds)
+    }
+
+    fn named(mnemonic: &str) -> impl Iterator<Item = (&'static Self, Option<ConditionCode>)> + '_ {
+        let mnemonic = canonical_mnemonic(mnemonic);
+        INSTRUCTION_TABLE.iter().filter_map(move |form| {
+            if !form.cc {
+                return if form.mnemonic == mnemonic {
+                    Some((form, None))
+                } else {
+                    None
+                };
+            }
+
+            let cond = mnemonic
+                .strip_prefix(form.mnemonic)
+                .and_then(ConditionCode::from_suffix)?;
+            Some((form, Some(cond)))
+        })
+    }
+}
+
+/// 別名や AT&T 構文での名前を,命令表での名前にする
+fn canonical_mnemonic(mnemonic: &str) -> &str {
+    let synonym = SYNONYMS
+        .iter()
+        .find(|(alias, _)| *alias == mnemonic)
+        .map(|(_, name)| *name);
+    let att = ATT_MNEMONICS
+        .iter()
+        .find(|(_, att)| *att == mnemonic)
+        .map(|(intel, _)| *intel);
+
+    synonym.or(att).unwrap_or(mnemonic)
+}
+
+#[cfg(test)]
+mod form_tests {
+    use super::*;
+
+    #[test]
+    fn candidates_test() {
+        // `shl %rax` は `shl $1, %rax` と `shl %cl, %rax` の形式になりうる
+        let candidates = InstructionForm::candidates("sal", 1);
+        assert_eq!(8, candidates.len());
+        assert!(candidates.iter().all(|(form, _)| form.mnemonic == "shl"));
+
+        let candidates = InstructionForm::candidates("setnae", 1);
+        assert_eq!(1, candidates.len());
+        assert_eq!(Some(ConditionCode::B), candidates[0].1);
+
+        assert!(InstructionForm::candidates("jmpq", 1).is_empty());
+        assert_eq!(1, InstructionForm::candidates("cltq", 0).len());
+    }
+
+    #[test]
+    fn size_test() {
+        let (push, _) = InstructionForm::find("push", &[OperandKind::SImm8]).unwrap();
+        assert_eq!(Some(OperandSize::QWORD), push.size());
+
+        let (movzx, _) = InstructionForm::find(
+            "movzx",
+            &[
+                OperandKind::Reg(OperandSize::DWORD),
+                OperandKind::RegMem(OperandSize::BYTE),
+            ],
+        )
+        .unwrap();
+        assert_eq!(Some(OperandSize::DWORD), movzx.size());
+        assert!(movzx.follows_size(0));
+        assert!(!movzx.follows_size(1));
+
+        let (jmp, _) =
+            InstructionForm::find("jmp", &[OperandKind::Rel(OperandSize::BYTE)]).unwrap();
+        assert_eq!(None, jmp.size());
+    }
+
+    #[test]
+    fn complete_test() {
+        let (shl, _) = InstructionForm::find(
+            "shl",
+            &[OperandKind::RegMem(OperandSize::QWORD), OperandKind::Cl],
+        )
+        .unwrap();
+        let rax = Operand::GENERALREGISTER(GeneralPurposeRegister::RAX);
+        let cl = Operand::GENERALREGISTER(GeneralPurposeRegister::CL);
+
+        assert_eq!(
+            Some(vec![rax.clone(), cl]),
+            shl.complete(std::slice::from_ref(&rax))
+        );
+        assert_eq!(None, shl.complete(&[]));
+    }
+}
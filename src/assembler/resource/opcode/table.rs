@@ -0,0 +1,551 @@
+use crate::assembler::resource::*;
+use CpuFeature::*;
+use Encoding::*;
+use OperandKind::*;
+use OperandSize::*;
+use VectorLength::*;
+
+/// the instruction table.
+/// 各行は Intel SDM の命令の形式( `REX.W + 01 /r` -> `ADD r/m64, r64` )に対応する
+/// 同じ命令に複数の形式がある場合,先に書いた形式が使われる( `addq $1, %rax` は `83 /0 ib` )
+#[rustfmt::skip]
+pub static INSTRUCTION_TABLE: &[InstructionForm] = &[
+    // Group-1 ALU instructions(add, or, adc, sbb, and, sub, xor, cmp)
+    form("add",     &[RegMem(BYTE), Reg(BYTE)],       MR,  &[0x00]).r(),
+    form("add",     &[RegMem(WORD), Reg(WORD)],       MR,  &[0x01]).r().prefix(0x66),
+    form("add",     &[RegMem(DWORD), Reg(DWORD)],     MR,  &[0x01]).r(),
+    form("add",     &[RegMem(QWORD), Reg(QWORD)],     MR,  &[0x01]).r().rex_w(),
+    form("add",     &[Reg(BYTE), RegMem(BYTE)],       RM,  &[0x02]).r(),
+    form("add",     &[Reg(WORD), RegMem(WORD)],       RM,  &[0x03]).r().prefix(0x66),
+    form("add",     &[Reg(DWORD), RegMem(DWORD)],     RM,  &[0x03]).r(),
+    form("add",     &[Reg(QWORD), RegMem(QWORD)],     RM,  &[0x03]).r().rex_w(),
+    form("add",     &[RegMem(WORD), SImm8],           MI,  &[0x83]).digit(0).prefix(0x66),
+    form("add",     &[RegMem(DWORD), SImm8],          MI,  &[0x83]).digit(0),
+    form("add",     &[RegMem(QWORD), SImm8],          MI,  &[0x83]).digit(0).rex_w(),
+    form("add",     &[Acc(BYTE), Imm(BYTE)],          I,   &[0x04]),
+    form("add",     &[Acc(WORD), Imm(WORD)],          I,   &[0x05]).prefix(0x66),
+    form("add",     &[Acc(DWORD), Imm(DWORD)],        I,   &[0x05]),
+    form("add",     &[Acc(QWORD), Imm(DWORD)],        I,   &[0x05]).rex_w(),
+    form("add",     &[RegMem(BYTE), Imm(BYTE)],       MI,  &[0x80]).digit(0),
+    form("add",     &[RegMem(WORD), Imm(WORD)],       MI,  &[0x81]).digit(0).prefix(0x66),
+    form("add",     &[RegMem(DWORD), Imm(DWORD)],     MI,  &[0x81]).digit(0),
+    form("add",     &[RegMem(QWORD), Imm(DWORD)],     MI,  &[0x81]).digit(0).rex_w(),
+
+    form("or",      &[RegMem(BYTE), Reg(BYTE)],       MR,  &[0x08]).r(),
+    form("or",      &[RegMem(WORD), Reg(WORD)],       MR,  &[0x09]).r().prefix(0x66),
+    form("or",      &[RegMem(DWORD), Reg(DWORD)],     MR,  &[0x09]).r(),
+    form("or",      &[RegMem(QWORD), Reg(QWORD)],     MR,  &[0x09]).r().rex_w(),
+    form("or",      &[Reg(BYTE), RegMem(BYTE)],       RM,  &[0x0a]).r(),
+    form("or",      &[Reg(WORD), RegMem(WORD)],       RM,  &[0x0b]).r().prefix(0x66),
+    form("or",      &[Reg(DWORD), RegMem(DWORD)],     RM,  &[0x0b]).r(),
+    form("or",      &[Reg(QWORD), RegMem(QWORD)],     RM,  &[0x0b]).r().rex_w(),
+    form("or",      &[RegMem(WORD), SImm8],           MI,  &[0x83]).digit(1).prefix(0x66),
+    form("or",      &[RegMem(DWORD), SImm8],          MI,  &[0x83]).digit(1),
+    form("or",      &[RegMem(QWORD), SImm8],          MI,  &[0x83]).digit(1).rex_w(),
+    form("or",      &[Acc(BYTE), Imm(BYTE)],          I,   &[0x0c]),
+    form("or",      &[Acc(WORD), Imm(WORD)],          I,   &[0x0d]).prefix(0x66),
+    form("or",      &[Acc(DWORD), Imm(DWORD)],        I,   &[0x0d]),
+    form("or",      &[Acc(QWORD), Imm(DWORD)],        I,   &[0x0d]).rex_w(),
+    form("or",      &[RegMem(BYTE), Imm(BYTE)],       MI,  &[0x80]).digit(1),
+    form("or",      &[RegMem(WORD), Imm(WORD)],       MI,  &[0x81]).digit(1).prefix(0x66),
+    form("or",      &[RegMem(DWORD), Imm(DWORD)],     MI,  &[0x81]).digit(1),
+    form("or",      &[RegMem(QWORD), Imm(DWORD)],     MI,  &[0x81]).digit(1).rex_w(),
+
+    form("adc",     &[RegMem(BYTE), Reg(BYTE)],       MR,  &[0x10]).r(),
+    form("adc",     &[RegMem(WORD), Reg(WORD)],       MR,  &[0x11]).r().prefix(0x66),
+    form("adc",     &[RegMem(DWORD), Reg(DWORD)],     MR,  &[0x11]).r(),
+    form("adc",     &[RegMem(QWORD), Reg(QWORD)],     MR,  &[0x11]).r().rex_w(),
+    form("adc",     &[Reg(BYTE), RegMem(BYTE)],       RM,  &[0x12]).r(),
+    form("adc",     &[Reg(WORD), RegMem(WORD)],       RM,  &[0x13]).r().prefix(0x66),
+    form("adc",     &[Reg(DWORD), RegMem(DWORD)],     RM,  &[0x13]).r(),
+    form("adc",     &[Reg(QWORD), RegMem(QWORD)],     RM,  &[0x13]).r().rex_w(),
+    form("adc",     &[RegMem(WORD), SImm8],           MI,  &[0x83]).digit(2).prefix(0x66),
+    form("adc",     &[RegMem(DWORD), SImm8],          MI,  &[0x83]).digit(2),
+    form("adc",     &[RegMem(QWORD), SImm8],          MI,  &[0x83]).digit(2).rex_w(),
+    form("adc",     &[Acc(BYTE), Imm(BYTE)],          I,   &[0x14]),
+    form("adc",     &[Acc(WORD), Imm(WORD)],          I,   &[0x15]).prefix(0x66),
+    form("adc",     &[Acc(DWORD), Imm(DWORD)],        I,   &[0x15]),
+    form("adc",     &[Acc(QWORD), Imm(DWORD)],        I,   &[0x15]).rex_w(),
+    form("adc",     &[RegMem(BYTE), Imm(BYTE)],       MI,  &[0x80]).digit(2),
+    form("adc",     &[RegMem(WORD), Imm(WORD)],       MI,  &[0x81]).digit(2).prefix(0x66),
+    form("adc",     &[RegMem(DWORD), Imm(DWORD)],     MI,  &[0x81]).digit(2),
+    form("adc",     &[RegMem(QWORD), Imm(DWORD)],     MI,  &[0x81]).digit(2).rex_w(),
+
+    form("sbb",     &[RegMem(BYTE), Reg(BYTE)],       MR,  &[0x18]).r(),
+    form("sbb",     &[RegMem(WORD), Reg(WORD)],       MR,  &[0x19]).r().prefix(0x66),
+    form("sbb",     &[RegMem(DWORD), Reg(DWORD)],     MR,  &[0x19]).r(),
+    form("sbb",     &[RegMem(QWORD), Reg(QWORD)],     MR,  &[0x19]).r().rex_w(),
+    form("sbb",     &[Reg(BYTE), RegMem(BYTE)],       RM,  &[0x1a]).r(),
+    form("sbb",     &[Reg(WORD), RegMem(WORD)],       RM,  &[0x1b]).r().prefix(0x66),
+    form("sbb",     &[Reg(DWORD), RegMem(DWORD)],     RM,  &[0x1b]).r(),
+    form("sbb",     &[Reg(QWORD), RegMem(QWORD)],     RM,  &[0x1b]).r().rex_w(),
+    form("sbb",     &[RegMem(WORD), SImm8],           MI,  &[0x83]).digit(3).prefix(0x66),
+    form("sbb",     &[RegMem(DWORD), SImm8],          MI,  &[0x83]).digit(3),
+    form("sbb",     &[RegMem(QWORD), SImm8],          MI,  &[0x83]).digit(3).rex_w(),
+    form("sbb",     &[Acc(BYTE), Imm(BYTE)],          I,   &[0x1c]),
+    form("sbb",     &[Acc(WORD), Imm(WORD)],          I,   &[0x1d]).prefix(0x66),
+    form("sbb",     &[Acc(DWORD), Imm(DWORD)],        I,   &[0x1d]),
+    form("sbb",     &[Acc(QWORD), Imm(DWORD)],        I,   &[0x1d]).rex_w(),
+    form("sbb",     &[RegMem(BYTE), Imm(BYTE)],       MI,  &[0x80]).digit(3),
+    form("sbb",     &[RegMem(WORD), Imm(WORD)],       MI,  &[0x81]).digit(3).prefix(0x66),
+    form("sbb",     &[RegMem(DWORD), Imm(DWORD)],     MI,  &[0x81]).digit(3),
+    form("sbb",     &[RegMem(QWORD), Imm(DWORD)],     MI,  &[0x81]).digit(3).rex_w(),
+
+    form("and",     &[RegMem(BYTE), Reg(BYTE)],       MR,  &[0x20]).r(),
+    form("and",     &[RegMem(WORD), Reg(WORD)],       MR,  &[0x21]).r().prefix(0x66),
+    form("and",     &[RegMem(DWORD), Reg(DWORD)],     MR,  &[0x21]).r(),
+    form("and",     &[RegMem(QWORD), Reg(QWORD)],     MR,  &[0x21]).r().rex_w(),
+    form("and",     &[Reg(BYTE), RegMem(BYTE)],       RM,  &[0x22]).r(),
+    form("and",     &[Reg(WORD), RegMem(WORD)],       RM,  &[0x23]).r().prefix(0x66),
+    form("and",     &[Reg(DWORD), RegMem(DWORD)],     RM,  &[0x23]).r(),
+    form("and",     &[Reg(QWORD), RegMem(QWORD)],     RM,  &[0x23]).r().rex_w(),
+    form("and",     &[RegMem(WORD), SImm8],           MI,  &[0x83]).digit(4).prefix(0x66),
+    form("and",     &[RegMem(DWORD), SImm8],          MI,  &[0x83]).digit(4),
+    form("and",     &[RegMem(QWORD), SImm8],          MI,  &[0x83]).digit(4).rex_w(),
+    form("and",     &[Acc(BYTE), Imm(BYTE)],          I,   &[0x24]),
+    form("and",     &[Acc(WORD), Imm(WORD)],          I,   &[0x25]).prefix(0x66),
+    form("and",     &[Acc(DWORD), Imm(DWORD)],        I,   &[0x25]),
+    form("and",     &[Acc(QWORD), Imm(DWORD)],        I,   &[0x25]).rex_w(),
+    form("and",     &[RegMem(BYTE), Imm(BYTE)],       MI,  &[0x80]).digit(4),
+    form("and",     &[RegMem(WORD), Imm(WORD)],       MI,  &[0x81]).digit(4).prefix(0x66),
+    form("and",     &[RegMem(DWORD), Imm(DWORD)],     MI,  &[0x81]).digit(4),
+    form("and",     &[RegMem(QWORD), Imm(DWORD)],     MI,  &[0x81]).digit(4).rex_w(),
+
+    form("sub",     &[RegMem(BYTE), Reg(BYTE)],       MR,  &[0x28]).r(),
+    form("sub",     &[RegMem(WORD), Reg(WORD)],       MR,  &[0x29]).r().prefix(0x66),
+    form("sub",     &[RegMem(DWORD), Reg(DWORD)],     MR,  &[0x29]).r(),
+    form("sub",     &[RegMem(QWORD), Reg(QWORD)],     MR,  &[0x29]).r().rex_w(),
+    form("sub",     &[Reg(BYTE), RegMem(BYTE)],       RM,  &[0x2a]).r(),
+    form("sub",     &[Reg(WORD), RegMem(WORD)],       RM,  &[0x2b]).r().prefix(0x66),
+    form("sub",     &[Reg(DWORD), RegMem(DWORD)],     RM,  &[0x2b]).r(),
+    form("sub",     &[Reg(QWORD), RegMem(QWORD)],     RM,  &[0x2b]).r().rex_w(),
+    form("sub",     &[RegMem(WORD), SImm8],           MI,  &[0x83]).digit(5).prefix(0x66),
+    form("sub",     &[RegMem(DWORD), SImm8],          MI,  &[0x83]).digit(5),
+    form("sub",     &[RegMem(QWORD), SImm8],          MI,  &[0x83]).digit(5).rex_w(),
+    form("sub",     &[Acc(BYTE), Imm(BYTE)],          I,   &[0x2c]),
+    form("sub",     &[Acc(WORD), Imm(WORD)],          I,   &[0x2d]).prefix(0x66),
+    form("sub",     &[Acc(DWORD), Imm(DWORD)],        I,   &[0x2d]),
+    form("sub",     &[Acc(QWORD), Imm(DWORD)],        I,   &[0x2d]).rex_w(),
+    form("sub",     &[RegMem(BYTE), Imm(BYTE)],       MI,  &[0x80]).digit(5),
+    form("sub",     &[RegMem(WORD), Imm(WORD)],       MI,  &[0x81]).digit(5).prefix(0x66),
+    form("sub",     &[RegMem(DWORD), Imm(DWORD)],     MI,  &[0x81]).digit(5),
+    form("sub",     &[RegMem(QWORD), Imm(DWORD)],     MI,  &[0x81]).digit(5).rex_w(),
+
+    form("xor",     &[RegMem(BYTE), Reg(BYTE)],       MR,  &[0x30]).r(),
+    form("xor",     &[RegMem(WORD), Reg(WORD)],       MR,  &[0x31]).r().prefix(0x66),
+    form("xor",     &[RegMem(DWORD), Reg(DWORD)],     MR,  &[0x31]).r(),
+    form("xor",     &[RegMem(QWORD), Reg(QWORD)],     MR,  &[0x31]).r().rex_w(),
+    form("xor",     &[Reg(BYTE), RegMem(BYTE)],       RM,  &[0x32]).r(),
+    form("xor",     &[Reg(WORD), RegMem(WORD)],       RM,  &[0x33]).r().prefix(0x66),
+    form("xor",     &[Reg(DWORD), RegMem(DWORD)],     RM,  &[0x33]).r(),
+    form("xor",     &[Reg(QWORD), RegMem(QWORD)],     RM,  &[0x33]).r().rex_w(),
+    form("xor",     &[RegMem(WORD), SImm8],           MI,  &[0x83]).digit(6).prefix(0x66),
+    form("xor",     &[RegMem(DWORD), SImm8],          MI,  &[0x83]).digit(6),
+    form("xor",     &[RegMem(QWORD), SImm8],          MI,  &[0x83]).digit(6).rex_w(),
+    form("xor",     &[Acc(BYTE), Imm(BYTE)],          I,   &[0x34]),
+    form("xor",     &[Acc(WORD), Imm(WORD)],          I,   &[0x35]).prefix(0x66),
+    form("xor",     &[Acc(DWORD), Imm(DWORD)],        I,   &[0x35]),
+    form("xor",     &[Acc(QWORD), Imm(DWORD)],        I,   &[0x35]).rex_w(),
+    form("xor",     &[RegMem(BYTE), Imm(BYTE)],       MI,  &[0x80]).digit(6),
+    form("xor",     &[RegMem(WORD), Imm(WORD)],       MI,  &[0x81]).digit(6).prefix(0x66),
+    form("xor",     &[RegMem(DWORD), Imm(DWORD)],     MI,  &[0x81]).digit(6),
+    form("xor",     &[RegMem(QWORD), Imm(DWORD)],     MI,  &[0x81]).digit(6).rex_w(),
+
+    form("cmp",     &[RegMem(BYTE), Reg(BYTE)],       MR,  &[0x38]).r(),
+    form("cmp",     &[RegMem(WORD), Reg(WORD)],       MR,  &[0x39]).r().prefix(0x66),
+    form("cmp",     &[RegMem(DWORD), Reg(DWORD)],     MR,  &[0x39]).r(),
+    form("cmp",     &[RegMem(QWORD), Reg(QWORD)],     MR,  &[0x39]).r().rex_w(),
+    form("cmp",     &[Reg(BYTE), RegMem(BYTE)],       RM,  &[0x3a]).r(),
+    form("cmp",     &[Reg(WORD), RegMem(WORD)],       RM,  &[0x3b]).r().prefix(0x66),
+    form("cmp",     &[Reg(DWORD), RegMem(DWORD)],     RM,  &[0x3b]).r(),
+    form("cmp",     &[Reg(QWORD), RegMem(QWORD)],     RM,  &[0x3b]).r().rex_w(),
+    form("cmp",     &[RegMem(WORD), SImm8],           MI,  &[0x83]).digit(7).prefix(0x66),
+    form("cmp",     &[RegMem(DWORD), SImm8],          MI,  &[0x83]).digit(7),
+    form("cmp",     &[RegMem(QWORD), SImm8],          MI,  &[0x83]).digit(7).rex_w(),
+    form("cmp",     &[Acc(BYTE), Imm(BYTE)],          I,   &[0x3c]),
+    form("cmp",     &[Acc(WORD), Imm(WORD)],          I,   &[0x3d]).prefix(0x66),
+    form("cmp",     &[Acc(DWORD), Imm(DWORD)],        I,   &[0x3d]),
+    form("cmp",     &[Acc(QWORD), Imm(DWORD)],        I,   &[0x3d]).rex_w(),
+    form("cmp",     &[RegMem(BYTE), Imm(BYTE)],       MI,  &[0x80]).digit(7),
+    form("cmp",     &[RegMem(WORD), Imm(WORD)],       MI,  &[0x81]).digit(7).prefix(0x66),
+    form("cmp",     &[RegMem(DWORD), Imm(DWORD)],     MI,  &[0x81]).digit(7),
+    form("cmp",     &[RegMem(QWORD), Imm(DWORD)],     MI,  &[0x81]).digit(7).rex_w(),
+
+    // Logical Compare
+    form("test",    &[RegMem(BYTE), Reg(BYTE)],       MR,  &[0x84]).r(),
+    form("test",    &[RegMem(WORD), Reg(WORD)],       MR,  &[0x85]).r().prefix(0x66),
+    form("test",    &[RegMem(DWORD), Reg(DWORD)],     MR,  &[0x85]).r(),
+    form("test",    &[RegMem(QWORD), Reg(QWORD)],     MR,  &[0x85]).r().rex_w(),
+    // `test r, r/m` は `test r/m, r` の別名
+    form("test",    &[Reg(BYTE), RegMem(BYTE)],       RM,  &[0x84]).r(),
+    form("test",    &[Reg(WORD), RegMem(WORD)],       RM,  &[0x85]).r().prefix(0x66),
+    form("test",    &[Reg(DWORD), RegMem(DWORD)],     RM,  &[0x85]).r(),
+    form("test",    &[Reg(QWORD), RegMem(QWORD)],     RM,  &[0x85]).r().rex_w(),
+    form("test",    &[Acc(BYTE), Imm(BYTE)],          I,   &[0xa8]),
+    form("test",    &[Acc(WORD), Imm(WORD)],          I,   &[0xa9]).prefix(0x66),
+    form("test",    &[Acc(DWORD), Imm(DWORD)],        I,   &[0xa9]),
+    form("test",    &[Acc(QWORD), Imm(DWORD)],        I,   &[0xa9]).rex_w(),
+    form("test",    &[RegMem(BYTE), Imm(BYTE)],       MI,  &[0xf6]).digit(0),
+    form("test",    &[RegMem(WORD), Imm(WORD)],       MI,  &[0xf7]).digit(0).prefix(0x66),
+    form("test",    &[RegMem(DWORD), Imm(DWORD)],     MI,  &[0xf7]).digit(0),
+    form("test",    &[RegMem(QWORD), Imm(DWORD)],     MI,  &[0xf7]).digit(0).rex_w(),
+
+    // One's Complement Negation
+    form("not",     &[RegMem(BYTE)],                  M,   &[0xf6]).digit(2),
+    form("not",     &[RegMem(WORD)],                  M,   &[0xf7]).digit(2).prefix(0x66),
+    form("not",     &[RegMem(DWORD)],                 M,   &[0xf7]).digit(2),
+    form("not",     &[RegMem(QWORD)],                 M,   &[0xf7]).digit(2).rex_w(),
+
+    // Two's Complement Negation
+    form("neg",     &[RegMem(BYTE)],                  M,   &[0xf6]).digit(3),
+    form("neg",     &[RegMem(WORD)],                  M,   &[0xf7]).digit(3).prefix(0x66),
+    form("neg",     &[RegMem(DWORD)],                 M,   &[0xf7]).digit(3),
+    form("neg",     &[RegMem(QWORD)],                 M,   &[0xf7]).digit(3).rex_w(),
+
+    // Increment
+    form("inc",     &[RegMem(BYTE)],                  M,   &[0xfe]).digit(0),
+    form("inc",     &[RegMem(WORD)],                  M,   &[0xff]).digit(0).prefix(0x66),
+    form("inc",     &[RegMem(DWORD)],                 M,   &[0xff]).digit(0),
+    form("inc",     &[RegMem(QWORD)],                 M,   &[0xff]).digit(0).rex_w(),
+
+    // (signed) Integer Divide
+    form("idiv",    &[RegMem(BYTE)],                  M,   &[0xf6]).digit(7),
+    form("idiv",    &[RegMem(WORD)],                  M,   &[0xf7]).digit(7).prefix(0x66),
+    form("idiv",    &[RegMem(DWORD)],                 M,   &[0xf7]).digit(7),
+    form("idiv",    &[RegMem(QWORD)],                 M,   &[0xf7]).digit(7).rex_w(),
+
+    // (signed) Integer Multiply
+    form("imul",    &[RegMem(BYTE)],                  M,   &[0xf6]).digit(5),
+    form("imul",    &[RegMem(WORD)],                  M,   &[0xf7]).digit(5).prefix(0x66),
+    form("imul",    &[RegMem(DWORD)],                 M,   &[0xf7]).digit(5),
+    form("imul",    &[RegMem(QWORD)],                 M,   &[0xf7]).digit(5).rex_w(),
+    form("imul",    &[Reg(WORD), RegMem(WORD)],       RM,  &[0x0f, 0xaf]).r().prefix(0x66),
+    form("imul",    &[Reg(DWORD), RegMem(DWORD)],     RM,  &[0x0f, 0xaf]).r(),
+    form("imul",    &[Reg(QWORD), RegMem(QWORD)],     RM,  &[0x0f, 0xaf]).r().rex_w(),
+
+    // Move
+    form("mov",     &[RegMem(BYTE), Reg(BYTE)],       MR,  &[0x88]).r(),
+    form("mov",     &[RegMem(WORD), Reg(WORD)],       MR,  &[0x89]).r().prefix(0x66),
+    form("mov",     &[RegMem(DWORD), Reg(DWORD)],     MR,  &[0x89]).r(),
+    form("mov",     &[RegMem(QWORD), Reg(QWORD)],     MR,  &[0x89]).r().rex_w(),
+    form("mov",     &[Reg(BYTE), RegMem(BYTE)],       RM,  &[0x8a]).r(),
+    form("mov",     &[Reg(WORD), RegMem(WORD)],       RM,  &[0x8b]).r().prefix(0x66),
+    form("mov",     &[Reg(DWORD), RegMem(DWORD)],     RM,  &[0x8b]).r(),
+    form("mov",     &[Reg(QWORD), RegMem(QWORD)],     RM,  &[0x8b]).r().rex_w(),
+    form("mov",     &[RegMem(BYTE), Imm(BYTE)],       MI,  &[0xc6]).digit(0),
+    form("mov",     &[RegMem(WORD), Imm(WORD)],       MI,  &[0xc7]).digit(0).prefix(0x66),
+    form("mov",     &[RegMem(DWORD), Imm(DWORD)],     MI,  &[0xc7]).digit(0),
+    form("mov",     &[RegMem(QWORD), Imm(DWORD)],     MI,  &[0xc7]).digit(0).rex_w(),
+
+    // Move with Sign/Zero-Extension
+    form("movzx",   &[Reg(WORD), RegMem(BYTE)],       RM,  &[0x0f, 0xb6]).r().prefix(0x66),
+    form("movzx",   &[Reg(DWORD), RegMem(BYTE)],      RM,  &[0x0f, 0xb6]).r(),
+    form("movzx",   &[Reg(QWORD), RegMem(BYTE)],      RM,  &[0x0f, 0xb6]).r().rex_w(),
+    form("movzx",   &[Reg(DWORD), RegMem(WORD)],      RM,  &[0x0f, 0xb7]).r(),
+    form("movzx",   &[Reg(QWORD), RegMem(WORD)],      RM,  &[0x0f, 0xb7]).r().rex_w(),
+    form("movsx",   &[Reg(WORD), RegMem(BYTE)],       RM,  &[0x0f, 0xbe]).r().prefix(0x66),
+    form("movsx",   &[Reg(DWORD), RegMem(BYTE)],      RM,  &[0x0f, 0xbe]).r(),
+    form("movsx",   &[Reg(QWORD), RegMem(BYTE)],      RM,  &[0x0f, 0xbe]).r().rex_w(),
+    form("movsx",   &[Reg(DWORD), RegMem(WORD)],      RM,  &[0x0f, 0xbf]).r(),
+    form("movsx",   &[Reg(QWORD), RegMem(WORD)],      RM,  &[0x0f, 0xbf]).r().rex_w(),
+    form("movsxd",  &[Reg(QWORD), RegMem(DWORD)],     RM,  &[0x63]).r().rex_w(),
+
+    // Conditional Move/Set Byte on Condition
+    form("cmov",    &[Reg(WORD), RegMem(WORD)],       RM,  &[0x0f, 0x40]).r().cc().prefix(0x66),
+    form("cmov",    &[Reg(DWORD), RegMem(DWORD)],     RM,  &[0x0f, 0x40]).r().cc(),
+    form("cmov",    &[Reg(QWORD), RegMem(QWORD)],     RM,  &[0x0f, 0x40]).r().cc().rex_w(),
+    form("set",     &[RegMem(BYTE)],                  M,   &[0x0f, 0x90]).digit(0).cc(),
+
+    // Load Effective Address
+    form("lea",     &[Reg(QWORD), Mem],               RM,  &[0x8d]).r().rex_w(),
+
+    // Push/Pop(64bit のオペランドサイズは REX.W を必要としない)
+    form("push",    &[Reg(QWORD)],                    O,   &[0x50]),
+    form("push",    &[RegMem(QWORD)],                 M,   &[0xff]).digit(6),
+    form("push",    &[SImm8],                         I,   &[0x6a]).operand_size(QWORD),
+    form("push",    &[Imm(DWORD)],                    I,   &[0x68]).operand_size(QWORD),
+    form("pop",     &[Reg(QWORD)],                    O,   &[0x58]),
+    form("pop",     &[RegMem(QWORD)],                 M,   &[0x8f]).digit(0),
+
+    // Shift/Rotate
+    form("rol",     &[RegMem(BYTE), One],             M1,  &[0xd0]).digit(0),
+    form("rol",     &[RegMem(WORD), One],             M1,  &[0xd1]).digit(0).prefix(0x66),
+    form("rol",     &[RegMem(DWORD), One],            M1,  &[0xd1]).digit(0),
+    form("rol",     &[RegMem(QWORD), One],            M1,  &[0xd1]).digit(0).rex_w(),
+    form("rol",     &[RegMem(BYTE), Imm(BYTE)],       MI,  &[0xc0]).digit(0),
+    form("rol",     &[RegMem(WORD), Imm(BYTE)],       MI,  &[0xc1]).digit(0).prefix(0x66),
+    form("rol",     &[RegMem(DWORD), Imm(BYTE)],      MI,  &[0xc1]).digit(0),
+    form("rol",     &[RegMem(QWORD), Imm(BYTE)],      MI,  &[0xc1]).digit(0).rex_w(),
+    form("rol",     &[RegMem(BYTE), Cl],              MC,  &[0xd2]).digit(0),
+    form("rol",     &[RegMem(WORD), Cl],              MC,  &[0xd3]).digit(0).prefix(0x66),
+    form("rol",     &[RegMem(DWORD), Cl],             MC,  &[0xd3]).digit(0),
+    form("rol",     &[RegMem(QWORD), Cl],             MC,  &[0xd3]).digit(0).rex_w(),
+
+    form("ror",     &[RegMem(BYTE), One],             M1,  &[0xd0]).digit(1),
+    form("ror",     &[RegMem(WORD), One],             M1,  &[0xd1]).digit(1).prefix(0x66),
+    form("ror",     &[RegMem(DWORD), One],            M1,  &[0xd1]).digit(1),
+    form("ror",     &[RegMem(QWORD), One],            M1,  &[0xd1]).digit(1).rex_w(),
+    form("ror",     &[RegMem(BYTE), Imm(BYTE)],       MI,  &[0xc0]).digit(1),
+    form("ror",     &[RegMem(WORD), Imm(BYTE)],       MI,  &[0xc1]).digit(1).prefix(0x66),
+    form("ror",     &[RegMem(DWORD), Imm(BYTE)],      MI,  &[0xc1]).digit(1),
+    form("ror",     &[RegMem(QWORD), Imm(BYTE)],      MI,  &[0xc1]).digit(1).rex_w(),
+    form("ror",     &[RegMem(BYTE), Cl],              MC,  &[0xd2]).digit(1),
+    form("ror",     &[RegMem(WORD), Cl],              MC,  &[0xd3]).digit(1).prefix(0x66),
+    form("ror",     &[RegMem(DWORD), Cl],             MC,  &[0xd3]).digit(1),
+    form("ror",     &[RegMem(QWORD), Cl],             MC,  &[0xd3]).digit(1).rex_w(),
+
+    form("rcl",     &[RegMem(BYTE), One],             M1,  &[0xd0]).digit(2),
+    form("rcl",     &[RegMem(WORD), One],             M1,  &[0xd1]).digit(2).prefix(0x66),
+    form("rcl",     &[RegMem(DWORD), One],            M1,  &[0xd1]).digit(2),
+    form("rcl",     &[RegMem(QWORD), One],            M1,  &[0xd1]).digit(2).rex_w(),
+    form("rcl",     &[RegMem(BYTE), Imm(BYTE)],       MI,  &[0xc0]).digit(2),
+    form("rcl",     &[RegMem(WORD), Imm(BYTE)],       MI,  &[0xc1]).digit(2).prefix(0x66),
+    form("rcl",     &[RegMem(DWORD), Imm(BYTE)],      MI,  &[0xc1]).digit(2),
+    form("rcl",     &[RegMem(QWORD), Imm(BYTE)],      MI,  &[0xc1]).digit(2).rex_w(),
+    form("rcl",     &[RegMem(BYTE), Cl],              MC,  &[0xd2]).digit(2),
+    form("rcl",     &[RegMem(WORD), Cl],              MC,  &[0xd3]).digit(2).prefix(0x66),
+    form("rcl",     &[RegMem(DWORD), Cl],             MC,  &[0xd3]).digit(2),
+    form("rcl",     &[RegMem(QWORD), Cl],             MC,  &[0xd3]).digit(2).rex_w(),
+
+    form("rcr",     &[RegMem(BYTE), One],             M1,  &[0xd0]).digit(3),
+    form("rcr",     &[RegMem(WORD), One],             M1,  &[0xd1]).digit(3).prefix(0x66),
+    form("rcr",     &[RegMem(DWORD), One],            M1,  &[0xd1]).digit(3),
+    form("rcr",     &[RegMem(QWORD), One],            M1,  &[0xd1]).digit(3).rex_w(),
+    form("rcr",     &[RegMem(BYTE), Imm(BYTE)],       MI,  &[0xc0]).digit(3),
+    form("rcr",     &[RegMem(WORD), Imm(BYTE)],       MI,  &[0xc1]).digit(3).prefix(0x66),
+    form("rcr",     &[RegMem(DWORD), Imm(BYTE)],      MI,  &[0xc1]).digit(3),
+    form("rcr",     &[RegMem(QWORD), Imm(BYTE)],      MI,  &[0xc1]).digit(3).rex_w(),
+    form("rcr",     &[RegMem(BYTE), Cl],              MC,  &[0xd2]).digit(3),
+    form("rcr",     &[RegMem(WORD), Cl],              MC,  &[0xd3]).digit(3).prefix(0x66),
+    form("rcr",     &[RegMem(DWORD), Cl],             MC,  &[0xd3]).digit(3),
+    form("rcr",     &[RegMem(QWORD), Cl],             MC,  &[0xd3]).digit(3).rex_w(),
+
+    form("shl",     &[RegMem(BYTE), One],             M1,  &[0xd0]).digit(4),
+    form("shl",     &[RegMem(WORD), One],             M1,  &[0xd1]).digit(4).prefix(0x66),
+    form("shl",     &[RegMem(DWORD), One],            M1,  &[0xd1]).digit(4),
+    form("shl",     &[RegMem(QWORD), One],            M1,  &[0xd1]).digit(4).rex_w(),
+    form("shl",     &[RegMem(BYTE), Imm(BYTE)],       MI,  &[0xc0]).digit(4),
+    form("shl",     &[RegMem(WORD), Imm(BYTE)],       MI,  &[0xc1]).digit(4).prefix(0x66),
+    form("shl",     &[RegMem(DWORD), Imm(BYTE)],      MI,  &[0xc1]).digit(4),
+    form("shl",     &[RegMem(QWORD), Imm(BYTE)],      MI,  &[0xc1]).digit(4).rex_w(),
+    form("shl",     &[RegMem(BYTE), Cl],              MC,  &[0xd2]).digit(4),
+    form("shl",     &[RegMem(WORD), Cl],              MC,  &[0xd3]).digit(4).prefix(0x66),
+    form("shl",     &[RegMem(DWORD), Cl],             MC,  &[0xd3]).digit(4),
+    form("shl",     &[RegMem(QWORD), Cl],             MC,  &[0xd3]).digit(4).rex_w(),
+
+    form("shr",     &[RegMem(BYTE), One],             M1,  &[0xd0]).digit(5),
+    form("shr",     &[RegMem(WORD), One],             M1,  &[0xd1]).digit(5).prefix(0x66),
+    form("shr",     &[RegMem(DWORD), One],            M1,  &[0xd1]).digit(5),
+    form("shr",     &[RegMem(QWORD), One],            M1,  &[0xd1]).digit(5).rex_w(),
+    form("shr",     &[RegMem(BYTE), Imm(BYTE)],       MI,  &[0xc0]).digit(5),
+    form("shr",     &[RegMem(WORD), Imm(BYTE)],       MI,  &[0xc1]).digit(5).prefix(0x66),
+    form("shr",     &[RegMem(DWORD), Imm(BYTE)],      MI,  &[0xc1]).digit(5),
+    form("shr",     &[RegMem(QWORD), Imm(BYTE)],      MI,  &[0xc1]).digit(5).rex_w(),
+    form("shr",     &[RegMem(BYTE), Cl],              MC,  &[0xd2]).digit(5),
+    form("shr",     &[RegMem(WORD), Cl],              MC,  &[0xd3]).digit(5).prefix(0x66),
+    form("shr",     &[RegMem(DWORD), Cl],             MC,  &[0xd3]).digit(5),
+    form("shr",     &[RegMem(QWORD), Cl],             MC,  &[0xd3]).digit(5).rex_w(),
+
+    form("sar",     &[RegMem(BYTE), One],             M1,  &[0xd0]).digit(7),
+    form("sar",     &[RegMem(WORD), One],             M1,  &[0xd1]).digit(7).prefix(0x66),
+    form("sar",     &[RegMem(DWORD), One],            M1,  &[0xd1]).digit(7),
+    form("sar",     &[RegMem(QWORD), One],            M1,  &[0xd1]).digit(7).rex_w(),
+    form("sar",     &[RegMem(BYTE), Imm(BYTE)],       MI,  &[0xc0]).digit(7),
+    form("sar",     &[RegMem(WORD), Imm(BYTE)],       MI,  &[0xc1]).digit(7).prefix(0x66),
+    form("sar",     &[RegMem(DWORD), Imm(BYTE)],      MI,  &[0xc1]).digit(7),
+    form("sar",     &[RegMem(QWORD), Imm(BYTE)],      MI,  &[0xc1]).digit(7).rex_w(),
+    form("sar",     &[RegMem(BYTE), Cl],              MC,  &[0xd2]).digit(7),
+    form("sar",     &[RegMem(WORD), Cl],              MC,  &[0xd3]).digit(7).prefix(0x66),
+    form("sar",     &[RegMem(DWORD), Cl],             MC,  &[0xd3]).digit(7),
+    form("sar",     &[RegMem(QWORD), Cl],             MC,  &[0xd3]).digit(7).rex_w(),
+
+    // Double Precision Shift
+    form("shld",    &[RegMem(WORD), Reg(WORD), Imm(BYTE)], MRI, &[0x0f, 0xa4]).r().prefix(0x66),
+    form("shld",    &[RegMem(DWORD), Reg(DWORD), Imm(BYTE)], MRI, &[0x0f, 0xa4]).r(),
+    form("shld",    &[RegMem(QWORD), Reg(QWORD), Imm(BYTE)], MRI, &[0x0f, 0xa4]).r().rex_w(),
+    form("shld",    &[RegMem(WORD), Reg(WORD), Cl],   MRC, &[0x0f, 0xa5]).r().prefix(0x66),
+    form("shld",    &[RegMem(DWORD), Reg(DWORD), Cl], MRC, &[0x0f, 0xa5]).r(),
+    form("shld",    &[RegMem(QWORD), Reg(QWORD), Cl], MRC, &[0x0f, 0xa5]).r().rex_w(),
+
+    form("shrd",    &[RegMem(WORD), Reg(WORD), Imm(BYTE)], MRI, &[0x0f, 0xac]).r().prefix(0x66),
+    form("shrd",    &[RegMem(DWORD), Reg(DWORD), Imm(BYTE)], MRI, &[0x0f, 0xac]).r(),
+    form("shrd",    &[RegMem(QWORD), Reg(QWORD), Imm(BYTE)], MRI, &[0x0f, 0xac]).r().rex_w(),
+    form("shrd",    &[RegMem(WORD), Reg(WORD), Cl],   MRC, &[0x0f, 0xad]).r().prefix(0x66),
+    form("shrd",    &[RegMem(DWORD), Reg(DWORD), Cl], MRC, &[0x0f, 0xad]).r(),
+    form("shrd",    &[RegMem(QWORD), Reg(QWORD), Cl], MRC, &[0x0f, 0xad]).r().rex_w(),
+
+    // Convert Byte to Word/Word to Doubleword/Doubleword to Quadword
+    form("cbw",     &[],                              ZO,  &[0x98]).prefix(0x66),
+    form("cwde",    &[],                              ZO,  &[0x98]),
+    form("cdqe",    &[],                              ZO,  &[0x98]).rex_w(),
+    form("cwd",     &[],                              ZO,  &[0x99]).prefix(0x66),
+    form("cdq",     &[],                              ZO,  &[0x99]),
+    form("cqo",     &[],                              ZO,  &[0x99]).rex_w(),
+
+    // Jump/Call
+    form("jmp",     &[Rel(BYTE)],                     D,   &[0xeb]),
+    form("jmp",     &[Rel(DWORD)],                    D,   &[0xe9]),
+    form("jmp",     &[RegMem(QWORD)],                 M,   &[0xff]).digit(4),
+    form("j",       &[Rel(BYTE)],                     D,   &[0x70]).cc(),
+    form("j",       &[Rel(DWORD)],                    D,   &[0x0f, 0x80]).cc(),
+    form("call",    &[Rel(DWORD)],                    D,   &[0xe8]),
+    form("call",    &[RegMem(QWORD)],                 M,   &[0xff]).digit(2),
+    form("ret",     &[],                              ZO,  &[0xc3]),
+
+    // System
+    form("syscall", &[],                              ZO,  &[0x0f, 0x05]),
+    form("endbr64", &[],                              ZO,  &[0x0f, 0x1e, 0xfa]).prefix(0xf3).feature(CET),
+
+    // SSE( 単精度浮動小数点数 )
+    form("movss",   &[Xmm, XmmMem(DWORD)],            RM,  &[0x0f, 0x10]).r().prefix(0xf3).feature(SSE),
+    form("movss",   &[XmmMem(DWORD), Xmm],            MR,  &[0x0f, 0x11]).r().prefix(0xf3).feature(SSE),
+    form("movaps",  &[Xmm, XmmMem(XMMWORD)],          RM,  &[0x0f, 0x28]).r().feature(SSE),
+    form("movaps",  &[XmmMem(XMMWORD), Xmm],          MR,  &[0x0f, 0x29]).r().feature(SSE),
+    form("addss",   &[Xmm, XmmMem(DWORD)],            RM,  &[0x0f, 0x58]).r().prefix(0xf3).feature(SSE),
+    form("subss",   &[Xmm, XmmMem(DWORD)],            RM,  &[0x0f, 0x5c]).r().prefix(0xf3).feature(SSE),
+    form("mulss",   &[Xmm, XmmMem(DWORD)],            RM,  &[0x0f, 0x59]).r().prefix(0xf3).feature(SSE),
+    form("divss",   &[Xmm, XmmMem(DWORD)],            RM,  &[0x0f, 0x5e]).r().prefix(0xf3).feature(SSE),
+    form("sqrtss",  &[Xmm, XmmMem(DWORD)],            RM,  &[0x0f, 0x51]).r().prefix(0xf3).feature(SSE),
+    form("cvtsi2ss", &[Xmm, RegMem(DWORD)],            RM,  &[0x0f, 0x2a]).r().prefix(0xf3).feature(SSE),
+    form("cvtsi2ss", &[Xmm, RegMem(QWORD)],            RM,  &[0x0f, 0x2a]).r().prefix(0xf3).rex_w().feature(SSE),
+    form("cvttss2si", &[Reg(DWORD), XmmMem(DWORD)],     RM,  &[0x0f, 0x2c]).r().prefix(0xf3).feature(SSE),
+    form("cvttss2si", &[Reg(QWORD), XmmMem(DWORD)],     RM,  &[0x0f, 0x2c]).r().prefix(0xf3).rex_w().feature(SSE),
+    form("ucomiss", &[Xmm, XmmMem(DWORD)],            RM,  &[0x0f, 0x2e]).r().feature(SSE),
+    form("comiss",  &[Xmm, XmmMem(DWORD)],            RM,  &[0x0f, 0x2f]).r().feature(SSE),
+    form("xorps",   &[Xmm, XmmMem(XMMWORD)],          RM,  &[0x0f, 0x57]).r().feature(SSE),
+
+    // SSE2( 倍精度浮動小数点数 )
+    form("movsd",   &[Xmm, XmmMem(QWORD)],            RM,  &[0x0f, 0x10]).r().prefix(0xf2).feature(SSE2),
+    form("movsd",   &[XmmMem(QWORD), Xmm],            MR,  &[0x0f, 0x11]).r().prefix(0xf2).feature(SSE2),
+    form("movapd",  &[Xmm, XmmMem(XMMWORD)],          RM,  &[0x0f, 0x28]).r().prefix(0x66).feature(SSE2),
+    form("movapd",  &[XmmMem(XMMWORD), Xmm],          MR,  &[0x0f, 0x29]).r().prefix(0x66).feature(SSE2),
+    form("addsd",   &[Xmm, XmmMem(QWORD)],            RM,  &[0x0f, 0x58]).r().prefix(0xf2).feature(SSE2),
+    form("subsd",   &[Xmm, XmmMem(QWORD)],            RM,  &[0x0f, 0x5c]).r().prefix(0xf2).feature(SSE2),
+    form("mulsd",   &[Xmm, XmmMem(QWORD)],            RM,  &[0x0f, 0x59]).r().prefix(0xf2).feature(SSE2),
+    form("divsd",   &[Xmm, XmmMem(QWORD)],            RM,  &[0x0f, 0x5e]).r().prefix(0xf2).feature(SSE2),
+    form("sqrtsd",  &[Xmm, XmmMem(QWORD)],            RM,  &[0x0f, 0x51]).r().prefix(0xf2).feature(SSE2),
+    form("cvtsi2sd", &[Xmm, RegMem(DWORD)],            RM,  &[0x0f, 0x2a]).r().prefix(0xf2).feature(SSE2),
+    form("cvtsi2sd", &[Xmm, RegMem(QWORD)],            RM,  &[0x0f, 0x2a]).r().prefix(0xf2).rex_w().feature(SSE2),
+    form("cvttsd2si", &[Reg(DWORD), XmmMem(QWORD)],     RM,  &[0x0f, 0x2c]).r().prefix(0xf2).feature(SSE2),
+    form("cvttsd2si", &[Reg(QWORD), XmmMem(QWORD)],     RM,  &[0x0f, 0x2c]).r().prefix(0xf2).rex_w().feature(SSE2),
+    form("cvtss2sd", &[Xmm, XmmMem(DWORD)],            RM,  &[0x0f, 0x5a]).r().prefix(0xf3).feature(SSE2),
+    form("cvtsd2ss", &[Xmm, XmmMem(QWORD)],            RM,  &[0x0f, 0x5a]).r().prefix(0xf2).feature(SSE2),
+    form("ucomisd", &[Xmm, XmmMem(QWORD)],            RM,  &[0x0f, 0x2e]).r().prefix(0x66).feature(SSE2),
+    form("comisd",  &[Xmm, XmmMem(QWORD)],            RM,  &[0x0f, 0x2f]).r().prefix(0x66).feature(SSE2),
+    form("xorpd",   &[Xmm, XmmMem(XMMWORD)],          RM,  &[0x0f, 0x57]).r().prefix(0x66).feature(SSE2),
+    form("pxor",    &[Xmm, XmmMem(XMMWORD)],          RM,  &[0x0f, 0xef]).r().prefix(0x66).feature(SSE2),
+    // 汎用レジスタとの転送
+    form("movd",    &[Xmm, RegMem(DWORD)],            RM,  &[0x0f, 0x6e]).r().prefix(0x66).feature(SSE2),
+    form("movd",    &[RegMem(DWORD), Xmm],            MR,  &[0x0f, 0x7e]).r().prefix(0x66).feature(SSE2),
+    form("movq",    &[Xmm, XmmMem(QWORD)],            RM,  &[0x0f, 0x7e]).r().prefix(0xf3).operand_size(QWORD).feature(SSE2),
+    form("movq",    &[XmmMem(QWORD), Xmm],            MR,  &[0x0f, 0xd6]).r().prefix(0x66).operand_size(QWORD).feature(SSE2),
+    form("movq",    &[Xmm, RegMem(QWORD)],            RM,  &[0x0f, 0x6e]).r().prefix(0x66).rex_w().feature(SSE2),
+    form("movq",    &[RegMem(QWORD), Xmm],            MR,  &[0x0f, 0x7e]).r().prefix(0x66).rex_w().feature(SSE2),
+
+    // AVX( VEX prefix でエンコードする )
+    form("vmovups", &[Xmm, XmmMem(XMMWORD)],          RM,  &[0x0f, 0x10]).r().vex(L128).feature(AVX),
+    form("vmovups", &[XmmMem(XMMWORD), Xmm],          MR,  &[0x0f, 0x11]).r().vex(L128).feature(AVX),
+    form("vmovups", &[Ymm, YmmMem(YMMWORD)],          RM,  &[0x0f, 0x10]).r().vex(L256).feature(AVX),
+    form("vmovups", &[YmmMem(YMMWORD), Ymm],          MR,  &[0x0f, 0x11]).r().vex(L256).feature(AVX),
+    form("vmovaps", &[Xmm, XmmMem(XMMWORD)],          RM,  &[0x0f, 0x28]).r().vex(L128).feature(AVX),
+    form("vmovaps", &[XmmMem(XMMWORD), Xmm],          MR,  &[0x0f, 0x29]).r().vex(L128).feature(AVX),
+    form("vmovaps", &[Ymm, YmmMem(YMMWORD)],          RM,  &[0x0f, 0x28]).r().vex(L256).feature(AVX),
+    form("vmovaps", &[YmmMem(YMMWORD), Ymm],          MR,  &[0x0f, 0x29]).r().vex(L256).feature(AVX),
+    form("vmovdqu", &[Xmm, XmmMem(XMMWORD)],          RM,  &[0x0f, 0x6f]).r().prefix(0xf3).vex(L128).feature(AVX),
+    form("vmovdqu", &[XmmMem(XMMWORD), Xmm],          MR,  &[0x0f, 0x7f]).r().prefix(0xf3).vex(L128).feature(AVX),
+    form("vmovdqu", &[Ymm, YmmMem(YMMWORD)],          RM,  &[0x0f, 0x6f]).r().prefix(0xf3).vex(L256).feature(AVX),
+    form("vmovdqu", &[YmmMem(YMMWORD), Ymm],          MR,  &[0x0f, 0x7f]).r().prefix(0xf3).vex(L256).feature(AVX),
+    form("vmovdqa", &[Xmm, XmmMem(XMMWORD)],          RM,  &[0x0f, 0x6f]).r().prefix(0x66).vex(L128).feature(AVX),
+    form("vmovdqa", &[XmmMem(XMMWORD), Xmm],          MR,  &[0x0f, 0x7f]).r().prefix(0x66).vex(L128).feature(AVX),
+    form("vmovdqa", &[Ymm, YmmMem(YMMWORD)],          RM,  &[0x0f, 0x6f]).r().prefix(0x66).vex(L256).feature(AVX),
+    form("vmovdqa", &[YmmMem(YMMWORD), Ymm],          MR,  &[0x0f, 0x7f]).r().prefix(0x66).vex(L256).feature(AVX),
+    form("vaddps",  &[Xmm, Xmm, XmmMem(XMMWORD)],     RVM, &[0x0f, 0x58]).r().vex(L128).feature(AVX),
+    form("vaddps",  &[Ymm, Ymm, YmmMem(YMMWORD)],     RVM, &[0x0f, 0x58]).r().vex(L256).feature(AVX),
+    form("vaddpd",  &[Xmm, Xmm, XmmMem(XMMWORD)],     RVM, &[0x0f, 0x58]).r().prefix(0x66).vex(L128).feature(AVX),
+    form("vaddpd",  &[Ymm, Ymm, YmmMem(YMMWORD)],     RVM, &[0x0f, 0x58]).r().prefix(0x66).vex(L256).feature(AVX),
+    form("vaddss",  &[Xmm, Xmm, XmmMem(DWORD)],       RVM, &[0x0f, 0x58]).r().prefix(0xf3).vex(L128).feature(AVX),
+    form("vaddsd",  &[Xmm, Xmm, XmmMem(QWORD)],       RVM, &[0x0f, 0x58]).r().prefix(0xf2).vex(L128).feature(AVX),
+    form("vsubps",  &[Xmm, Xmm, XmmMem(XMMWORD)],     RVM, &[0x0f, 0x5c]).r().vex(L128).feature(AVX),
+    form("vsubps",  &[Ymm, Ymm, YmmMem(YMMWORD)],     RVM, &[0x0f, 0x5c]).r().vex(L256).feature(AVX),
+    form("vsubpd",  &[Xmm, Xmm, XmmMem(XMMWORD)],     RVM, &[0x0f, 0x5c]).r().prefix(0x66).vex(L128).feature(AVX),
+    form("vsubpd",  &[Ymm, Ymm, YmmMem(YMMWORD)],     RVM, &[0x0f, 0x5c]).r().prefix(0x66).vex(L256).feature(AVX),
+    form("vsubss",  &[Xmm, Xmm, XmmMem(DWORD)],       RVM, &[0x0f, 0x5c]).r().prefix(0xf3).vex(L128).feature(AVX),
+    form("vsubsd",  &[Xmm, Xmm, XmmMem(QWORD)],       RVM, &[0x0f, 0x5c]).r().prefix(0xf2).vex(L128).feature(AVX),
+    form("vmulps",  &[Xmm, Xmm, XmmMem(XMMWORD)],     RVM, &[0x0f, 0x59]).r().vex(L128).feature(AVX),
+    form("vmulps",  &[Ymm, Ymm, YmmMem(YMMWORD)],     RVM, &[0x0f, 0x59]).r().vex(L256).feature(AVX),
+    form("vmulpd",  &[Xmm, Xmm, XmmMem(XMMWORD)],     RVM, &[0x0f, 0x59]).r().prefix(0x66).vex(L128).feature(AVX),
+    form("vmulpd",  &[Ymm, Ymm, YmmMem(YMMWORD)],     RVM, &[0x0f, 0x59]).r().prefix(0x66).vex(L256).feature(AVX),
+    form("vmulss",  &[Xmm, Xmm, XmmMem(DWORD)],       RVM, &[0x0f, 0x59]).r().prefix(0xf3).vex(L128).feature(AVX),
+    form("vmulsd",  &[Xmm, Xmm, XmmMem(QWORD)],       RVM, &[0x0f, 0x59]).r().prefix(0xf2).vex(L128).feature(AVX),
+    form("vdivps",  &[Xmm, Xmm, XmmMem(XMMWORD)],     RVM, &[0x0f, 0x5e]).r().vex(L128).feature(AVX),
+    form("vdivps",  &[Ymm, Ymm, YmmMem(YMMWORD)],     RVM, &[0x0f, 0x5e]).r().vex(L256).feature(AVX),
+    form("vdivpd",  &[Xmm, Xmm, XmmMem(XMMWORD)],     RVM, &[0x0f, 0x5e]).r().prefix(0x66).vex(L128).feature(AVX),
+    form("vdivpd",  &[Ymm, Ymm, YmmMem(YMMWORD)],     RVM, &[0x0f, 0x5e]).r().prefix(0x66).vex(L256).feature(AVX),
+    form("vdivss",  &[Xmm, Xmm, XmmMem(DWORD)],       RVM, &[0x0f, 0x5e]).r().prefix(0xf3).vex(L128).feature(AVX),
+    form("vdivsd",  &[Xmm, Xmm, XmmMem(QWORD)],       RVM, &[0x0f, 0x5e]).r().prefix(0xf2).vex(L128).feature(AVX),
+    form("vxorps",  &[Xmm, Xmm, XmmMem(XMMWORD)],     RVM, &[0x0f, 0x57]).r().vex(L128).feature(AVX),
+    form("vxorps",  &[Ymm, Ymm, YmmMem(YMMWORD)],     RVM, &[0x0f, 0x57]).r().vex(L256).feature(AVX),
+    form("vxorpd",  &[Xmm, Xmm, XmmMem(XMMWORD)],     RVM, &[0x0f, 0x57]).r().prefix(0x66).vex(L128).feature(AVX),
+    form("vxorpd",  &[Ymm, Ymm, YmmMem(YMMWORD)],     RVM, &[0x0f, 0x57]).r().prefix(0x66).vex(L256).feature(AVX),
+    form("vbroadcastss", &[Xmm, XmmMem(DWORD)],            RM,  &[0x0f, 0x38, 0x18]).r().prefix(0x66).vex(L128).feature(AVX),
+    form("vbroadcastss", &[Ymm, XmmMem(DWORD)],            RM,  &[0x0f, 0x38, 0x18]).r().prefix(0x66).vex(L256).feature(AVX),
+    form("vzeroupper", &[],                              ZO,  &[0x0f, 0x77]).vex(L128).feature(AVX),
+    form("vzeroall", &[],                              ZO,  &[0x0f, 0x77]).vex(L256).feature(AVX),
+
+    // AVX2( 128bit の形式は AVX )
+    form("vpaddd",  &[Xmm, Xmm, XmmMem(XMMWORD)],     RVM, &[0x0f, 0xfe]).r().prefix(0x66).vex(L128).feature(AVX),
+    form("vpaddd",  &[Ymm, Ymm, YmmMem(YMMWORD)],     RVM, &[0x0f, 0xfe]).r().prefix(0x66).vex(L256).feature(AVX2),
+    form("vpaddq",  &[Xmm, Xmm, XmmMem(XMMWORD)],     RVM, &[0x0f, 0xd4]).r().prefix(0x66).vex(L128).feature(AVX),
+    form("vpaddq",  &[Ymm, Ymm, YmmMem(YMMWORD)],     RVM, &[0x0f, 0xd4]).r().prefix(0x66).vex(L256).feature(AVX2),
+    form("vpsubd",  &[Xmm, Xmm, XmmMem(XMMWORD)],     RVM, &[0x0f, 0xfa]).r().prefix(0x66).vex(L128).feature(AVX),
+    form("vpsubd",  &[Ymm, Ymm, YmmMem(YMMWORD)],     RVM, &[0x0f, 0xfa]).r().prefix(0x66).vex(L256).feature(AVX2),
+    form("vpsubq",  &[Xmm, Xmm, XmmMem(XMMWORD)],     RVM, &[0x0f, 0xfb]).r().prefix(0x66).vex(L128).feature(AVX),
+    form("vpsubq",  &[Ymm, Ymm, YmmMem(YMMWORD)],     RVM, &[0x0f, 0xfb]).r().prefix(0x66).vex(L256).feature(AVX2),
+    form("vpmulld", &[Xmm, Xmm, XmmMem(XMMWORD)],     RVM, &[0x0f, 0x38, 0x40]).r().prefix(0x66).vex(L128).feature(AVX),
+    form("vpmulld", &[Ymm, Ymm, YmmMem(YMMWORD)],     RVM, &[0x0f, 0x38, 0x40]).r().prefix(0x66).vex(L256).feature(AVX2),
+    form("vpand",   &[Xmm, Xmm, XmmMem(XMMWORD)],     RVM, &[0x0f, 0xdb]).r().prefix(0x66).vex(L128).feature(AVX),
+    form("vpand",   &[Ymm, Ymm, YmmMem(YMMWORD)],     RVM, &[0x0f, 0xdb]).r().prefix(0x66).vex(L256).feature(AVX2),
+    form("vpor",    &[Xmm, Xmm, XmmMem(XMMWORD)],     RVM, &[0x0f, 0xeb]).r().prefix(0x66).vex(L128).feature(AVX),
+    form("vpor",    &[Ymm, Ymm, YmmMem(YMMWORD)],     RVM, &[0x0f, 0xeb]).r().prefix(0x66).vex(L256).feature(AVX2),
+    form("vpxor",   &[Xmm, Xmm, XmmMem(XMMWORD)],     RVM, &[0x0f, 0xef]).r().prefix(0x66).vex(L128).feature(AVX),
+    form("vpxor",   &[Ymm, Ymm, YmmMem(YMMWORD)],     RVM, &[0x0f, 0xef]).r().prefix(0x66).vex(L256).feature(AVX2),
+    form("vpbroadcastd", &[Xmm, XmmMem(DWORD)],            RM,  &[0x0f, 0x38, 0x58]).r().prefix(0x66).vex(L128).feature(AVX2),
+    form("vpbroadcastd", &[Ymm, XmmMem(DWORD)],            RM,  &[0x0f, 0x38, 0x58]).r().prefix(0x66).vex(L256).feature(AVX2),
+    form("vpbroadcastq", &[Xmm, XmmMem(QWORD)],            RM,  &[0x0f, 0x38, 0x59]).r().prefix(0x66).vex(L128).feature(AVX2),
+    form("vpbroadcastq", &[Ymm, XmmMem(QWORD)],            RM,  &[0x0f, 0x38, 0x59]).r().prefix(0x66).vex(L256).feature(AVX2),
+    form("vpermd",  &[Ymm, Ymm, YmmMem(YMMWORD)],     RVM, &[0x0f, 0x38, 0x36]).r().prefix(0x66).vex(L256).feature(AVX2),
+    form("vpermq",  &[Ymm, YmmMem(YMMWORD), Imm(BYTE)], RMI, &[0x0f, 0x3a, 0x00]).r().prefix(0x66).rex_w().vex(L256).feature(AVX2),
+
+    // FMA( 132: dst * src2 + src1, 213: src1 * dst + src2, 231: src1 * src2 + dst )
+    form("vfmadd132ps", &[Xmm, Xmm, XmmMem(XMMWORD)],     RVM, &[0x0f, 0x38, 0x98]).r().prefix(0x66).vex(L128).feature(FMA),
+    form("vfmadd132ps", &[Ymm, Ymm, YmmMem(YMMWORD)],     RVM, &[0x0f, 0x38, 0x98]).r().prefix(0x66).vex(L256).feature(FMA),
+    form("vfmadd132pd", &[Xmm, Xmm, XmmMem(XMMWORD)],     RVM, &[0x0f, 0x38, 0x98]).r().prefix(0x66).rex_w().vex(L128).feature(FMA),
+    form("vfmadd132pd", &[Ymm, Ymm, YmmMem(YMMWORD)],     RVM, &[0x0f, 0x38, 0x98]).r().prefix(0x66).rex_w().vex(L256).feature(FMA),
+    form("vfmadd132ss", &[Xmm, Xmm, XmmMem(DWORD)],       RVM, &[0x0f, 0x38, 0x99]).r().prefix(0x66).vex(L128).feature(FMA),
+    form("vfmadd132sd", &[Xmm, Xmm, XmmMem(QWORD)],       RVM, &[0x0f, 0x38, 0x99]).r().prefix(0x66).rex_w().vex(L128).feature(FMA),
+    form("vfmadd213ps", &[Xmm, Xmm, XmmMem(XMMWORD)],     RVM, &[0x0f, 0x38, 0xa8]).r().prefix(0x66).vex(L128).feature(FMA),
+    form("vfmadd213ps", &[Ymm, Ymm, YmmMem(YMMWORD)],     RVM, &[0x0f, 0x38, 0xa8]).r().prefix(0x66).vex(L256).feature(FMA),
+    form("vfmadd213pd", &[Xmm, Xmm, XmmMem(XMMWORD)],     RVM, &[0x0f, 0x38, 0xa8]).r().prefix(0x66).rex_w().vex(L128).feature(FMA),
+    form("vfmadd213pd", &[Ymm, Ymm, YmmMem(YMMWORD)],     RVM, &[0x0f, 0x38, 0xa8]).r().prefix(0x66).rex_w().vex(L256).feature(FMA),
+    form("vfmadd213ss", &[Xmm, Xmm, XmmMem(DWORD)],       RVM, &[0x0f, 0x38, 0xa9]).r().prefix(0x66).vex(L128).feature(FMA),
+    form("vfmadd213sd", &[Xmm, Xmm, XmmMem(QWORD)],       RVM, &[0x0f, 0x38, 0xa9]).r().prefix(0x66).rex_w().vex(L128).feature(FMA),
+    form("vfmadd231ps", &[Xmm, Xmm, XmmMem(XMMWORD)],     RVM, &[0x0f, 0x38, 0xb8]).r().prefix(0x66).vex(L128).feature(FMA),
+    form("vfmadd231ps", &[Ymm, Ymm, YmmMem(YMMWORD)],     RVM, &[0x0f, 0x38, 0xb8]).r().prefix(0x66).vex(L256).feature(FMA),
+    form("vfmadd231pd", &[Xmm, Xmm, XmmMem(XMMWORD)],     RVM, &[0x0f, 0x38, 0xb8]).r().prefix(0x66).rex_w().vex(L128).feature(FMA),
+    form("vfmadd231pd", &[Ymm, Ymm, YmmMem(YMMWORD)],     RVM, &[0x0f, 0x38, 0xb8]).r().prefix(0x66).rex_w().vex(L256).feature(FMA),
+    form("vfmadd231ss", &[Xmm, Xmm, XmmMem(DWORD)],       RVM, &[0x0f, 0x38, 0xb9]).r().prefix(0x66).vex(L128).feature(FMA),
+    form("vfmadd231sd", &[Xmm, Xmm, XmmMem(QWORD)],       RVM, &[0x0f, 0x38, 0xb9]).r().prefix(0x66).rex_w().vex(L128).feature(FMA),
+];
+
+/// AT&T 構文での名前が異なる命令( Intel 構文での名前, AT&T 構文での名前 )
+pub static ATT_MNEMONICS: &[(&str, &str)] = &[
+    ("cbw", "cbtw"),
+    ("cwde", "cwtl"),
+    ("cdqe", "cltq"),
+    ("cwd", "cwtd"),
+    ("cdq", "cltd"),
+    ("cqo", "cqto"),
+];
+
+/// 別名( 別名, 命令表での名前 )
+pub static SYNONYMS: &[(&str, &str)] = &[("sal", "shl")];
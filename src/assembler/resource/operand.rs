@@ -2,8 +2,12 @@ mod base;
 mod disp;
 mod gpr;
 mod imm;
+mod xmm;
+mod ymm;
 
 pub use base::*;
 pub use disp::*;
 pub use gpr::*;
 pub use imm::*;
+pub use xmm::*;
+pub use ymm::*;
@@ -1,5 +1,6 @@
 use crate::assembler::resource::{
     AddressingMode, Displacement, GeneralPurposeRegister, Immediate, RegisterSize, SIBByte,
+    XMMRegister, YMMRegister,
 };
 
 #[allow(dead_code)]
@@ -11,7 +12,8 @@ pub enum Operand {
     // FLAGS,
     // X87FPU
     // MMX
-    // XMM
+    XMMREGISTER(XMMRegister),
+    YMMREGISTER(YMMRegister),
     // CONTROL
     /// memory addressing
     /// ex. [rax], -4[rbp]
@@ -22,10 +24,40 @@ pub enum Operand {
         scale: Option<u8>,
     },
 
+    /// memory addressing without a base register.
+    /// ex. 0(,%rax,4), 0x1000, [rax*4]
+    /// SIB:base を 0b101 , mod を 0b00 とし, displacement は常に disp32 でエンコードする
+    ABSOLUTE {
+        index: Option<GeneralPurposeRegister>,
+        disp: i32,
+        scale: Option<u8>,
+    },
+
+    /// RIP-relative addressing with a symbol.
+    /// ex. .LC0(%rip), .LC0+8(%rip), [rip + .LC0]
+    /// the displacement is resolved by a relocation.
+    RIPRELATIVE {
+        label: String,
+        addend: i64,
+    },
+
     /// label in assembly code.
     /// using label operand in jump-related instructions.
-    LABEL(String),
+    /// ex. .L2, foo@PLT, bar+4
+    LABEL {
+        label: String,
+        addend: i64,
+    },
     Immediate(Immediate),
+
+    /// an imm32 with a symbol.
+    /// ex. $sym, $sym+4, $.L2-.L1
+    /// the value is resolved after the layout or by a relocation.
+    SYMBOLIC {
+        symbol: String,
+        subtrahend: Option<String>,
+        addend: i64,
+    },
 }
 
 #[allow(dead_code)]
@@ -33,22 +65,34 @@ impl Operand {
     /// ラベルの文字列を取得
     pub fn copy_label(&self) -> String {
         match self {
-            Operand::LABEL(contents) => contents.to_string(),
+            Operand::LABEL { label, .. } => label.to_string(),
             _ => unimplemented!(),
         }
     }
 
     /// メモリアドレッシングかチェック
     pub fn is_addressing(&self) -> bool {
-        match self {
+        matches!(
+            self,
             Operand::ADDRESSING {
                 base: _,
                 index: _,
                 disp: _,
                 scale: _,
-            } => true,
-            _ => false,
-        }
+            }
+        )
+    }
+    /// ModRM:r/m でエンコードできるか( レジスタかメモリ )
+    pub fn is_rm(&self) -> bool {
+        matches!(
+            self,
+            Operand::GENERALREGISTER(_)
+                | Operand::XMMREGISTER(_)
+                | Operand::YMMREGISTER(_)
+                | Operand::ADDRESSING { .. }
+                | Operand::ABSOLUTE { .. }
+                | Operand::RIPRELATIVE { .. }
+        )
     }
     /// 使用しているレジスタがx64拡張のものかチェック
     /// REX-Prefix の計算に使用
@@ -61,6 +105,8 @@ impl Operand {
                 scale: _,
             } => base_reg.is_expanded(),
             Operand::GENERALREGISTER(gpr) => gpr.is_expanded(),
+            Operand::XMMREGISTER(xmm) => xmm.is_expanded(),
+            Operand::YMMREGISTER(ymm) => ymm.is_expanded(),
             _ => false,
         }
     }
@@ -80,6 +126,7 @@ impl Operand {
 
                 index_reg.unwrap().is_expanded()
             }
+            Operand::ABSOLUTE { index, .. } => index.is_some_and(|index| index.is_expanded()),
 
             _ => false,
         }
@@ -92,32 +139,48 @@ impl Operand {
             return None;
         }
 
+        // ベースレジスタが無い場合, SIB:base は 0b101
+        if let Operand::ABSOLUTE { index, scale, .. } = self {
+            return Some(SIBByte {
+                base_reg: 0b101,
+                index_reg: index.map_or(0b100, |index| index.number()),
+                scale: scale.unwrap_or(1),
+            });
+        }
+
         let (base, index, _disp, scale) = self.get_addressing();
 
-        if let Some(scale_byte) = scale {
-            Some(SIBByte {
-                base_reg: base.number(),
-                index_reg: index.unwrap().number(),
-                scale: scale_byte,
-            })
-        } else {
-            Some(SIBByte {
-                base_reg: base.number(),
-                index_reg: index.unwrap().number(),
-                scale: 0,
-            })
-        }
+        // scaleが省略された場合は 1 として扱う
+        // index-reg が 0b100 ならインデックスを使わない( `(%rsp)` )
+        Some(SIBByte {
+            base_reg: base.number(),
+            index_reg: index.map_or(0b100, |index| index.number()),
+            scale: scale.unwrap_or(1),
+        })
     }
 
     /// displacementを取得
     /// コード生成に使用
     pub fn get_displacement(&self) -> Option<Displacement> {
+        // RIP相対の場合,再配置によって埋められる
+        match self {
+            Operand::RIPRELATIVE { .. } => return Some(Displacement::DISP32(0)),
+            // mod が 0b00 なので, disp8 に収まっても disp32
+            Operand::ABSOLUTE { disp, .. } => return Some(Displacement::DISP32(*disp)),
+            _ => {}
+        }
+
         if !self.is_addressing() {
             return None;
         }
 
-        let (_base, _index, disp, _scale) = self.get_addressing();
-        disp
+        let (base, _index, disp, _scale) = self.get_addressing();
+        // mod が 0b00 で ModRM:r/m( SIB:base )が 0b101 だと,
+        // RIP相対( ベース無し )になってしまうので, `(%rbp)` は `0(%rbp)` としてエンコードする
+        match disp {
+            None if base.number() & 0b111 == 0b101 => Some(Displacement::DISP8(0)),
+            _ => disp,
+        }
     }
 
     /// immediateを取得
@@ -132,12 +195,14 @@ impl Operand {
     /// SIB-Byteを必要とするかチェック
     pub fn req_sib_byte(&self) -> bool {
         match self {
+            // ModRM:r/m が 0b100 だと SIB-Byte が続くので, %rsp, %r12 をベースとする場合も必要
             Operand::ADDRESSING {
-                base: _,
+                base: base_reg,
                 index: index_reg,
                 disp: _,
                 scale: _,
-            } => index_reg.is_some(),
+            } => index_reg.is_some() || base_reg.number() & 0b111 == 0b100,
+            Operand::ABSOLUTE { .. } => true,
 
             _ => false,
         }
@@ -148,12 +213,16 @@ impl Operand {
     pub fn number(&self) -> u8 {
         match self {
             Self::GENERALREGISTER(reg) => reg.number(),
+            Self::XMMREGISTER(xmm) => xmm.number(),
+            Self::YMMREGISTER(ymm) => ymm.number(),
             Self::ADDRESSING {
                 base: base_reg,
                 index: _,
                 disp: _,
                 scale: _,
             } => base_reg.number(),
+            // ModRM:r/m が 0b101 かつ mod が 0b00 のときRIP相対となる
+            Self::RIPRELATIVE { .. } => 0b101,
             _ => panic!("cannot get register-number from {:?}", self),
         }
     }
@@ -161,23 +230,15 @@ impl Operand {
     /// get addressing mode in ModRM:mode
     pub fn addressing_mode(&self) -> AddressingMode {
         match self {
-            Operand::ADDRESSING {
-                base: _,
-                index: _,
-                disp: displacement,
-                scale: _,
-            } => {
-                if displacement.is_none() {
-                    return AddressingMode::REGISTER;
-                }
-
-                let disp = displacement.unwrap();
-                match disp {
-                    Displacement::DISP8(_v8) => AddressingMode::DISP8,
-                    Displacement::DISP32(_v32) => AddressingMode::DISP32,
-                }
+            Operand::ADDRESSING { .. } => match self.get_displacement() {
+                None => AddressingMode::REGISTER,
+                Some(Displacement::DISP8(_v8)) => AddressingMode::DISP8,
+                Some(Displacement::DISP32(_v32)) => AddressingMode::DISP32,
+            },
+            Operand::GENERALREGISTER(_) | Operand::XMMREGISTER(_) | Operand::YMMREGISTER(_) => {
+                AddressingMode::DIRECTREG
             }
-            Operand::GENERALREGISTER(_reg) => AddressingMode::DIRECTREG,
+            Operand::RIPRELATIVE { .. } | Operand::ABSOLUTE { .. } => AddressingMode::REGISTER,
             _ => panic!("cannot get addressing mode from {:?}", self),
         }
     }
@@ -208,23 +269,37 @@ impl Operand {
     pub fn to_intel_string(&self) -> String {
         match self {
             Operand::GENERALREGISTER(gpr) => gpr.to_intel_string(),
+            Operand::XMMREGISTER(xmm) => xmm.to_intel_string(),
+            Operand::YMMREGISTER(ymm) => ymm.to_intel_string(),
             Operand::Immediate(imm) => imm.to_intel_string(),
-            Operand::LABEL(s) => s.to_string(),
+            Operand::LABEL { .. } => self.label_expression(),
+            Operand::SYMBOLIC { .. } => self.symbolic_expression(),
+            Operand::RIPRELATIVE { .. } | Operand::ABSOLUTE { .. } => self.to_intel_address(),
+            Operand::ADDRESSING { .. } => {
+                format!(
+                    "{} PTR {}",
+                    self.size().to_intel_string(),
+                    self.to_intel_address()
+                )
+            }
+        }
+    }
+
+    /// メモリオペランドを `BYTE PTR` 等を付けずに表す( `-8[rbp]`, `[rip + foo]` )
+    pub fn to_intel_address(&self) -> String {
+        match self {
+            Operand::RIPRELATIVE { label, addend } => match addend {
+                0 => format!("[rip + {}]", label),
+                _ => format!("[rip + {}{:+}]", label, addend),
+            },
             Operand::ADDRESSING {
                 base: base_reg,
                 index: index_reg,
                 disp: displacement,
                 scale,
             } => {
-                let size_ptr = match base_reg.size() {
-                    RegisterSize::S8 => "BYTE PTR",
-                    RegisterSize::S16 => "WORD PTR",
-                    RegisterSize::S32 => "DWORD PTR",
-                    RegisterSize::S64 => "QWORD PTR",
-                };
-
                 let mut addressing = if displacement.is_some() {
-                    format!("{}[", displacement.unwrap().to_string())
+                    format!("{}[", displacement.unwrap())
                 } else {
                     "[".to_string()
                 };
@@ -238,16 +313,61 @@ impl Operand {
                 }
                 addressing += "]";
 
-                format!("{} {}", size_ptr, addressing)
+                addressing
+            }
+            Operand::ABSOLUTE { index, disp, scale } => match (index, scale) {
+                (Some(index), Some(s)) => {
+                    format!("[{} * {} + {}]", index.to_intel_string(), s, disp)
+                }
+                (Some(index), None) => format!("[{} + {}]", index.to_intel_string(), disp),
+                _ => format!("[{}]", disp),
+            },
+            _ => self.to_intel_string(),
+        }
+    }
+
+    /// `sym+4`, `.L2-.L1` のような即値の式
+    fn symbolic_expression(&self) -> String {
+        match self {
+            Operand::SYMBOLIC {
+                symbol,
+                subtrahend,
+                addend,
+            } => {
+                let mut expression = symbol.to_string();
+                if let Some(subtrahend) = subtrahend {
+                    expression += &format!("-{}", subtrahend);
+                }
+                if *addend != 0 {
+                    expression += &format!("{:+}", addend);
+                }
+                expression
             }
+            _ => unreachable!(),
+        }
+    }
+
+    /// `bar+4` のように,ラベルと addend を式にする
+    fn label_expression(&self) -> String {
+        match self {
+            Operand::LABEL { label, addend: 0 } => label.to_string(),
+            Operand::LABEL { label, addend } => format!("{}{:+}", label, addend),
+            _ => unreachable!(),
         }
     }
 
     pub fn to_at_string(&self) -> String {
         match self {
             Operand::GENERALREGISTER(gpr) => gpr.to_at_string(),
+            Operand::XMMREGISTER(xmm) => xmm.to_at_string(),
+            Operand::YMMREGISTER(ymm) => ymm.to_at_string(),
             Operand::Immediate(imm) => imm.to_at_string(),
-            Operand::LABEL(s) => s.to_string(),
+            Operand::LABEL { .. } => self.label_expression(),
+            Operand::SYMBOLIC { .. } => format!("${}", self.symbolic_expression()),
+            Operand::RIPRELATIVE { label, addend } => match addend {
+                0 => format!("{}(%rip)", label),
+                _ => format!("{}{:+}(%rip)", label, addend),
+            },
             Operand::ADDRESSING {
                 base: base_reg,
                 index: index_reg,
@@ -271,6 +391,11 @@ impl Operand {
 
                 format!("{}({})", disp_str, addressing)
             }
+            Operand::ABSOLUTE { index, disp, scale } => match (index, scale) {
+                (Some(index), Some(s)) => format!("{}(, {}, {})", disp, index.to_at_string(), s),
+                (Some(index), None) => format!("{}(, {})", disp, index.to_at_string()),
+                _ => disp.to_string(),
+            },
         }
     }
 
@@ -278,63 +403,42 @@ impl Operand {
         match self {
             Operand::GENERALREGISTER(gpr) => Operand::GENERALREGISTER(gpr.to_8bit()),
             Operand::Immediate(imm) => Operand::Immediate(imm.as_8bit()),
-            Operand::ADDRESSING {
-                base: b,
-                index: i,
-                disp: d,
-                scale: s,
-            } => Operand::ADDRESSING {
-                base: b.to_8bit(),
-                index: match i {
-                    Some(ireg) => Some(ireg.to_8bit()),
-                    None => None,
-                },
-                disp: *d,
-                scale: *s,
-            },
-            Operand::LABEL(_label) => unreachable!(),
+            // アドレスの計算に使うレジスタは,オペランドサイズによらない
+            Operand::ADDRESSING { .. } | Operand::ABSOLUTE { .. } | Operand::RIPRELATIVE { .. } => {
+                self.clone()
+            }
+            // 即値のサイズは命令の形式で決まる
+            Operand::SYMBOLIC { .. } => self.clone(),
+            Operand::XMMREGISTER(_) | Operand::YMMREGISTER(_) => self.clone(),
+            Operand::LABEL { .. } => unreachable!(),
         }
     }
     pub fn to_16bit(&self) -> Self {
         match self {
             Operand::GENERALREGISTER(gpr) => Operand::GENERALREGISTER(gpr.to_16bit()),
             Operand::Immediate(imm) => Operand::Immediate(imm.as_16bit()),
-            Operand::ADDRESSING {
-                base: b,
-                index: i,
-                disp: d,
-                scale: s,
-            } => Operand::ADDRESSING {
-                base: b.to_16bit(),
-                index: match i {
-                    Some(ireg) => Some(ireg.to_16bit()),
-                    None => None,
-                },
-                disp: *d,
-                scale: *s,
-            },
-            Operand::LABEL(_label) => unreachable!(),
+            // アドレスの計算に使うレジスタは,オペランドサイズによらない
+            Operand::ADDRESSING { .. } | Operand::ABSOLUTE { .. } | Operand::RIPRELATIVE { .. } => {
+                self.clone()
+            }
+            // 即値のサイズは命令の形式で決まる
+            Operand::SYMBOLIC { .. } => self.clone(),
+            Operand::XMMREGISTER(_) | Operand::YMMREGISTER(_) => self.clone(),
+            Operand::LABEL { .. } => unreachable!(),
         }
     }
     pub fn to_32bit(&self) -> Self {
         match self {
             Operand::GENERALREGISTER(gpr) => Operand::GENERALREGISTER(gpr.to_32bit()),
             Operand::Immediate(imm) => Operand::Immediate(imm.as_32bit()),
-            Operand::ADDRESSING {
-                base: b,
-                index: i,
-                disp: d,
-                scale: s,
-            } => Operand::ADDRESSING {
-                base: b.to_32bit(),
-                index: match i {
-                    Some(ireg) => Some(ireg.to_32bit()),
-                    None => None,
-                },
-                disp: *d,
-                scale: *s,
-            },
-            Operand::LABEL(_label) => unreachable!(),
+            // アドレスの計算に使うレジスタは,オペランドサイズによらない
+            Operand::ADDRESSING { .. } | Operand::ABSOLUTE { .. } | Operand::RIPRELATIVE { .. } => {
+                self.clone()
+            }
+            // 即値のサイズは命令の形式で決まる
+            Operand::SYMBOLIC { .. } => self.clone(),
+            Operand::XMMREGISTER(_) | Operand::YMMREGISTER(_) => self.clone(),
+            Operand::LABEL { .. } => unreachable!(),
         }
     }
 
@@ -342,21 +446,14 @@ impl Operand {
         match self {
             Operand::GENERALREGISTER(gpr) => Operand::GENERALREGISTER(gpr.to_64bit()),
             Operand::Immediate(imm) => Operand::Immediate(imm.as_32bit()),
-            Operand::ADDRESSING {
-                base: b,
-                index: i,
-                disp: d,
-                scale: s,
-            } => Operand::ADDRESSING {
-                base: b.to_64bit(),
-                index: match i {
-                    Some(ireg) => Some(ireg.to_64bit()),
-                    None => None,
-                },
-                disp: *d,
-                scale: *s,
-            },
-            Operand::LABEL(_label) => unreachable!(),
+            // アドレスの計算に使うレジスタは,オペランドサイズによらない
+            Operand::ADDRESSING { .. } | Operand::ABSOLUTE { .. } | Operand::RIPRELATIVE { .. } => {
+                self.clone()
+            }
+            // 即値のサイズは命令の形式で決まる
+            Operand::SYMBOLIC { .. } => self.clone(),
+            Operand::XMMREGISTER(_) | Operand::YMMREGISTER(_) => self.clone(),
+            Operand::LABEL { .. } => unreachable!(),
         }
     }
 
@@ -368,6 +465,8 @@ impl Operand {
                 RegisterSize::S32 => OperandSize::DWORD,
                 RegisterSize::S64 => OperandSize::QWORD,
             },
+            Operand::XMMREGISTER(_) => OperandSize::XMMWORD,
+            Operand::YMMREGISTER(_) => OperandSize::YMMWORD,
             Operand::ADDRESSING {
                 base: base_reg,
                 index: _,
@@ -379,20 +478,55 @@ impl Operand {
                 RegisterSize::S32 => OperandSize::DWORD,
                 RegisterSize::S64 => OperandSize::QWORD,
             },
-            Operand::LABEL(_label) => unreachable!(),
+            Operand::LABEL { .. } | Operand::ABSOLUTE { .. } | Operand::RIPRELATIVE { .. } => {
+                unreachable!()
+            }
+            Operand::SYMBOLIC { .. } => OperandSize::DWORD,
             Operand::Immediate(imm) => match imm {
                 Immediate::I8(_v) => OperandSize::BYTE,
                 Immediate::I16(_v) => OperandSize::WORD,
                 Immediate::I32(_v) => OperandSize::DWORD,
+                Immediate::I64(_v) => OperandSize::QWORD,
             },
         }
     }
 }
 
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Eq, Ord, PartialOrd, PartialEq, Debug, Clone, Copy)]
 pub enum OperandSize {
     BYTE,
     WORD,
     DWORD,
     QWORD,
+    /// 128bit( SSE のメモリオペランド )
+    XMMWORD,
+    /// 256bit( AVX のメモリオペランド )
+    YMMWORD,
+}
+
+impl OperandSize {
+    /// the suffix in AT&T syntax(e.g. `q` in `movq`).
+    pub fn to_at_suffix(self) -> &'static str {
+        match self {
+            OperandSize::BYTE => "b",
+            OperandSize::WORD => "w",
+            OperandSize::DWORD => "l",
+            OperandSize::QWORD => "q",
+            // XMM, YMM レジスタのサイズを表すサフィックスは無い
+            OperandSize::XMMWORD | OperandSize::YMMWORD => "",
+        }
+    }
+
+    /// `BYTE PTR` 等の `BYTE`
+    pub fn to_intel_string(self) -> &'static str {
+        match self {
+            OperandSize::BYTE => "BYTE",
+            OperandSize::WORD => "WORD",
+            OperandSize::DWORD => "DWORD",
+            OperandSize::QWORD => "QWORD",
+            OperandSize::XMMWORD => "XMMWORD",
+            OperandSize::YMMWORD => "YMMWORD",
+        }
+    }
 }
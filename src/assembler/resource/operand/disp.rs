@@ -13,10 +13,10 @@ pub enum Displacement {
 
 impl Displacement {
     /// translate to LE bytes for generating machine-code
-    pub fn to_bytes(&self) -> Vec<u8> {
+    pub fn to_bytes(self) -> Vec<u8> {
         match self {
-            Displacement::DISP8(v8) => vec![*v8 as u8],
-            Displacement::DISP32(v32) => (*v32 as u32).to_le_bytes().to_vec(),
+            Displacement::DISP8(v8) => vec![v8 as u8],
+            Displacement::DISP32(v32) => (v32 as u32).to_le_bytes().to_vec(),
         }
     }
 }
@@ -1,7 +1,7 @@
 use fmt::Formatter;
 use std::fmt;
 
-#[allow(dead_code)]
+#[allow(dead_code, clippy::upper_case_acronyms)]
 #[derive(Eq, Ord, PartialOrd, PartialEq, Debug, Clone, Copy)]
 pub enum GeneralPurposeRegister {
     // 8bit general-purpose registers
@@ -13,6 +13,19 @@ pub enum GeneralPurposeRegister {
     BL,
     CL,
     DL,
+    /// REX prefix がある場合のみ使える
+    SPL,
+    BPL,
+    SIL,
+    DIL,
+    R8B,
+    R9B,
+    R10B,
+    R11B,
+    R12B,
+    R13B,
+    R14B,
+    R15B,
 
     // 16bit general-purpose registers
     AX,
@@ -23,6 +36,14 @@ pub enum GeneralPurposeRegister {
     DI,
     BP,
     SP,
+    R8W,
+    R9W,
+    R10W,
+    R11W,
+    R12W,
+    R13W,
+    R14W,
+    R15W,
 
     // 32bit general-purpose registers
     /// Accumulator Register
@@ -42,6 +63,14 @@ pub enum GeneralPurposeRegister {
     ECX,
     /// Base Register
     EBX,
+    R8D,
+    R9D,
+    R10D,
+    R11D,
+    R12D,
+    R13D,
+    R14D,
+    R15D,
 
     // 64bit general-purpose registers
     /// Accumulator Register
@@ -95,30 +124,34 @@ impl GeneralPurposeRegister {
             | GeneralPurposeRegister::EBX
             | GeneralPurposeRegister::RBX => 3,
             GeneralPurposeRegister::AH
+            | GeneralPurposeRegister::SPL
             | GeneralPurposeRegister::SP
             | GeneralPurposeRegister::ESP
             | GeneralPurposeRegister::RSP => 4,
             GeneralPurposeRegister::CH
+            | GeneralPurposeRegister::BPL
             | GeneralPurposeRegister::BP
             | GeneralPurposeRegister::EBP
             | GeneralPurposeRegister::RBP => 5,
             GeneralPurposeRegister::DH
+            | GeneralPurposeRegister::SIL
             | GeneralPurposeRegister::SI
             | GeneralPurposeRegister::ESI
             | GeneralPurposeRegister::RSI => 6,
             GeneralPurposeRegister::BH
+            | GeneralPurposeRegister::DIL
             | GeneralPurposeRegister::DI
             | GeneralPurposeRegister::EDI
             | GeneralPurposeRegister::RDI => 7,
 
-            GeneralPurposeRegister::R8 => 8,
-            GeneralPurposeRegister::R9 => 9,
-            GeneralPurposeRegister::R10 => 10,
-            GeneralPurposeRegister::R11 => 11,
-            GeneralPurposeRegister::R12 => 12,
-            GeneralPurposeRegister::R13 => 13,
-            GeneralPurposeRegister::R14 => 14,
-            GeneralPurposeRegister::R15 => 15,
+            Self::R8B | Self::R8W | Self::R8D | Self::R8 => 8,
+            Self::R9B | Self::R9W | Self::R9D | Self::R9 => 9,
+            Self::R10B | Self::R10W | Self::R10D | Self::R10 => 10,
+            Self::R11B | Self::R11W | Self::R11D | Self::R11 => 11,
+            Self::R12B | Self::R12W | Self::R12D | Self::R12 => 12,
+            Self::R13B | Self::R13W | Self::R13D | Self::R13 => 13,
+            Self::R14B | Self::R14W | Self::R14D | Self::R14 => 14,
+            Self::R15B | Self::R15W | Self::R15D | Self::R15 => 15,
         }
     }
 
@@ -132,7 +165,37 @@ impl GeneralPurposeRegister {
             | GeneralPurposeRegister::AH
             | GeneralPurposeRegister::CH
             | GeneralPurposeRegister::DH
-            | GeneralPurposeRegister::BH => RegisterSize::S8,
+            | GeneralPurposeRegister::BH
+            | GeneralPurposeRegister::SPL
+            | GeneralPurposeRegister::BPL
+            | GeneralPurposeRegister::SIL
+            | GeneralPurposeRegister::DIL
+            | GeneralPurposeRegister::R8B
+            | GeneralPurposeRegister::R9B
+            | GeneralPurposeRegister::R10B
+            | GeneralPurposeRegister::R11B
+            | GeneralPurposeRegister::R12B
+            | GeneralPurposeRegister::R13B
+            | GeneralPurposeRegister::R14B
+            | GeneralPurposeRegister::R15B => RegisterSize::S8,
+
+            // 16bit
+            GeneralPurposeRegister::AX
+            | GeneralPurposeRegister::CX
+            | GeneralPurposeRegister::DX
+            | GeneralPurposeRegister::BX
+            | GeneralPurposeRegister::SP
+            | GeneralPurposeRegister::BP
+            | GeneralPurposeRegister::SI
+            | GeneralPurposeRegister::DI
+            | GeneralPurposeRegister::R8W
+            | GeneralPurposeRegister::R9W
+            | GeneralPurposeRegister::R10W
+            | GeneralPurposeRegister::R11W
+            | GeneralPurposeRegister::R12W
+            | GeneralPurposeRegister::R13W
+            | GeneralPurposeRegister::R14W
+            | GeneralPurposeRegister::R15W => RegisterSize::S16,
 
             // 32bit
             GeneralPurposeRegister::EAX
@@ -142,7 +205,15 @@ impl GeneralPurposeRegister {
             | GeneralPurposeRegister::ESP
             | GeneralPurposeRegister::EBP
             | GeneralPurposeRegister::ESI
-            | GeneralPurposeRegister::EDI => RegisterSize::S32,
+            | GeneralPurposeRegister::EDI
+            | GeneralPurposeRegister::R8D
+            | GeneralPurposeRegister::R9D
+            | GeneralPurposeRegister::R10D
+            | GeneralPurposeRegister::R11D
+            | GeneralPurposeRegister::R12D
+            | GeneralPurposeRegister::R13D
+            | GeneralPurposeRegister::R14D
+            | GeneralPurposeRegister::R15D => RegisterSize::S32,
             _ => RegisterSize::S64,
         }
     }
@@ -151,20 +222,20 @@ impl GeneralPurposeRegister {
     /// 拡張されたレジスタかどうかのチェック
     /// REX prefixに用いる
     pub fn is_expanded(&self) -> bool {
-        match self {
-            Self::R8
-            | Self::R9
-            | Self::R10
-            | Self::R11
-            | Self::R12
-            | Self::R13
-            | Self::R14
-            | Self::R15 => true,
-            _ => false,
-        }
+        self.number() >= 8
     }
 
-    pub fn to_str(&self) -> &'static str {
+    /// `%spl`, `%bpl`, `%sil`, `%dil` はREX prefixが無いと `%ah` 等と解釈される
+    pub fn requires_rex(&self) -> bool {
+        matches!(self, Self::SPL | Self::BPL | Self::SIL | Self::DIL)
+    }
+
+    /// `%ah` 等はREX prefixがあると `%spl` 等と解釈されるので,エンコードできない
+    pub fn is_high_byte(&self) -> bool {
+        matches!(self, Self::AH | Self::CH | Self::DH | Self::BH)
+    }
+
+    pub fn to_str(self) -> &'static str {
         match self {
             // 8bit general-purpose registers
             GeneralPurposeRegister::AH => "ah",
@@ -175,6 +246,18 @@ impl GeneralPurposeRegister {
             GeneralPurposeRegister::BL => "bl",
             GeneralPurposeRegister::CL => "cl",
             GeneralPurposeRegister::DL => "dl",
+            GeneralPurposeRegister::SPL => "spl",
+            GeneralPurposeRegister::BPL => "bpl",
+            GeneralPurposeRegister::SIL => "sil",
+            GeneralPurposeRegister::DIL => "dil",
+            Self::R8B => "r8b",
+            Self::R9B => "r9b",
+            Self::R10B => "r10b",
+            Self::R11B => "r11b",
+            Self::R12B => "r12b",
+            Self::R13B => "r13b",
+            Self::R14B => "r14b",
+            Self::R15B => "r15b",
 
             // 16bit general-purpose registers
             GeneralPurposeRegister::AX => "ax",
@@ -185,6 +268,14 @@ impl GeneralPurposeRegister {
             GeneralPurposeRegister::BP => "bp",
             GeneralPurposeRegister::DI => "di",
             GeneralPurposeRegister::SI => "si",
+            Self::R8W => "r8w",
+            Self::R9W => "r9w",
+            Self::R10W => "r10w",
+            Self::R11W => "r11w",
+            Self::R12W => "r12w",
+            Self::R13W => "r13w",
+            Self::R14W => "r14w",
+            Self::R15W => "r15w",
 
             // 32bit general-purpose registers
             Self::EAX => "eax",
@@ -195,6 +286,14 @@ impl GeneralPurposeRegister {
             Self::EBP => "ebp",
             Self::ESI => "esi",
             Self::EDI => "edi",
+            Self::R8D => "r8d",
+            Self::R9D => "r9d",
+            Self::R10D => "r10d",
+            Self::R11D => "r11d",
+            Self::R12D => "r12d",
+            Self::R13D => "r13d",
+            Self::R14D => "r14d",
+            Self::R15D => "r15d",
 
             // 64bit general-purpose registers
             Self::RAX => "rax",
@@ -226,6 +325,14 @@ impl GeneralPurposeRegister {
             5 => GeneralPurposeRegister::CH,
             6 => GeneralPurposeRegister::DH,
             7 => GeneralPurposeRegister::BH,
+            8 => GeneralPurposeRegister::R8B,
+            9 => GeneralPurposeRegister::R9B,
+            10 => GeneralPurposeRegister::R10B,
+            11 => GeneralPurposeRegister::R11B,
+            12 => GeneralPurposeRegister::R12B,
+            13 => GeneralPurposeRegister::R13B,
+            14 => GeneralPurposeRegister::R14B,
+            15 => GeneralPurposeRegister::R15B,
             _ => unimplemented!(),
         }
     }
@@ -239,6 +346,14 @@ impl GeneralPurposeRegister {
             5 => GeneralPurposeRegister::BP,
             6 => GeneralPurposeRegister::SI,
             7 => GeneralPurposeRegister::DI,
+            8 => GeneralPurposeRegister::R8W,
+            9 => GeneralPurposeRegister::R9W,
+            10 => GeneralPurposeRegister::R10W,
+            11 => GeneralPurposeRegister::R11W,
+            12 => GeneralPurposeRegister::R12W,
+            13 => GeneralPurposeRegister::R13W,
+            14 => GeneralPurposeRegister::R14W,
+            15 => GeneralPurposeRegister::R15W,
             _ => unimplemented!(),
         }
     }
@@ -252,6 +367,14 @@ impl GeneralPurposeRegister {
             5 => GeneralPurposeRegister::EBP,
             6 => GeneralPurposeRegister::ESI,
             7 => GeneralPurposeRegister::EDI,
+            8 => GeneralPurposeRegister::R8D,
+            9 => GeneralPurposeRegister::R9D,
+            10 => GeneralPurposeRegister::R10D,
+            11 => GeneralPurposeRegister::R11D,
+            12 => GeneralPurposeRegister::R12D,
+            13 => GeneralPurposeRegister::R13D,
+            14 => GeneralPurposeRegister::R14D,
+            15 => GeneralPurposeRegister::R15D,
             _ => unimplemented!(),
         }
     }
@@ -278,58 +401,119 @@ impl GeneralPurposeRegister {
         }
     }
 
-    pub fn from_at_string(s: &str) -> Self {
-        match s {
+    /// `None` if `s` isn't a register name.
+    pub fn from_at_string(s: &str) -> Option<Self> {
+        Self::from_intel_string(s.strip_prefix('%')?)
+    }
+
+    /// `None` if `s` isn't a register name.
+    pub fn from_intel_string(s: &str) -> Option<Self> {
+        let reg = match s {
+            // 8bit
+            "al" => GeneralPurposeRegister::AL,
+            "cl" => GeneralPurposeRegister::CL,
+            "dl" => GeneralPurposeRegister::DL,
+            "bl" => GeneralPurposeRegister::BL,
+            "ah" => GeneralPurposeRegister::AH,
+            "ch" => GeneralPurposeRegister::CH,
+            "dh" => GeneralPurposeRegister::DH,
+            "bh" => GeneralPurposeRegister::BH,
+            "spl" => GeneralPurposeRegister::SPL,
+            "bpl" => GeneralPurposeRegister::BPL,
+            "sil" => GeneralPurposeRegister::SIL,
+            "dil" => GeneralPurposeRegister::DIL,
+            "r8b" | "r8l" => GeneralPurposeRegister::R8B,
+            "r9b" | "r9l" => GeneralPurposeRegister::R9B,
+            "r10b" | "r10l" => GeneralPurposeRegister::R10B,
+            "r11b" | "r11l" => GeneralPurposeRegister::R11B,
+            "r12b" | "r12l" => GeneralPurposeRegister::R12B,
+            "r13b" | "r13l" => GeneralPurposeRegister::R13B,
+            "r14b" | "r14l" => GeneralPurposeRegister::R14B,
+            "r15b" | "r15l" => GeneralPurposeRegister::R15B,
+
+            // 16bit
+            "ax" => GeneralPurposeRegister::AX,
+            "cx" => GeneralPurposeRegister::CX,
+            "dx" => GeneralPurposeRegister::DX,
+            "bx" => GeneralPurposeRegister::BX,
+            "sp" => GeneralPurposeRegister::SP,
+            "bp" => GeneralPurposeRegister::BP,
+            "si" => GeneralPurposeRegister::SI,
+            "di" => GeneralPurposeRegister::DI,
+            "r8w" => GeneralPurposeRegister::R8W,
+            "r9w" => GeneralPurposeRegister::R9W,
+            "r10w" => GeneralPurposeRegister::R10W,
+            "r11w" => GeneralPurposeRegister::R11W,
+            "r12w" => GeneralPurposeRegister::R12W,
+            "r13w" => GeneralPurposeRegister::R13W,
+            "r14w" => GeneralPurposeRegister::R14W,
+            "r15w" => GeneralPurposeRegister::R15W,
+
             // 32bit
-            "%eax" => GeneralPurposeRegister::EAX,
-            "%ecx" => GeneralPurposeRegister::ECX,
-            "%edx" => GeneralPurposeRegister::EDX,
-            "%ebx" => GeneralPurposeRegister::EBX,
-            "%esp" => GeneralPurposeRegister::ESP,
-            "%ebp" => GeneralPurposeRegister::EBP,
-            "%esi" => GeneralPurposeRegister::ESI,
-            "%edi" => GeneralPurposeRegister::EDI,
+            "eax" => GeneralPurposeRegister::EAX,
+            "ecx" => GeneralPurposeRegister::ECX,
+            "edx" => GeneralPurposeRegister::EDX,
+            "ebx" => GeneralPurposeRegister::EBX,
+            "esp" => GeneralPurposeRegister::ESP,
+            "ebp" => GeneralPurposeRegister::EBP,
+            "esi" => GeneralPurposeRegister::ESI,
+            "edi" => GeneralPurposeRegister::EDI,
+            "r8d" => GeneralPurposeRegister::R8D,
+            "r9d" => GeneralPurposeRegister::R9D,
+            "r10d" => GeneralPurposeRegister::R10D,
+            "r11d" => GeneralPurposeRegister::R11D,
+            "r12d" => GeneralPurposeRegister::R12D,
+            "r13d" => GeneralPurposeRegister::R13D,
+            "r14d" => GeneralPurposeRegister::R14D,
+            "r15d" => GeneralPurposeRegister::R15D,
 
             // 64bit
-            "%rax" => GeneralPurposeRegister::RAX,
-            "%rcx" => GeneralPurposeRegister::RCX,
-            "%rdx" => GeneralPurposeRegister::RDX,
-            "%rbx" => GeneralPurposeRegister::RBX,
-            "%rsp" => GeneralPurposeRegister::RSP,
-            "%rbp" => GeneralPurposeRegister::RBP,
-            "%rsi" => GeneralPurposeRegister::RSI,
-            "%rdi" => GeneralPurposeRegister::RDI,
-            "%r8" => GeneralPurposeRegister::R8,
-            "%r9" => GeneralPurposeRegister::R9,
-            "%r10" => GeneralPurposeRegister::R10,
-            "%r11" => GeneralPurposeRegister::R11,
-            "%r12" => GeneralPurposeRegister::R12,
-            "%r13" => GeneralPurposeRegister::R13,
-            "%r14" => GeneralPurposeRegister::R14,
-            "%r15" => GeneralPurposeRegister::R15,
-            _ => panic!("{} is not a register", s),
-        }
+            "rax" => GeneralPurposeRegister::RAX,
+            "rcx" => GeneralPurposeRegister::RCX,
+            "rdx" => GeneralPurposeRegister::RDX,
+            "rbx" => GeneralPurposeRegister::RBX,
+            "rsp" => GeneralPurposeRegister::RSP,
+            "rbp" => GeneralPurposeRegister::RBP,
+            "rsi" => GeneralPurposeRegister::RSI,
+            "rdi" => GeneralPurposeRegister::RDI,
+            "r8" => GeneralPurposeRegister::R8,
+            "r9" => GeneralPurposeRegister::R9,
+            "r10" => GeneralPurposeRegister::R10,
+            "r11" => GeneralPurposeRegister::R11,
+            "r12" => GeneralPurposeRegister::R12,
+            "r13" => GeneralPurposeRegister::R13,
+            "r14" => GeneralPurposeRegister::R14,
+            "r15" => GeneralPurposeRegister::R15,
+            _ => return None,
+        };
+
+        Some(reg)
     }
 
-    pub fn to_8bit(&self) -> Self {
+    pub fn to_8bit(self) -> Self {
+        // `%sil` 等はコードだけでは `%dh` 等と区別できない
+        if self.size() == RegisterSize::S8 {
+            return self;
+        }
+
         Self::new_8bit_from_code(self.number() as usize)
     }
-    pub fn to_16bit(&self) -> Self {
+    pub fn to_16bit(self) -> Self {
         Self::new_16bit_from_code(self.number() as usize)
     }
-    pub fn to_32bit(&self) -> Self {
+    pub fn to_32bit(self) -> Self {
         Self::new_32bit_from_code(self.number() as usize)
     }
 
-    pub fn to_64bit(&self) -> Self {
+    pub fn to_64bit(self) -> Self {
         Self::new_64bit_from_code(self.number() as usize)
     }
 
-    pub fn to_intel_string(&self) -> String {
+    pub fn to_intel_string(self) -> String {
         self.to_str().to_string()
     }
 
-    pub fn to_at_string(&self) -> String {
+    pub fn to_at_string(self) -> String {
         format!("%{}", self.to_str())
     }
 }
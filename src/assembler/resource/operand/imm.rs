@@ -1,3 +1,4 @@
+use crate::assembler::resource::OperandSize;
 use fmt::Formatter;
 use std::fmt;
 
@@ -6,20 +7,25 @@ pub enum Immediate {
     I8(i8),
     I16(i16),
     I32(i32),
+    /// imm32 に収まらない値( `$0xffffffff` )
+    /// 命令のサイズが決まってから検査して, imm32 に切り詰める
+    I64(i64),
 }
 
 impl Immediate {
-    pub fn to_bytes(&self) -> Vec<u8> {
+    pub fn to_bytes(self) -> Vec<u8> {
         match self {
-            Immediate::I8(v8) => vec![*v8 as u8],
-            Immediate::I16(v16) => (*v16 as u16).to_le_bytes().to_vec(),
-            Immediate::I32(v32) => (*v32 as u32).to_le_bytes().to_vec(),
+            Immediate::I8(v8) => vec![v8 as u8],
+            Immediate::I16(v16) => (v16 as u16).to_le_bytes().to_vec(),
+            Immediate::I32(v32) => (v32 as u32).to_le_bytes().to_vec(),
+            Immediate::I64(v64) => (v64 as u64).to_le_bytes().to_vec(),
         }
     }
     pub fn as_8bit(&self) -> Self {
         match self {
             Immediate::I32(v8) => Self::I8(*v8 as i8),
             Immediate::I16(v16) => Self::I8(*v16 as i8),
+            Immediate::I64(v64) => Self::I8(*v64 as i8),
             Immediate::I8(_v8) => *self,
         }
     }
@@ -27,6 +33,7 @@ impl Immediate {
         match self {
             Immediate::I32(v32) => Self::I16(*v32 as i16),
             Immediate::I8(v8) => Self::I16(*v8 as i16),
+            Immediate::I64(v64) => Self::I16(*v64 as i16),
             _ => *self,
         }
     }
@@ -34,16 +41,39 @@ impl Immediate {
         match self {
             Immediate::I8(v8) => Self::I32(*v8 as i32),
             Immediate::I16(v16) => Self::I32(*v16 as i32),
+            Immediate::I64(v64) => Self::I32(*v64 as i32),
             Immediate::I32(_v32) => *self,
         }
     }
 
-    pub fn to_intel_string(&self) -> String {
+    pub fn value(&self) -> i64 {
+        match self {
+            Immediate::I8(v8) => *v8 as i64,
+            Immediate::I16(v16) => *v16 as i64,
+            Immediate::I32(v32) => *v32 as i64,
+            Immediate::I64(v64) => *v64,
+        }
+    }
+
+    /// whether the value can be encoded as a `size` immediate,
+    /// either as a signed or an unsigned value.
+    /// 64bit の命令の即値は,符号拡張される imm32
+    pub fn fits_in(&self, size: OperandSize) -> bool {
+        let value = self.value();
+        match size {
+            OperandSize::BYTE => i8::MIN as i64 <= value && value <= u8::MAX as i64,
+            OperandSize::WORD => i16::MIN as i64 <= value && value <= u16::MAX as i64,
+            OperandSize::DWORD => i32::MIN as i64 <= value && value <= u32::MAX as i64,
+            _ => i32::MIN as i64 <= value && value <= i32::MAX as i64,
+        }
+    }
+
+    pub fn to_intel_string(self) -> String {
         self.to_string()
     }
 
-    pub fn to_at_string(&self) -> String {
-        format!("${}", self.to_string())
+    pub fn to_at_string(self) -> String {
+        format!("${}", self)
     }
 }
 
@@ -53,6 +83,7 @@ impl fmt::Display for Immediate {
             Immediate::I8(v8) => write!(f, "{}", v8),
             Immediate::I16(v16) => write!(f, "{}", v16),
             Immediate::I32(v32) => write!(f, "{}", v32),
+            Immediate::I64(v64) => write!(f, "{}", v64),
         }
     }
 }
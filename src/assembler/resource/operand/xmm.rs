@@ -0,0 +1,92 @@
+use fmt::Formatter;
+use std::fmt;
+
+/// 128bit registers for SSE instructions.
+#[derive(Eq, Ord, PartialOrd, PartialEq, Debug, Clone, Copy)]
+pub enum XMMRegister {
+    XMM0,
+    XMM1,
+    XMM2,
+    XMM3,
+    XMM4,
+    XMM5,
+    XMM6,
+    XMM7,
+    /// REX prefix で拡張されたレジスタ
+    XMM8,
+    XMM9,
+    XMM10,
+    XMM11,
+    XMM12,
+    XMM13,
+    XMM14,
+    XMM15,
+}
+
+impl XMMRegister {
+    const ALL: [Self; 16] = [
+        Self::XMM0,
+        Self::XMM1,
+        Self::XMM2,
+        Self::XMM3,
+        Self::XMM4,
+        Self::XMM5,
+        Self::XMM6,
+        Self::XMM7,
+        Self::XMM8,
+        Self::XMM9,
+        Self::XMM10,
+        Self::XMM11,
+        Self::XMM12,
+        Self::XMM13,
+        Self::XMM14,
+        Self::XMM15,
+    ];
+
+    /// register code
+    pub fn number(&self) -> u8 {
+        *self as u8
+    }
+
+    /// REX prefix に用いる
+    pub fn is_expanded(&self) -> bool {
+        self.number() >= 8
+    }
+
+    pub fn new_from_code(code: usize) -> Self {
+        Self::ALL[code]
+    }
+
+    /// `None` if `s` isn't a register name.
+    pub fn from_at_string(s: &str) -> Option<Self> {
+        Self::from_intel_string(s.strip_prefix('%')?)
+    }
+
+    /// `None` if `s` isn't a register name.
+    pub fn from_intel_string(s: &str) -> Option<Self> {
+        let number = s.strip_prefix("xmm")?;
+        // `xmm01` のような書き方は認めない
+        if number.len() > 1 && number.starts_with('0') {
+            return None;
+        }
+
+        match number.parse::<usize>() {
+            Ok(code) if code < Self::ALL.len() => Some(Self::new_from_code(code)),
+            _ => None,
+        }
+    }
+
+    pub fn to_intel_string(self) -> String {
+        format!("xmm{}", self.number())
+    }
+
+    pub fn to_at_string(self) -> String {
+        format!("%xmm{}", self.number())
+    }
+}
+
+impl fmt::Display for XMMRegister {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Register::xmm{}", self.number())
+    }
+}
@@ -0,0 +1,93 @@
+use fmt::Formatter;
+use std::fmt;
+
+/// 256bit registers for AVX instructions.
+/// 下位 128bit は XMM レジスタと共有する
+#[derive(Eq, Ord, PartialOrd, PartialEq, Debug, Clone, Copy)]
+pub enum YMMRegister {
+    YMM0,
+    YMM1,
+    YMM2,
+    YMM3,
+    YMM4,
+    YMM5,
+    YMM6,
+    YMM7,
+    /// REX prefix で拡張されたレジスタ
+    YMM8,
+    YMM9,
+    YMM10,
+    YMM11,
+    YMM12,
+    YMM13,
+    YMM14,
+    YMM15,
+}
+
+impl YMMRegister {
+    const ALL: [Self; 16] = [
+        Self::YMM0,
+        Self::YMM1,
+        Self::YMM2,
+        Self::YMM3,
+        Self::YMM4,
+        Self::YMM5,
+        Self::YMM6,
+        Self::YMM7,
+        Self::YMM8,
+        Self::YMM9,
+        Self::YMM10,
+        Self::YMM11,
+        Self::YMM12,
+        Self::YMM13,
+        Self::YMM14,
+        Self::YMM15,
+    ];
+
+    /// register code
+    pub fn number(&self) -> u8 {
+        *self as u8
+    }
+
+    /// REX prefix に用いる
+    pub fn is_expanded(&self) -> bool {
+        self.number() >= 8
+    }
+
+    pub fn new_from_code(code: usize) -> Self {
+        Self::ALL[code]
+    }
+
+    /// `None` if `s` isn't a register name.
+    pub fn from_at_string(s: &str) -> Option<Self> {
+        Self::from_intel_string(s.strip_prefix('%')?)
+    }
+
+    /// `None` if `s` isn't a register name.
+    pub fn from_intel_string(s: &str) -> Option<Self> {
+        let number = s.strip_prefix("ymm")?;
+        // `ymm01` のような書き方は認めない
+        if number.len() > 1 && number.starts_with('0') {
+            return None;
+        }
+
+        match number.parse::<usize>() {
+            Ok(code) if code < Self::ALL.len() => Some(Self::new_from_code(code)),
+            _ => None,
+        }
+    }
+
+    pub fn to_intel_string(self) -> String {
+        format!("ymm{}", self.number())
+    }
+
+    pub fn to_at_string(self) -> String {
+        format!("%ymm{}", self.number())
+    }
+}
+
+impl fmt::Display for YMMRegister {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Register::ymm{}", self.number())
+    }
+}
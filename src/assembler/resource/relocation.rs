@@ -1,6 +1,15 @@
 use elf_utilities::relocation::Rela64;
 
-#[derive(Hash, Eq, Ord, PartialOrd, PartialEq, Debug, Clone)]
+// elf_utilities doesn't define these relocation types.
+pub const R_X86_64_64: u64 = 1;
+pub const R_X86_64_16: u64 = 12;
+pub const R_X86_64_PC16: u64 = 13;
+pub const R_X86_64_8: u64 = 14;
+pub const R_X86_64_PC8: u64 = 15;
+pub const R_X86_64_32S: u64 = 11;
+pub const R_X86_64_PC64: u64 = 24;
+
+#[derive(Hash, Eq, Ord, PartialOrd, PartialEq, Debug, Clone, Default)]
 pub struct RelaSymbol {
     pub name: String,
     pub rela64: Rela64,
@@ -11,13 +20,47 @@ impl RelaSymbol {
     pub fn to_le_bytes(&self) -> Vec<u8> {
         self.rela64.to_le_bytes()
     }
-}
 
-impl Default for RelaSymbol {
-    fn default() -> Self {
-        Self {
-            name: String::new(),
-            rela64: Default::default(),
+    /// the name of the relocation type(same as `readelf`).
+    pub fn type_name(&self) -> &'static str {
+        match self.rela64.get_type() {
+            R_X86_64_64 => "R_X86_64_64",
+            elf_utilities::relocation::R_X86_64_PC32 => "R_X86_64_PC32",
+            elf_utilities::relocation::R_X86_64_PLT32 => "R_X86_64_PLT32",
+            elf_utilities::relocation::R_X86_64_32 => "R_X86_64_32",
+            R_X86_64_32S => "R_X86_64_32S",
+            R_X86_64_16 => "R_X86_64_16",
+            R_X86_64_PC16 => "R_X86_64_PC16",
+            R_X86_64_8 => "R_X86_64_8",
+            R_X86_64_PC8 => "R_X86_64_PC8",
+            R_X86_64_PC64 => "R_X86_64_PC64",
+            _ => "R_X86_64_NONE",
         }
     }
+
+    /// the size of the field the linker fills.
+    pub fn field_size(&self) -> usize {
+        match self.rela64.get_type() {
+            R_X86_64_64 | R_X86_64_PC64 => 8,
+            R_X86_64_16 | R_X86_64_PC16 => 2,
+            R_X86_64_8 | R_X86_64_PC8 => 1,
+            _ => 4,
+        }
+    }
+}
+
+/// a value in data directives or immediates resolved after the layout.
+/// ex. `.quad .LC0+8`, `.long .L3-.L2`, `.long foo-.`, `movq $sym, %rax`
+#[derive(Eq, Ord, PartialOrd, PartialEq, Debug, Clone)]
+pub struct Fixup {
+    /// offset in the symbol's bytes
+    pub offset: usize,
+    /// 1, 2, 4 or 8
+    pub size: usize,
+    pub symbol: String,
+    /// `.L2` in `.L3-.L2`
+    pub subtrahend: Option<String>,
+    pub addend: i64,
+    /// 64bit に符号拡張される即値( `R_X86_64_32S` で再配置する )
+    pub signed: bool,
 }
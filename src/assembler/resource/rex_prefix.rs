@@ -70,7 +70,39 @@ impl REXPrefix {
         )
     }
 
-    pub fn to_byte(&self) -> u8 {
+    /// REX prefix for an instruction.
+    /// `reg` is the operand in ModRM:reg, `rm` is the one in ModRM:r/m(or opcode + rd).
+    /// if the prefix isn't needed, it returns Option::None.
+    pub fn from_operands(w: bool, reg: Option<&Operand>, rm: Option<&Operand>) -> Option<Self> {
+        let prefix = Self::new(
+            w,
+            reg.is_some_and(|r| r.is_expanded()),
+            rm.is_some_and(|rm| rm.req_sib_byte() && rm.index_reg_is_expanded()),
+            rm.is_some_and(|rm| rm.is_expanded()),
+        );
+
+        // `%sil` 等は何もビットが立っていなくても REX prefix が必要
+        let requires_rex = [reg, rm].iter().any(
+            |operand| matches!(operand, Some(Operand::GENERALREGISTER(r)) if r.requires_rex()),
+        );
+
+        match prefix.to_byte() != Self::BASE || requires_rex {
+            true => Some(prefix),
+            false => None,
+        }
+    }
+
+    /// `0x48` -> REX.W
+    pub fn from_byte(byte: u8) -> Self {
+        Self::new(
+            byte & Self::W_BIT != 0,
+            byte & Self::R_BIT != 0,
+            byte & Self::X_BIT != 0,
+            byte & Self::B_BIT != 0,
+        )
+    }
+
+    pub fn to_byte(self) -> u8 {
         let base = Self::BASE;
         let f = |bit: bool, byte: u8| -> u8 {
             if bit {
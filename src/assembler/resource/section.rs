@@ -0,0 +1,89 @@
+use elf_utilities::{section, Elf64Word, Elf64Xword};
+
+// elf_utilities doesn't define these flags.
+pub const SHF_WRITE: Elf64Xword = 1 << 0;
+pub const SHF_MERGE: Elf64Xword = 1 << 4;
+pub const SHF_STRINGS: Elf64Xword = 1 << 5;
+pub const SHF_TLS: Elf64Xword = 1 << 10;
+
+/// `.section` で登録しなくても出力するセクション
+pub const BUILTIN_SECTIONS: [&str; 4] = [".text", ".data", ".bss", ".rodata"];
+
+/// a section defined by `.section` other than `.text`, `.data`, `.bss` and `.rodata`.
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub struct CustomSection {
+    /// sh_type( `SHT_PROGBITS` 等 )
+    pub ty: Elf64Word,
+    pub flags: Elf64Xword,
+    /// the size of each entry in `"M"` sections.
+    pub entsize: Elf64Xword,
+}
+
+impl CustomSection {
+    /// the attributes GNU as gives to a section without flags and type.
+    pub fn with_name(name: &str) -> Self {
+        let write_alloc = section::SHF_ALLOC | SHF_WRITE;
+        let (ty, flags) = match name {
+            ".init_array" => (section::Type::InitArray, write_alloc),
+            ".fini_array" => (section::Type::FiniArray, write_alloc),
+            ".preinit_array" => (section::Type::PreInitArray, write_alloc),
+            _ if Self::has_prefix(name, ".tdata") => {
+                (section::Type::ProgBits, write_alloc | SHF_TLS)
+            }
+            _ if Self::has_prefix(name, ".tbss") => (section::Type::NoBits, write_alloc | SHF_TLS),
+            _ if Self::has_prefix(name, ".note") => (section::Type::Note, 0),
+            // `.data.rel.local`, `.rodata.cst8`, `.bss.counter` 等
+            _ if Self::has_prefix(name, ".data") => (section::Type::ProgBits, write_alloc),
+            _ if Self::has_prefix(name, ".rodata") => (section::Type::ProgBits, section::SHF_ALLOC),
+            _ if Self::has_prefix(name, ".bss") => (section::Type::NoBits, write_alloc),
+            _ => (section::Type::ProgBits, 0),
+        };
+
+        Self {
+            ty: ty.to_bytes(),
+            flags,
+            entsize: 0,
+        }
+    }
+
+    /// `"awx"` みたいなやつ
+    /// if `flags` contains an unsupported flag, it returns the flag as an error.
+    pub fn parse_flags(flags: &str) -> Result<Elf64Xword, char> {
+        flags.chars().try_fold(0, |acc, c| {
+            let flag = match c {
+                'a' => section::SHF_ALLOC,
+                'w' => SHF_WRITE,
+                'x' => section::SHF_EXECINSTR,
+                'M' => SHF_MERGE,
+                'S' => SHF_STRINGS,
+                'T' => SHF_TLS,
+                _ => return Err(c),
+            };
+            Ok(acc | flag)
+        })
+    }
+
+    /// `@progbits` みたいなやつ( `%progbits` とも書ける )
+    pub fn parse_type(ty: &str) -> Option<Elf64Word> {
+        let ty = match ty.strip_prefix(['@', '%'])? {
+            "progbits" => section::Type::ProgBits,
+            "nobits" => section::Type::NoBits,
+            "note" => section::Type::Note,
+            "init_array" => section::Type::InitArray,
+            "fini_array" => section::Type::FiniArray,
+            "preinit_array" => section::Type::PreInitArray,
+            _ => return None,
+        };
+        Some(ty.to_bytes())
+    }
+
+    /// `.tbss` 等はファイル上にバイト列を持たない
+    pub fn is_nobits(&self) -> bool {
+        self.ty == section::Type::NoBits.to_bytes()
+    }
+
+    /// `.tdata` は `.tdata.foo` も含む
+    fn has_prefix(name: &str, prefix: &str) -> bool {
+        name == prefix || name.starts_with(&format!("{}.", prefix))
+    }
+}
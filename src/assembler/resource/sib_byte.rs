@@ -10,11 +10,12 @@ pub struct SIBByte {
 }
 
 impl SIBByte {
+    /// x64拡張のレジスタの 4bit 目は REX prefix( VEX prefix )に含める
     pub fn base_field(byte: u8) -> u8 {
-        byte
+        byte & 0b111
     }
     pub fn index_field(byte: u8) -> u8 {
-        byte << 3
+        (byte & 0b111) << 3
     }
     pub fn scale_field(byte: u8) -> u8 {
         match byte {
@@ -26,7 +27,7 @@ impl SIBByte {
         }
     }
 
-    pub fn to_byte(&self) -> u8 {
+    pub fn to_byte(self) -> u8 {
         Self::base_field(self.base_reg)
             | Self::index_field(self.index_reg)
             | Self::scale_field(self.scale)
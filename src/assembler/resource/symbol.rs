@@ -1,16 +1,55 @@
-use crate::assembler::resource::Group;
+use crate::assembler::resource::{Fixup, Group};
 use elf_utilities::symbol;
 
-#[allow(dead_code)]
+/// the pseudo section name of absolute symbols(same as `readelf`)
+const ABSOLUTE_SECTION: &str = "*ABS*";
+/// the pseudo section name of common symbols(same as `objdump`)
+const COMMON_SECTION: &str = "*COM*";
+
 #[derive(Eq, Ord, PartialOrd, PartialEq, Debug)]
 pub struct Symbol {
     pub groups: Vec<Group>,
     /// Symbol Bind(GLOBAL/LOCAL/etc.)
     pub bind: symbol::Bind,
+    /// declared by `.local`(otherwise `bind` is LOCAL by default)
+    pub declared_local: bool,
     /// Symbol Type(NOTYPE/FUNCTION/etc.)
     pub ty: symbol::Type,
-    /// machine codes
+    /// machine codes, or data bytes for symbols in data sections
     pub codes: Vec<u8>,
+    /// the name of the section this symbol belongs to(`.text`, `.data`, etc.)
+    pub section: String,
+    /// required alignment of the symbol address(`.align` before its label)
+    pub align: u64,
+    /// offset in the section(determined after code generation)
+    pub value: u64,
+    /// the size specified by `.size`
+    pub size: Option<SymbolSize>,
+    /// values in `codes` resolved after the layout
+    pub fixups: Vec<Fixup>,
+    /// the source lines which emitted `codes`(for the listing)
+    pub lines: Vec<SourceLine>,
+}
+
+/// the range of `codes` emitted by a line of the source file.
+/// .text のシンボルでは,コード生成までは命令の番号の範囲
+#[derive(Eq, Ord, PartialOrd, PartialEq, Debug, Clone, Copy)]
+pub struct SourceLine {
+    /// the file which contains the line(an index of the source files, `0` is the source file itself)
+    pub source: usize,
+    /// 1始まりの行番号
+    pub line: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// the size specified by `.size`
+#[derive(Eq, Ord, PartialOrd, PartialEq, Debug, Clone, Copy)]
+pub enum SymbolSize {
+    Bytes(u64),
+    /// `.size main, .-main` in `.text`.
+    /// the length of the code isn't known until code generation.
+    CodeLength,
 }
 
 impl Default for Symbol {
@@ -19,26 +58,131 @@ impl Default for Symbol {
             groups: Vec::new(),
             ty: symbol::Type::NoType,
             bind: symbol::Bind::Local,
+            declared_local: false,
             codes: Vec::new(),
+            section: ".text".to_string(),
+            align: 1,
+            value: 0,
+            size: None,
+            fixups: Vec::new(),
+            lines: Vec::new(),
         }
     }
 }
 
-#[allow(dead_code)]
 impl Symbol {
+    /// a symbol referenced but not defined in this file.
+    /// the linker resolves it with other object files.
+    pub fn undefined() -> Self {
+        Self {
+            bind: symbol::Bind::Global,
+            section: String::new(),
+            ..Default::default()
+        }
+    }
+
+    /// a symbol only declared by `.globl`, `.type`, etc.
+    /// it's undefined until its label appears.
+    pub fn declared() -> Self {
+        Self {
+            section: String::new(),
+            ..Default::default()
+        }
+    }
+
+    /// a symbol defined by `.set`, `.equ` or `.equiv` with a constant.
+    /// it's emitted with `SHN_ABS` and doesn't belong to any section.
+    pub fn absolute(value: u64) -> Self {
+        Self {
+            section: ABSOLUTE_SECTION.to_string(),
+            value,
+            ..Default::default()
+        }
+    }
+
+    /// a symbol defined by `.comm`.
+    /// it's emitted with `SHN_COMMON` and the linker allocates it.
+    /// st_value of a common symbol is its alignment.
+    pub fn as_common(&mut self, size: u64, align: u64) {
+        self.section = COMMON_SECTION.to_string();
+        self.value = align;
+        self.align = align;
+        if self.size.is_none() {
+            self.size = Some(SymbolSize::Bytes(size));
+        }
+    }
+
     pub fn as_function(&mut self) {
         self.ty = symbol::Type::Func;
     }
 
+    pub fn as_object(&mut self) {
+        self.ty = symbol::Type::Object;
+    }
+
     pub fn as_global(&mut self) {
         self.bind = symbol::Bind::Global;
     }
 
-    pub fn is_function(&self) -> bool {
-        self.ty == symbol::Type::Func
+    pub fn as_local(&mut self) {
+        self.bind = symbol::Bind::Local;
+        self.declared_local = true;
+    }
+
+    pub fn as_weak(&mut self) {
+        self.bind = symbol::Bind::Weak;
+    }
+
+    pub fn is_local(&self) -> bool {
+        self.bind == symbol::Bind::Local
+    }
+
+    pub fn is_undefined(&self) -> bool {
+        self.section.is_empty()
+    }
+
+    pub fn is_absolute(&self) -> bool {
+        self.section == ABSOLUTE_SECTION
+    }
+
+    pub fn is_common(&self) -> bool {
+        self.section == COMMON_SECTION
     }
 
-    pub fn is_global(&self) -> bool {
-        self.bind == symbol::Bind::Global
+    pub fn in_text(&self) -> bool {
+        self.section == ".text"
+    }
+
+    /// `.L` から始まるシンボルはシンボルテーブルに出力しない
+    pub fn is_local_label(name: &str) -> bool {
+        name.starts_with(".L")
+    }
+
+    /// st_size( `.size` が無ければ0 )
+    pub fn st_size(&self) -> u64 {
+        match self.size {
+            Some(SymbolSize::Bytes(size)) => size,
+            _ => 0,
+        }
+    }
+
+    /// `source` の `line` 行が `start..end` を出力したことを記録する
+    /// 同じ行から続けて出力した範囲はまとめる
+    pub fn push_line(&mut self, (source, line): (usize, usize), start: usize, end: usize) {
+        if start == end {
+            return;
+        }
+
+        match self.lines.last_mut() {
+            Some(last) if (last.source, last.line) == (source, line) && last.end == start => {
+                last.end = end
+            }
+            _ => self.lines.push(SourceLine {
+                source,
+                line,
+                start,
+                end,
+            }),
+        }
     }
 }
@@ -1,3 +1,4 @@
+#[derive(Eq, Ord, PartialOrd, PartialEq, Debug, Clone, Copy)]
 pub enum Syntax {
     INTEL,
     ATANDT,
@@ -0,0 +1,132 @@
+use crate::assembler::resource::*;
+use fmt::Formatter;
+use std::fmt;
+
+/// using for AVX instructions.
+/// REX prefix のビット,必須の prefix( `0x66` 等 ),オペコードのエスケープ( `0f 38` 等 )をまとめてエンコードする
+#[derive(Eq, Ord, PartialOrd, PartialEq, Debug, Clone, Copy)]
+pub struct VEXPrefix {
+    /// related with reg-field in ModR/M
+    pub r_bit: bool,
+    /// related with index-field in ModR/M
+    pub x_bit: bool,
+    /// related with r/m-field in ModR/M, base in SIB-byte
+    pub b_bit: bool,
+    /// VEX.mmmmm( 1 -> `0f`, 2 -> `0f 38`, 3 -> `0f 3a` )
+    pub map: u8,
+    /// related with operand-size( REX.W と同じ )
+    pub w_bit: bool,
+    /// the register number of the operand in VEX.vvvv( 反転する前の値 )
+    pub vvvv: u8,
+    pub length: VectorLength,
+    /// VEX.pp( 0 -> none, 1 -> `0x66`, 2 -> `0xf3`, 3 -> `0xf2` )
+    pub pp: u8,
+}
+
+impl VEXPrefix {
+    /// 2-byte form( `c5` )
+    pub const TWO_BYTE: u8 = 0xc5;
+    /// 3-byte form( `c4` )
+    pub const THREE_BYTE: u8 = 0xc4;
+
+    /// VEX.pp で表す prefix
+    const PREFIXES: [Option<u8>; 4] = [None, Some(0x66), Some(0xf3), Some(0xf2)];
+    /// VEX.mmmmm で表すエスケープ
+    const ESCAPES: [&'static [u8]; 4] = [&[], &[0x0f], &[0x0f, 0x38], &[0x0f, 0x3a]];
+
+    /// VEX prefix for an instruction.
+    /// `reg` is the operand in ModRM:reg, `vvvv` is the one in VEX.vvvv, `rm` is the one in ModRM:r/m.
+    /// if the form isn't VEX-encoded, it returns Option::None.
+    pub fn from_operands(
+        form: &InstructionForm,
+        reg: Option<&Operand>,
+        vvvv: Option<&Operand>,
+        rm: Option<&Operand>,
+    ) -> Option<Self> {
+        let length = form.vex?;
+        let (escape, _) = form.split_escape();
+
+        Some(Self {
+            r_bit: reg.is_some_and(|r| r.is_expanded()),
+            x_bit: rm.is_some_and(|rm| rm.req_sib_byte() && rm.index_reg_is_expanded()),
+            b_bit: rm.is_some_and(|rm| rm.is_expanded()),
+            map: Self::ESCAPES.iter().position(|e| *e == escape)? as u8,
+            w_bit: form.rex_w,
+            vvvv: vvvv.map_or(0, |r| r.number()),
+            length,
+            pp: Self::PREFIXES.iter().position(|p| *p == form.prefix)? as u8,
+        })
+    }
+
+    /// `[0xc5, 0xfc]` -> VEX.256.0F.W0
+    /// `bytes` must start with `c4` or `c5` and contain the whole prefix.
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        // R, X, B, vvvv は反転して格納されている
+        let (r_x_b_map, w_vvvv_l_pp) = match bytes[0] {
+            Self::TWO_BYTE => (bytes[1] & 0x80 | 0x60 | 0x01, bytes[1] & 0x7f),
+            _ => (bytes[1], bytes[2]),
+        };
+
+        Self {
+            r_bit: r_x_b_map & 0x80 == 0,
+            x_bit: r_x_b_map & 0x40 == 0,
+            b_bit: r_x_b_map & 0x20 == 0,
+            map: r_x_b_map & 0x1f,
+            w_bit: w_vvvv_l_pp & 0x80 != 0,
+            vvvv: !(w_vvvv_l_pp >> 3) & 0xf,
+            length: match w_vvvv_l_pp & 0x04 {
+                0 => VectorLength::L128,
+                _ => VectorLength::L256,
+            },
+            pp: w_vvvv_l_pp & 0x03,
+        }
+    }
+
+    /// 2-byte form は X, B, W が無く,エスケープが `0f` の場合のみ使える
+    pub fn to_bytes(self) -> Vec<u8> {
+        let f = |bit: bool, byte: u8| -> u8 {
+            if bit {
+                byte
+            } else {
+                0b0
+            }
+        };
+
+        let vvvv_l_pp =
+            (!self.vvvv & 0xf) << 3 | f(self.length == VectorLength::L256, 0x04) | self.pp;
+        if !self.x_bit && !self.b_bit && !self.w_bit && self.map == 1 {
+            return vec![Self::TWO_BYTE, f(!self.r_bit, 0x80) | vvvv_l_pp];
+        }
+
+        vec![
+            Self::THREE_BYTE,
+            f(!self.r_bit, 0x80) | f(!self.x_bit, 0x40) | f(!self.b_bit, 0x20) | self.map,
+            f(self.w_bit, 0x80) | vvvv_l_pp,
+        ]
+    }
+
+    /// the prefix encoded in VEX.pp.
+    pub fn prefix(&self) -> Option<u8> {
+        Self::PREFIXES[self.pp as usize]
+    }
+
+    /// the opcode escape encoded in VEX.mmmmm.
+    /// if VEX.mmmmm is reserved, it returns Option::None.
+    pub fn escape(&self) -> Option<&'static [u8]> {
+        match self.map {
+            1..=3 => Some(Self::ESCAPES[self.map as usize]),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for VEXPrefix {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let bytes: Vec<String> = self
+            .to_bytes()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        write!(f, "VEX({})", bytes.join(" "))
+    }
+}
@@ -1,3 +1,4 @@
 mod opcode_tests;
 mod rex_prefix_tests;
 mod sib_byte_tests;
+mod vex_prefix_tests;
@@ -1,4 +1,8 @@
 mod add_tests;
+mod alu_tests;
+mod avx_tests;
+mod conditional_tests;
+mod extend_tests;
 mod idiv_tests;
 mod imul_tests;
 mod inc_tests;
@@ -6,4 +10,42 @@ mod mov_tests;
 mod neg_tests;
 mod pop_tests;
 mod push_tests;
+mod shift_tests;
+mod sse_tests;
 mod sub_tests;
+
+/// 各命令のテストで共通のヘルパー
+#[cfg(test)]
+mod common {
+    use crate::assembler::resource::*;
+
+    pub fn to_bytes(mnemonic: &str, kinds: &[OperandKind], operands: Vec<Operand>) -> Vec<u8> {
+        let opcode = Opcode::with_form(mnemonic, kinds, operands).unwrap();
+        Instruction { opcode }.to_bytes()
+    }
+
+    pub fn reg(r: GeneralPurposeRegister) -> Operand {
+        Operand::GENERALREGISTER(r)
+    }
+
+    pub fn xmm(r: XMMRegister) -> Operand {
+        Operand::XMMREGISTER(r)
+    }
+
+    pub fn ymm(r: YMMRegister) -> Operand {
+        Operand::YMMREGISTER(r)
+    }
+
+    pub fn imm(imm: Immediate) -> Operand {
+        Operand::Immediate(imm)
+    }
+
+    pub fn addressing(base: GeneralPurposeRegister, disp: Option<Displacement>) -> Operand {
+        Operand::ADDRESSING {
+            base,
+            index: None,
+            disp,
+            scale: None,
+        }
+    }
+}
@@ -0,0 +1,267 @@
+#[cfg(test)]
+mod to_bytes_tests {
+    use super::super::common::*;
+    use crate::assembler::resource::*;
+    use OperandKind::*;
+    use OperandSize::*;
+
+    #[test]
+    fn arithrmr_test() {
+        // xorl %eax, %eax
+        assert_eq!(
+            vec![0x31, 0xc0],
+            to_bytes(
+                "xor",
+                &[RegMem(DWORD), Reg(DWORD)],
+                vec![
+                    reg(GeneralPurposeRegister::EAX),
+                    reg(GeneralPurposeRegister::EAX)
+                ],
+            )
+        );
+        // xorw %cx, %dx
+        assert_eq!(
+            vec![0x66, 0x31, 0xca],
+            to_bytes(
+                "xor",
+                &[RegMem(WORD), Reg(WORD)],
+                vec![
+                    reg(GeneralPurposeRegister::DX),
+                    reg(GeneralPurposeRegister::CX)
+                ],
+            )
+        );
+        // orb %r9b, %dil
+        assert_eq!(
+            vec![0x44, 0x08, 0xcf],
+            to_bytes(
+                "or",
+                &[RegMem(BYTE), Reg(BYTE)],
+                vec![
+                    reg(GeneralPurposeRegister::DIL),
+                    reg(GeneralPurposeRegister::R9B)
+                ],
+            )
+        );
+        // sbbq %r9, (%r10)
+        assert_eq!(
+            vec![0x4d, 0x19, 0x0a],
+            to_bytes(
+                "sbb",
+                &[RegMem(QWORD), Reg(QWORD)],
+                vec![
+                    addressing(GeneralPurposeRegister::R10, None),
+                    reg(GeneralPurposeRegister::R9),
+                ],
+            )
+        );
+        // addl %eax, %r13d
+        assert_eq!(
+            vec![0x41, 0x01, 0xc5],
+            to_bytes(
+                "add",
+                &[RegMem(DWORD), Reg(DWORD)],
+                vec![
+                    reg(GeneralPurposeRegister::R13D),
+                    reg(GeneralPurposeRegister::EAX)
+                ],
+            )
+        );
+    }
+
+    #[test]
+    fn arithrrm_test() {
+        // sbbq (%r10, %r11, 8), %r12
+        assert_eq!(
+            vec![0x4f, 0x1b, 0x24, 0xda],
+            to_bytes(
+                "sbb",
+                &[Reg(QWORD), RegMem(QWORD)],
+                vec![
+                    reg(GeneralPurposeRegister::R12),
+                    Operand::ADDRESSING {
+                        base: GeneralPurposeRegister::R10,
+                        index: Some(GeneralPurposeRegister::R11),
+                        disp: None,
+                        scale: Some(8),
+                    },
+                ],
+            )
+        );
+    }
+
+    #[test]
+    fn arithrmimm_test() {
+        // addq $8, %rsp
+        assert_eq!(
+            vec![0x48, 0x83, 0xc4, 0x08],
+            to_bytes(
+                "add",
+                &[RegMem(QWORD), SImm8],
+                vec![reg(GeneralPurposeRegister::RSP), imm(Immediate::I8(8))],
+            )
+        );
+        // andb $0xff, %sil
+        assert_eq!(
+            vec![0x40, 0x80, 0xe6, 0xff],
+            to_bytes(
+                "and",
+                &[RegMem(BYTE), Imm(BYTE)],
+                vec![reg(GeneralPurposeRegister::SIL), imm(Immediate::I8(-1))],
+            )
+        );
+        // orw $3, %bx
+        assert_eq!(
+            vec![0x66, 0x83, 0xcb, 0x03],
+            to_bytes(
+                "or",
+                &[RegMem(WORD), SImm8],
+                vec![reg(GeneralPurposeRegister::BX), imm(Immediate::I8(3))],
+            )
+        );
+        // adcl $5, -4(%rbp)
+        assert_eq!(
+            vec![0x83, 0x55, 0xfc, 0x05],
+            to_bytes(
+                "adc",
+                &[RegMem(DWORD), SImm8],
+                vec![
+                    addressing(GeneralPurposeRegister::RBP, Some(Displacement::DISP8(-4))),
+                    imm(Immediate::I8(5)),
+                ],
+            )
+        );
+        // andl $0x1000, %r8d
+        assert_eq!(
+            vec![0x41, 0x81, 0xe0, 0x00, 0x10, 0x00, 0x00],
+            to_bytes(
+                "and",
+                &[RegMem(DWORD), Imm(DWORD)],
+                vec![
+                    reg(GeneralPurposeRegister::R8D),
+                    imm(Immediate::I32(0x1000))
+                ],
+            )
+        );
+    }
+
+    #[test]
+    fn arithaccimm_test() {
+        // cmpq $300, %rax
+        assert_eq!(
+            vec![0x48, 0x3d, 0x2c, 0x01, 0x00, 0x00],
+            to_bytes(
+                "cmp",
+                &[Acc(QWORD), Imm(DWORD)],
+                vec![reg(GeneralPurposeRegister::RAX), imm(Immediate::I32(300))],
+            )
+        );
+        // andb $1, %al
+        assert_eq!(
+            vec![0x24, 0x01],
+            to_bytes(
+                "and",
+                &[Acc(BYTE), Imm(BYTE)],
+                vec![reg(GeneralPurposeRegister::AL), imm(Immediate::I8(1))],
+            )
+        );
+        // orw $300, %ax
+        assert_eq!(
+            vec![0x66, 0x0d, 0x2c, 0x01],
+            to_bytes(
+                "or",
+                &[Acc(WORD), Imm(WORD)],
+                vec![reg(GeneralPurposeRegister::AX), imm(Immediate::I16(300))],
+            )
+        );
+    }
+
+    #[test]
+    fn test_test() {
+        // testq %rbx, (%rax)
+        assert_eq!(
+            vec![0x48, 0x85, 0x18],
+            to_bytes(
+                "test",
+                &[RegMem(QWORD), Reg(QWORD)],
+                vec![
+                    addressing(GeneralPurposeRegister::RAX, None),
+                    reg(GeneralPurposeRegister::RBX),
+                ],
+            )
+        );
+        // testb %ah, %bl
+        assert_eq!(
+            vec![0x84, 0xe3],
+            to_bytes(
+                "test",
+                &[RegMem(BYTE), Reg(BYTE)],
+                vec![
+                    reg(GeneralPurposeRegister::BL),
+                    reg(GeneralPurposeRegister::AH)
+                ],
+            )
+        );
+        // testw $1, -2(%rbp)
+        assert_eq!(
+            vec![0x66, 0xf7, 0x45, 0xfe, 0x01, 0x00],
+            to_bytes(
+                "test",
+                &[RegMem(WORD), Imm(WORD)],
+                vec![
+                    addressing(GeneralPurposeRegister::RBP, Some(Displacement::DISP8(-2))),
+                    imm(Immediate::I16(1)),
+                ],
+            )
+        );
+        // testb $1, %al
+        assert_eq!(
+            vec![0xa8, 0x01],
+            to_bytes(
+                "test",
+                &[Acc(BYTE), Imm(BYTE)],
+                vec![reg(GeneralPurposeRegister::AL), imm(Immediate::I8(1))],
+            )
+        );
+        // testl $1, %eax
+        assert_eq!(
+            vec![0xa9, 0x01, 0x00, 0x00, 0x00],
+            to_bytes(
+                "test",
+                &[Acc(DWORD), Imm(DWORD)],
+                vec![reg(GeneralPurposeRegister::EAX), imm(Immediate::I32(1))],
+            )
+        );
+    }
+
+    #[test]
+    fn notrm_test() {
+        // notq %rax
+        assert_eq!(
+            vec![0x48, 0xf7, 0xd0],
+            to_bytes(
+                "not",
+                &[RegMem(QWORD)],
+                vec![reg(GeneralPurposeRegister::RAX)]
+            )
+        );
+        // notb (%rdx)
+        assert_eq!(
+            vec![0xf6, 0x12],
+            to_bytes(
+                "not",
+                &[RegMem(BYTE)],
+                vec![addressing(GeneralPurposeRegister::RDX, None)],
+            )
+        );
+        // notw %r15w
+        assert_eq!(
+            vec![0x66, 0x41, 0xf7, 0xd7],
+            to_bytes(
+                "not",
+                &[RegMem(WORD)],
+                vec![reg(GeneralPurposeRegister::R15W)]
+            )
+        );
+    }
+}
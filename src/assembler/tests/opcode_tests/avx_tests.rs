@@ -0,0 +1,172 @@
+#[cfg(test)]
+mod to_bytes_tests {
+    use super::super::common::*;
+    use crate::assembler::resource::*;
+    use OperandKind::*;
+    use OperandSize::*;
+
+    #[test]
+    fn vaddps_test() {
+        // vaddps %ymm2, %ymm1, %ymm0
+        assert_eq!(
+            vec![0xc5, 0xf4, 0x58, 0xc2],
+            to_bytes(
+                "vaddps",
+                &[Ymm, Ymm, YmmMem(YMMWORD)],
+                vec![
+                    ymm(YMMRegister::YMM0),
+                    ymm(YMMRegister::YMM1),
+                    ymm(YMMRegister::YMM2)
+                ],
+            )
+        );
+        // vaddps %ymm2, %ymm11, %ymm12
+        // VEX.vvvv は4bit なので,2-byte form のまま
+        assert_eq!(
+            vec![0xc5, 0x24, 0x58, 0xe2],
+            to_bytes(
+                "vaddps",
+                &[Ymm, Ymm, YmmMem(YMMWORD)],
+                vec![
+                    ymm(YMMRegister::YMM12),
+                    ymm(YMMRegister::YMM11),
+                    ymm(YMMRegister::YMM2)
+                ],
+            )
+        );
+        // vaddps %ymm10, %ymm1, %ymm0
+        // VEX.B が必要なので 3-byte form
+        assert_eq!(
+            vec![0xc4, 0xc1, 0x74, 0x58, 0xc2],
+            to_bytes(
+                "vaddps",
+                &[Ymm, Ymm, YmmMem(YMMWORD)],
+                vec![
+                    ymm(YMMRegister::YMM0),
+                    ymm(YMMRegister::YMM1),
+                    ymm(YMMRegister::YMM10)
+                ],
+            )
+        );
+        // vaddps 32(%rax, %r10, 4), %ymm1, %ymm0
+        assert_eq!(
+            vec![0xc4, 0xa1, 0x74, 0x58, 0x44, 0x90, 0x20],
+            to_bytes(
+                "vaddps",
+                &[Ymm, Ymm, YmmMem(YMMWORD)],
+                vec![
+                    ymm(YMMRegister::YMM0),
+                    ymm(YMMRegister::YMM1),
+                    Operand::ADDRESSING {
+                        base: GeneralPurposeRegister::RAX,
+                        index: Some(GeneralPurposeRegister::R10),
+                        disp: Some(Displacement::DISP8(32)),
+                        scale: Some(4),
+                    },
+                ],
+            )
+        );
+    }
+
+    #[test]
+    fn vmovdqu_test() {
+        // vmovdqu (%rdi), %ymm0
+        assert_eq!(
+            vec![0xc5, 0xfe, 0x6f, 0x07],
+            to_bytes(
+                "vmovdqu",
+                &[Ymm, YmmMem(YMMWORD)],
+                vec![
+                    ymm(YMMRegister::YMM0),
+                    addressing(GeneralPurposeRegister::RDI, None)
+                ],
+            )
+        );
+        // vmovdqu %ymm8, (%r8)
+        assert_eq!(
+            vec![0xc4, 0x41, 0x7e, 0x7f, 0x00],
+            to_bytes(
+                "vmovdqu",
+                &[YmmMem(YMMWORD), Ymm],
+                vec![
+                    addressing(GeneralPurposeRegister::R8, None),
+                    ymm(YMMRegister::YMM8)
+                ],
+            )
+        );
+    }
+
+    #[test]
+    fn avx2_test() {
+        // vpxor %xmm9, %xmm9, %xmm9
+        assert_eq!(
+            vec![0xc4, 0x41, 0x31, 0xef, 0xc9],
+            to_bytes(
+                "vpxor",
+                &[Xmm, Xmm, XmmMem(XMMWORD)],
+                vec![
+                    xmm(XMMRegister::XMM9),
+                    xmm(XMMRegister::XMM9),
+                    xmm(XMMRegister::XMM9)
+                ],
+            )
+        );
+        // vpbroadcastd %xmm0, %ymm1
+        assert_eq!(
+            vec![0xc4, 0xe2, 0x7d, 0x58, 0xc8],
+            to_bytes(
+                "vpbroadcastd",
+                &[Ymm, XmmMem(DWORD)],
+                vec![ymm(YMMRegister::YMM1), xmm(XMMRegister::XMM0)],
+            )
+        );
+        // vpermq $0xd8, %ymm1, %ymm0
+        assert_eq!(
+            vec![0xc4, 0xe3, 0xfd, 0x00, 0xc1, 0xd8],
+            to_bytes(
+                "vpermq",
+                &[Ymm, YmmMem(YMMWORD), Imm(BYTE)],
+                vec![
+                    ymm(YMMRegister::YMM0),
+                    ymm(YMMRegister::YMM1),
+                    Operand::Immediate(Immediate::I16(0xd8))
+                ],
+            )
+        );
+    }
+
+    #[test]
+    fn fma_test() {
+        // vfmadd231ps %ymm2, %ymm1, %ymm0
+        assert_eq!(
+            vec![0xc4, 0xe2, 0x75, 0xb8, 0xc2],
+            to_bytes(
+                "vfmadd231ps",
+                &[Ymm, Ymm, YmmMem(YMMWORD)],
+                vec![
+                    ymm(YMMRegister::YMM0),
+                    ymm(YMMRegister::YMM1),
+                    ymm(YMMRegister::YMM2)
+                ],
+            )
+        );
+        // vfmadd231sd %xmm2, %xmm1, %xmm0
+        assert_eq!(
+            vec![0xc4, 0xe2, 0xf1, 0xb9, 0xc2],
+            to_bytes(
+                "vfmadd231sd",
+                &[Xmm, Xmm, XmmMem(QWORD)],
+                vec![
+                    xmm(XMMRegister::XMM0),
+                    xmm(XMMRegister::XMM1),
+                    xmm(XMMRegister::XMM2)
+                ],
+            )
+        );
+    }
+
+    #[test]
+    fn vzeroupper_test() {
+        assert_eq!(vec![0xc5, 0xf8, 0x77], to_bytes("vzeroupper", &[], vec![]));
+    }
+}
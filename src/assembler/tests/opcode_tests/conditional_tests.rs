@@ -0,0 +1,118 @@
+#[cfg(test)]
+mod to_bytes_tests {
+    use super::super::common::*;
+    use crate::assembler::resource::*;
+    use OperandKind::*;
+    use OperandSize::*;
+
+    #[test]
+    fn setccrm_test() {
+        // sete %al
+        assert_eq!(
+            vec![0x0f, 0x94, 0xc0],
+            to_bytes(
+                "sete",
+                &[RegMem(BYTE)],
+                vec![reg(GeneralPurposeRegister::AL)]
+            )
+        );
+        // setb %sil
+        assert_eq!(
+            vec![0x40, 0x0f, 0x92, 0xc6],
+            to_bytes(
+                "setb",
+                &[RegMem(BYTE)],
+                vec![reg(GeneralPurposeRegister::SIL)]
+            )
+        );
+        // setg %r15b
+        assert_eq!(
+            vec![0x41, 0x0f, 0x9f, 0xc7],
+            to_bytes(
+                "setg",
+                &[RegMem(BYTE)],
+                vec![reg(GeneralPurposeRegister::R15B)]
+            )
+        );
+        // setns %dh
+        assert_eq!(
+            vec![0x0f, 0x99, 0xc6],
+            to_bytes(
+                "setns",
+                &[RegMem(BYTE)],
+                vec![reg(GeneralPurposeRegister::DH)]
+            )
+        );
+        // setnp -1(%rbp)
+        assert_eq!(
+            vec![0x0f, 0x9b, 0x45, 0xff],
+            to_bytes(
+                "setnp",
+                &[RegMem(BYTE)],
+                vec![Operand::ADDRESSING {
+                    base: GeneralPurposeRegister::RBP,
+                    index: None,
+                    disp: Some(Displacement::DISP8(-1)),
+                    scale: None,
+                }],
+            )
+        );
+    }
+
+    #[test]
+    fn cmovccrrm_test() {
+        // cmovlq %rbx, %rax
+        assert_eq!(
+            vec![0x48, 0x0f, 0x4c, 0xc3],
+            to_bytes(
+                "cmovl",
+                &[Reg(QWORD), RegMem(QWORD)],
+                vec![
+                    reg(GeneralPurposeRegister::RAX),
+                    reg(GeneralPurposeRegister::RBX)
+                ],
+            )
+        );
+        // cmovew %cx, %dx
+        assert_eq!(
+            vec![0x66, 0x0f, 0x44, 0xd1],
+            to_bytes(
+                "cmove",
+                &[Reg(WORD), RegMem(WORD)],
+                vec![
+                    reg(GeneralPurposeRegister::DX),
+                    reg(GeneralPurposeRegister::CX)
+                ],
+            )
+        );
+        // cmovnel -4(%rbp), %r9d
+        assert_eq!(
+            vec![0x44, 0x0f, 0x45, 0x4d, 0xfc],
+            to_bytes(
+                "cmovne",
+                &[Reg(DWORD), RegMem(DWORD)],
+                vec![
+                    reg(GeneralPurposeRegister::R9D),
+                    Operand::ADDRESSING {
+                        base: GeneralPurposeRegister::RBP,
+                        index: None,
+                        disp: Some(Displacement::DISP8(-4)),
+                        scale: None,
+                    },
+                ],
+            )
+        );
+        // cmovgeq %r8, %r9
+        assert_eq!(
+            vec![0x4d, 0x0f, 0x4d, 0xc8],
+            to_bytes(
+                "cmovge",
+                &[Reg(QWORD), RegMem(QWORD)],
+                vec![
+                    reg(GeneralPurposeRegister::R9),
+                    reg(GeneralPurposeRegister::R8)
+                ],
+            )
+        );
+    }
+}
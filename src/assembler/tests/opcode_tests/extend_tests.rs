@@ -0,0 +1,135 @@
+#[cfg(test)]
+mod to_bytes_tests {
+    use super::super::common::*;
+    use crate::assembler::resource::*;
+    use OperandKind::*;
+    use OperandSize::*;
+
+    #[test]
+    fn movzxrrm_test() {
+        // movzbl %al, %eax
+        assert_eq!(
+            vec![0x0f, 0xb6, 0xc0],
+            to_bytes(
+                "movzx",
+                &[Reg(DWORD), RegMem(BYTE)],
+                vec![
+                    reg(GeneralPurposeRegister::EAX),
+                    reg(GeneralPurposeRegister::AL)
+                ],
+            )
+        );
+        // movzbw %sil, %ax
+        assert_eq!(
+            vec![0x66, 0x40, 0x0f, 0xb6, 0xc6],
+            to_bytes(
+                "movzx",
+                &[Reg(WORD), RegMem(BYTE)],
+                vec![
+                    reg(GeneralPurposeRegister::AX),
+                    reg(GeneralPurposeRegister::SIL)
+                ],
+            )
+        );
+        // movzwq (%rdi), %r8
+        assert_eq!(
+            vec![0x4c, 0x0f, 0xb7, 0x07],
+            to_bytes(
+                "movzx",
+                &[Reg(QWORD), RegMem(WORD)],
+                vec![
+                    reg(GeneralPurposeRegister::R8),
+                    Operand::ADDRESSING {
+                        base: GeneralPurposeRegister::RDI,
+                        index: None,
+                        disp: None,
+                        scale: None,
+                    },
+                ],
+            )
+        );
+    }
+
+    #[test]
+    fn movsxrrm_test() {
+        // movsbl -1(%rbp), %eax
+        assert_eq!(
+            vec![0x0f, 0xbe, 0x45, 0xff],
+            to_bytes(
+                "movsx",
+                &[Reg(DWORD), RegMem(BYTE)],
+                vec![
+                    reg(GeneralPurposeRegister::EAX),
+                    Operand::ADDRESSING {
+                        base: GeneralPurposeRegister::RBP,
+                        index: None,
+                        disp: Some(Displacement::DISP8(-1)),
+                        scale: None,
+                    },
+                ],
+            )
+        );
+        // movsbq %r9b, %rax
+        assert_eq!(
+            vec![0x49, 0x0f, 0xbe, 0xc1],
+            to_bytes(
+                "movsx",
+                &[Reg(QWORD), RegMem(BYTE)],
+                vec![
+                    reg(GeneralPurposeRegister::RAX),
+                    reg(GeneralPurposeRegister::R9B)
+                ],
+            )
+        );
+        // movswq %ax, %r15
+        assert_eq!(
+            vec![0x4c, 0x0f, 0xbf, 0xf8],
+            to_bytes(
+                "movsx",
+                &[Reg(QWORD), RegMem(WORD)],
+                vec![
+                    reg(GeneralPurposeRegister::R15),
+                    reg(GeneralPurposeRegister::AX)
+                ],
+            )
+        );
+    }
+
+    #[test]
+    fn movsxdr64rm32_test() {
+        // movslq %eax, %rax
+        assert_eq!(
+            vec![0x48, 0x63, 0xc0],
+            to_bytes(
+                "movsxd",
+                &[Reg(QWORD), RegMem(DWORD)],
+                vec![
+                    reg(GeneralPurposeRegister::RAX),
+                    reg(GeneralPurposeRegister::EAX)
+                ],
+            )
+        );
+        // movslq %r10d, %r11
+        assert_eq!(
+            vec![0x4d, 0x63, 0xda],
+            to_bytes(
+                "movsxd",
+                &[Reg(QWORD), RegMem(DWORD)],
+                vec![
+                    reg(GeneralPurposeRegister::R11),
+                    reg(GeneralPurposeRegister::R10D)
+                ],
+            )
+        );
+    }
+
+    #[test]
+    fn convert_test() {
+        assert_eq!(vec![0x66, 0x98], to_bytes("cbw", &[], vec![]));
+        assert_eq!(vec![0x98], to_bytes("cwde", &[], vec![]));
+        assert_eq!(vec![0x48, 0x98], to_bytes("cdqe", &[], vec![]));
+        assert_eq!(vec![0x66, 0x99], to_bytes("cwd", &[], vec![]));
+        assert_eq!(vec![0x99], to_bytes("cdq", &[], vec![]));
+        assert_eq!(vec![0x48, 0x99], to_bytes("cqo", &[], vec![]));
+    }
+}
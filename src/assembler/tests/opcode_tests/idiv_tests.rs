@@ -1,24 +1,37 @@
-use crate::assembler::resource::*;
-
-#[allow(dead_code)]
-const IDIVRM64: [Instruction; 1] = [Instruction {
-    opcode: Opcode::IDIVRM64 {
-        rm64: Operand::ADDRESSING {
-            base: GeneralPurposeRegister::RAX,
-            index: None,
-            disp: None,
-            scale: None,
-        },
-    },
-}];
-
 #[cfg(test)]
 mod to_bytes_tests {
-    use super::*;
+    use super::super::common::*;
+    use crate::assembler::resource::*;
+    use OperandKind::*;
+    use OperandSize::*;
 
     #[test]
-    fn idivrm64_test() {
-        let inst = &IDIVRM64[0];
-        assert_eq!(inst.to_bytes(), vec![0x48, 0xf7, 0x38]);
+    fn idivrm_test() {
+        assert_eq!(
+            to_bytes(
+                "idiv",
+                &[RegMem(QWORD)],
+                vec![addressing(GeneralPurposeRegister::RAX, None)],
+            ),
+            vec![0x48, 0xf7, 0x38]
+        );
+
+        assert_eq!(
+            to_bytes(
+                "idiv",
+                &[RegMem(WORD)],
+                vec![reg(GeneralPurposeRegister::BX)]
+            ),
+            vec![0x66, 0xf7, 0xfb]
+        );
+
+        assert_eq!(
+            to_bytes(
+                "idiv",
+                &[RegMem(BYTE)],
+                vec![reg(GeneralPurposeRegister::BL)]
+            ),
+            vec![0xf6, 0xfb]
+        );
     }
 }
@@ -1,25 +1,55 @@
-use crate::assembler::resource::*;
-
-#[allow(dead_code)]
-const IMULR64RM64: [Instruction; 1] = [Instruction {
-    opcode: Opcode::IMULR64RM64 {
-        r64: GeneralPurposeRegister::R12,
-        rm64: Operand::ADDRESSING {
-            base: GeneralPurposeRegister::RBP,
-            index: None,
-            disp: Some(Displacement::DISP8(-16)),
-            scale: None,
-        },
-    },
-}];
-
 #[cfg(test)]
 mod to_bytes_tests {
-    use super::*;
+    use super::super::common::*;
+    use crate::assembler::resource::*;
+    use OperandKind::*;
+    use OperandSize::*;
 
     #[test]
-    fn imulr64rm64_test() {
-        let inst = &IMULR64RM64[0];
-        assert_eq!(inst.to_bytes(), vec![0x4c, 0x0f, 0xaf, 0x65, 0xf0]);
+    fn imulrrm_test() {
+        assert_eq!(
+            to_bytes(
+                "imul",
+                &[Reg(QWORD), RegMem(QWORD)],
+                vec![
+                    reg(GeneralPurposeRegister::R12),
+                    addressing(GeneralPurposeRegister::RBP, Some(Displacement::DISP8(-16))),
+                ],
+            ),
+            vec![0x4c, 0x0f, 0xaf, 0x65, 0xf0]
+        );
+
+        assert_eq!(
+            to_bytes(
+                "imul",
+                &[Reg(WORD), RegMem(WORD)],
+                vec![
+                    reg(GeneralPurposeRegister::AX),
+                    reg(GeneralPurposeRegister::BX)
+                ],
+            ),
+            vec![0x66, 0x0f, 0xaf, 0xc3]
+        );
+    }
+
+    #[test]
+    fn imulrm_test() {
+        assert_eq!(
+            to_bytes(
+                "imul",
+                &[RegMem(QWORD)],
+                vec![reg(GeneralPurposeRegister::RBX)]
+            ),
+            vec![0x48, 0xf7, 0xeb]
+        );
+
+        assert_eq!(
+            to_bytes(
+                "imul",
+                &[RegMem(BYTE)],
+                vec![reg(GeneralPurposeRegister::CL)]
+            ),
+            vec![0xf6, 0xe9]
+        );
     }
 }
@@ -1,20 +1,40 @@
-use crate::assembler::resource::*;
-
-#[allow(dead_code)]
-const INCRM64_CASES: [Instruction; 1] = [Instruction {
-    opcode: Opcode::INCRM64 {
-        rm64: Operand::GENERALREGISTER(GeneralPurposeRegister::RAX),
-    },
-}];
-
 #[cfg(test)]
 mod to_bytes_tests {
-    use super::*;
+    use super::super::common::*;
+    use crate::assembler::resource::*;
+    use OperandKind::*;
+    use OperandSize::*;
 
     #[test]
-    fn incrm64_test() {
-        let inst = &INCRM64_CASES[0];
+    fn incrm_test() {
+        assert_eq!(
+            to_bytes(
+                "inc",
+                &[RegMem(QWORD)],
+                vec![reg(GeneralPurposeRegister::RAX)]
+            ),
+            vec![0x48, 0xff, 0xc0]
+        );
+
+        assert_eq!(
+            to_bytes(
+                "inc",
+                &[RegMem(WORD)],
+                vec![addressing(
+                    GeneralPurposeRegister::RBP,
+                    Some(Displacement::DISP8(-2))
+                )],
+            ),
+            vec![0x66, 0xff, 0x45, 0xfe]
+        );
 
-        assert_eq!(inst.to_bytes(), vec![0x48, 0xff, 0xc0]);
+        assert_eq!(
+            to_bytes(
+                "inc",
+                &[RegMem(BYTE)],
+                vec![reg(GeneralPurposeRegister::AL)]
+            ),
+            vec![0xfe, 0xc0]
+        );
     }
 }
@@ -1,118 +1,245 @@
-use crate::assembler::resource::*;
-
-#[allow(dead_code)]
-const MOVRM8R8_CASES: [Instruction; 2] = [
-    Instruction {
-        opcode: Opcode::MOVRM8R8 {
-            rm8: Operand::GENERALREGISTER(GeneralPurposeRegister::BH),
-            r8: GeneralPurposeRegister::AH,
-        },
-    },
-    Instruction {
-        opcode: Opcode::MOVRM8R8 {
-            rm8: Operand::ADDRESSING {
-                base: GeneralPurposeRegister::AL,
-                index: None,
-                disp: None,
-                scale: None,
-            },
-            r8: GeneralPurposeRegister::BH,
-        },
-    },
-];
-
-#[allow(dead_code)]
-const MOVRM64R64_CASES: [Instruction; 2] = [
-    Instruction {
-        opcode: Opcode::MOVRM64R64 {
-            rm64: Operand::GENERALREGISTER(GeneralPurposeRegister::RAX),
-            r64: GeneralPurposeRegister::RCX,
-        },
-    },
-    Instruction {
-        opcode: Opcode::MOVRM64R64 {
-            rm64: Operand::ADDRESSING {
-                base: GeneralPurposeRegister::RAX,
-                index: Some(GeneralPurposeRegister::RBX),
-                disp: None,
-                scale: Some(0x4),
-            },
-            r64: GeneralPurposeRegister::RCX,
-        },
-    },
-];
-
-#[allow(dead_code)]
-const MOVR64RM64_CASES: [Instruction; 1] = [Instruction {
-    opcode: Opcode::MOVR64RM64 {
-        r64: GeneralPurposeRegister::RAX,
-        rm64: Operand::ADDRESSING {
-            base: GeneralPurposeRegister::RAX,
-            index: None,
-            disp: None,
-            scale: None,
-        },
-    },
-}];
-
-#[allow(dead_code)]
-const MOVRM64IMM32_CASES: [Instruction; 1] = [Instruction {
-    opcode: Opcode::MOVRM64IMM32 {
-        rm64: Operand::ADDRESSING {
-            base: GeneralPurposeRegister::RAX,
-            index: None,
-            disp: None,
-            scale: None,
-        },
-        imm: Immediate::I32(60),
-    },
-}];
-
 #[cfg(test)]
 mod to_bytes_tests {
-    use super::*;
+    use super::super::common::*;
+    use crate::assembler::resource::*;
+    use OperandKind::*;
+    use OperandSize::*;
 
     #[test]
-    fn movrm8r8_test() {
+    fn movrmr_test() {
         // mov bh, ah
-        let inst = &MOVRM8R8_CASES[0];
-
-        assert_eq!(inst.to_bytes(), vec![0x88, 0xe7]);
+        assert_eq!(
+            to_bytes(
+                "mov",
+                &[RegMem(BYTE), Reg(BYTE)],
+                vec![
+                    reg(GeneralPurposeRegister::BH),
+                    reg(GeneralPurposeRegister::AH)
+                ],
+            ),
+            vec![0x88, 0xe7]
+        );
 
         // mov BYTE PTR [rax], bh
-        let inst = &MOVRM8R8_CASES[1];
-
-        assert_eq!(inst.to_bytes(), vec![0x88, 0x38]);
-    }
+        assert_eq!(
+            to_bytes(
+                "mov",
+                &[RegMem(BYTE), Reg(BYTE)],
+                vec![
+                    addressing(GeneralPurposeRegister::RAX, None),
+                    reg(GeneralPurposeRegister::BH),
+                ],
+            ),
+            vec![0x88, 0x38]
+        );
+
+        // mov WORD PTR [rbp-2], ax
+        assert_eq!(
+            to_bytes(
+                "mov",
+                &[RegMem(WORD), Reg(WORD)],
+                vec![
+                    addressing(GeneralPurposeRegister::RBP, Some(Displacement::DISP8(-2))),
+                    reg(GeneralPurposeRegister::AX),
+                ],
+            ),
+            vec![0x66, 0x89, 0x45, 0xfe]
+        );
 
-    #[test]
-    fn movrm64r64_test() {
         // mov rax, rcx
-        let inst = &MOVRM64R64_CASES[0];
-
-        assert_eq!(inst.to_bytes(), vec![0x48, 0x89, 0xc8]);
+        assert_eq!(
+            to_bytes(
+                "mov",
+                &[RegMem(QWORD), Reg(QWORD)],
+                vec![
+                    reg(GeneralPurposeRegister::RAX),
+                    reg(GeneralPurposeRegister::RCX)
+                ],
+            ),
+            vec![0x48, 0x89, 0xc8]
+        );
 
         // mov [rax + rbx * 4], rcx
-        let inst = &MOVRM64R64_CASES[1];
-
-        assert_eq!(inst.to_bytes(), vec![0x48, 0x89, 0x0c, 0x98]);
+        assert_eq!(
+            to_bytes(
+                "mov",
+                &[RegMem(QWORD), Reg(QWORD)],
+                vec![
+                    Operand::ADDRESSING {
+                        base: GeneralPurposeRegister::RAX,
+                        index: Some(GeneralPurposeRegister::RBX),
+                        disp: None,
+                        scale: Some(0x4),
+                    },
+                    reg(GeneralPurposeRegister::RCX),
+                ],
+            ),
+            vec![0x48, 0x89, 0x0c, 0x98]
+        );
     }
 
     #[test]
-    fn movr64rm64_test() {
+    fn movrrm_test() {
         // mov rax, [rax]
-        let inst = &MOVR64RM64_CASES[0];
+        assert_eq!(
+            to_bytes(
+                "mov",
+                &[Reg(QWORD), RegMem(QWORD)],
+                vec![
+                    reg(GeneralPurposeRegister::RAX),
+                    addressing(GeneralPurposeRegister::RAX, None),
+                ],
+            ),
+            vec![0x48, 0x8b, 0x00]
+        );
+
+        // mov eax, DWORD PTR [rbp-4]
+        assert_eq!(
+            to_bytes(
+                "mov",
+                &[Reg(DWORD), RegMem(DWORD)],
+                vec![
+                    reg(GeneralPurposeRegister::EAX),
+                    addressing(GeneralPurposeRegister::RBP, Some(Displacement::DISP8(-4))),
+                ],
+            ),
+            vec![0x8b, 0x45, 0xfc]
+        );
+
+        // mov al, BYTE PTR [rbp-1]
+        assert_eq!(
+            to_bytes(
+                "mov",
+                &[Reg(BYTE), RegMem(BYTE)],
+                vec![
+                    reg(GeneralPurposeRegister::AL),
+                    addressing(GeneralPurposeRegister::RBP, Some(Displacement::DISP8(-1))),
+                ],
+            ),
+            vec![0x8a, 0x45, 0xff]
+        );
+    }
 
-        assert_eq!(inst.to_bytes(), vec![0x48, 0x8b, 0x00]);
+    #[test]
+    fn mov_special_base_test() {
+        // %rsp, %r12 をベースとすると SIB-Byte が必要
+        // %rbp, %r13 をベースとすると displacement が必要
+        let table = [
+            // mov rax, [rsp]
+            (
+                GeneralPurposeRegister::RSP,
+                None,
+                vec![0x48, 0x8b, 0x04, 0x24],
+            ),
+            // mov rax, [rsp+8]
+            (
+                GeneralPurposeRegister::RSP,
+                Some(Displacement::DISP8(8)),
+                vec![0x48, 0x8b, 0x44, 0x24, 0x08],
+            ),
+            // mov rax, [r12]
+            (
+                GeneralPurposeRegister::R12,
+                None,
+                vec![0x49, 0x8b, 0x04, 0x24],
+            ),
+            // mov rax, [r12+8]
+            (
+                GeneralPurposeRegister::R12,
+                Some(Displacement::DISP8(8)),
+                vec![0x49, 0x8b, 0x44, 0x24, 0x08],
+            ),
+            // mov rax, [rbp]
+            (
+                GeneralPurposeRegister::RBP,
+                None,
+                vec![0x48, 0x8b, 0x45, 0x00],
+            ),
+            // mov rax, [r13]
+            (
+                GeneralPurposeRegister::R13,
+                None,
+                vec![0x49, 0x8b, 0x45, 0x00],
+            ),
+            // mov rax, [r13+0x100]
+            (
+                GeneralPurposeRegister::R13,
+                Some(Displacement::DISP32(0x100)),
+                vec![0x49, 0x8b, 0x85, 0x00, 0x01, 0x00, 0x00],
+            ),
+        ];
+
+        for (base, disp, expected) in table {
+            assert_eq!(
+                expected,
+                to_bytes(
+                    "mov",
+                    &[Reg(QWORD), RegMem(QWORD)],
+                    vec![reg(GeneralPurposeRegister::RAX), addressing(base, disp)],
+                ),
+                "{:?}",
+                (base, disp)
+            );
+        }
+
+        // mov rcx, [rbp+rax*1]
+        assert_eq!(
+            vec![0x48, 0x8b, 0x4c, 0x05, 0x00],
+            to_bytes(
+                "mov",
+                &[Reg(QWORD), RegMem(QWORD)],
+                vec![
+                    reg(GeneralPurposeRegister::RCX),
+                    Operand::ADDRESSING {
+                        base: GeneralPurposeRegister::RBP,
+                        index: Some(GeneralPurposeRegister::RAX),
+                        disp: None,
+                        scale: None,
+                    },
+                ],
+            )
+        );
     }
 
     #[test]
-    fn movrm64imm32_test() {
+    fn movrmimm_test() {
         // mov QWORD PTR [rax], 60
-        let inst = &MOVRM64IMM32_CASES[0];
         assert_eq!(
-            inst.to_bytes(),
+            to_bytes(
+                "mov",
+                &[RegMem(QWORD), Imm(DWORD)],
+                vec![
+                    addressing(GeneralPurposeRegister::RAX, None),
+                    imm(Immediate::I32(60)),
+                ],
+            ),
             vec![0x48, 0xc7, 0x00, 0x3c, 0x00, 0x00, 0x00]
-        )
+        );
+
+        // mov BYTE PTR [rax], 1
+        assert_eq!(
+            to_bytes(
+                "mov",
+                &[RegMem(BYTE), Imm(BYTE)],
+                vec![
+                    addressing(GeneralPurposeRegister::RAX, None),
+                    imm(Immediate::I8(1))
+                ],
+            ),
+            vec![0xc6, 0x00, 0x01]
+        );
+
+        // mov WORD PTR [rbp-2], 300
+        assert_eq!(
+            to_bytes(
+                "mov",
+                &[RegMem(WORD), Imm(WORD)],
+                vec![
+                    addressing(GeneralPurposeRegister::RBP, Some(Displacement::DISP8(-2))),
+                    imm(Immediate::I16(300)),
+                ],
+            ),
+            vec![0x66, 0xc7, 0x45, 0xfe, 0x2c, 0x01]
+        );
     }
 }
@@ -1,20 +1,38 @@
-use crate::assembler::resource::*;
-
-#[allow(dead_code)]
-const NEGRM64_CASES: [Instruction; 1] = [Instruction {
-    opcode: Opcode::NEGRM64 {
-        rm64: Operand::GENERALREGISTER(GeneralPurposeRegister::RAX),
-    },
-}];
-
 #[cfg(test)]
 mod to_bytes_tests {
-    use super::*;
+    use super::super::common::*;
+    use crate::assembler::resource::*;
+    use OperandKind::*;
+    use OperandSize::*;
 
     #[test]
-    fn negrm64_test() {
-        let inst = &NEGRM64_CASES[0];
+    fn negrm_test() {
+        assert_eq!(
+            to_bytes(
+                "neg",
+                &[RegMem(QWORD)],
+                vec![reg(GeneralPurposeRegister::RAX)]
+            ),
+            vec![0x48, 0xf7, 0xd8]
+        );
+
+        assert_eq!(
+            to_bytes(
+                "neg",
+                &[RegMem(DWORD)],
+                vec![reg(GeneralPurposeRegister::ECX)]
+            ),
+            vec![0xf7, 0xd9]
+        );
 
-        assert_eq!(inst.to_bytes(), vec![0x48, 0xf7, 0xd8]);
+        // sil を使うので REX が必要
+        assert_eq!(
+            to_bytes(
+                "neg",
+                &[RegMem(BYTE)],
+                vec![reg(GeneralPurposeRegister::SIL)]
+            ),
+            vec![0x40, 0xf6, 0xde]
+        );
     }
 }
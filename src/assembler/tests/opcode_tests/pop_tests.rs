@@ -1,27 +1,21 @@
-use crate::assembler::resource::*;
-
-#[allow(dead_code)]
-const POPR64_CASES: [Instruction; 2] = [
-    Instruction {
-        opcode: Opcode::POPR64 {
-            r64: GeneralPurposeRegister::RAX,
-        },
-    },
-    Instruction {
-        opcode: Opcode::POPR64 {
-            r64: GeneralPurposeRegister::R15,
-        },
-    },
-];
-
 #[cfg(test)]
 mod to_bytes_tests {
-    use super::*;
+    use super::super::common::*;
+    use crate::assembler::resource::*;
+    use OperandKind::*;
+    use OperandSize::*;
 
     #[test]
     fn popr64_test() {
-        let inst = &POPR64_CASES[0];
+        assert_eq!(
+            to_bytes("pop", &[Reg(QWORD)], vec![reg(GeneralPurposeRegister::RAX)]),
+            vec![0x58]
+        );
 
-        assert_eq!(inst.to_bytes(), vec![0x58]);
+        // r8 以降は REX.B で拡張する
+        assert_eq!(
+            to_bytes("pop", &[Reg(QWORD)], vec![reg(GeneralPurposeRegister::R15)]),
+            vec![0x41, 0x5f]
+        );
     }
 }
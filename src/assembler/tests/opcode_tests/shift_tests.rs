@@ -0,0 +1,186 @@
+#[cfg(test)]
+mod to_bytes_tests {
+    use super::super::common::*;
+    use crate::assembler::resource::*;
+    use OperandKind::*;
+    use OperandSize::*;
+
+    #[test]
+    fn shiftrm1_test() {
+        // shlq %rax
+        assert_eq!(
+            vec![0x48, 0xd1, 0xe0],
+            to_bytes(
+                "shl",
+                &[RegMem(QWORD), One],
+                vec![reg(GeneralPurposeRegister::RAX), imm(Immediate::I8(1))],
+            )
+        );
+        // shrb %al
+        assert_eq!(
+            vec![0xd0, 0xe8],
+            to_bytes(
+                "shr",
+                &[RegMem(BYTE), One],
+                vec![reg(GeneralPurposeRegister::AL), imm(Immediate::I8(1))],
+            )
+        );
+        // rclw %dx
+        assert_eq!(
+            vec![0x66, 0xd1, 0xd2],
+            to_bytes(
+                "rcl",
+                &[RegMem(WORD), One],
+                vec![reg(GeneralPurposeRegister::DX), imm(Immediate::I8(1))],
+            )
+        );
+    }
+
+    #[test]
+    fn shiftrmimm_test() {
+        // shrl $31, %eax
+        assert_eq!(
+            vec![0xc1, 0xe8, 0x1f],
+            to_bytes(
+                "shr",
+                &[RegMem(DWORD), Imm(BYTE)],
+                vec![reg(GeneralPurposeRegister::EAX), imm(Immediate::I8(31))],
+            )
+        );
+        // shrb $7, %r10b
+        assert_eq!(
+            vec![0x41, 0xc0, 0xea, 0x07],
+            to_bytes(
+                "shr",
+                &[RegMem(BYTE), Imm(BYTE)],
+                vec![reg(GeneralPurposeRegister::R10B), imm(Immediate::I8(7))],
+            )
+        );
+        // sarq $63, %r15
+        assert_eq!(
+            vec![0x49, 0xc1, 0xff, 0x3f],
+            to_bytes(
+                "sar",
+                &[RegMem(QWORD), Imm(BYTE)],
+                vec![reg(GeneralPurposeRegister::R15), imm(Immediate::I8(63))],
+            )
+        );
+        // shlq $4, -8(%rbp)
+        assert_eq!(
+            vec![0x48, 0xc1, 0x65, 0xf8, 0x04],
+            to_bytes(
+                "shl",
+                &[RegMem(QWORD), Imm(BYTE)],
+                vec![
+                    Operand::ADDRESSING {
+                        base: GeneralPurposeRegister::RBP,
+                        index: None,
+                        disp: Some(Displacement::DISP8(-8)),
+                        scale: None,
+                    },
+                    imm(Immediate::I8(4)),
+                ],
+            )
+        );
+    }
+
+    #[test]
+    fn shiftrmcl_test() {
+        // sarl %cl, %r9d
+        assert_eq!(
+            vec![0x41, 0xd3, 0xf9],
+            to_bytes(
+                "sar",
+                &[RegMem(DWORD), Cl],
+                vec![reg(GeneralPurposeRegister::R9D), cl()],
+            )
+        );
+        // rolb %cl, %sil
+        assert_eq!(
+            vec![0x40, 0xd2, 0xc6],
+            to_bytes(
+                "rol",
+                &[RegMem(BYTE), Cl],
+                vec![reg(GeneralPurposeRegister::SIL), cl()],
+            )
+        );
+    }
+
+    #[test]
+    fn double_shift_test() {
+        // shldq $3, %rbx, %rax
+        assert_eq!(
+            vec![0x48, 0x0f, 0xa4, 0xd8, 0x03],
+            to_bytes(
+                "shld",
+                &[RegMem(QWORD), Reg(QWORD), Imm(BYTE)],
+                vec![
+                    reg(GeneralPurposeRegister::RAX),
+                    reg(GeneralPurposeRegister::RBX),
+                    imm(Immediate::I8(3)),
+                ],
+            )
+        );
+        // shldw $4, %cx, %dx
+        assert_eq!(
+            vec![0x66, 0x0f, 0xa4, 0xca, 0x04],
+            to_bytes(
+                "shld",
+                &[RegMem(WORD), Reg(WORD), Imm(BYTE)],
+                vec![
+                    reg(GeneralPurposeRegister::DX),
+                    reg(GeneralPurposeRegister::CX),
+                    imm(Immediate::I8(4)),
+                ],
+            )
+        );
+        // shldl %cl, %r8d, (%rdi)
+        assert_eq!(
+            vec![0x44, 0x0f, 0xa5, 0x07],
+            to_bytes(
+                "shld",
+                &[RegMem(DWORD), Reg(DWORD), Cl],
+                vec![
+                    Operand::ADDRESSING {
+                        base: GeneralPurposeRegister::RDI,
+                        index: None,
+                        disp: None,
+                        scale: None,
+                    },
+                    reg(GeneralPurposeRegister::R8D),
+                    cl(),
+                ],
+            )
+        );
+        // shrdq $5, %r12, %r13
+        assert_eq!(
+            vec![0x4d, 0x0f, 0xac, 0xe5, 0x05],
+            to_bytes(
+                "shrd",
+                &[RegMem(QWORD), Reg(QWORD), Imm(BYTE)],
+                vec![
+                    reg(GeneralPurposeRegister::R13),
+                    reg(GeneralPurposeRegister::R12),
+                    imm(Immediate::I8(5)),
+                ],
+            )
+        );
+        // shrdl %cl, %eax, %edx
+        assert_eq!(
+            vec![0x0f, 0xad, 0xc2],
+            to_bytes(
+                "shrd",
+                &[RegMem(DWORD), Reg(DWORD), Cl],
+                vec![
+                    reg(GeneralPurposeRegister::EDX),
+                    reg(GeneralPurposeRegister::EAX),
+                    cl(),
+                ],
+            )
+        );
+    }
+
+    fn cl() -> Operand {
+        Operand::GENERALREGISTER(GeneralPurposeRegister::CL)
+    }
+}
@@ -0,0 +1,151 @@
+#[cfg(test)]
+mod to_bytes_tests {
+    use super::super::common::*;
+    use crate::assembler::resource::*;
+    use OperandKind::*;
+    use OperandSize::*;
+
+    #[test]
+    fn movq_test() {
+        // movq %rax, %xmm0
+        assert_eq!(
+            vec![0x66, 0x48, 0x0f, 0x6e, 0xc0],
+            to_bytes(
+                "movq",
+                &[Xmm, RegMem(QWORD)],
+                vec![xmm(XMMRegister::XMM0), reg(GeneralPurposeRegister::RAX)],
+            )
+        );
+        // movq %xmm9, %r10
+        assert_eq!(
+            vec![0x66, 0x4d, 0x0f, 0x7e, 0xca],
+            to_bytes(
+                "movq",
+                &[RegMem(QWORD), Xmm],
+                vec![reg(GeneralPurposeRegister::R10), xmm(XMMRegister::XMM9)],
+            )
+        );
+        // movq %xmm0, %xmm1
+        assert_eq!(
+            vec![0xf3, 0x0f, 0x7e, 0xc8],
+            to_bytes(
+                "movq",
+                &[Xmm, XmmMem(QWORD)],
+                vec![xmm(XMMRegister::XMM1), xmm(XMMRegister::XMM0)],
+            )
+        );
+        // movd %eax, %xmm15
+        assert_eq!(
+            vec![0x66, 0x44, 0x0f, 0x6e, 0xf8],
+            to_bytes(
+                "movd",
+                &[Xmm, RegMem(DWORD)],
+                vec![xmm(XMMRegister::XMM15), reg(GeneralPurposeRegister::EAX)],
+            )
+        );
+    }
+
+    #[test]
+    fn movsd_test() {
+        // movsd %xmm1, %xmm0
+        assert_eq!(
+            vec![0xf2, 0x0f, 0x10, 0xc1],
+            to_bytes(
+                "movsd",
+                &[Xmm, XmmMem(QWORD)],
+                vec![xmm(XMMRegister::XMM0), xmm(XMMRegister::XMM1)],
+            )
+        );
+        // movsd -8(%rbp), %xmm8
+        // mandatory prefix は REX prefix より前
+        assert_eq!(
+            vec![0xf2, 0x44, 0x0f, 0x10, 0x45, 0xf8],
+            to_bytes(
+                "movsd",
+                &[Xmm, XmmMem(QWORD)],
+                vec![
+                    xmm(XMMRegister::XMM8),
+                    addressing(GeneralPurposeRegister::RBP, Some(Displacement::DISP8(-8))),
+                ],
+            )
+        );
+        // movsd %xmm0, -8(%rbp)
+        assert_eq!(
+            vec![0xf2, 0x0f, 0x11, 0x45, 0xf8],
+            to_bytes(
+                "movsd",
+                &[XmmMem(QWORD), Xmm],
+                vec![
+                    addressing(GeneralPurposeRegister::RBP, Some(Displacement::DISP8(-8))),
+                    xmm(XMMRegister::XMM0),
+                ],
+            )
+        );
+    }
+
+    #[test]
+    fn arithmetic_test() {
+        let all = [
+            ("addsd", vec![0xf2, 0x0f, 0x58, 0xc1]),
+            ("subsd", vec![0xf2, 0x0f, 0x5c, 0xc1]),
+            ("mulsd", vec![0xf2, 0x0f, 0x59, 0xc1]),
+            ("divsd", vec![0xf2, 0x0f, 0x5e, 0xc1]),
+            ("sqrtsd", vec![0xf2, 0x0f, 0x51, 0xc1]),
+            ("ucomisd", vec![0x66, 0x0f, 0x2e, 0xc1]),
+            ("comisd", vec![0x66, 0x0f, 0x2f, 0xc1]),
+        ];
+
+        // xxx %xmm1, %xmm0
+        for (mnemonic, codes) in all.iter() {
+            assert_eq!(
+                *codes,
+                to_bytes(
+                    mnemonic,
+                    &[Xmm, XmmMem(QWORD)],
+                    vec![xmm(XMMRegister::XMM0), xmm(XMMRegister::XMM1)],
+                )
+            );
+        }
+
+        // pxor %xmm8, %xmm8
+        assert_eq!(
+            vec![0x66, 0x45, 0x0f, 0xef, 0xc0],
+            to_bytes(
+                "pxor",
+                &[Xmm, XmmMem(XMMWORD)],
+                vec![xmm(XMMRegister::XMM8), xmm(XMMRegister::XMM8)],
+            )
+        );
+    }
+
+    #[test]
+    fn convert_test() {
+        // cvtsi2sdq %rax, %xmm0
+        assert_eq!(
+            vec![0xf2, 0x48, 0x0f, 0x2a, 0xc0],
+            to_bytes(
+                "cvtsi2sd",
+                &[Xmm, RegMem(QWORD)],
+                vec![xmm(XMMRegister::XMM0), reg(GeneralPurposeRegister::RAX)],
+            )
+        );
+        // cvttsd2si %xmm0, %r11
+        assert_eq!(
+            vec![0xf2, 0x4c, 0x0f, 0x2c, 0xd8],
+            to_bytes(
+                "cvttsd2si",
+                &[Reg(QWORD), XmmMem(QWORD)],
+                vec![reg(GeneralPurposeRegister::R11), xmm(XMMRegister::XMM0)],
+            )
+        );
+        // cvtss2sd %xmm1, %xmm0
+        assert_eq!(
+            vec![0xf3, 0x0f, 0x5a, 0xc1],
+            to_bytes(
+                "cvtss2sd",
+                &[Xmm, XmmMem(DWORD)],
+                vec![xmm(XMMRegister::XMM0), xmm(XMMRegister::XMM1)],
+            )
+        );
+    }
+}
@@ -1,66 +1,51 @@
-use crate::assembler::resource::*;
-
-#[allow(dead_code)]
-const SUBRM64R64_CASES: [Instruction; 1] = [Instruction {
-    opcode: Opcode::SUBRM64R64 {
-        rm64: Operand::ADDRESSING {
-            base: GeneralPurposeRegister::RAX,
-            index: None,
-            scale: None,
-            disp: None,
-        },
-        r64: GeneralPurposeRegister::RBX,
-    },
-}];
-
-#[allow(dead_code)]
-const SUBR64RM64_CASES: [Instruction; 1] = [Instruction {
-    opcode: Opcode::SUBR64RM64 {
-        r64: GeneralPurposeRegister::RBX,
-        rm64: Operand::ADDRESSING {
-            base: GeneralPurposeRegister::RAX,
-            index: None,
-            scale: None,
-            disp: None,
-        },
-    },
-}];
-
-#[allow(dead_code)]
-const SUBRM64IMM32_CASES: [Instruction; 1] = [Instruction {
-    opcode: Opcode::SUBRM64IMM32 {
-        rm64: Operand::GENERALREGISTER(GeneralPurposeRegister::RAX),
-        imm: Immediate::I32(60),
-    },
-}];
-
 #[cfg(test)]
 mod to_bytes_tests {
-    use super::*;
+    use super::super::common::*;
+    use crate::assembler::resource::*;
+    use OperandKind::*;
+    use OperandSize::*;
 
     #[test]
     fn subrm64r64_test() {
         // sub QWORD PTR [rax], rbx
-        let inst = &SUBRM64R64_CASES[0];
-
-        assert_eq!(inst.to_bytes(), vec![0x48, 0x29, 0x18]);
+        assert_eq!(
+            to_bytes(
+                "sub",
+                &[RegMem(QWORD), Reg(QWORD)],
+                vec![
+                    addressing(GeneralPurposeRegister::RAX, None),
+                    reg(GeneralPurposeRegister::RBX),
+                ],
+            ),
+            vec![0x48, 0x29, 0x18]
+        );
     }
 
     #[test]
     fn subr64rm64_test() {
         // sub rbx, QWORD PTR [rax]
-        let inst = &SUBR64RM64_CASES[0];
-
-        assert_eq!(inst.to_bytes(), vec![0x48, 0x2b, 0x18]);
+        assert_eq!(
+            to_bytes(
+                "sub",
+                &[Reg(QWORD), RegMem(QWORD)],
+                vec![
+                    reg(GeneralPurposeRegister::RBX),
+                    addressing(GeneralPurposeRegister::RAX, None),
+                ],
+            ),
+            vec![0x48, 0x2b, 0x18]
+        );
     }
 
     #[test]
     fn subrm64imm32_test() {
         // sub rax, 60
-        let inst = &SUBRM64IMM32_CASES[0];
-
         assert_eq!(
-            inst.to_bytes(),
+            to_bytes(
+                "sub",
+                &[RegMem(QWORD), Imm(DWORD)],
+                vec![reg(GeneralPurposeRegister::RAX), imm(Immediate::I32(60))],
+            ),
             vec![0x48, 0x81, 0xe8, 0x3c, 0x00, 0x00, 0x00]
         );
     }
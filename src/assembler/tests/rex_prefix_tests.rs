@@ -45,4 +45,12 @@ mod to_byte_tests {
             prefix.to_byte()
         );
     }
+
+    #[test]
+    fn from_byte_test() {
+        let prefix = REXPrefix::from_byte(0x4d);
+
+        assert_eq!("0100WR-B", format!("{:?}", prefix).as_str());
+        assert_eq!(0x4d, prefix.to_byte());
+    }
 }
@@ -0,0 +1,54 @@
+#[cfg(test)]
+mod format_tests {
+    use crate::assembler::resource::*;
+
+    #[test]
+    fn display_vex_prefix_test() {
+        // VEX.256.0F.W0( vaddps %ymm2, %ymm1, %ymm0 )
+        let prefix = VEXPrefix {
+            r_bit: false,
+            x_bit: false,
+            b_bit: false,
+            map: 1,
+            w_bit: false,
+            vvvv: 1,
+            length: VectorLength::L256,
+            pp: 0,
+        };
+
+        assert_eq!("VEX(c5 f4)", format!("{}", prefix).as_str());
+    }
+
+    #[test]
+    fn three_byte_vex_prefix_test() {
+        // VEX.256.66.0F3A.W1( vpermq $1, (%r11), %ymm0 )
+        let prefix = VEXPrefix {
+            r_bit: false,
+            x_bit: false,
+            b_bit: true,
+            map: 3,
+            w_bit: true,
+            vvvv: 0,
+            length: VectorLength::L256,
+            pp: 1,
+        };
+
+        assert_eq!(vec![0xc4, 0xc3, 0xfd], prefix.to_bytes());
+        assert_eq!(prefix, VEXPrefix::from_bytes(&prefix.to_bytes()));
+        assert_eq!(Some(0x66), prefix.prefix());
+        assert_eq!(Some(&[0x0f, 0x3a][..]), prefix.escape());
+    }
+
+    #[test]
+    fn two_byte_vex_prefix_test() {
+        // VEX.128.F3.0F.W0, ModRM:reg が拡張されている
+        let prefix = VEXPrefix::from_bytes(&[0xc5, 0x7a]);
+
+        assert!(prefix.r_bit && !prefix.x_bit && !prefix.b_bit && !prefix.w_bit);
+        assert_eq!(1, prefix.map);
+        assert_eq!(0, prefix.vvvv);
+        assert_eq!(VectorLength::L128, prefix.length);
+        assert_eq!(Some(0xf3), prefix.prefix());
+        assert_eq!(vec![0xc5, 0x7a], prefix.to_bytes());
+    }
+}
@@ -2,4 +2,8 @@
 
 mod assembler;
 
-pub use assembler::{assemble_code, assemble_file, Syntax};
+pub use assembler::{
+    assemble_code, assemble_code_with_options, assemble_file, assemble_file_with_options, decode,
+    decode_instruction, AsmError, Assembled, DecodeError, DecodedInstruction, Instruction, Listing,
+    ListingChunk, ListingLine, ListingRelocation, Opcode, Operand, Options, Severity, Syntax,
+};
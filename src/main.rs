@@ -1,14 +1,82 @@
+const USAGE: &str =
+    "usage: ./asmpeach [--defsym <name>=<value>] [-I <dir>] [-al[=<listing-file>]] <file-path>";
+
 pub fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = std::env::args().collect::<Vec<String>>();
 
-    if args.len() != 2 {
-        eprintln!("usage: ./asmpeach <file-path>");
-        std::process::exit(1);
+    let mut options = asmpeach::Options::new(asmpeach::Syntax::ATANDT);
+    let mut file_path = None;
+    // `-al` のみの場合は標準出力に出力する
+    let mut listing_file = None;
+
+    let mut args = args.iter().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--defsym" => {
+                let (name, value) = match args.next().and_then(|d| parse_defsym(d)) {
+                    Some(defsym) => defsym,
+                    None => exit_with_usage(),
+                };
+                options.define_symbol(name, value);
+            }
+            "-I" => match args.next() {
+                Some(dir) => options.add_include_path(dir),
+                None => exit_with_usage(),
+            },
+            _ if arg.starts_with("-I") => options.add_include_path(&arg[2..]),
+            "-al" => options.listing = true,
+            _ if arg.starts_with("-al=") => {
+                options.listing = true;
+                listing_file = Some(&arg[4..]);
+            }
+            _ if file_path.is_none() && !arg.starts_with('-') => file_path = Some(arg),
+            _ => exit_with_usage(),
+        }
+    }
+
+    let file_path = match file_path {
+        Some(file_path) => file_path,
+        None => exit_with_usage(),
+    };
+
+    let assembled = match asmpeach::assemble_file_with_options(file_path, &options) {
+        Ok(assembled) => assembled,
+        Err(errors) => {
+            for e in errors.iter() {
+                eprintln!("{}", e);
+            }
+            std::process::exit(1);
+        }
+    };
+
+    for warning in assembled.warnings.iter() {
+        eprintln!("{}", warning);
     }
 
-    let elf_builder = asmpeach::assemble_file(&args[1], asmpeach::Syntax::ATANDT)?;
+    if let Some(listing) = &assembled.listing {
+        match listing_file {
+            Some(listing_file) => std::fs::write(listing_file, listing.to_string())?,
+            None => print!("{}", listing),
+        }
+    }
 
-    elf_builder.generate_elf_file("obj.o", 0o644)?;
+    assembled.elf.generate_elf_file("obj.o", 0o644)?;
 
     Ok(())
 }
+
+/// `NAME=VALUE` ( VALUE は10進数か `0x` で始まる16進数 )
+fn parse_defsym(defsym: &str) -> Option<(&str, i64)> {
+    let (name, value) = defsym.split_once('=')?;
+    let value = match value.strip_prefix("0x") {
+        Some(hex) => i64::from_str_radix(hex, 16).ok()?,
+        None => value.parse().ok()?,
+    };
+
+    Some((name, value))
+}
+
+fn exit_with_usage() -> ! {
+    eprintln!("{}", USAGE);
+    std::process::exit(1);
+}
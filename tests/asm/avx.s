@@ -0,0 +1,42 @@
+	.section	.rodata
+	.align 32
+.LC0:
+	.long	1, 2, 3, 4, 5, 6, 7, 8
+.LC1:
+	.long	3
+.LC2:
+	# 2.0f
+	.long	0x40000000
+.LC3:
+	# 15.0f
+	.long	0x41700000
+
+	.text
+	.globl	main
+	.type	main, @function
+main:
+	pushq	%rbp
+	movq	%rsp, %rbp
+	# [1, ..., 8] + 3 = [4, ..., 11]
+	vmovdqu	.LC0(%rip), %ymm0
+	vpbroadcastd	.LC1(%rip), %ymm1
+	vpaddd	%ymm1, %ymm0, %ymm0
+	# 上位と下位の 128bit を入れ替えて足す( 4 + 8 = 12 )
+	vpermq	$0x4e, %ymm0, %ymm2
+	vpaddd	%ymm2, %ymm0, %ymm8
+	vmovdqu	%ymm8, -32(%rbp)
+	movl	-32(%rbp), %eax
+	# 0 + 2.0 * 15.0 = 30.0
+	vbroadcastss	.LC2(%rip), %ymm4
+	vbroadcastss	.LC3(%rip), %ymm5
+	vxorps	%ymm6, %ymm6, %ymm6
+	vfmadd231ps	%ymm4, %ymm5, %ymm6
+	vmovups	%ymm6, -64(%rbp)
+	vzeroupper
+	movss	-64(%rbp), %xmm7
+	cvttss2si	%xmm7, %ecx
+	# 12 + 30 = 42
+	addl	%ecx, %eax
+	popq	%rbp
+	ret
+	.size	main, .-main
@@ -0,0 +1,52 @@
+	.text
+	.globl	main
+	.type	main, @function
+main:
+	pushq	%rbp
+	movq	%rsp, %rbp
+	subq	$16, %rsp
+	movw	$300, -2(%rbp)
+	movb	$1, -3(%rbp)
+	# 1 + 1 = 2
+	incb	-3(%rbp)
+	movb	-3(%rbp), %cl
+	negb	%cl
+	cmpb	$-2, %cl
+	jne	.L1
+	# 300 + 1 = 301
+	incw	-2(%rbp)
+	cmpw	$301, -2(%rbp)
+	jne	.L1
+	# 301 - 49 = 252
+	movw	-2(%rbp), %ax
+	subw	$49, %ax
+	# 252 / 6 = 42 ... 0
+	movb	$6, %bl
+	idivb	%bl
+	cmpb	$0, %ah
+	jne	.L1
+	movb	%al, -4(%rbp)
+	# 7 * 6 = 42
+	movb	$7, %al
+	imulb	%bl
+	cmpb	-4(%rbp), %al
+	jne	.L1
+	# 42 * 1000 = 42000
+	movw	$1000, %dx
+	imulw	%dx, %ax
+	negw	%ax
+	negw	%ax
+	movw	%ax, -6(%rbp)
+	movzwl	-6(%rbp), %eax
+	movl	$1000, %ecx
+	cltd
+	idivl	%ecx
+	movq	%rbp, %rsp
+	popq	%rbp
+	ret
+.L1:
+	movq	$1, %rax
+	movq	%rbp, %rsp
+	popq	%rbp
+	ret
+	.size	main, .-main
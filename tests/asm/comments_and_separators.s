@@ -0,0 +1,11 @@
+/*
+ * comments, statement separators and spacing inside operands
+ */
+	.text
+	.globl	main ; .type main,@function
+main:	pushq %rbp; movq %rsp,%rbp    # prologue
+	subq	$16,%rsp
+	movq	$40, -8( %rbp )
+	movq	$2,%rax ; addq -8(%rbp),%rax    /* 42 */
+	movq	%rbp,%rsp; popq %rbp
+	ret
@@ -0,0 +1,38 @@
+	.set	FRAME_SIZE, 256
+
+	.macro	frame_setup size
+	.ifc	\size, large
+	subq	$FRAME_SIZE, %rsp
+	.else
+	subq	$16, %rsp
+	.endif
+	.endm
+
+	.text
+	.globl	main
+	.type	main, @function
+main:
+	pushq	%rbp
+	movq	%rsp, %rbp
+	.if FRAME_SIZE > 128
+	frame_setup large
+	.ifdef HAVE_AVX2
+	movq	$1, %rax
+	.elseif FRAME_SIZE == 256
+	movq	$42, %rax
+	.else
+	movq	$2, %rax
+	.endif
+	.else
+	.error	"FRAME_SIZE is too small"
+	frame_setup small
+	movq	$3, %rax
+	.endif
+	.ifndef HAVE_AVX2
+	.ifnc	avx2, sse2
+	movq	%rbp, %rsp
+	.endif
+	.endif
+	popq	%rbp
+	ret
+	.size	main, .-main
@@ -0,0 +1,35 @@
+	.text
+	.globl	main
+	.type	main, @function
+main:
+	movq	$0, %rax
+	movq	$1, %rdx
+	cmpq	$0, %rax
+	jnz	.L1
+	addq	%rdx, %rax
+.L1:
+	cmpq	$1, %rax
+	jne	.L1
+	cmpq	$2, %rax
+	jnae	.L2
+	movq	$0, %rax
+	ret
+.L2:
+	cmpq	$5, %rax
+	jg	.L2
+	movq	$40, %rdx
+	addq	%rdx, %rax
+	cmpq	$41, %rax
+	jz	.L3
+	cmpq	$41, %rax
+	ja	.L3
+	movq	$0, %rax
+.L3:
+	addq	%rax, %rax
+	cmpq	$82, %rax
+	je	.L4
+	ret
+.L4:
+	movq	$42, %rax
+	ret
+	.size	main, .-main
@@ -0,0 +1,24 @@
+	.text
+	.type	negate, @function
+negate:
+	movq	$-42, %rdi
+	jmp	absolute
+.Lnegated:
+	ret
+	.size	negate, .-negate
+
+	.type	absolute, @function
+absolute:
+	call	abs
+	jmp	.Lnegated
+	.size	absolute, .-absolute
+
+	.globl	main
+	.type	main, @function
+main:
+	pushq	%rbp
+	movq	%rsp, %rbp
+	call	negate
+	popq	%rbp
+	ret
+	.size	main, .-main
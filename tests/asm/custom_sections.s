@@ -0,0 +1,30 @@
+	.text
+	.type	init, @function
+init:
+	movl	$40, value(%rip)
+	ret
+	.size	init, .-init
+
+	.globl	main
+	.type	main, @function
+main:
+	movl	value(%rip), %eax
+	addl	bonus(%rip), %eax
+	ret
+	.size	main, .-main
+
+	.data
+	.type	value, @object
+value:
+	.long	0
+	.type	bonus, @object
+bonus:
+	# 4 バイトの 2
+	.fill	1, 4, 2
+
+	# main より前に init を呼び出す
+	.section	.init_array,"aw"
+	.align 8
+	.quad	init
+
+	.section	.note.GNU-stack,"",@progbits
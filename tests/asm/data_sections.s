@@ -0,0 +1,32 @@
+	.globl	g
+	.data
+	.align 8
+	.type	g, @object
+	.size	g, 8
+g:
+	.quad	30
+	.globl	z
+	.bss
+	.align 8
+	.type	z, @object
+	.size	z, 8
+z:
+	.zero	8
+	.section	.rodata
+.LC0:
+	.string	"abc"
+	.align 8
+.LC1:
+	.quad	12
+	.text
+	.globl	main
+	.type	main, @function
+main:
+	pushq	%rbp
+	movq	%rsp, %rbp
+	movq	g(%rip), %rax
+	movq	.LC1(%rip), %rdx
+	movq	%rdx, z(%rip)
+	addq	z(%rip), %rax
+	popq	%rbp
+	ret
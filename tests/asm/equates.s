@@ -0,0 +1,29 @@
+	.globl	ANSWER
+	.set	ANSWER, 42
+	.equ	FRAME, 2*8
+	.equiv	SLOT, -FRAME+8
+	.set	OFFSET, answer+8
+
+	.data
+	.align 8
+answer:
+	.quad	0
+	.quad	ANSWER-FRAME
+
+	.text
+	.globl	main
+	.type	main, @function
+main:
+	pushq	%rbp
+	movq	%rsp, %rbp
+	subq	$FRAME, %rsp
+	movq	$ANSWER, SLOT(%rbp)
+	movq	OFFSET(%rip), %rax
+	addq	SLOT(%rbp), %rax
+	subq	$(ANSWER-FRAME), %rax
+	.set	FRAME, 0
+	subq	$FRAME, %rsp
+	movq	%rbp, %rsp
+	popq	%rbp
+	ret
+	.size	main, .-main
@@ -0,0 +1,41 @@
+	.data
+	.align 8
+pair:
+	.quad	10
+	.quad	(1<<4)+4
+pointer:
+	.quad	pair+8
+labels:
+	.quad	.L2
+	.long	main-.
+	.zero	2*2
+
+	.section	.rodata
+	.align 8
+.Ltable:
+	.quad	.L3-.L2
+
+	.text
+	.globl	main
+	.type	main, @function
+main:
+	pushq	%rbp
+	movq	%rsp, %rbp
+	subq	$(2*8), %rsp
+	movq	pair(%rip), %rax
+	movq	pair+8(%rip), %rcx
+	movq	%rcx, -16+8(%rbp)
+	movq	pointer(%rip), %rdx
+	movq	(%rdx), %rdx
+	movq	%rdx, -16(%rbp)
+	addq	-8(%rbp), %rax
+	addq	-2*8(%rbp), %rax
+	subq	$0x14, %rax
+.L2:
+	subq	$-(1<<3), %rax
+.L3:
+	addq	.Ltable(%rip), %rax
+	movq	%rbp, %rsp
+	popq	%rbp
+	ret
+	.size	main, .-main
@@ -0,0 +1,51 @@
+	.data
+bytes:
+	.byte	0xff, 0x80
+words:
+	.short	-2
+longs:
+	.long	-40
+
+	.text
+	.globl	main
+	.type	main, @function
+main:
+	# 255 + (-128) = 127
+	leaq	bytes(%rip), %rdi
+	movzbl	(%rdi), %eax
+	movsbq	1(%rdi), %rcx
+	addq	%rcx, %rax
+	# 127 + 65534 + (-2) = 65659
+	movzwq	words(%rip), %rdx
+	addq	%rdx, %rax
+	movswl	words(%rip), %edx
+	addl	%edx, %eax
+	# 65659 & 0xffff = 123
+	movzwl	%ax, %eax
+	# 123 + (-40) = 83
+	movslq	longs(%rip), %rsi
+	addq	%rsi, %rax
+	movq	%rax, %r8
+	# -1 を rdx:rax に符号拡張する
+	movl	$-1, %eax
+	cltq
+	cqto
+	cmpq	%rax, %rdx
+	jne	.L1
+	movq	$0xff80, %rax
+	cwtl
+	cmpl	$-128, %eax
+	jne	.L1
+	# 83 / 2 = 41
+	movq	%r8, %rax
+	sarq	%rax
+	cltd
+	cmpl	$0, %edx
+	sete	%cl
+	movzbl	%cl, %ecx
+	addl	%ecx, %eax
+	ret
+.L1:
+	movq	$1, %rax
+	ret
+	.size	main, .-main
@@ -0,0 +1,15 @@
+	.include "include/frame.inc"
+
+	.data
+	.align 8
+table:
+	.incbin	"include/table.bin", 4, 8
+
+	.text
+	.globl	main
+	.type	main, @function
+main:
+	enter
+	movq	table(%rip), %rax
+	leave_and_ret
+	.size	main, .-main
@@ -0,0 +1,21 @@
+	.intel_syntax noprefix
+	.text
+	.globl	foo
+	.type	foo, @function
+foo:
+	push	rbp
+	mov	rbp, rsp
+	mov	QWORD PTR [rbp-8], 40
+	mov	rax, QWORD PTR -8[rbp]
+	mov	rdx, 2
+	add	rax, rdx
+	pop	rbp
+	ret
+	.globl	main
+	.type	main, @function
+main:
+	push	rbp
+	mov	rbp, rsp
+	call	foo
+	pop	rbp
+	ret
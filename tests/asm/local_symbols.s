@@ -0,0 +1,18 @@
+	.text
+	.type	helper, @function
+helper:
+	pushq	%rbp
+	movq	%rsp, %rbp
+	movq	$42, %rax
+	popq	%rbp
+	ret
+	.size	helper, .-helper
+	.globl	main
+	.type	main, @function
+main:
+	pushq	%rbp
+	movq	%rsp, %rbp
+	call	helper
+	popq	%rbp
+	ret
+	.size	main, .-main
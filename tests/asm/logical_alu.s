@@ -0,0 +1,34 @@
+	.text
+	.globl	main
+	.type	main, @function
+main:
+	pushq	%rbp
+	movq	%rsp, %rbp
+	movq	$0xff, %rax
+	andb	$0x2a, %al
+	movq	$-1, %rcx
+	xorq	%rcx, %rax
+	notq	%rax
+	orw	$0x100, %ax
+	andl	$0xff, %eax
+	cmpq	$42, %rax
+	jne	.L1
+	pushq	%rax
+	xorl	%eax, %eax
+	adcl	-8(%rbp), %eax
+	popq	%rcx
+	testq	%rax, %rcx
+	jz	.L1
+	cmpl	%ecx, %eax
+	jne	.L1
+	sbbb	%cl, %cl
+	testb	$1, %cl
+	jnz	.L1
+	popq	%rbp
+	ret
+.L1:
+	movq	$1, %rax
+	movq	%rbp, %rsp
+	popq	%rbp
+	ret
+	.size	main, .-main
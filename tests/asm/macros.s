@@ -0,0 +1,35 @@
+	.macro	push_all regs:vararg
+	.irp	r, \regs
+	pushq	%\r
+	.endr
+	.endm
+
+	.macro	add_twice reg, n=1
+	.rept	2
+	subq	$-\n, %\reg
+	.endr
+	.endm
+
+	.macro	skip
+	jmp	.Lskip\@
+	movq	$0, %rax
+.Lskip\@:
+	.endm
+
+	.text
+	.globl	main
+	.type	main, @function
+main:
+	pushq	%rbp
+	movq	%rsp, %rbp
+	push_all rbx, rcx
+	movq	$0, %rax
+	add_twice rax, 20
+	skip
+	add_twice rax
+	skip
+	popq	%rcx
+	popq	%rbx
+	popq	%rbp
+	ret
+	.size	main, .-main
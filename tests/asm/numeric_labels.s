@@ -0,0 +1,21 @@
+	.text
+	.globl	main
+	.type	main, @function
+main:
+	movq	$0, %rax
+	movq	$2, %rdx
+	jmp	2f
+1:
+	addq	%rdx, %rax
+2:
+	cmpq	$40, %rax
+	jne	1b
+	movq	$1, %rdx
+	jmp	1f
+	movq	$0, %rax
+1:
+	addq	%rdx, %rax
+	cmpq	$42, %rax
+	jne	1b
+	ret
+	.size	main, .-main
@@ -0,0 +1,21 @@
+# 関数の途中で .rodata に切り替えても,.text に戻れば同じ関数の続きになる
+	.text
+	.globl	main
+	.type	main, @function
+main:
+	movl	$40, %eax
+	.section	.rodata
+.LC0:
+	.long	2
+	.text
+	addl	.LC0(%rip), %eax
+	.section	.rodata
+.LC1:
+	.long	1
+	.text
+# 直前の命令から続けて実行される
+.L2:
+	incl	%eax
+	subl	$1, %eax
+	ret
+	.size	main, .-main
@@ -0,0 +1,25 @@
+	.text
+	.globl	main
+	.type	main, @function
+main:
+	movq	$10, %rcx
+	movq	$32, %rdx
+	xorl	%eax, %eax
+	cmpq	%rdx, %rcx
+	setl	%al
+	cmovl	%rdx, %rcx
+	cmovge	%rax, %rcx
+	addq	%rax, %rcx
+	xorl	%esi, %esi
+	cmpq	$33, %rcx
+	sete	%sil
+	shlq	$3, %rsi
+	addq	%rsi, %rcx
+	xorl	%edi, %edi
+	cmpq	$0, %rcx
+	setg	%dil
+	setng	%al
+	addq	%rdi, %rcx
+	movq	%rcx, %rax
+	ret
+	.size	main, .-main
@@ -0,0 +1,38 @@
+	.text
+	.globl	main
+	.type	main, @function
+main:
+	pushq	%rbp
+	movq	%rsp, %rbp
+	# (3 << 4) >> 1 = 24
+	movq	$3, %rax
+	shlq	$4, %rax
+	shrq	%rax
+	# -96 >> 2 = -24
+	movq	$-96, %rdx
+	movq	$2, %rcx
+	sarq	%cl, %rdx
+	addq	%rdx, %rax
+	jnz	.L1
+	# 0x2a00000000000000 を rol で戻す
+	movq	$0x2a, %rax
+	rorq	$8, %rax
+	rolq	$8, %rax
+	# 上位 32bit を shld で下位に移す
+	movq	%rax, %rdx
+	shlq	$32, %rdx
+	movq	$0, %rax
+	shldq	$32, %rdx, %rax
+	shrdq	$16, %rax, %rdx
+	shrq	$48, %rdx
+	cmpq	%rdx, %rax
+	jne	.L1
+	movq	%rbp, %rsp
+	popq	%rbp
+	ret
+.L1:
+	movq	$1, %rax
+	movq	%rbp, %rsp
+	popq	%rbp
+	ret
+	.size	main, .-main
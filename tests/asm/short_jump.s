@@ -0,0 +1,17 @@
+	.text
+	.globl	main
+	.type	main, @function
+main:
+	pushq	%rbp
+	movq	%rsp, %rbp
+	movq	$0, %rax
+	movq	$1, %rdx
+	jmp	.L2
+.L3:
+	addq	%rdx, %rax
+.L2:
+	cmpq	$41, %rax
+	jle	.L3
+	popq	%rbp
+	ret
+	.size	main, .-main
@@ -0,0 +1,44 @@
+	.section	.rodata
+	.align 8
+.LC0:
+	# 40.5
+	.quad	0x4044400000000000
+.LC1:
+	# 1.5
+	.quad	0x3ff8000000000000
+.LC2:
+	# 2.0f
+	.long	0x40000000
+
+	.text
+	.globl	main
+	.type	main, @function
+main:
+	# 40.5 + 1.5 = 42.0
+	movsd	.LC0(%rip), %xmm0
+	addsd	.LC1(%rip), %xmm0
+	# sqrt(4.0) = 2.0
+	movl	$4, %eax
+	cvtsi2sd	%eax, %xmm1
+	sqrtsd	%xmm1, %xmm1
+	# 42.0 * 2.0 / 2.0 = 42.0
+	mulsd	%xmm1, %xmm0
+	movss	.LC2(%rip), %xmm2
+	cvtss2sd	%xmm2, %xmm2
+	divsd	%xmm2, %xmm0
+	movq	$42, %rcx
+	cvtsi2sdq	%rcx, %xmm9
+	ucomisd	%xmm9, %xmm0
+	jne	.L1
+	# 汎用レジスタを経由しても値は変わらない
+	movq	%xmm0, %rax
+	movq	%rax, %xmm10
+	pxor	%xmm3, %xmm3
+	subsd	%xmm3, %xmm10
+	movapd	%xmm10, %xmm11
+	cvttsd2si	%xmm11, %eax
+	ret
+.L1:
+	movl	$1, %eax
+	ret
+	.size	main, .-main
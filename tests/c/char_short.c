@@ -0,0 +1,12 @@
+int widen(char c, short s, int i)
+{
+    long l = i;
+    unsigned char uc = c;
+    unsigned short us = s;
+    return (int)(l + uc + us + c + s + (c < s));
+}
+
+int main()
+{
+    return widen(-1, -2, -65744);
+}
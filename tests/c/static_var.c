@@ -0,0 +1,7 @@
+static int counter;
+
+int main() {
+    counter += 40;
+    counter += 2;
+    return counter;
+}
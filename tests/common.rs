@@ -15,13 +15,11 @@ pub fn assembly_file_test(file_base: &str) -> i32 {
         .status()
         .expect("failed to spawn a process");
 
-    let code = Command::new(&executable_path)
+    Command::new(&executable_path)
         .status()
         .expect("failed to spawn a process")
         .code()
-        .unwrap();
-
-    code
+        .unwrap()
 }
 
 pub fn c_program_test(file_base: &str) -> i32 {
@@ -49,11 +47,9 @@ pub fn c_program_test(file_base: &str) -> i32 {
         .status()
         .expect("failed to spawn a process");
 
-    let code = Command::new(&executable_path)
+    Command::new(&executable_path)
         .status()
         .expect("failed to spawn a process")
         .code()
-        .unwrap();
-
-    code
+        .unwrap()
 }
@@ -25,10 +25,17 @@ mod c_integration_tests {
         assert_eq!(42, c_program_test("declare_autovar1"));
     }
     #[test]
-    #[ignore]
     fn while1_test() {
         assert_eq!(10, c_program_test("while1"));
     }
+    #[test]
+    fn char_short_test() {
+        assert_eq!(42, c_program_test("char_short"));
+    }
+    #[test]
+    fn static_var_test() {
+        assert_eq!(42, c_program_test("static_var"));
+    }
 }
 
 #[cfg(test)]
@@ -39,4 +46,92 @@ mod asm_integration_tests {
     fn double_quote_test() {
         assert_eq!(42, assembly_file_test("double_quote"));
     }
+    #[test]
+    fn intel_syntax_test() {
+        assert_eq!(42, assembly_file_test("intel_syntax"));
+    }
+    #[test]
+    fn data_sections_test() {
+        assert_eq!(42, assembly_file_test("data_sections"));
+    }
+    #[test]
+    fn custom_sections_test() {
+        assert_eq!(42, assembly_file_test("custom_sections"));
+    }
+    #[test]
+    fn local_symbols_test() {
+        assert_eq!(42, assembly_file_test("local_symbols"));
+    }
+    #[test]
+    fn short_jump_test() {
+        assert_eq!(42, assembly_file_test("short_jump"));
+    }
+    #[test]
+    fn conditional_jump_test() {
+        assert_eq!(42, assembly_file_test("conditional_jump"));
+    }
+    #[test]
+    fn cross_symbol_jump_test() {
+        assert_eq!(42, assembly_file_test("cross_symbol_jump"));
+    }
+    #[test]
+    fn numeric_labels_test() {
+        assert_eq!(42, assembly_file_test("numeric_labels"));
+    }
+    #[test]
+    fn comments_and_separators_test() {
+        assert_eq!(42, assembly_file_test("comments_and_separators"));
+    }
+    #[test]
+    fn expressions_test() {
+        assert_eq!(42, assembly_file_test("expressions"));
+    }
+    #[test]
+    fn equates_test() {
+        assert_eq!(42, assembly_file_test("equates"));
+    }
+    #[test]
+    fn macros_test() {
+        assert_eq!(42, assembly_file_test("macros"));
+    }
+    #[test]
+    fn conditional_assembly_test() {
+        assert_eq!(42, assembly_file_test("conditional_assembly"));
+    }
+    #[test]
+    fn include_test() {
+        assert_eq!(42, assembly_file_test("include"));
+    }
+    #[test]
+    fn logical_alu_test() {
+        assert_eq!(42, assembly_file_test("logical_alu"));
+    }
+    #[test]
+    fn shift_rotate_test() {
+        assert_eq!(42, assembly_file_test("shift_rotate"));
+    }
+    #[test]
+    fn setcc_cmovcc_test() {
+        assert_eq!(42, assembly_file_test("setcc_cmovcc"));
+    }
+    #[test]
+    fn extend_test() {
+        assert_eq!(42, assembly_file_test("extend"));
+    }
+    #[test]
+    fn byte_word_test() {
+        assert_eq!(42, assembly_file_test("byte_word"));
+    }
+    #[test]
+    fn sse_test() {
+        assert_eq!(42, assembly_file_test("sse"));
+    }
+    #[test]
+    fn avx_test() {
+        assert_eq!(42, assembly_file_test("avx"));
+    }
+    #[test]
+    fn section_switch_test() {
+        assert_eq!(42, assembly_file_test("section_switch"));
+    }
 }